@@ -0,0 +1,4 @@
+-- Add migration script here
+
+-- price at which stop loss / take profit orders are moved into the order book (null for regular orders)
+ALTER TABLE polymarket.orders ADD COLUMN IF NOT EXISTS "trigger_price" decimal;
@@ -0,0 +1,8 @@
+-- Add migration script here
+
+-- gtc (good till cancelled), ioc (immediate or cancel), fok (fill or kill), gtd (good till date)
+CREATE TYPE polymarket.time_in_force AS ENUM ('gtc', 'ioc', 'fok', 'gtd');
+
+ALTER TABLE polymarket.orders
+    ADD COLUMN IF NOT EXISTS "time_in_force" polymarket.time_in_force NOT NULL DEFAULT 'gtc',
+    ADD COLUMN IF NOT EXISTS "expires_at" timestamp; -- only used by gtd orders
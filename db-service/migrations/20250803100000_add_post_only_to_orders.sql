@@ -0,0 +1,3 @@
+ALTER TABLE polymarket.orders
+    ADD COLUMN IF NOT EXISTS "post_only" boolean NOT NULL DEFAULT false,
+    ADD COLUMN IF NOT EXISTS "status_reason" text; -- reason of rejection / cancellation by the matching engine
@@ -0,0 +1,7 @@
+-- serialized (message pack) global order book of order-service, `stream_sequence` is the last processed sequence of `ORDER` jetstream
+CREATE TABLE IF NOT EXISTS polymarket.order_book_snapshots (
+    "id" uuid DEFAULT gen_random_uuid() PRIMARY KEY,
+    "stream_sequence" bigint NOT NULL,
+    "snapshot" bytea NOT NULL,
+    "created_at" timestamp NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
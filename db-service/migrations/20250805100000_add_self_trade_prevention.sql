@@ -0,0 +1,10 @@
+-- Add migration script here
+
+-- action taken when an order would match against a resting order of the same user
+CREATE TYPE polymarket.self_trade_prevention AS ENUM ('cancel_newest', 'cancel_oldest', 'cancel_both', 'decrement_and_cancel');
+
+ALTER TABLE polymarket.users
+    ADD COLUMN IF NOT EXISTS "self_trade_prevention" polymarket.self_trade_prevention NOT NULL DEFAULT 'cancel_newest'; -- account default
+
+ALTER TABLE polymarket.orders
+    ADD COLUMN IF NOT EXISTS "self_trade_prevention" polymarket.self_trade_prevention NOT NULL DEFAULT 'cancel_newest';
@@ -0,0 +1,4 @@
+ALTER TABLE polymarket.markets
+    ADD COLUMN IF NOT EXISTS "tick_size" decimal NOT NULL DEFAULT 0.01 CHECK ("tick_size" > 0 AND "tick_size" <= 1), -- every order price must be a multiple of it
+    ADD COLUMN IF NOT EXISTS "min_quantity" decimal NOT NULL DEFAULT 0.01 CHECK ("min_quantity" > 0),
+    ADD COLUMN IF NOT EXISTS "lot_size" decimal NOT NULL DEFAULT 0.01 CHECK ("lot_size" > 0); -- every order quantity must be a multiple of it
@@ -0,0 +1,5 @@
+-- Add migration script here
+
+-- visible quantity of iceberg orders, rest of the order is kept hidden in the book
+ALTER TABLE polymarket.orders
+    ADD COLUMN IF NOT EXISTS "display_quantity" decimal CHECK ("display_quantity" > 0);
@@ -0,0 +1,12 @@
+-- execution summary of market orders, `price_limit` is the worst price the order was allowed to match at (max for buys, min for sells)
+CREATE TABLE IF NOT EXISTS polymarket.market_order_executions (
+    "order_id" uuid PRIMARY KEY REFERENCES polymarket.orders("id") ON DELETE CASCADE,
+    "budget" decimal NOT NULL,
+    "price_limit" decimal,
+    "max_slippage" decimal,
+    "average_price" decimal,
+    "filled_quantity" decimal NOT NULL DEFAULT 0,
+    "levels_consumed" integer NOT NULL DEFAULT 0,
+    "unfilled_budget" decimal NOT NULL DEFAULT 0,
+    "created_at" timestamp NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,33 @@
+ALTER TABLE polymarket.markets
+    ADD COLUMN IF NOT EXISTS "market_maker" boolean NOT NULL DEFAULT false; -- LMSR market maker fills the orders the book can't
+
+-- system account of the LMSR market maker, `q_yes` / `q_no` are the outstanding shares sold by it and `cash` is collected from the traders
+CREATE TABLE IF NOT EXISTS polymarket.market_maker_accounts (
+    "market_id" uuid PRIMARY KEY REFERENCES polymarket.markets("id") ON DELETE CASCADE,
+    "liquidity_b" decimal NOT NULL CHECK ("liquidity_b" > 0),
+    "subsidy" decimal NOT NULL, -- max loss of the market maker, b * ln(2)
+    "q_yes" decimal NOT NULL DEFAULT 0,
+    "q_no" decimal NOT NULL DEFAULT 0,
+    "cash" decimal NOT NULL DEFAULT 0,
+    "realized_pnl" decimal, -- set once the market is settled (cash - outstanding shares of the winning outcome)
+    "created_at" timestamp NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "updated_at" timestamp NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TRIGGER set_updated_at_market_maker_accounts_trigger
+BEFORE UPDATE ON "polymarket"."market_maker_accounts"
+FOR EACH ROW
+EXECUTE FUNCTION polymarket.set_updated_at();
+
+-- trades of the users against the market maker, `side` is the side of the user
+CREATE TABLE IF NOT EXISTS polymarket.market_maker_trades (
+    "id" uuid DEFAULT gen_random_uuid() PRIMARY KEY,
+    "market_id" uuid NOT NULL REFERENCES polymarket.market_maker_accounts("market_id") ON DELETE CASCADE,
+    "order_id" uuid NOT NULL REFERENCES polymarket.orders("id") ON DELETE CASCADE,
+    "user_id" uuid NOT NULL REFERENCES polymarket.users("id"),
+    "outcome" polymarket.outcome NOT NULL,
+    "side" polymarket.order_side NOT NULL,
+    "price" decimal NOT NULL,
+    "quantity" decimal NOT NULL CHECK ("quantity" > 0),
+    "created_at" timestamp NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
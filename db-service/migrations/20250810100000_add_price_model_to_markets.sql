@@ -0,0 +1,7 @@
+-- Add migration script here
+
+-- model used to derive the displayed yes / no probability of the market
+CREATE TYPE polymarket.price_model AS ENUM ('lmsr_weighted', 'midpoint', 'last_trade', 'vwap_midpoint', 'microprice');
+
+ALTER TABLE polymarket.markets
+    ADD COLUMN IF NOT EXISTS "price_model" polymarket.price_model NOT NULL DEFAULT 'lmsr_weighted';
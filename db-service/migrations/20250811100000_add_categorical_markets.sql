@@ -0,0 +1,128 @@
+-- Add migration script here
+
+-- binary markets trade YES / NO shares, categorical markets trade a share of every named outcome (pays out if it's the winning outcome)
+CREATE TYPE polymarket.market_type AS ENUM ('binary', 'categorical');
+
+ALTER TABLE polymarket.markets
+    ADD COLUMN IF NOT EXISTS "market_type" polymarket.market_type NOT NULL DEFAULT 'binary',
+    ADD COLUMN IF NOT EXISTS "outcome_count" integer NOT NULL DEFAULT 2 CHECK ("outcome_count" >= 2),
+    ADD COLUMN IF NOT EXISTS "final_outcome_index" integer; -- winning outcome of categorical market, set once it's settled
+
+-- named outcomes of the market, binary market has `Yes` (0) and `No` (1) outcomes
+CREATE TABLE IF NOT EXISTS polymarket.market_outcomes (
+    "id" uuid DEFAULT gen_random_uuid() PRIMARY KEY,
+    "market_id" uuid NOT NULL REFERENCES polymarket.markets("id") ON DELETE CASCADE,
+    "outcome_index" integer NOT NULL CHECK ("outcome_index" >= 0),
+    "name" varchar(255) NOT NULL,
+    "created_at" timestamp NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE ("market_id", "outcome_index")
+);
+
+INSERT INTO polymarket.market_outcomes (market_id, outcome_index, name)
+SELECT id, 0, 'Yes' FROM polymarket.markets
+UNION ALL
+SELECT id, 1, 'No' FROM polymarket.markets
+ON CONFLICT DO NOTHING;
+
+-- outcome of the categorical market the order / holding / trade belongs to (binary markets always use 0 along with their `outcome`)
+ALTER TABLE polymarket.orders
+    ADD COLUMN IF NOT EXISTS "outcome_index" integer NOT NULL DEFAULT 0 CHECK ("outcome_index" >= 0);
+
+ALTER TABLE polymarket.user_trades
+    ADD COLUMN IF NOT EXISTS "outcome_index" integer NOT NULL DEFAULT 0;
+
+ALTER TABLE polymarket.user_holdings
+    ADD COLUMN IF NOT EXISTS "outcome_index" integer NOT NULL DEFAULT 0;
+
+ALTER TABLE polymarket.user_holdings
+    DROP CONSTRAINT IF EXISTS user_holdings_user_id_market_id_outcome_key,
+    ADD CONSTRAINT user_holdings_user_id_market_id_outcome_outcome_index_key UNIQUE (user_id, market_id, outcome, outcome_index);
+
+-- holdings are unique per outcome index as well
+CREATE OR REPLACE PROCEDURE polymarket.update_order_and_process_trade(
+    in_current_order_id UUID, -- order which is to match
+    in_opposite_order_id UUID, -- order which was matched with above order
+    in_new_filled_quantity DECIMAL
+) LANGUAGE plpgsql
+AS $$ 
+DECLARE 
+    -- declare necessary variables
+    v_user_id UUID;
+    v_market_id UUID;
+    v_outcome polymarket.outcome;
+    v_outcome_index INTEGER;
+    v_side polymarket.order_side;
+    v_price DECIMAL;
+    v_quantity DECIMAL;
+    v_total_filled DECIMAL;
+    v_status polymarket.order_status;
+    v_new_total_filled DECIMAL;
+BEGIN
+    -- Get order details
+    SELECT user_id, market_id, outcome, outcome_index, side, price, quantity, filled_quantity
+    INTO v_user_id, v_market_id, v_outcome, v_outcome_index, v_side, v_price, v_quantity, v_total_filled
+    FROM polymarket.orders
+    WHERE id = in_opposite_order_id
+    FOR UPDATE;
+
+    -- update filled quantity
+    v_new_total_filled := v_total_filled + in_new_filled_quantity;
+
+    -- validate total balance
+    IF v_new_total_filled > v_quantity THEN
+        RAISE EXCEPTION 'Filled quantity (%.2f) exceeds total quantity (%.2f)', v_new_total_filled, v_quantity;
+    END IF;
+
+    -- update status
+    IF v_total_filled >= v_quantity THEN
+        v_status := 'filled'::polymarket.order_status;    
+    END IF;
+
+    -- update order
+    UPDATE polymarket.orders
+    SET filled_quantity = v_new_total_filled,
+        status = v_status
+    WHERE id = in_opposite_order_id;
+
+    IF v_new_total_filled = v_quantity THEN
+        -- insert new trade 
+        INSERT INTO polymarket.user_trades (
+            user_id,
+            buy_order_id,
+            sell_order_id,
+            market_id,
+            outcome,
+            outcome_index,
+            price,
+            quantity
+        ) VALUES (
+            v_user_id,
+            in_current_order_id,
+            in_opposite_order_id,
+            v_market_id,
+            v_outcome,
+            v_outcome_index,
+            v_price,
+            in_new_filled_quantity
+        );
+
+        -- update or insert into user holdings
+        INSERT INTO polymarket.user_holdings (
+            user_id,
+            market_id,
+            outcome,
+            outcome_index,
+            shares
+        ) 
+        VALUES (
+            v_user_id,
+            v_market_id,
+            v_outcome,
+            v_outcome_index,
+            v_new_total_filled
+        ) ON CONFLICT (user_id, market_id, outcome, outcome_index)
+        DO UPDATE SET
+         shares = polymarket.user_holdings.shares + in_new_filled_quantity;
+    END IF;
+END;
+$$;
@@ -0,0 +1,11 @@
+-- Add migration script here
+
+-- scalar markets trade LONG (yes, 0) / SHORT (no, 1) shares, LONG pays linearly between the bounds of the market and SHORT pays the rest
+ALTER TYPE polymarket.market_type ADD VALUE IF NOT EXISTS 'scalar';
+
+ALTER TABLE polymarket.markets
+    ADD COLUMN IF NOT EXISTS "lower_bound" decimal,
+    ADD COLUMN IF NOT EXISTS "upper_bound" decimal,
+    ADD COLUMN IF NOT EXISTS "unit" varchar(32), -- unit of the bounds, e.g. USD
+    ADD COLUMN IF NOT EXISTS "resolved_value" decimal, -- set once the scalar market is settled
+    ADD CONSTRAINT "markets_scalar_bounds_check" CHECK ("lower_bound" < "upper_bound");
@@ -0,0 +1,5 @@
+-- Add migration script here
+
+-- scheduled open of the opening call auction, orders are collected without matching till then (null once continuous trading started)
+ALTER TABLE polymarket.markets
+    ADD COLUMN IF NOT EXISTS "auction_opens_at" timestamp;
@@ -0,0 +1,9 @@
+-- Add migration script here
+
+-- volatility circuit breaker, orders are accepted within `price_band` (0.15 is ±15%) of the reference price which is rolled every `price_band_window` seconds
+-- market order which would move the price outside the band halts the trading for `halt_cooldown` seconds
+ALTER TABLE polymarket.markets
+    ADD COLUMN IF NOT EXISTS "price_band" decimal CHECK ("price_band" > 0 AND "price_band" < 1), -- null disables the circuit breaker
+    ADD COLUMN IF NOT EXISTS "price_band_window" integer NOT NULL DEFAULT 300 CHECK ("price_band_window" > 0),
+    ADD COLUMN IF NOT EXISTS "halt_cooldown" integer NOT NULL DEFAULT 60 CHECK ("halt_cooldown" >= 0),
+    ADD COLUMN IF NOT EXISTS "halted_until" timestamp; -- set while the trading is halted
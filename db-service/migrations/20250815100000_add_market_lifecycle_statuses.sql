@@ -0,0 +1,8 @@
+-- Add migration script here
+
+-- lifecycle of the market, draft -> open <-> paused -> closed -> resolving -> settled | voided
+-- new values can't be used in the transaction they are added in, so they are used by the next migration
+ALTER TYPE polymarket.market_status ADD VALUE IF NOT EXISTS 'draft' BEFORE 'open';
+ALTER TYPE polymarket.market_status ADD VALUE IF NOT EXISTS 'paused' AFTER 'open';
+ALTER TYPE polymarket.market_status ADD VALUE IF NOT EXISTS 'resolving' AFTER 'closed';
+ALTER TYPE polymarket.market_status ADD VALUE IF NOT EXISTS 'voided' AFTER 'settled';
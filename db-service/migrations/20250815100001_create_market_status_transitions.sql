@@ -0,0 +1,27 @@
+-- Add migration script here
+
+-- markets are created as drafts and opened once their order book is initialized
+ALTER TABLE polymarket.markets
+    ALTER COLUMN "status" SET DEFAULT 'draft'::polymarket.market_status;
+
+-- every status change of the market, `changed_by` is the admin who made it (null for the expiry of the market)
+CREATE TABLE IF NOT EXISTS polymarket.market_status_transitions (
+    "id" uuid DEFAULT gen_random_uuid() PRIMARY KEY,
+    "market_id" uuid NOT NULL REFERENCES polymarket.markets("id") ON DELETE CASCADE,
+    "from_status" polymarket.market_status NOT NULL,
+    "to_status" polymarket.market_status NOT NULL,
+    "changed_by" uuid REFERENCES polymarket.users("id") ON DELETE SET NULL,
+    "created_at" timestamp NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_market_status_transitions_market_id
+    ON polymarket.market_status_transitions("market_id", "created_at");
+
+-- expired markets are closed by the order service (which freezes their order books), so the cron jobs are dropped
+DO $$
+BEGIN
+    IF to_regclass('cron.job') IS NOT NULL THEN
+        PERFORM cron.unschedule(jobname) FROM cron.job WHERE jobname LIKE 'close_market_job_%';
+    END IF;
+END $$;
+DROP FUNCTION IF EXISTS polymarket.close_market(UUID);
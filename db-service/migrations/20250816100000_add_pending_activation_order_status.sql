@@ -0,0 +1,4 @@
+-- Add migration script here
+
+-- exit orders of the bracket wait in this status until their entry order is filled
+ALTER TYPE polymarket.order_status ADD VALUE IF NOT EXISTS 'pending_activation';
@@ -0,0 +1,24 @@
+-- Add migration script here
+
+-- oco (one cancels other) and bracket (entry order with take profit / stop loss exits) groups of orders
+CREATE TYPE polymarket.order_group_type AS ENUM ('oco', 'bracket');
+CREATE TYPE polymarket.order_group_leg AS ENUM ('entry', 'take_profit', 'stop_loss');
+
+CREATE TABLE IF NOT EXISTS polymarket.order_groups (
+    "id" uuid DEFAULT gen_random_uuid() PRIMARY KEY,
+    "user_id" uuid NOT NULL REFERENCES polymarket.users("id") ON DELETE CASCADE,
+    "market_id" uuid NOT NULL REFERENCES polymarket.markets("id") ON DELETE CASCADE,
+    "group_type" polymarket.order_group_type NOT NULL,
+    "created_at" timestamp NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- order belongs to at most one group
+CREATE TABLE IF NOT EXISTS polymarket.order_group_orders (
+    "order_id" uuid PRIMARY KEY REFERENCES polymarket.orders("id") ON DELETE CASCADE,
+    "group_id" uuid NOT NULL REFERENCES polymarket.order_groups("id") ON DELETE CASCADE,
+    "leg" polymarket.order_group_leg NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_order_groups_market_id ON polymarket.order_groups("market_id");
+CREATE INDEX IF NOT EXISTS idx_order_groups_user_id ON polymarket.order_groups("user_id", "created_at");
+CREATE INDEX IF NOT EXISTS idx_order_group_orders_group_id ON polymarket.order_group_orders("group_id");
@@ -0,0 +1,12 @@
+-- Add migration script here
+
+-- trailing stop is a stop loss order whose trigger price follows the best price seen since it's placement,
+-- either by a fixed offset (`trail_amount`, 0.00-1.00) or by a percentage of the best price (`trail_percent`)
+CREATE TABLE IF NOT EXISTS polymarket.trailing_stops (
+    "order_id" uuid PRIMARY KEY REFERENCES polymarket.orders("id") ON DELETE CASCADE,
+    "trail_amount" decimal,
+    "trail_percent" decimal,
+    "best_price" decimal, -- highest price for sells, lowest for buys (null until the order reaches the book)
+    "updated_at" timestamp NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT trailing_stops_offset_check CHECK (("trail_amount" IS NULL) <> ("trail_percent" IS NULL))
+);
@@ -3,7 +3,6 @@ use std::error::Error as StdError;
 pub mod pagination;
 pub mod procedures;
 pub mod schema;
-pub mod utils;
 
 pub struct DbService;
 
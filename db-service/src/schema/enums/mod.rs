@@ -5,12 +5,49 @@ use serde::{Deserialize, Serialize};
 #[sqlx(rename_all = "lowercase")]
 pub enum MarketStatus {
     #[default]
+    #[serde(rename = "draft")]
+    DRAFT = 4,
     #[serde(rename = "open")]
     OPEN = 1,
+    #[serde(rename = "paused")]
+    PAUSED = 5,
     #[serde(rename = "closed")]
     CLOSED = 2,
+    #[serde(rename = "resolving")]
+    RESOLVING = 6,
     #[serde(rename = "settled")]
     SETTLED = 3,
+    #[serde(rename = "voided")]
+    VOIDED = 7,
+}
+
+impl MarketStatus {
+    /// Statuses the market can be moved to `self` from,
+    /// DRAFT -> OPEN <-> PAUSED -> CLOSED -> RESOLVING -> SETTLED | VOIDED
+    pub fn previous_statuses(&self) -> &'static [MarketStatus] {
+        match self {
+            MarketStatus::DRAFT => &[],
+            MarketStatus::OPEN => &[MarketStatus::DRAFT, MarketStatus::PAUSED],
+            MarketStatus::PAUSED => &[MarketStatus::OPEN],
+            MarketStatus::CLOSED => &[MarketStatus::OPEN, MarketStatus::PAUSED],
+            MarketStatus::RESOLVING => &[MarketStatus::CLOSED],
+            MarketStatus::SETTLED | MarketStatus::VOIDED => &[MarketStatus::RESOLVING],
+        }
+    }
+
+    pub fn can_transition_to(&self, to: MarketStatus) -> bool {
+        to.previous_statuses().contains(self)
+    }
+
+    /// Orders are accepted (and matched) only by the open market
+    pub fn is_tradable(&self) -> bool {
+        *self == MarketStatus::OPEN
+    }
+
+    /// Settled and voided markets are final, their order books are dropped
+    pub fn is_final(&self) -> bool {
+        matches!(self, MarketStatus::SETTLED | MarketStatus::VOIDED)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, sqlx::Type, Clone, PartialEq, Default, Copy, Eq, Hash)]
@@ -26,6 +63,17 @@ pub enum Outcome {
     UNSPECIFIED = 0,
 }
 
+impl Outcome {
+    /// Complementary outcome of binary market (YES <-> NO)
+    pub fn complement(&self) -> Outcome {
+        match self {
+            Outcome::YES => Outcome::NO,
+            Outcome::NO => Outcome::YES,
+            Outcome::UNSPECIFIED => Outcome::UNSPECIFIED,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, sqlx::Type, Clone, PartialEq, Default, Copy, Eq, Hash)]
 #[sqlx(type_name = "\"polymarket\".\"order_side\"")]
 #[sqlx(rename_all = "lowercase")]
@@ -59,6 +107,10 @@ pub enum OrderStatus {
     // NOT USED!!!! and DON'T USE IT
     #[sqlx(rename = "partial_fill")]
     PartialFill = 8,
+    /// Exit order of the bracket, waiting for the entry order to be filled
+    #[serde(rename = "pending_activation")]
+    #[sqlx(rename = "pending_activation")]
+    PendingActivation = 9,
 }
 
 #[derive(Debug, Serialize, Deserialize, sqlx::Type, Clone, PartialEq, Default, Copy)]
@@ -97,7 +149,105 @@ pub enum OrderType {
     #[serde(rename = "market")]
     MARKET = 2,
     #[serde(rename = "stop_loss")]
+    #[sqlx(rename = "stop_loss")]
     StopLoss = 3,
     #[serde(rename = "take_profit")]
+    #[sqlx(rename = "take_profit")]
     TakeProfit = 4,
 }
+
+#[derive(Debug, Serialize, Deserialize, sqlx::Type, Clone, PartialEq, Default, Copy)]
+#[sqlx(type_name = "\"polymarket\".\"time_in_force\"")]
+#[sqlx(rename_all = "lowercase")]
+pub enum TimeInForce {
+    /// Good till cancelled
+    #[default]
+    #[serde(rename = "gtc")]
+    GTC = 1,
+    /// Immediate or cancel, fills what it can and cancels the rest
+    #[serde(rename = "ioc")]
+    IOC = 2,
+    /// Fill or kill, either fills completely or gets cancelled without touching the book
+    #[serde(rename = "fok")]
+    FOK = 3,
+    /// Good till date, rests in the book until `expires_at`
+    #[serde(rename = "gtd")]
+    GTD = 4,
+}
+
+/// Action taken when an incoming order would match against a resting order of the same user
+#[derive(Debug, Serialize, Deserialize, sqlx::Type, Clone, PartialEq, Default, Copy)]
+#[sqlx(type_name = "\"polymarket\".\"self_trade_prevention\"")]
+#[sqlx(rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum SelfTradePrevention {
+    /// Incoming order is cancelled, resting order stays in the book
+    #[default]
+    CancelNewest = 1,
+    /// Resting order is cancelled, incoming order continues matching
+    CancelOldest = 2,
+    /// Both orders are cancelled
+    CancelBoth = 3,
+    /// Both orders are decremented by the smaller remaining quantity, the order with nothing left is cancelled
+    DecrementAndCancel = 4,
+}
+
+/// Model used to derive the displayed probability of the market's outcomes
+#[derive(Debug, Serialize, Deserialize, sqlx::Type, Clone, PartialEq, Default, Copy)]
+#[sqlx(type_name = "\"polymarket\".\"price_model\"")]
+#[sqlx(rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum PriceModelKind {
+    /// Price of the LMSR market maker, or the book funds weighted by the liquidity `b` if the market has none
+    #[default]
+    LmsrWeighted = 1,
+    /// Midpoint of the best bid and ask
+    Midpoint = 2,
+    /// Last traded price
+    LastTrade = 3,
+    /// Midpoint of the volume weighted bid and ask prices of the top levels
+    VwapMidpoint = 4,
+    /// Best bid and ask weighted by the quantity on the opposite side
+    Microprice = 5,
+}
+
+/// Shape of the market's outcomes
+#[derive(Debug, Serialize, Deserialize, sqlx::Type, Clone, PartialEq, Default, Copy)]
+#[sqlx(type_name = "\"polymarket\".\"market_type\"")]
+#[sqlx(rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum MarketType {
+    /// YES / NO outcomes
+    #[default]
+    Binary = 1,
+    /// Three or more named outcomes, exactly one of them pays out
+    Categorical = 2,
+    /// LONG (yes) / SHORT (no) outcomes, paid linearly by the resolved value between the bounds
+    Scalar = 3,
+}
+
+/// Relationship of the orders in the order group
+#[derive(Debug, Serialize, Deserialize, sqlx::Type, Clone, PartialEq, Default, Copy)]
+#[sqlx(type_name = "\"polymarket\".\"order_group_type\"")]
+#[sqlx(rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum OrderGroupType {
+    /// One cancels other, fill, cancel or expiry of a leg cancels the other legs
+    #[default]
+    Oco = 1,
+    /// Entry order whose take profit / stop loss exits are activated once it's filled, exits are oco of each other
+    Bracket = 2,
+}
+
+/// Role of the order in it's group
+#[derive(Debug, Serialize, Deserialize, sqlx::Type, Clone, PartialEq, Default, Copy)]
+#[sqlx(type_name = "\"polymarket\".\"order_group_leg\"")]
+#[sqlx(rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum OrderGroupLeg {
+    /// Entry order of the bracket
+    #[default]
+    Entry = 1,
+    TakeProfit = 2,
+    StopLoss = 3,
+}
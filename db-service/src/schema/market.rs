@@ -1,15 +1,40 @@
 use chrono::NaiveDateTime;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use sqlx::PgPool;
+use sqlx::{Executor, PgPool, Postgres};
 use utility_helpers::log_info;
 use uuid::Uuid;
 
-use super::enums::{MarketStatus, Outcome};
-use crate::{
-    pagination::PaginatedResponse,
-    utils::{CronJobName, to_cron_expression},
+use super::{
+    enums::{MarketStatus, MarketType, Outcome, PriceModelKind},
+    market_outcomes::MarketOutcome,
+    market_status_transitions::MarketStatusTransition,
 };
+use crate::pagination::PaginatedResponse;
+
+/// Bounds of the scalar market, LONG pays from 0 at `lower_bound` up to 1 at `upper_bound`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScalarRange {
+    pub lower_bound: Decimal,
+    pub upper_bound: Decimal,
+    pub unit: String,
+}
+
+impl ScalarRange {
+    /// Payout of a LONG share for the resolved value, SHORT share pays the rest
+    pub fn long_payout(&self, resolved_value: Decimal) -> Decimal {
+        ((resolved_value - self.lower_bound) / (self.upper_bound - self.lower_bound))
+            .clamp(Decimal::ZERO, Decimal::ONE)
+    }
+}
+
+/// Volatility circuit breaker of the market, orders are accepted within `price_band` (0.15 is ±15%) of the reference price
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct PriceBand {
+    pub price_band: Decimal,
+    pub window: i32,        // seconds after which the reference price is rolled
+    pub halt_cooldown: i32, // seconds the trading is halted for once the band is breached
+}
 
 // serialized by redis
 #[derive(Debug, Serialize, sqlx::FromRow, Deserialize, Default)]
@@ -20,21 +45,56 @@ pub struct Market {
     pub logo: String,
     pub status: MarketStatus,
     pub liquidity_b: Decimal,
+    pub tick_size: Decimal,          // price increment
+    pub min_quantity: Decimal,       // smallest order quantity
+    pub lot_size: Decimal,           // quantity increment
+    pub market_maker: bool,          // LMSR market maker is enabled
+    pub price_model: PriceModelKind, // displayed probability of the outcomes
+    pub market_type: MarketType,
+    pub outcome_count: i32, // 2 for binary markets
     pub final_outcome: Outcome,
+    pub final_outcome_index: Option<i32>, // winning outcome of the categorical market
+    pub lower_bound: Option<Decimal>,     // LONG pays nothing at or below it (scalar market)
+    pub upper_bound: Option<Decimal>,     // LONG pays out fully at or above it (scalar market)
+    pub unit: Option<String>,             // unit of the bounds
+    pub resolved_value: Option<Decimal>,  // value the scalar market is settled with
+    pub auction_opens_at: Option<NaiveDateTime>, // scheduled open of the opening call auction
+    pub price_band: Option<Decimal>, // orders are accepted within ±price_band of the reference price
+    pub price_band_window: i32,      // seconds after which the reference price is rolled
+    pub halt_cooldown: i32,          // seconds the trading is halted for after the band is breached
+    pub halted_until: Option<NaiveDateTime>, // trading is halted by the circuit breaker till then
     pub market_expiry: NaiveDateTime,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
 }
 
+pub struct NewMarket {
+    pub name: String,
+    pub description: String,
+    pub logo: String,
+    pub liquidity_b: Decimal,
+    pub tick_size: Decimal,
+    pub min_quantity: Decimal,
+    pub lot_size: Decimal,
+    pub market_maker: bool,
+    pub price_model: PriceModelKind,
+    pub outcomes: Vec<String>, // names of the outcomes, market with more than 2 outcomes is categorical
+    pub scalar_range: Option<ScalarRange>, // scalar market with LONG / SHORT outcomes
+    pub market_expiry: NaiveDateTime,
+}
+
 impl Market {
     pub async fn create_new_market(
-        name: String,
-        description: String,
-        logo: String,
-        liquidity_b: Decimal,
-        market_expiry: NaiveDateTime,
         pg_pool: &PgPool,
+        new_market: &NewMarket,
     ) -> Result<Self, sqlx::Error> {
+        let market_type = if new_market.scalar_range.is_some() {
+            MarketType::Scalar
+        } else if new_market.outcomes.len() > 2 {
+            MarketType::Categorical
+        } else {
+            MarketType::Binary
+        };
         let mut tx = pg_pool.begin().await?;
 
         let market = sqlx::query_as!(
@@ -45,13 +105,33 @@ impl Market {
                 description,
                 logo,
                 liquidity_b,
+                tick_size,
+                min_quantity,
+                lot_size,
+                market_maker,
+                price_model,
+                market_type,
+                outcome_count,
+                lower_bound,
+                upper_bound,
+                unit,
                 market_expiry
             ) VALUES (
                 $1,
                 $2,
                 $3,
                 $4,
-                $5
+                $5,
+                $6,
+                $7,
+                $8,
+                $9,
+                $10,
+                $11,
+                $12,
+                $13,
+                $14,
+                $15
             ) RETURNING 
                 id,
                 name,
@@ -60,39 +140,70 @@ impl Market {
                 status as "status: MarketStatus",
                 final_outcome as "final_outcome: Outcome",
                 liquidity_b,
+                tick_size,
+                min_quantity,
+                lot_size,
+                market_maker,
+                price_model as "price_model: PriceModelKind",
+                market_type as "market_type: MarketType",
+                outcome_count,
+                final_outcome_index,
+                lower_bound,
+                upper_bound,
+                unit,
+                resolved_value,
+                auction_opens_at,
+                price_band,
+                price_band_window,
+                halt_cooldown,
+                halted_until,
                 market_expiry,
                 created_at,
                 updated_at
             "#,
-            name,
-            description,
-            logo,
-            liquidity_b,
-            market_expiry
+            new_market.name,
+            new_market.description,
+            new_market.logo,
+            new_market.liquidity_b,
+            new_market.tick_size,
+            new_market.min_quantity,
+            new_market.lot_size,
+            new_market.market_maker,
+            new_market.price_model as _,
+            market_type as _,
+            new_market.outcomes.len() as i32,
+            new_market
+                .scalar_range
+                .as_ref()
+                .map(|range| range.lower_bound),
+            new_market
+                .scalar_range
+                .as_ref()
+                .map(|range| range.upper_bound),
+            new_market
+                .scalar_range
+                .as_ref()
+                .map(|range| range.unit.clone()),
+            new_market.market_expiry
         )
         .fetch_one(&mut *tx)
         .await?;
 
-        // create cron
-        let cron_name = CronJobName::CloseMarket(market.id).to_string();
-
-        let cron_query = format!("SELECT polymarket.close_market('{}'::uuid);", market.id); // cron function
-        let cron_expression = to_cron_expression(market.market_expiry);
+        MarketOutcome::create_market_outcomes(&mut *tx, market.id, &new_market.outcomes).await?;
 
-        sqlx::query(
-            r#"
-            SELECT cron.schedule(
-                $1, -- cron name
-                $2, -- cron run time
-                $3 -- cron function
+        // system account of the market maker, it's loss is bounded by b * ln(2)
+        if market.market_maker {
+            sqlx::query!(
+                r#"
+                INSERT INTO polymarket.market_maker_accounts (market_id, liquidity_b, subsidy)
+                VALUES ($1, $2, $2::numeric * ln(2))
+                "#,
+                market.id,
+                market.liquidity_b
             )
-            "#,
-        )
-        .bind(cron_name)
-        .bind(cron_expression)
-        .bind(cron_query)
-        .execute(&mut *tx)
-        .await?;
+            .execute(&mut *tx)
+            .await?;
+        }
 
         tx.commit().await?;
 
@@ -112,6 +223,23 @@ impl Market {
                 status as "status: MarketStatus",
                 final_outcome as "final_outcome: Outcome",
                 liquidity_b,
+                tick_size,
+                min_quantity,
+                lot_size,
+                market_maker,
+                price_model as "price_model: PriceModelKind",
+                market_type as "market_type: MarketType",
+                outcome_count,
+                final_outcome_index,
+                lower_bound,
+                upper_bound,
+                unit,
+                resolved_value,
+                auction_opens_at,
+                price_band,
+                price_band_window,
+                halt_cooldown,
+                halted_until,
                 market_expiry,
                 created_at,
                 updated_at
@@ -153,6 +281,23 @@ impl Market {
                 status as "status: MarketStatus",
                 final_outcome as "final_outcome: Outcome",
                 liquidity_b,
+                tick_size,
+                min_quantity,
+                lot_size,
+                market_maker,
+                price_model as "price_model: PriceModelKind",
+                market_type as "market_type: MarketType",
+                outcome_count,
+                final_outcome_index,
+                lower_bound,
+                upper_bound,
+                unit,
+                resolved_value,
+                auction_opens_at,
+                price_band,
+                price_band_window,
+                halt_cooldown,
+                halted_until,
                 market_expiry,
                 created_at,
                 updated_at
@@ -206,6 +351,23 @@ impl Market {
                 status as "status: MarketStatus",
                 final_outcome as "final_outcome: Outcome",
                 liquidity_b,
+                tick_size,
+                min_quantity,
+                lot_size,
+                market_maker,
+                price_model as "price_model: PriceModelKind",
+                market_type as "market_type: MarketType",
+                outcome_count,
+                final_outcome_index,
+                lower_bound,
+                upper_bound,
+                unit,
+                resolved_value,
+                auction_opens_at,
+                price_band,
+                price_band_window,
+                halt_cooldown,
+                halted_until,
                 market_expiry,
                 created_at,
                 updated_at
@@ -244,6 +406,23 @@ impl Market {
                 status as "status: MarketStatus",
                 final_outcome as "final_outcome: Outcome",
                 liquidity_b,
+                tick_size,
+                min_quantity,
+                lot_size,
+                market_maker,
+                price_model as "price_model: PriceModelKind",
+                market_type as "market_type: MarketType",
+                outcome_count,
+                final_outcome_index,
+                lower_bound,
+                upper_bound,
+                unit,
+                resolved_value,
+                auction_opens_at,
+                price_band,
+                price_band_window,
+                halt_cooldown,
+                halted_until,
                 market_expiry,
                 created_at,
                 updated_at
@@ -278,7 +457,8 @@ impl Market {
         Ok(())
     }
 
-    pub async fn get_all_open_markets(pg_pool: &PgPool) -> Result<Vec<Market>, sqlx::Error> {
+    /// Markets whose order book is live, orders of paused, closed and resolving markets are kept in their frozen books
+    pub async fn get_all_live_markets(pg_pool: &PgPool) -> Result<Vec<Market>, sqlx::Error> {
         let orders = sqlx::query_as!(
             Market,
             r#"
@@ -290,11 +470,33 @@ impl Market {
                 status as "status: MarketStatus",
                 final_outcome as "final_outcome: Outcome",
                 liquidity_b,
+                tick_size,
+                min_quantity,
+                lot_size,
+                market_maker,
+                price_model as "price_model: PriceModelKind",
+                market_type as "market_type: MarketType",
+                outcome_count,
+                final_outcome_index,
+                lower_bound,
+                upper_bound,
+                unit,
+                resolved_value,
+                auction_opens_at,
+                price_band,
+                price_band_window,
+                halt_cooldown,
+                halted_until,
                 market_expiry,
                 created_at,
                 updated_at
             FROM polymarket.markets WHERE
-            status = 'open'::polymarket.market_status;
+            status IN (
+                'open'::polymarket.market_status,
+                'paused'::polymarket.market_status,
+                'closed'::polymarket.market_status,
+                'resolving'::polymarket.market_status
+            );
             "#
         )
         .fetch_all(pg_pool)
@@ -303,68 +505,201 @@ impl Market {
         Ok(orders)
     }
 
+    /// Pays out the holdings of the winning outcome, categorical market wins with `(YES, final_outcome_index)`.
+    /// Market must be resolving, `changed_by` is the admin who settled it
     pub async fn settle_market(
         pg_pool: &PgPool,
         market_id: &Uuid,
         final_outcome: Outcome,
+        final_outcome_index: Option<i32>,
+        changed_by: Option<Uuid>,
     ) -> Result<(), sqlx::Error> {
         let mut tx = pg_pool.begin().await?;
 
         // 1. Updating the market status to settled
+        Self::settle_resolving_market(&mut tx, market_id, MarketStatus::SETTLED, changed_by)
+            .await?;
         sqlx::query!(
             r#"
             UPDATE polymarket.markets
-            SET status = 'settled'::polymarket.market_status,
-                final_outcome = $2
+            SET final_outcome = $2,
+                final_outcome_index = $3
             WHERE id = $1
             "#,
             market_id,
-            final_outcome as _
+            final_outcome as _,
+            final_outcome_index
         )
         .execute(&mut *tx)
         .await?;
 
         // 2. Expiring all open orders in the market
+        Self::expire_open_orders(&mut *tx, market_id).await?;
+
+        // 3. Credit the balance to the user's holdings
         sqlx::query!(
             r#"
-            UPDATE polymarket.orders
-            SET status = 'expired'::polymarket.order_status
-            WHERE market_id = $1 AND status in (
-                'open'::polymarket.order_status,
-                'partial_fill'::polymarket.order_status,
-                'pending_update'::polymarket.order_status,
-                'pending_cancel'::polymarket.order_status
-            )
+            UPDATE polymarket.users u
+            SET balance = balance + (payout.total * 100) -- Each share is worth 100 after settlement
+            FROM (
+                SELECT user_id, SUM(shares) AS total
+                FROM polymarket.user_holdings
+                WHERE market_id = $1 AND outcome = $2 AND outcome_index = COALESCE($3, 0)
+                GROUP BY user_id
+            ) AS payout
+             WHERE u.id = payout.user_id
             "#,
-            market_id
+            market_id,
+            final_outcome as _,
+            final_outcome_index
         )
         .execute(&mut *tx)
         .await?;
 
-        // 3. Credit the balance to the user's holdings
+        // 4. Zero out all the holdings for the market
+        Self::clear_holdings(&mut *tx, market_id).await?;
+
+        // 5. Realizing the P&L of the market maker (outstanding winning shares are paid out to the holders above)
+        sqlx::query!(
+            r#"
+            UPDATE polymarket.market_maker_accounts
+            SET realized_pnl = cash - (CASE WHEN $2 = 'yes'::polymarket.outcome THEN q_yes ELSE q_no END)
+            WHERE market_id = $1
+            "#,
+            market_id,
+            final_outcome as _
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Pays out LONG holders linearly between the bounds of the scalar market and SHORT holders the rest.
+    /// Market must be resolving, `changed_by` is the admin who settled it
+    pub async fn settle_scalar_market(
+        pg_pool: &PgPool,
+        market_id: &Uuid,
+        resolved_value: Decimal,
+        changed_by: Option<Uuid>,
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pg_pool.begin().await?;
+
+        // 1. Updating the market status to settled
+        Self::settle_resolving_market(&mut tx, market_id, MarketStatus::SETTLED, changed_by)
+            .await?;
+        let bounds = sqlx::query!(
+            r#"
+            UPDATE polymarket.markets
+            SET resolved_value = $2
+            WHERE id = $1 AND market_type = 'scalar'::polymarket.market_type
+            RETURNING lower_bound, upper_bound, unit
+            "#,
+            market_id,
+            resolved_value
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        let (Some(lower_bound), Some(upper_bound)) = (bounds.lower_bound, bounds.upper_bound)
+        else {
+            return Err(sqlx::Error::RowNotFound);
+        };
+        let long_payout = ScalarRange {
+            lower_bound,
+            upper_bound,
+            unit: bounds.unit.unwrap_or_default(),
+        }
+        .long_payout(resolved_value);
+
+        // 2. Expiring all open orders in the market
+        Self::expire_open_orders(&mut *tx, market_id).await?;
+
+        // 3. Credit the balance to the user's holdings, LONG (yes) shares pay `long_payout` and SHORT (no) shares the rest
         sqlx::query!(
             r#"
             UPDATE polymarket.users u
-            SET balance = balance + (payout.total * 100) -- Each share is worth 100 after settlement
+            SET balance = balance + (payout.total * 100) -- Each share is worth at most 100 after settlement
             FROM (
-                SELECT user_id, SUM(shares) AS total
+                SELECT user_id, SUM(
+                    CASE WHEN outcome = 'yes'::polymarket.outcome THEN shares * $2 ELSE shares * (1 - $2) END
+                ) AS total
                 FROM polymarket.user_holdings
-                WHERE market_id = $1 AND outcome = $2
+                WHERE market_id = $1
                 GROUP BY user_id
             ) AS payout
              WHERE u.id = payout.user_id
             "#,
             market_id,
-            final_outcome as _
+            long_payout
         )
         .execute(&mut *tx)
         .await?;
 
         // 4. Zero out all the holdings for the market
+        Self::clear_holdings(&mut *tx, market_id).await?;
+
+        // 5. Realizing the P&L of the market maker
         sqlx::query!(
             r#"
-            UPDATE polymarket.user_holdings
-            SET shares = 0
+            UPDATE polymarket.market_maker_accounts
+            SET realized_pnl = cash - q_yes * $2 - q_no * (1 - $2)
+            WHERE market_id = $1
+            "#,
+            market_id,
+            long_payout
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Voids the market, every share is refunded at the even price (`1 / outcome_count`) whatever the outcome.
+    /// Market must be resolving, `changed_by` is the admin who voided it
+    pub async fn void_market(
+        pg_pool: &PgPool,
+        market_id: &Uuid,
+        changed_by: Option<Uuid>,
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pg_pool.begin().await?;
+
+        // 1. Updating the market status to voided
+        Self::settle_resolving_market(&mut tx, market_id, MarketStatus::VOIDED, changed_by).await?;
+
+        // 2. Expiring all open orders in the market
+        Self::expire_open_orders(&mut *tx, market_id).await?;
+
+        // 3. Refunding the holdings of every outcome
+        sqlx::query!(
+            r#"
+            UPDATE polymarket.users u
+            SET balance = balance + (payout.total * 100)
+            FROM (
+                SELECT h.user_id, SUM(h.shares / m.outcome_count) AS total
+                FROM polymarket.user_holdings h
+                JOIN polymarket.markets m ON m.id = h.market_id
+                WHERE h.market_id = $1
+                GROUP BY h.user_id
+            ) AS payout
+             WHERE u.id = payout.user_id
+            "#,
+            market_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        // 4. Zero out all the holdings for the market
+        Self::clear_holdings(&mut *tx, market_id).await?;
+
+        // 5. Realizing the P&L of the market maker (binary market, both outcomes are refunded at 0.5)
+        sqlx::query!(
+            r#"
+            UPDATE polymarket.market_maker_accounts
+            SET realized_pnl = cash - (q_yes + q_no) / 2
             WHERE market_id = $1
             "#,
             market_id
@@ -376,6 +711,281 @@ impl Market {
 
         Ok(())
     }
+
+    /// Moves the market from `from` to `to` status and records the transition along with the admin who made it (`None` for the system).
+    /// Returns false if the transition is not allowed or the market is not in `from` status anymore
+    pub async fn transition_status<'a>(
+        executor: impl Executor<'a, Database = Postgres>,
+        market_id: &Uuid,
+        from: MarketStatus,
+        to: MarketStatus,
+        changed_by: Option<Uuid>,
+    ) -> Result<bool, sqlx::Error> {
+        if !from.can_transition_to(to) {
+            return Ok(false);
+        }
+
+        let transition_id = sqlx::query_scalar!(
+            r#"
+            WITH updated AS (
+                UPDATE polymarket.markets
+                SET status = $3, updated_at = CURRENT_TIMESTAMP
+                WHERE id = $1 AND status = $2
+                RETURNING id
+            )
+            INSERT INTO polymarket.market_status_transitions (market_id, from_status, to_status, changed_by)
+            SELECT id, $2, $3, $4 FROM updated
+            RETURNING id
+            "#,
+            market_id,
+            from as _,
+            to as _,
+            changed_by
+        )
+        .fetch_optional(executor)
+        .await?;
+
+        Ok(transition_id.is_some())
+    }
+
+    /// Closes the open and paused markets expired at `current_time`, returns the transitions of the closed markets
+    pub async fn close_expired_markets(
+        pg_pool: &PgPool,
+        current_time: NaiveDateTime,
+    ) -> Result<Vec<MarketStatusTransition>, sqlx::Error> {
+        let transitions = sqlx::query_as!(
+            MarketStatusTransition,
+            r#"
+            WITH expired AS (
+                SELECT id, status FROM polymarket.markets
+                WHERE market_expiry <= $1 AND status IN (
+                    'open'::polymarket.market_status,
+                    'paused'::polymarket.market_status
+                )
+                FOR UPDATE
+            ), closed AS (
+                UPDATE polymarket.markets m
+                SET status = 'closed'::polymarket.market_status, updated_at = CURRENT_TIMESTAMP
+                FROM expired
+                WHERE m.id = expired.id
+                RETURNING m.id, expired.status AS from_status
+            )
+            INSERT INTO polymarket.market_status_transitions (market_id, from_status, to_status)
+            SELECT id, from_status, 'closed'::polymarket.market_status FROM closed
+            RETURNING
+                id,
+                market_id,
+                from_status as "from_status: MarketStatus",
+                to_status as "to_status: MarketStatus",
+                changed_by,
+                created_at
+            "#,
+            current_time
+        )
+        .fetch_all(pg_pool)
+        .await?;
+
+        Ok(transitions)
+    }
+
+    /// Bounds of the scalar market
+    pub fn scalar_range(&self) -> Option<ScalarRange> {
+        Some(ScalarRange {
+            lower_bound: self.lower_bound?,
+            upper_bound: self.upper_bound?,
+            unit: self.unit.clone().unwrap_or_default(),
+        })
+    }
+
+    /// Schedules the opening call auction, orders are collected without matching till `opens_at`
+    pub async fn schedule_auction(
+        pg_pool: &PgPool,
+        market_id: &Uuid,
+        opens_at: NaiveDateTime,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE polymarket.markets
+            SET auction_opens_at = $2
+            WHERE id = $1
+            "#,
+            market_id,
+            opens_at
+        )
+        .execute(pg_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Marks the opening call auction as uncrossed, market is matched continuously from now on
+    pub async fn end_auction(pg_pool: &PgPool, market_id: &Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE polymarket.markets
+            SET auction_opens_at = NULL
+            WHERE id = $1
+            "#,
+            market_id
+        )
+        .execute(pg_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub fn price_band(&self) -> Option<PriceBand> {
+        Some(PriceBand {
+            price_band: self.price_band?,
+            window: self.price_band_window,
+            halt_cooldown: self.halt_cooldown,
+        })
+    }
+
+    /// Enables the circuit breaker of the market
+    pub async fn update_price_band(
+        pg_pool: &PgPool,
+        market_id: &Uuid,
+        price_band: &PriceBand,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE polymarket.markets
+            SET price_band = $2,
+                price_band_window = $3,
+                halt_cooldown = $4
+            WHERE id = $1
+            "#,
+            market_id,
+            price_band.price_band,
+            price_band.window,
+            price_band.halt_cooldown
+        )
+        .execute(pg_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Halts the trading of the market for it's cool-down, returns the time trading is resumed at
+    ///
+    /// `None` if the market is already halted (or doesn't exist), so the halt is recorded only once
+    pub async fn halt_trading(
+        pg_pool: &PgPool,
+        market_id: &Uuid,
+        halted_at: NaiveDateTime,
+    ) -> Result<Option<NaiveDateTime>, sqlx::Error> {
+        let halted_until = sqlx::query_scalar!(
+            r#"
+            UPDATE polymarket.markets
+            SET halted_until = $2::timestamp + make_interval(secs => halt_cooldown)
+            WHERE id = $1 AND halted_until IS NULL
+            RETURNING halted_until as "halted_until!"
+            "#,
+            market_id,
+            halted_at
+        )
+        .fetch_optional(pg_pool)
+        .await?;
+
+        Ok(halted_until)
+    }
+
+    /// Markets whose trading halt is over at `current_time`
+    pub async fn get_markets_to_resume(
+        pg_pool: &PgPool,
+        current_time: NaiveDateTime,
+    ) -> Result<Vec<Uuid>, sqlx::Error> {
+        let market_ids = sqlx::query_scalar!(
+            r#"
+            SELECT id FROM polymarket.markets
+            WHERE halted_until <= $1
+            "#,
+            current_time
+        )
+        .fetch_all(pg_pool)
+        .await?;
+
+        Ok(market_ids)
+    }
+
+    pub async fn resume_trading(pg_pool: &PgPool, market_id: &Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE polymarket.markets
+            SET halted_until = NULL
+            WHERE id = $1
+            "#,
+            market_id
+        )
+        .execute(pg_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    // settled and voided markets are final, so they are only reached from the resolving status
+    async fn settle_resolving_market(
+        tx: &mut sqlx::Transaction<'_, Postgres>,
+        market_id: &Uuid,
+        to: MarketStatus,
+        changed_by: Option<Uuid>,
+    ) -> Result<(), sqlx::Error> {
+        let settled = Self::transition_status(
+            &mut **tx,
+            market_id,
+            MarketStatus::RESOLVING,
+            to,
+            changed_by,
+        )
+        .await?;
+        if !settled {
+            return Err(sqlx::Error::RowNotFound); // market is not being resolved
+        }
+
+        Ok(())
+    }
+
+    async fn expire_open_orders<'a>(
+        executor: impl Executor<'a, Database = Postgres>,
+        market_id: &Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE polymarket.orders
+            SET status = 'expired'::polymarket.order_status
+            WHERE market_id = $1 AND status in (
+                'open'::polymarket.order_status,
+                'partial_fill'::polymarket.order_status,
+                'pending_update'::polymarket.order_status,
+                'pending_cancel'::polymarket.order_status
+            )
+            "#,
+            market_id
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn clear_holdings<'a>(
+        executor: impl Executor<'a, Database = Postgres>,
+        market_id: &Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE polymarket.user_holdings
+            SET shares = 0
+            WHERE market_id = $1
+            "#,
+            market_id
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -383,8 +993,11 @@ mod tests {
     use std::env;
 
     use chrono::DateTime;
+    use rust_decimal_macros::dec;
+    use utility_helpers::types::GoogleClaims;
 
     use super::*;
+    use crate::schema::{user_holdings::UserHoldings, users::User};
 
     #[tokio::test]
     async fn test_create_new_market() {
@@ -397,12 +1010,21 @@ mod tests {
         let market_expiry = date_time.naive_utc();
 
         let market = Market::create_new_market(
-            "Test Market 0".to_string(),
-            "Test Description".to_string(),
-            "Test Logo".to_string(),
-            Decimal::new(100, 2),
-            market_expiry,
             &pg_pool,
+            &NewMarket {
+                name: "Test Market 0".to_string(),
+                description: "Test Description".to_string(),
+                logo: "Test Logo".to_string(),
+                liquidity_b: Decimal::new(100, 2),
+                tick_size: Decimal::new(1, 2),
+                min_quantity: Decimal::new(1, 2),
+                lot_size: Decimal::new(1, 2),
+                market_maker: false,
+                price_model: PriceModelKind::LmsrWeighted,
+                outcomes: vec!["Yes".to_string(), "No".to_string()],
+                scalar_range: None,
+                market_expiry,
+            },
         )
         .await
         .unwrap();
@@ -461,12 +1083,21 @@ mod tests {
         let market_expiry = date_time.naive_utc();
 
         let market = Market::create_new_market(
-            "Test Market 0".to_string(),
-            "Test Description".to_string(),
-            "Test Logo".to_string(),
-            Decimal::new(100, 2),
-            market_expiry,
             &pg_pool,
+            &NewMarket {
+                name: "Test Market 0".to_string(),
+                description: "Test Description".to_string(),
+                logo: "Test Logo".to_string(),
+                liquidity_b: Decimal::new(100, 2),
+                tick_size: Decimal::new(1, 2),
+                min_quantity: Decimal::new(1, 2),
+                lot_size: Decimal::new(1, 2),
+                market_maker: false,
+                price_model: PriceModelKind::LmsrWeighted,
+                outcomes: vec!["Yes".to_string(), "No".to_string()],
+                scalar_range: None,
+                market_expiry,
+            },
         )
         .await
         .unwrap();
@@ -494,4 +1125,241 @@ mod tests {
         .await
         .unwrap();
     }
+
+    #[test]
+    fn test_scalar_long_payout() {
+        let range = ScalarRange {
+            lower_bound: dec!(50000),
+            upper_bound: dec!(100000),
+            unit: "USD".to_string(),
+        };
+
+        assert_eq!(range.long_payout(dec!(62500)), dec!(0.25));
+        // clamped to the bounds
+        assert_eq!(range.long_payout(dec!(40000)), Decimal::ZERO);
+        assert_eq!(range.long_payout(dec!(120000)), Decimal::ONE);
+    }
+
+    async fn start_resolving(pg_pool: &PgPool, market_id: &Uuid) {
+        for (from, to) in [
+            (MarketStatus::DRAFT, MarketStatus::OPEN),
+            (MarketStatus::OPEN, MarketStatus::CLOSED),
+            (MarketStatus::CLOSED, MarketStatus::RESOLVING),
+        ] {
+            assert!(
+                Market::transition_status(pg_pool, market_id, from, to, None)
+                    .await
+                    .unwrap()
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_settle_scalar_market() {
+        dotenv::dotenv().ok();
+
+        let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let pg_pool = PgPool::connect(&database_url).await.unwrap();
+
+        let user = User::create_new_user(
+            &pg_pool,
+            &GoogleClaims {
+                email: "scalar_market@gmail.com".to_string(),
+                exp: 0,
+                name: "temp".to_string(),
+                picture: "temp".to_string(),
+                sub: "scalar_market".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+
+        let market_expiry = DateTime::parse_from_rfc3339("2025-06-20T12:28:33.675Z")
+            .unwrap()
+            .naive_utc();
+        let market = Market::create_new_market(
+            &pg_pool,
+            &NewMarket {
+                name: "Test Scalar Market".to_string(),
+                description: "Test Description".to_string(),
+                logo: "Test Logo".to_string(),
+                liquidity_b: dec!(100),
+                tick_size: dec!(0.01),
+                min_quantity: dec!(0.01),
+                lot_size: dec!(0.01),
+                market_maker: false,
+                price_model: PriceModelKind::Midpoint,
+                outcomes: vec!["Long".to_string(), "Short".to_string()],
+                scalar_range: Some(ScalarRange {
+                    lower_bound: dec!(50000),
+                    upper_bound: dec!(100000),
+                    unit: "USD".to_string(),
+                }),
+                market_expiry,
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(market.market_type, MarketType::Scalar);
+        assert_eq!(market.scalar_range().unwrap().unit, "USD");
+
+        UserHoldings::update_user_holdings(&pg_pool, user.id, market.id, dec!(10), Outcome::YES, 0)
+            .await
+            .unwrap();
+        UserHoldings::update_user_holdings(&pg_pool, user.id, market.id, dec!(4), Outcome::NO, 0)
+            .await
+            .unwrap();
+        let balance = User::get_user_balance(&pg_pool, user.id).await.unwrap();
+
+        // only resolving markets are settled
+        assert!(
+            Market::settle_scalar_market(&pg_pool, &market.id, dec!(62500), None)
+                .await
+                .is_err()
+        );
+        start_resolving(&pg_pool, &market.id).await;
+        Market::settle_scalar_market(&pg_pool, &market.id, dec!(62500), None)
+            .await
+            .unwrap();
+
+        // LONG pays 0.25 and SHORT 0.75, (10 * 0.25 + 4 * 0.75) * 100
+        let settled_balance = User::get_user_balance(&pg_pool, user.id).await.unwrap();
+        assert_eq!(settled_balance - balance, dec!(550));
+        let market = Market::get_market_by_id(&pg_pool, &market.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(market.status, MarketStatus::SETTLED);
+        assert_eq!(market.resolved_value, Some(dec!(62500)));
+
+        // binary markets can't be settled with a value
+        let binary_market = Market::create_new_market(
+            &pg_pool,
+            &NewMarket {
+                name: "Test Binary Market".to_string(),
+                description: "Test Description".to_string(),
+                logo: "Test Logo".to_string(),
+                liquidity_b: dec!(100),
+                tick_size: dec!(0.01),
+                min_quantity: dec!(0.01),
+                lot_size: dec!(0.01),
+                market_maker: false,
+                price_model: PriceModelKind::Midpoint,
+                outcomes: vec!["Yes".to_string(), "No".to_string()],
+                scalar_range: None,
+                market_expiry,
+            },
+        )
+        .await
+        .unwrap();
+        start_resolving(&pg_pool, &binary_market.id).await;
+        assert!(
+            Market::settle_scalar_market(&pg_pool, &binary_market.id, dec!(1), None)
+                .await
+                .is_err()
+        );
+
+        // Clean up
+        sqlx::query(r#"DELETE FROM "polymarket"."user_holdings" WHERE user_id = $1"#)
+            .bind(user.id)
+            .execute(&pg_pool)
+            .await
+            .unwrap();
+        sqlx::query(r#"DELETE FROM "polymarket"."markets" WHERE id = ANY($1)"#)
+            .bind(vec![market.id, binary_market.id])
+            .execute(&pg_pool)
+            .await
+            .unwrap();
+        sqlx::query(r#"DELETE FROM "polymarket"."users" WHERE id = $1"#)
+            .bind(user.id)
+            .execute(&pg_pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_trading_halt() {
+        dotenv::dotenv().ok();
+
+        let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let pg_pool = PgPool::connect(&database_url).await.unwrap();
+
+        let market_expiry = DateTime::parse_from_rfc3339("2025-06-20T12:28:33.675Z")
+            .unwrap()
+            .naive_utc();
+        let market = Market::create_new_market(
+            &pg_pool,
+            &NewMarket {
+                name: "Test Circuit Breaker".to_string(),
+                description: "Test Description".to_string(),
+                logo: "Test Logo".to_string(),
+                liquidity_b: dec!(100),
+                tick_size: dec!(0.01),
+                min_quantity: dec!(0.01),
+                lot_size: dec!(0.01),
+                market_maker: false,
+                price_model: PriceModelKind::Midpoint,
+                outcomes: vec!["Yes".to_string(), "No".to_string()],
+                scalar_range: None,
+                market_expiry,
+            },
+        )
+        .await
+        .unwrap();
+        assert!(market.price_band().is_none());
+
+        Market::update_price_band(
+            &pg_pool,
+            &market.id,
+            &PriceBand {
+                price_band: dec!(0.15),
+                window: 300,
+                halt_cooldown: 90,
+            },
+        )
+        .await
+        .unwrap();
+
+        let halted_at = DateTime::parse_from_rfc3339("2025-06-01T10:00:00Z")
+            .unwrap()
+            .naive_utc();
+        let halted_until = Market::halt_trading(&pg_pool, &market.id, halted_at)
+            .await
+            .unwrap();
+        assert_eq!(
+            halted_until,
+            Some(halted_at + chrono::Duration::seconds(90))
+        );
+
+        // halt is recorded only once
+        let halted_again = Market::halt_trading(&pg_pool, &market.id, halted_at)
+            .await
+            .unwrap();
+        assert_eq!(halted_again, None);
+
+        let before_resume = halted_at + chrono::Duration::seconds(60);
+        let markets = Market::get_markets_to_resume(&pg_pool, before_resume)
+            .await
+            .unwrap();
+        assert!(!markets.contains(&market.id));
+        let markets = Market::get_markets_to_resume(&pg_pool, halted_until.unwrap())
+            .await
+            .unwrap();
+        assert!(markets.contains(&market.id));
+
+        Market::resume_trading(&pg_pool, &market.id).await.unwrap();
+        let market = Market::get_market_by_id(&pg_pool, &market.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(market.halted_until, None);
+        assert_eq!(market.price_band().unwrap().price_band, dec!(0.15));
+
+        // Clean up
+        sqlx::query(r#"DELETE FROM "polymarket"."markets" WHERE id = $1"#)
+            .bind(market.id)
+            .execute(&pg_pool)
+            .await
+            .unwrap();
+    }
 }
@@ -0,0 +1,234 @@
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use sqlx::{Executor, PgPool, Postgres};
+use uuid::Uuid;
+
+use super::enums::{OrderSide, Outcome};
+
+/// System account of the market's LMSR market maker, quantities and cash are in share units (a winning share pays 1)
+#[derive(Debug, Serialize, sqlx::FromRow, Default)]
+pub struct MarketMakerAccount {
+    pub market_id: Uuid,
+    pub liquidity_b: Decimal,
+    pub subsidy: Decimal,              // max loss of the market maker, b * ln(2)
+    pub q_yes: Decimal,                // outstanding yes shares sold by the market maker
+    pub q_no: Decimal,                 // outstanding no shares sold by the market maker
+    pub cash: Decimal,                 // collected from the traders (buys - sells)
+    pub realized_pnl: Option<Decimal>, // none till the market is settled
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl MarketMakerAccount {
+    /// P&L of the market maker if the market is resolved to `outcome`, never below `-subsidy`
+    pub fn pnl(&self, outcome: Outcome) -> Decimal {
+        match outcome {
+            Outcome::YES => self.cash - self.q_yes,
+            Outcome::NO => self.cash - self.q_no,
+            Outcome::UNSPECIFIED => self.cash,
+        }
+    }
+
+    pub async fn get_account_by_market_id(
+        pool: &PgPool,
+        market_id: Uuid,
+    ) -> Result<Option<MarketMakerAccount>, sqlx::Error> {
+        let account = sqlx::query_as!(
+            MarketMakerAccount,
+            r#"
+            SELECT
+                market_id, liquidity_b, subsidy, q_yes, q_no, cash, realized_pnl, created_at, updated_at
+            FROM polymarket.market_maker_accounts
+            WHERE market_id = $1
+            "#,
+            market_id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(account)
+    }
+
+    /// Accounts of the markets whose order book is live (their market makers are restored in the order book)
+    pub async fn get_live_market_accounts(
+        pool: &PgPool,
+    ) -> Result<Vec<MarketMakerAccount>, sqlx::Error> {
+        let accounts = sqlx::query_as!(
+            MarketMakerAccount,
+            r#"
+            SELECT
+                a.market_id, a.liquidity_b, a.subsidy, a.q_yes, a.q_no, a.cash, a.realized_pnl, a.created_at, a.updated_at
+            FROM polymarket.market_maker_accounts a
+            JOIN polymarket.markets m ON m.id = a.market_id
+            WHERE m.status IN (
+                'open'::polymarket.market_status,
+                'paused'::polymarket.market_status,
+                'closed'::polymarket.market_status,
+                'resolving'::polymarket.market_status
+            )
+            "#
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(accounts)
+    }
+
+    /// Moves the inventory and cash of the market maker by the trade of the user (`side` is the side of the user)
+    pub async fn apply_trade<'a>(
+        executor: impl Executor<'a, Database = Postgres>,
+        market_id: Uuid,
+        outcome: Outcome,
+        side: OrderSide,
+        quantity: Decimal,
+        amount: Decimal,
+    ) -> Result<(), sqlx::Error> {
+        let (quantity, amount) = match side {
+            OrderSide::BUY => (quantity, amount),
+            OrderSide::SELL => (-quantity, -amount),
+        };
+        let (q_yes, q_no) = match outcome {
+            Outcome::YES => (quantity, Decimal::ZERO),
+            _ => (Decimal::ZERO, quantity),
+        };
+
+        sqlx::query!(
+            r#"
+            UPDATE polymarket.market_maker_accounts
+            SET q_yes = q_yes + $2,
+                q_no = q_no + $3,
+                cash = cash + $4
+            WHERE market_id = $1
+            "#,
+            market_id,
+            q_yes,
+            q_no,
+            amount
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::DateTime;
+    use rust_decimal_macros::dec;
+
+    use super::*;
+    use crate::schema::{
+        enums::{MarketStatus, PriceModelKind},
+        market::{Market, NewMarket},
+    };
+
+    #[tokio::test]
+    async fn test_market_maker_account() {
+        dotenv::dotenv().ok();
+        let pool = PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+
+        let market_expiry = DateTime::parse_from_rfc3339("2025-06-20T12:28:33.675Z")
+            .unwrap()
+            .naive_utc();
+        let market = Market::create_new_market(
+            &pool,
+            &NewMarket {
+                name: "Test Market Maker".to_string(),
+                description: "Test Description".to_string(),
+                logo: "Test Logo".to_string(),
+                liquidity_b: dec!(100),
+                tick_size: dec!(0.01),
+                min_quantity: dec!(0.01),
+                lot_size: dec!(0.01),
+                market_maker: true,
+                price_model: PriceModelKind::LmsrWeighted,
+                outcomes: vec!["Yes".to_string(), "No".to_string()],
+                scalar_range: None,
+                market_expiry,
+            },
+        )
+        .await
+        .unwrap();
+
+        // account is created along with the market
+        let account = MarketMakerAccount::get_account_by_market_id(&pool, market.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(account.liquidity_b, dec!(100));
+        assert_eq!(account.subsidy.round_dp(6), dec!(69.314718));
+        assert_eq!(account.cash, Decimal::ZERO);
+
+        MarketMakerAccount::apply_trade(
+            &pool,
+            market.id,
+            Outcome::YES,
+            OrderSide::BUY,
+            dec!(10),
+            dec!(5.12),
+        )
+        .await
+        .unwrap();
+        MarketMakerAccount::apply_trade(
+            &pool,
+            market.id,
+            Outcome::YES,
+            OrderSide::SELL,
+            dec!(4),
+            dec!(2.1),
+        )
+        .await
+        .unwrap();
+        MarketMakerAccount::apply_trade(
+            &pool,
+            market.id,
+            Outcome::NO,
+            OrderSide::BUY,
+            dec!(3),
+            dec!(1.4),
+        )
+        .await
+        .unwrap();
+
+        let account = MarketMakerAccount::get_account_by_market_id(&pool, market.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(account.q_yes, dec!(6));
+        assert_eq!(account.q_no, dec!(3));
+        assert_eq!(account.cash, dec!(4.42));
+        assert_eq!(account.pnl(Outcome::YES), dec!(-1.58));
+        assert_eq!(account.pnl(Outcome::NO), dec!(1.42));
+
+        // settlement realizes the P&L of the winning outcome
+        for (from, to) in [
+            (MarketStatus::DRAFT, MarketStatus::OPEN),
+            (MarketStatus::OPEN, MarketStatus::CLOSED),
+            (MarketStatus::CLOSED, MarketStatus::RESOLVING),
+        ] {
+            Market::transition_status(&pool, &market.id, from, to, None)
+                .await
+                .unwrap();
+        }
+        Market::settle_market(&pool, &market.id, Outcome::NO, None, None)
+            .await
+            .unwrap();
+        let account = MarketMakerAccount::get_account_by_market_id(&pool, market.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(account.realized_pnl, Some(dec!(1.42)));
+
+        // Clean up (account is deleted along with the market)
+        sqlx::query(r#"DELETE FROM "polymarket"."markets" WHERE id = $1"#)
+            .bind(market.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        pool.close().await;
+    }
+}
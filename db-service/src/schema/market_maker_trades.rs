@@ -0,0 +1,72 @@
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use sqlx::{Executor, PgPool, Postgres};
+use uuid::Uuid;
+
+use super::enums::{OrderSide, Outcome};
+
+/// Trade of the user against the market maker, `side` is the side of the user
+#[derive(Debug, Serialize, sqlx::FromRow, Default)]
+pub struct MarketMakerTrade {
+    pub id: Uuid,
+    pub market_id: Uuid,
+    pub order_id: Uuid,
+    pub user_id: Uuid,
+    pub outcome: Outcome,
+    pub side: OrderSide,
+    pub price: Decimal, // average price of the traded quantity
+    pub quantity: Decimal,
+    pub created_at: NaiveDateTime,
+}
+
+impl MarketMakerTrade {
+    /// Saves the trade (`id` and `created_at` are set by the database)
+    pub async fn create<'a>(
+        &self,
+        executor: impl Executor<'a, Database = Postgres>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO polymarket.market_maker_trades (
+                market_id, order_id, user_id, outcome, side, price, quantity
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+            self.market_id,
+            self.order_id,
+            self.user_id,
+            self.outcome as _,
+            self.side as _,
+            self.price,
+            self.quantity
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_trades_by_market_id(
+        pool: &PgPool,
+        market_id: Uuid,
+    ) -> Result<Vec<MarketMakerTrade>, sqlx::Error> {
+        let trades = sqlx::query_as!(
+            MarketMakerTrade,
+            r#"
+            SELECT
+                id, market_id, order_id, user_id,
+                outcome as "outcome: Outcome",
+                side as "side: OrderSide",
+                price, quantity, created_at
+            FROM polymarket.market_maker_trades
+            WHERE market_id = $1
+            ORDER BY created_at DESC
+            "#,
+            market_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(trades)
+    }
+}
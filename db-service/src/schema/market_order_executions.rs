@@ -0,0 +1,208 @@
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(Debug, Serialize, sqlx::FromRow, Default)]
+pub struct MarketOrderExecution {
+    pub order_id: Uuid,
+    pub budget: Decimal,
+    pub price_limit: Option<Decimal>, // worst price the order is allowed to match at
+    pub max_slippage: Option<Decimal>, // relative to the midpoint at the time of matching
+    pub average_price: Option<Decimal>, // none if the order is not matched at all
+    pub filled_quantity: Decimal,
+    pub levels_consumed: i32,
+    pub unfilled_budget: Decimal,
+    pub created_at: NaiveDateTime,
+}
+
+impl MarketOrderExecution {
+    /// Saves the execution summary of the market order (`created_at` is set by the database), existing summary of the order is kept as it is
+    pub async fn create(&self, pool: &PgPool) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO polymarket.market_order_executions (
+                order_id, budget, price_limit, max_slippage, average_price, filled_quantity, levels_consumed, unfilled_budget
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (order_id) DO NOTHING
+            "#,
+            self.order_id,
+            self.budget,
+            self.price_limit,
+            self.max_slippage,
+            self.average_price,
+            self.filled_quantity,
+            self.levels_consumed,
+            self.unfilled_budget
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_user_execution_by_order_id(
+        pool: &PgPool,
+        user_id: Uuid,
+        order_id: Uuid,
+    ) -> Result<Option<MarketOrderExecution>, sqlx::Error> {
+        let execution = sqlx::query_as!(
+            MarketOrderExecution,
+            r#"
+            SELECT
+                e.order_id, e.budget, e.price_limit, e.max_slippage, e.average_price,
+                e.filled_quantity, e.levels_consumed, e.unfilled_budget, e.created_at
+            FROM polymarket.market_order_executions e
+            JOIN polymarket.orders o ON o.id = e.order_id
+            WHERE e.order_id = $1 AND o.user_id = $2
+            "#,
+            order_id,
+            user_id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(execution)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::DateTime;
+    use rust_decimal_macros::dec;
+    use utility_helpers::types::GoogleClaims;
+
+    use super::*;
+    use crate::schema::{
+        enums::{OrderSide, OrderType, Outcome, PriceModelKind, SelfTradePrevention, TimeInForce},
+        market::{Market, NewMarket},
+        orders::{NewOrder, Order},
+        users::User,
+    };
+
+    #[tokio::test]
+    async fn test_create_and_get_execution() {
+        dotenv::dotenv().ok();
+        let pool = PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+
+        let user = User::create_new_user(
+            &pool,
+            &GoogleClaims {
+                email: "market_order_execution@gmail.com".to_string(),
+                exp: 0,
+                name: "temp".to_string(),
+                picture: "temp".to_string(),
+                sub: "market_order_execution".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+
+        let market_expiry = DateTime::parse_from_rfc3339("2025-06-20T12:28:33.675Z")
+            .unwrap()
+            .naive_utc();
+        let market = Market::create_new_market(
+            &pool,
+            &NewMarket {
+                name: "Test Market Execution".to_string(),
+                description: "Test Description".to_string(),
+                logo: "Test Logo".to_string(),
+                liquidity_b: dec!(1),
+                tick_size: dec!(0.01),
+                min_quantity: dec!(0.01),
+                lot_size: dec!(0.01),
+                market_maker: false,
+                price_model: PriceModelKind::LmsrWeighted,
+                outcomes: vec!["Yes".to_string(), "No".to_string()],
+                scalar_range: None,
+                market_expiry,
+            },
+        )
+        .await
+        .unwrap();
+
+        let order = Order::create_order(
+            &pool,
+            &NewOrder {
+                user_id: user.id,
+                market_id: market.id,
+                price: Decimal::ZERO,
+                quantity: Decimal::ZERO,
+                side: OrderSide::BUY,
+                outcome: Outcome::YES,
+                order_type: OrderType::MARKET,
+                trigger_price: None,
+                time_in_force: TimeInForce::FOK,
+                expires_at: None,
+                post_only: false,
+                self_trade_prevention: SelfTradePrevention::CancelNewest,
+                display_quantity: None,
+                outcome_index: 0,
+            },
+        )
+        .await
+        .unwrap();
+
+        MarketOrderExecution {
+            order_id: order.id,
+            budget: dec!(5),
+            price_limit: Some(dec!(0.6)),
+            average_price: Some(dec!(0.55)),
+            filled_quantity: dec!(8),
+            levels_consumed: 2,
+            unfilled_budget: dec!(0.6),
+            ..Default::default()
+        }
+        .create(&pool)
+        .await
+        .unwrap();
+        // summary is saved only once
+        MarketOrderExecution {
+            order_id: order.id,
+            budget: dec!(5),
+            unfilled_budget: dec!(5),
+            ..Default::default()
+        }
+        .create(&pool)
+        .await
+        .unwrap();
+
+        let execution =
+            MarketOrderExecution::get_user_execution_by_order_id(&pool, user.id, order.id)
+                .await
+                .unwrap()
+                .unwrap();
+        assert_eq!(execution.price_limit, Some(dec!(0.6)));
+        assert_eq!(execution.average_price, Some(dec!(0.55)));
+        assert_eq!(execution.levels_consumed, 2);
+        assert_eq!(execution.unfilled_budget, dec!(0.6));
+
+        // other users can't see the execution
+        let other_user_execution =
+            MarketOrderExecution::get_user_execution_by_order_id(&pool, market.id, order.id)
+                .await
+                .unwrap();
+        assert!(other_user_execution.is_none());
+
+        // Clean up (execution is deleted along with the order)
+        sqlx::query(r#"DELETE FROM "polymarket"."orders" WHERE id = $1"#)
+            .bind(order.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(r#"DELETE FROM "polymarket"."markets" WHERE id = $1"#)
+            .bind(market.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(r#"DELETE FROM "polymarket"."users" WHERE id = $1"#)
+            .bind(user.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        pool.close().await;
+    }
+}
@@ -0,0 +1,169 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::{Executor, PgPool, Postgres};
+use uuid::Uuid;
+
+/// Named outcome of the market, binary market has `Yes` (0) and `No` (1) outcomes
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, Default, Clone)]
+pub struct MarketOutcome {
+    pub id: Uuid,
+    pub market_id: Uuid,
+    pub outcome_index: i32,
+    pub name: String,
+    pub created_at: NaiveDateTime,
+}
+
+impl MarketOutcome {
+    /// Creates the outcomes of the market, indexed by their position in `names`
+    pub async fn create_market_outcomes<'a>(
+        executor: impl Executor<'a, Database = Postgres>,
+        market_id: Uuid,
+        names: &[String],
+    ) -> Result<Vec<MarketOutcome>, sqlx::Error> {
+        let indexes = (0..names.len() as i32).collect::<Vec<_>>();
+
+        let outcomes = sqlx::query_as!(
+            MarketOutcome,
+            r#"
+            INSERT INTO polymarket.market_outcomes (market_id, outcome_index, name)
+            SELECT $1, outcome_index, name
+            FROM UNNEST($2::integer[], $3::varchar[]) AS o(outcome_index, name)
+            RETURNING id, market_id, outcome_index, name, created_at
+            "#,
+            market_id,
+            &indexes,
+            names
+        )
+        .fetch_all(executor)
+        .await?;
+
+        Ok(outcomes)
+    }
+
+    pub async fn get_outcomes_by_market_id(
+        pool: &PgPool,
+        market_id: Uuid,
+    ) -> Result<Vec<MarketOutcome>, sqlx::Error> {
+        let outcomes = sqlx::query_as!(
+            MarketOutcome,
+            r#"
+            SELECT id, market_id, outcome_index, name, created_at
+            FROM polymarket.market_outcomes
+            WHERE market_id = $1
+            ORDER BY outcome_index
+            "#,
+            market_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(outcomes)
+    }
+
+    /// Outcomes of all the given markets (used by paginated market lists), ordered by their index
+    pub async fn get_outcomes_by_market_ids(
+        pool: &PgPool,
+        market_ids: &[Uuid],
+    ) -> Result<Vec<MarketOutcome>, sqlx::Error> {
+        let outcomes = sqlx::query_as!(
+            MarketOutcome,
+            r#"
+            SELECT id, market_id, outcome_index, name, created_at
+            FROM polymarket.market_outcomes
+            WHERE market_id = ANY($1)
+            ORDER BY market_id, outcome_index
+            "#,
+            market_ids
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(outcomes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::DateTime;
+    use rust_decimal_macros::dec;
+
+    use super::*;
+    use crate::schema::{
+        enums::{MarketStatus, MarketType, Outcome, PriceModelKind},
+        market::{Market, NewMarket},
+    };
+
+    #[tokio::test]
+    async fn test_market_outcomes() {
+        dotenv::dotenv().ok();
+        let pool = PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+
+        let market_expiry = DateTime::parse_from_rfc3339("2025-06-20T12:28:33.675Z")
+            .unwrap()
+            .naive_utc();
+        let market = Market::create_new_market(
+            &pool,
+            &NewMarket {
+                name: "Test Categorical Market".to_string(),
+                description: "Test Description".to_string(),
+                logo: "Test Logo".to_string(),
+                liquidity_b: dec!(100),
+                tick_size: dec!(0.01),
+                min_quantity: dec!(0.01),
+                lot_size: dec!(0.01),
+                market_maker: false,
+                price_model: PriceModelKind::Midpoint,
+                outcomes: vec!["Red".to_string(), "Green".to_string(), "Blue".to_string()],
+                scalar_range: None,
+                market_expiry,
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(market.market_type, MarketType::Categorical);
+        assert_eq!(market.outcome_count, 3);
+
+        let outcomes = MarketOutcome::get_outcomes_by_market_id(&pool, market.id)
+            .await
+            .unwrap();
+        let names = outcomes.iter().map(|o| o.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, ["Red", "Green", "Blue"]);
+        assert_eq!(outcomes[2].outcome_index, 2);
+
+        let listed_outcomes = MarketOutcome::get_outcomes_by_market_ids(&pool, &[market.id])
+            .await
+            .unwrap();
+        assert_eq!(listed_outcomes.len(), 3);
+        assert_eq!(listed_outcomes[0].name, "Red");
+
+        // settlement pays the yes shares of one of the outcomes
+        for (from, to) in [
+            (MarketStatus::DRAFT, MarketStatus::OPEN),
+            (MarketStatus::OPEN, MarketStatus::CLOSED),
+            (MarketStatus::CLOSED, MarketStatus::RESOLVING),
+        ] {
+            Market::transition_status(&pool, &market.id, from, to, None)
+                .await
+                .unwrap();
+        }
+        Market::settle_market(&pool, &market.id, Outcome::YES, Some(1), None)
+            .await
+            .unwrap();
+        let market = Market::get_market_by_id(&pool, &market.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(market.final_outcome_index, Some(1));
+
+        // Clean up (outcomes are deleted along with the market)
+        sqlx::query(r#"DELETE FROM "polymarket"."markets" WHERE id = $1"#)
+            .bind(market.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        pool.close().await;
+    }
+}
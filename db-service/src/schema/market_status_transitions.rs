@@ -0,0 +1,218 @@
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::enums::MarketStatus;
+
+/// Status change of the market, transitions are made through `Market::transition_status`
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct MarketStatusTransition {
+    pub id: Uuid,
+    pub market_id: Uuid,
+    pub from_status: MarketStatus,
+    pub to_status: MarketStatus,
+    pub changed_by: Option<Uuid>, // admin who made the transition, none for the expiry of the market
+    pub created_at: NaiveDateTime,
+}
+
+impl MarketStatusTransition {
+    /// Transitions of the market, oldest first
+    pub async fn get_transitions_by_market_id(
+        pool: &PgPool,
+        market_id: &Uuid,
+    ) -> Result<Vec<MarketStatusTransition>, sqlx::Error> {
+        let transitions = sqlx::query_as!(
+            MarketStatusTransition,
+            r#"
+            SELECT
+                id,
+                market_id,
+                from_status as "from_status: MarketStatus",
+                to_status as "to_status: MarketStatus",
+                changed_by,
+                created_at
+            FROM polymarket.market_status_transitions
+            WHERE market_id = $1
+            ORDER BY created_at ASC
+            "#,
+            market_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(transitions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::DateTime;
+    use rust_decimal_macros::dec;
+    use utility_helpers::types::GoogleClaims;
+
+    use super::*;
+    use crate::schema::{
+        enums::{Outcome, PriceModelKind},
+        market::{Market, NewMarket},
+        user_holdings::UserHoldings,
+        users::User,
+    };
+
+    #[tokio::test]
+    async fn test_market_lifecycle() {
+        dotenv::dotenv().ok();
+        let pool = PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+
+        let admin = User::create_new_user(
+            &pool,
+            &GoogleClaims {
+                email: "market_lifecycle@gmail.com".to_string(),
+                exp: 0,
+                name: "temp".to_string(),
+                picture: "temp".to_string(),
+                sub: "market_lifecycle".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+
+        // expires before the markets of other tests, so only this market is closed below
+        let market_expiry = DateTime::parse_from_rfc3339("2025-01-01T00:00:00Z")
+            .unwrap()
+            .naive_utc();
+        let market = Market::create_new_market(
+            &pool,
+            &NewMarket {
+                name: "Test Market Lifecycle".to_string(),
+                description: "Test Description".to_string(),
+                logo: "Test Logo".to_string(),
+                liquidity_b: dec!(100),
+                tick_size: dec!(0.01),
+                min_quantity: dec!(0.01),
+                lot_size: dec!(0.01),
+                market_maker: false,
+                price_model: PriceModelKind::Midpoint,
+                outcomes: vec!["Yes".to_string(), "No".to_string()],
+                scalar_range: None,
+                market_expiry,
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(market.status, MarketStatus::DRAFT);
+
+        // draft market can't be closed
+        assert!(
+            !Market::transition_status(
+                &pool,
+                &market.id,
+                MarketStatus::DRAFT,
+                MarketStatus::CLOSED,
+                Some(admin.id)
+            )
+            .await
+            .unwrap()
+        );
+        for (from, to) in [
+            (MarketStatus::DRAFT, MarketStatus::OPEN),
+            (MarketStatus::OPEN, MarketStatus::PAUSED),
+        ] {
+            assert!(
+                Market::transition_status(&pool, &market.id, from, to, Some(admin.id))
+                    .await
+                    .unwrap()
+            );
+        }
+        // market is not open anymore
+        assert!(
+            !Market::transition_status(
+                &pool,
+                &market.id,
+                MarketStatus::OPEN,
+                MarketStatus::PAUSED,
+                Some(admin.id)
+            )
+            .await
+            .unwrap()
+        );
+
+        // expired paused market is closed without an admin
+        let closed_markets = Market::close_expired_markets(&pool, market_expiry)
+            .await
+            .unwrap();
+        assert!(closed_markets.iter().any(|t| t.market_id == market.id
+            && t.from_status == MarketStatus::PAUSED
+            && t.changed_by.is_none()));
+
+        UserHoldings::update_user_holdings(&pool, admin.id, market.id, dec!(10), Outcome::YES, 0)
+            .await
+            .unwrap();
+        let balance = User::get_user_balance(&pool, admin.id).await.unwrap();
+
+        // closed market must be resolved before it's voided
+        assert!(
+            Market::void_market(&pool, &market.id, Some(admin.id))
+                .await
+                .is_err()
+        );
+        assert!(
+            Market::transition_status(
+                &pool,
+                &market.id,
+                MarketStatus::CLOSED,
+                MarketStatus::RESOLVING,
+                Some(admin.id)
+            )
+            .await
+            .unwrap()
+        );
+        Market::void_market(&pool, &market.id, Some(admin.id))
+            .await
+            .unwrap();
+
+        // every share is refunded at 0.5, 10 * 0.5 * 100
+        let voided_balance = User::get_user_balance(&pool, admin.id).await.unwrap();
+        assert_eq!(voided_balance - balance, dec!(500));
+
+        let transitions = MarketStatusTransition::get_transitions_by_market_id(&pool, &market.id)
+            .await
+            .unwrap();
+        let statuses = transitions
+            .iter()
+            .map(|t| (t.from_status, t.to_status))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            statuses,
+            vec![
+                (MarketStatus::DRAFT, MarketStatus::OPEN),
+                (MarketStatus::OPEN, MarketStatus::PAUSED),
+                (MarketStatus::PAUSED, MarketStatus::CLOSED),
+                (MarketStatus::CLOSED, MarketStatus::RESOLVING),
+                (MarketStatus::RESOLVING, MarketStatus::VOIDED),
+            ]
+        );
+        assert_eq!(transitions[2].changed_by, None);
+        assert_eq!(transitions[4].changed_by, Some(admin.id));
+
+        // Clean up (transitions are deleted along with the market)
+        sqlx::query(r#"DELETE FROM "polymarket"."user_holdings" WHERE user_id = $1"#)
+            .bind(admin.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(r#"DELETE FROM "polymarket"."markets" WHERE id = $1"#)
+            .bind(market.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(r#"DELETE FROM "polymarket"."users" WHERE id = $1"#)
+            .bind(admin.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        pool.close().await;
+    }
+}
@@ -1,6 +1,14 @@
 pub mod enums;
 pub mod market;
+pub mod market_maker_accounts;
+pub mod market_maker_trades;
+pub mod market_order_executions;
+pub mod market_outcomes;
+pub mod market_status_transitions;
+pub mod order_book_snapshots;
+pub mod order_groups;
 pub mod orders;
+pub mod trailing_stops;
 pub mod user_holdings;
 pub mod user_trades;
 pub mod user_transactions;
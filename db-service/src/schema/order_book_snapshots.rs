@@ -0,0 +1,113 @@
+use chrono::NaiveDateTime;
+use sqlx::PgPool;
+use utility_helpers::log_info;
+use uuid::Uuid;
+
+#[derive(Debug, sqlx::FromRow, Default)]
+pub struct OrderBookSnapshot {
+    pub id: Uuid,
+    pub stream_sequence: i64,
+    pub snapshot: Vec<u8>,
+    pub created_at: NaiveDateTime,
+}
+
+impl OrderBookSnapshot {
+    pub async fn create_snapshot(
+        pool: &PgPool,
+        stream_sequence: i64,
+        snapshot: Vec<u8>,
+    ) -> Result<OrderBookSnapshot, sqlx::Error> {
+        let snapshot = sqlx::query_as!(
+            OrderBookSnapshot,
+            r#"
+            INSERT INTO polymarket.order_book_snapshots (stream_sequence, snapshot)
+            VALUES ($1, $2)
+            RETURNING id, stream_sequence, snapshot, created_at
+            "#,
+            stream_sequence,
+            snapshot
+        )
+        .fetch_one(pool)
+        .await?;
+
+        log_info!(
+            "Order book snapshot created - {:?} (sequence {})",
+            snapshot.id,
+            snapshot.stream_sequence
+        );
+        Ok(snapshot)
+    }
+
+    pub async fn get_latest_snapshot(
+        pool: &PgPool,
+    ) -> Result<Option<OrderBookSnapshot>, sqlx::Error> {
+        let snapshot = sqlx::query_as!(
+            OrderBookSnapshot,
+            r#"
+            SELECT id, stream_sequence, snapshot, created_at
+            FROM polymarket.order_book_snapshots
+            ORDER BY stream_sequence DESC, created_at DESC
+            LIMIT 1
+            "#
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(snapshot)
+    }
+
+    /// Deletes all snapshots except the latest `keep` snapshots
+    pub async fn delete_old_snapshots(pool: &PgPool, keep: i64) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM polymarket.order_book_snapshots
+            WHERE id NOT IN (
+                SELECT id FROM polymarket.order_book_snapshots
+                ORDER BY stream_sequence DESC, created_at DESC
+                LIMIT $1
+            )
+            "#,
+            keep
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_create_and_get_latest_snapshot() {
+        dotenv::dotenv().ok();
+
+        let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let pool = PgPool::connect(&database_url).await.unwrap();
+
+        let stream_sequence = i64::MAX; // always the latest snapshot
+        let snapshot = OrderBookSnapshot::create_snapshot(&pool, stream_sequence, vec![1, 2, 3])
+            .await
+            .unwrap();
+
+        let latest = OrderBookSnapshot::get_latest_snapshot(&pool)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(latest.id, snapshot.id);
+        assert_eq!(latest.stream_sequence, stream_sequence);
+        assert_eq!(latest.snapshot, vec![1, 2, 3]);
+
+        // Clean up
+        sqlx::query(r#"DELETE FROM "polymarket"."order_book_snapshots" WHERE id = $1"#)
+            .bind(snapshot.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        pool.close().await;
+    }
+}
@@ -0,0 +1,662 @@
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::{
+    enums::{
+        OrderGroupLeg, OrderGroupType, OrderSide, OrderStatus, OrderType, Outcome,
+        SelfTradePrevention, TimeInForce,
+    },
+    orders::Order,
+};
+
+/// Reason of the orders cancelled by their group, these cancellations don't cascade to the other legs
+pub const ORDER_GROUP_CANCEL_REASON: &str = "Cancelled by it's order group";
+
+/// OCO or bracket group of orders, orders of the group share the user, market and outcome
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct OrderGroup {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub market_id: Uuid,
+    pub group_type: OrderGroupType,
+    pub created_at: NaiveDateTime,
+}
+
+/// Order of the group along with it's role
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct GroupOrder {
+    pub leg: OrderGroupLeg,
+    pub order_id: Uuid,
+    pub side: OrderSide,
+    pub order_type: OrderType,
+    pub price: Decimal,
+    pub trigger_price: Option<Decimal>,
+    pub quantity: Decimal,
+    pub filled_quantity: Decimal,
+    pub status: OrderStatus,
+    pub status_reason: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+/// Linked order to be amended to the unfilled quantity of it's partially filled leg
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct LinkedOrderShrink {
+    pub order_id: Uuid,
+    pub new_quantity: Decimal,
+    pub price: Decimal,
+}
+
+pub struct NewGroupLeg {
+    pub leg: OrderGroupLeg,
+    pub side: OrderSide,
+    pub order_type: OrderType,
+    pub price: Decimal, // limit price, zero for the market execution of triggered orders
+    pub trigger_price: Option<Decimal>,
+    pub quantity: Decimal,
+    pub status: OrderStatus, // unspecified for the orders sent to the book, pending activation for the bracket exits
+}
+
+pub struct NewOrderGroup {
+    pub user_id: Uuid,
+    pub market_id: Uuid,
+    pub group_type: OrderGroupType,
+    pub outcome: Outcome,
+    pub outcome_index: i32,
+    pub self_trade_prevention: SelfTradePrevention,
+    pub legs: Vec<NewGroupLeg>,
+}
+
+impl OrderGroup {
+    /// Creates the group along with it's orders, orders are returned in the order of the legs
+    pub async fn create_order_group(
+        pool: &PgPool,
+        new_group: &NewOrderGroup,
+    ) -> Result<(OrderGroup, Vec<Order>), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        let group = sqlx::query_as!(
+            OrderGroup,
+            r#"
+            INSERT INTO polymarket.order_groups (user_id, market_id, group_type)
+            VALUES ($1, $2, $3)
+            RETURNING id, user_id, market_id, group_type as "group_type: OrderGroupType", created_at
+            "#,
+            new_group.user_id,
+            new_group.market_id,
+            new_group.group_type as _,
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let mut orders = Vec::with_capacity(new_group.legs.len());
+        for leg in new_group.legs.iter() {
+            let order = sqlx::query_as!(
+                Order,
+                r#"
+                INSERT INTO "polymarket"."orders"
+                (user_id, market_id, price, quantity, trigger_price, side, outcome, order_type, status, self_trade_prevention, outcome_index)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+                RETURNING
+                id, user_id, market_id,
+                outcome as "outcome: Outcome",
+                price, quantity, filled_quantity,
+                status as "status: OrderStatus",
+                side as "side: OrderSide",
+                created_at, updated_at,
+                order_type as "order_type: OrderType", trigger_price,
+                time_in_force as "time_in_force: TimeInForce", expires_at, post_only, status_reason, self_trade_prevention as "self_trade_prevention: SelfTradePrevention", outcome_index, display_quantity
+                "#,
+                new_group.user_id,
+                new_group.market_id,
+                leg.price,
+                leg.quantity,
+                leg.trigger_price,
+                leg.side as _,
+                new_group.outcome as _,
+                leg.order_type as _,
+                leg.status as _,
+                new_group.self_trade_prevention as _,
+                new_group.outcome_index,
+            )
+            .fetch_one(&mut *tx)
+            .await?;
+
+            sqlx::query!(
+                r#"
+                INSERT INTO polymarket.order_group_orders (order_id, group_id, leg)
+                VALUES ($1, $2, $3)
+                "#,
+                order.id,
+                group.id,
+                leg.leg as _,
+            )
+            .execute(&mut *tx)
+            .await?;
+
+            orders.push(order);
+        }
+
+        tx.commit().await?;
+        Ok((group, orders))
+    }
+
+    pub async fn get_order_group_by_id(
+        pool: &PgPool,
+        group_id: &Uuid,
+    ) -> Result<Option<OrderGroup>, sqlx::Error> {
+        let group = sqlx::query_as!(
+            OrderGroup,
+            r#"
+            SELECT id, user_id, market_id, group_type as "group_type: OrderGroupType", created_at
+            FROM polymarket.order_groups
+            WHERE id = $1
+            "#,
+            group_id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(group)
+    }
+
+    /// Groups of the user, latest first
+    pub async fn get_user_order_groups(
+        pool: &PgPool,
+        user_id: &Uuid,
+    ) -> Result<Vec<OrderGroup>, sqlx::Error> {
+        let groups = sqlx::query_as!(
+            OrderGroup,
+            r#"
+            SELECT id, user_id, market_id, group_type as "group_type: OrderGroupType", created_at
+            FROM polymarket.order_groups
+            WHERE user_id = $1
+            ORDER BY created_at DESC
+            "#,
+            user_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(groups)
+    }
+
+    pub async fn get_group_orders(
+        pool: &PgPool,
+        group_id: &Uuid,
+    ) -> Result<Vec<GroupOrder>, sqlx::Error> {
+        let orders = sqlx::query_as!(
+            GroupOrder,
+            r#"
+            SELECT
+                m.leg as "leg: OrderGroupLeg",
+                o.id as order_id,
+                o.side as "side: OrderSide",
+                o.order_type as "order_type: OrderType",
+                o.price, o.trigger_price, o.quantity, o.filled_quantity,
+                o.status as "status: OrderStatus",
+                o.status_reason, o.created_at, o.updated_at
+            FROM polymarket.order_group_orders m
+            JOIN polymarket.orders o ON o.id = m.order_id
+            WHERE m.group_id = $1
+            ORDER BY m.leg
+            "#,
+            group_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(orders)
+    }
+
+    /// Cancels the linked orders of the market's groups, returns the open orders which are moved to pending cancel (they are still in the book).
+    ///
+    /// - Open legs (other than the entry) are cancelled once any other such leg of their group is filled, cancelled or expired.
+    ///   Partial fill of the other leg doesn't cancel them, they are shrunk by [`OrderGroup::shrink_linked_orders`] instead.
+    /// - Exits of the bracket are cancelled along with their entry order, if it's cancelled or expired without any fill.
+    pub async fn cancel_linked_orders(
+        pool: &PgPool,
+        market_id: &Uuid,
+    ) -> Result<Vec<Uuid>, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        let pending_cancel_ids = sqlx::query_scalar!(
+            r#"
+            UPDATE polymarket.orders o
+            SET status = 'pending_cancel'::polymarket.order_status, status_reason = $2
+            FROM polymarket.order_group_orders m
+            JOIN polymarket.order_groups g ON g.id = m.group_id
+            WHERE o.id = m.order_id
+                AND g.market_id = $1
+                AND m.leg <> 'entry'::polymarket.order_group_leg
+                AND o.status = 'open'::polymarket.order_status
+                AND EXISTS (
+                    SELECT 1
+                    FROM polymarket.order_group_orders sm
+                    JOIN polymarket.orders s ON s.id = sm.order_id
+                    WHERE sm.group_id = m.group_id
+                        AND sm.order_id <> o.id
+                        AND sm.leg <> 'entry'::polymarket.order_group_leg
+                        AND (
+                            s.status IN ('filled'::polymarket.order_status, 'expired'::polymarket.order_status)
+                            OR (
+                                s.status IN ('cancelled'::polymarket.order_status, 'pending_cancel'::polymarket.order_status)
+                                AND s.status_reason IS DISTINCT FROM $2
+                            )
+                        )
+                )
+            RETURNING o.id
+            "#,
+            market_id,
+            ORDER_GROUP_CANCEL_REASON
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        // exits waiting for activation are not in the book, so they are cancelled right away
+        sqlx::query!(
+            r#"
+            UPDATE polymarket.orders o
+            SET status = 'cancelled'::polymarket.order_status, status_reason = $2
+            FROM polymarket.order_group_orders m
+            JOIN polymarket.order_groups g ON g.id = m.group_id
+            JOIN polymarket.order_group_orders em ON em.group_id = m.group_id AND em.leg = 'entry'::polymarket.order_group_leg
+            JOIN polymarket.orders e ON e.id = em.order_id
+            WHERE o.id = m.order_id
+                AND g.market_id = $1
+                AND o.status = 'pending_activation'::polymarket.order_status
+                AND e.filled_quantity = 0
+                AND e.status IN ('cancelled'::polymarket.order_status, 'expired'::polymarket.order_status)
+            "#,
+            market_id,
+            ORDER_GROUP_CANCEL_REASON
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(pending_cancel_ids)
+    }
+
+    /// Shrinks the linked orders of the market's groups to the unfilled quantity of their partially filled leg, so the legs don't exit more than the position.
+    ///
+    /// Open legs (other than the entry) which are not filled yet are moved to pending update, they are returned along with their new quantity (amended in the book)
+    pub async fn shrink_linked_orders(
+        pool: &PgPool,
+        market_id: &Uuid,
+    ) -> Result<Vec<LinkedOrderShrink>, sqlx::Error> {
+        let shrunk_orders = sqlx::query_as!(
+            LinkedOrderShrink,
+            r#"
+            UPDATE polymarket.orders o
+            SET status = 'pending_update'::polymarket.order_status
+            FROM polymarket.order_group_orders m
+            JOIN polymarket.order_groups g ON g.id = m.group_id
+            JOIN polymarket.order_group_orders sm ON sm.group_id = m.group_id AND sm.leg <> 'entry'::polymarket.order_group_leg
+            JOIN polymarket.orders s ON s.id = sm.order_id
+            WHERE o.id = m.order_id
+                AND sm.order_id <> o.id
+                AND g.market_id = $1
+                AND m.leg <> 'entry'::polymarket.order_group_leg
+                AND o.status = 'open'::polymarket.order_status
+                AND o.filled_quantity = 0
+                AND s.status = 'open'::polymarket.order_status
+                AND s.filled_quantity > 0
+                AND s.quantity - s.filled_quantity < o.quantity
+            RETURNING o.id as order_id, s.quantity - s.filled_quantity as "new_quantity!", o.price
+            "#,
+            market_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(shrunk_orders)
+    }
+
+    /// Activates the exits of the brackets whose entry order is done with some fill, exits are sized by the filled quantity of the entry.
+    /// Activated exits are unspecified (yet to be sent to the book), their ids are returned
+    pub async fn activate_bracket_exits(
+        pool: &PgPool,
+        market_id: &Uuid,
+    ) -> Result<Vec<Uuid>, sqlx::Error> {
+        let activated_ids = sqlx::query_scalar!(
+            r#"
+            UPDATE polymarket.orders o
+            SET status = 'unspecified'::polymarket.order_status, quantity = e.filled_quantity
+            FROM polymarket.order_group_orders m
+            JOIN polymarket.order_groups g ON g.id = m.group_id
+            JOIN polymarket.order_group_orders em ON em.group_id = m.group_id AND em.leg = 'entry'::polymarket.order_group_leg
+            JOIN polymarket.orders e ON e.id = em.order_id
+            WHERE o.id = m.order_id
+                AND g.market_id = $1
+                AND o.status = 'pending_activation'::polymarket.order_status
+                AND e.filled_quantity > 0
+                AND e.status IN ('filled'::polymarket.order_status, 'cancelled'::polymarket.order_status, 'expired'::polymarket.order_status)
+            RETURNING o.id
+            "#,
+            market_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(activated_ids)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::DateTime;
+    use rust_decimal_macros::dec;
+    use utility_helpers::types::GoogleClaims;
+
+    use super::*;
+    use crate::schema::{
+        enums::PriceModelKind,
+        market::{Market, NewMarket},
+        users::User,
+    };
+
+    fn get_exit_legs(side: OrderSide, status: OrderStatus) -> Vec<NewGroupLeg> {
+        vec![
+            NewGroupLeg {
+                leg: OrderGroupLeg::TakeProfit,
+                side,
+                order_type: OrderType::TakeProfit,
+                price: Decimal::ZERO,
+                trigger_price: Some(dec!(0.7)),
+                quantity: dec!(10),
+                status,
+            },
+            NewGroupLeg {
+                leg: OrderGroupLeg::StopLoss,
+                side,
+                order_type: OrderType::StopLoss,
+                price: Decimal::ZERO,
+                trigger_price: Some(dec!(0.3)),
+                quantity: dec!(10),
+                status,
+            },
+        ]
+    }
+
+    async fn set_order_state(pool: &PgPool, order_id: Uuid, status: &str, filled: Decimal) {
+        sqlx::query(
+            r#"UPDATE polymarket.orders SET status = $1::polymarket.order_status, filled_quantity = $2 WHERE id = $3"#,
+        )
+        .bind(status)
+        .bind(filled)
+        .bind(order_id)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_order_groups() {
+        dotenv::dotenv().ok();
+        let pool = PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+
+        let user = User::create_new_user(
+            &pool,
+            &GoogleClaims {
+                email: "order_groups@gmail.com".to_string(),
+                exp: 0,
+                name: "temp".to_string(),
+                picture: "temp".to_string(),
+                sub: "order_groups".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+
+        let market_expiry = DateTime::parse_from_rfc3339("2030-01-01T00:00:00Z")
+            .unwrap()
+            .naive_utc();
+        let market = Market::create_new_market(
+            &pool,
+            &NewMarket {
+                name: "Test Market Order Groups".to_string(),
+                description: "Test Description".to_string(),
+                logo: "Test Logo".to_string(),
+                liquidity_b: dec!(100),
+                tick_size: dec!(0.01),
+                min_quantity: dec!(0.01),
+                lot_size: dec!(0.01),
+                market_maker: false,
+                price_model: PriceModelKind::Midpoint,
+                outcomes: vec!["Yes".to_string(), "No".to_string()],
+                scalar_range: None,
+                market_expiry,
+            },
+        )
+        .await
+        .unwrap();
+
+        // oco, fill of the take profit cancels the stop loss
+        let (oco, oco_orders) = OrderGroup::create_order_group(
+            &pool,
+            &NewOrderGroup {
+                user_id: user.id,
+                market_id: market.id,
+                group_type: OrderGroupType::Oco,
+                outcome: Outcome::YES,
+                outcome_index: 0,
+                self_trade_prevention: SelfTradePrevention::CancelNewest,
+                legs: get_exit_legs(OrderSide::SELL, OrderStatus::UNSPECIFIED),
+            },
+        )
+        .await
+        .unwrap();
+        let (take_profit, stop_loss) = (oco_orders[0].id, oco_orders[1].id);
+        set_order_state(&pool, stop_loss, "open", Decimal::ZERO).await;
+        set_order_state(&pool, take_profit, "filled", dec!(10)).await;
+
+        let cancelled = OrderGroup::cancel_linked_orders(&pool, &market.id)
+            .await
+            .unwrap();
+        assert_eq!(cancelled, vec![stop_loss]);
+        // group cancellation doesn't cascade
+        assert!(
+            OrderGroup::cancel_linked_orders(&pool, &market.id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        // oco, partial fill of the take profit shrinks the stop loss to the unfilled quantity
+        let (_, oco_orders) = OrderGroup::create_order_group(
+            &pool,
+            &NewOrderGroup {
+                user_id: user.id,
+                market_id: market.id,
+                group_type: OrderGroupType::Oco,
+                outcome: Outcome::YES,
+                outcome_index: 0,
+                self_trade_prevention: SelfTradePrevention::CancelNewest,
+                legs: get_exit_legs(OrderSide::SELL, OrderStatus::UNSPECIFIED),
+            },
+        )
+        .await
+        .unwrap();
+        let (take_profit, stop_loss) = (oco_orders[0].id, oco_orders[1].id);
+        set_order_state(&pool, stop_loss, "open", Decimal::ZERO).await;
+        set_order_state(&pool, take_profit, "open", dec!(4)).await;
+
+        assert!(
+            OrderGroup::cancel_linked_orders(&pool, &market.id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        let shrunk = OrderGroup::shrink_linked_orders(&pool, &market.id)
+            .await
+            .unwrap();
+        assert_eq!(shrunk.len(), 1);
+        assert_eq!(shrunk[0].order_id, stop_loss);
+        assert_eq!(shrunk[0].new_quantity, dec!(6));
+        // amendment is pending, so it's not shrunk again
+        assert!(
+            OrderGroup::shrink_linked_orders(&pool, &market.id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        // oco, user's cancellation of the take profit waiting for it's trigger cancels the stop loss
+        let (_, oco_orders) = OrderGroup::create_order_group(
+            &pool,
+            &NewOrderGroup {
+                user_id: user.id,
+                market_id: market.id,
+                group_type: OrderGroupType::Oco,
+                outcome: Outcome::YES,
+                outcome_index: 0,
+                self_trade_prevention: SelfTradePrevention::CancelNewest,
+                legs: get_exit_legs(OrderSide::SELL, OrderStatus::UNSPECIFIED),
+            },
+        )
+        .await
+        .unwrap();
+        let (take_profit, stop_loss) = (oco_orders[0].id, oco_orders[1].id);
+        set_order_state(&pool, stop_loss, "open", Decimal::ZERO).await;
+        set_order_state(&pool, take_profit, "cancelled", Decimal::ZERO).await;
+
+        let cancelled = OrderGroup::cancel_linked_orders(&pool, &market.id)
+            .await
+            .unwrap();
+        assert_eq!(cancelled, vec![stop_loss]);
+
+        // bracket, exits are activated by the partially filled entry once it's cancelled
+        let mut legs = vec![NewGroupLeg {
+            leg: OrderGroupLeg::Entry,
+            side: OrderSide::BUY,
+            order_type: OrderType::LIMIT,
+            price: dec!(0.5),
+            trigger_price: None,
+            quantity: dec!(10),
+            status: OrderStatus::UNSPECIFIED,
+        }];
+        legs.extend(get_exit_legs(
+            OrderSide::SELL,
+            OrderStatus::PendingActivation,
+        ));
+        let (bracket, bracket_orders) = OrderGroup::create_order_group(
+            &pool,
+            &NewOrderGroup {
+                user_id: user.id,
+                market_id: market.id,
+                group_type: OrderGroupType::Bracket,
+                outcome: Outcome::YES,
+                outcome_index: 0,
+                self_trade_prevention: SelfTradePrevention::CancelNewest,
+                legs,
+            },
+        )
+        .await
+        .unwrap();
+        let entry = bracket_orders[0].id;
+
+        set_order_state(&pool, entry, "open", dec!(4)).await;
+        assert!(
+            OrderGroup::activate_bracket_exits(&pool, &market.id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        set_order_state(&pool, entry, "cancelled", dec!(4)).await;
+        let activated = OrderGroup::activate_bracket_exits(&pool, &market.id)
+            .await
+            .unwrap();
+        assert_eq!(activated.len(), 2);
+
+        let group_orders = OrderGroup::get_group_orders(&pool, &bracket.id)
+            .await
+            .unwrap();
+        let legs = group_orders.iter().map(|o| o.leg).collect::<Vec<_>>();
+        assert_eq!(
+            legs,
+            vec![
+                OrderGroupLeg::Entry,
+                OrderGroupLeg::TakeProfit,
+                OrderGroupLeg::StopLoss
+            ]
+        );
+        assert!(
+            group_orders[1..]
+                .iter()
+                .all(|o| { o.status == OrderStatus::UNSPECIFIED && o.quantity == dec!(4) })
+        );
+
+        // bracket, exits are activated with the full quantity of the filled entry
+        let mut legs = vec![NewGroupLeg {
+            leg: OrderGroupLeg::Entry,
+            side: OrderSide::BUY,
+            order_type: OrderType::LIMIT,
+            price: dec!(0.5),
+            trigger_price: None,
+            quantity: dec!(10),
+            status: OrderStatus::UNSPECIFIED,
+        }];
+        legs.extend(get_exit_legs(
+            OrderSide::SELL,
+            OrderStatus::PendingActivation,
+        ));
+        let (_, bracket_orders) = OrderGroup::create_order_group(
+            &pool,
+            &NewOrderGroup {
+                user_id: user.id,
+                market_id: market.id,
+                group_type: OrderGroupType::Bracket,
+                outcome: Outcome::YES,
+                outcome_index: 0,
+                self_trade_prevention: SelfTradePrevention::CancelNewest,
+                legs,
+            },
+        )
+        .await
+        .unwrap();
+
+        set_order_state(&pool, bracket_orders[0].id, "filled", dec!(10)).await;
+        let mut activated = OrderGroup::activate_bracket_exits(&pool, &market.id)
+            .await
+            .unwrap();
+        activated.sort();
+        let mut exit_ids = vec![bracket_orders[1].id, bracket_orders[2].id];
+        exit_ids.sort();
+        assert_eq!(activated, exit_ids);
+
+        let groups = OrderGroup::get_user_order_groups(&pool, &user.id)
+            .await
+            .unwrap();
+        assert_eq!(groups.len(), 5);
+        assert!(
+            OrderGroup::get_order_group_by_id(&pool, &oco.id)
+                .await
+                .unwrap()
+                .is_some_and(|group| group.group_type == OrderGroupType::Oco)
+        );
+
+        // Clean up (groups are deleted along with the market)
+        sqlx::query(r#"DELETE FROM "polymarket"."orders" WHERE market_id = $1"#)
+            .bind(market.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(r#"DELETE FROM "polymarket"."markets" WHERE id = $1"#)
+            .bind(market.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(r#"DELETE FROM "polymarket"."users" WHERE id = $1"#)
+            .bind(user.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        pool.close().await;
+    }
+}
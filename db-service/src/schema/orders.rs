@@ -5,12 +5,12 @@ use sqlx::{Executor, PgPool, Postgres};
 use utility_helpers::log_info;
 use uuid::Uuid;
 
-use crate::schema::enums::OrderType;
+use crate::schema::enums::{OrderType, PriceModelKind, SelfTradePrevention, TimeInForce};
 
 use super::enums::{OrderSide, OrderStatus, Outcome};
 
 // need serialize for message pack
-#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, Default)]
 pub struct Order {
     pub id: Uuid,
     pub user_id: Uuid,
@@ -22,6 +22,14 @@ pub struct Order {
     pub filled_quantity: Decimal,
     pub status: OrderStatus,
     pub order_type: OrderType,
+    pub trigger_price: Option<Decimal>,
+    pub time_in_force: TimeInForce,
+    pub expires_at: Option<NaiveDateTime>,
+    pub post_only: bool,
+    pub status_reason: Option<String>, // reason of rejection by the matching engine
+    pub self_trade_prevention: SelfTradePrevention,
+    pub display_quantity: Option<Decimal>, // visible quantity of iceberg order
+    pub outcome_index: i32, // outcome of the categorical market, 0 for binary markets
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
 }
@@ -39,9 +47,26 @@ pub struct OrderWithMarket {
     pub filled_quantity: Decimal,
     pub status: OrderStatus,
     pub order_type: OrderType,
+    pub trigger_price: Option<Decimal>,
+    pub time_in_force: TimeInForce,
+    pub expires_at: Option<NaiveDateTime>,
+    pub post_only: bool,
+    pub status_reason: Option<String>, // reason of rejection by the matching engine
+    pub self_trade_prevention: SelfTradePrevention,
+    pub display_quantity: Option<Decimal>, // visible quantity of iceberg order
+    pub outcome_index: i32, // outcome of the categorical market, 0 for binary markets
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
     pub liquidity_b: Decimal,
+    pub tick_size: Decimal,
+    pub min_quantity: Decimal,
+    pub lot_size: Decimal,
+    pub market_maker: bool,
+    pub price_model: PriceModelKind,
+    pub outcome_count: i32,
+    pub auction_opens_at: Option<NaiveDateTime>,
+    pub price_band: Option<Decimal>,
+    pub price_band_window: i32,
 }
 
 impl From<OrderWithMarket> for Order {
@@ -59,27 +84,43 @@ impl From<OrderWithMarket> for Order {
             created_at: order.created_at,
             updated_at: order.updated_at,
             order_type: order.order_type,
+            trigger_price: order.trigger_price,
+            time_in_force: order.time_in_force,
+            expires_at: order.expires_at,
+            post_only: order.post_only,
+            status_reason: order.status_reason,
+            self_trade_prevention: order.self_trade_prevention,
+            outcome_index: order.outcome_index,
+            display_quantity: order.display_quantity,
         }
     }
 }
 
+pub struct NewOrder {
+    pub user_id: Uuid,
+    pub market_id: Uuid,
+    pub price: Decimal,
+    pub quantity: Decimal,
+    pub side: OrderSide,
+    pub outcome: Outcome,
+    pub order_type: OrderType,
+    pub trigger_price: Option<Decimal>, // stop loss / take profit orders only
+    pub time_in_force: TimeInForce,
+    pub expires_at: Option<NaiveDateTime>, // GTD orders only
+    pub post_only: bool,
+    pub self_trade_prevention: SelfTradePrevention,
+    pub display_quantity: Option<Decimal>, // visible quantity of iceberg order
+    pub outcome_index: i32, // outcome of the categorical market, 0 for binary markets
+}
+
 impl Order {
-    pub async fn create_order(
-        user_id: Uuid,
-        market_id: Uuid,
-        price: Decimal,
-        quantity: Decimal,
-        side: OrderSide,
-        outcome_side: Outcome,
-        order_type: OrderType,
-        pool: &PgPool,
-    ) -> Result<Order, sqlx::Error> {
+    pub async fn create_order(pool: &PgPool, new_order: &NewOrder) -> Result<Order, sqlx::Error> {
         let order = sqlx::query_as!(
             Order,
             r#"
             INSERT INTO "polymarket"."orders"
-            (user_id, market_id, price, quantity, side, outcome, order_type)
-            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            (user_id, market_id, price, quantity, side, outcome, order_type, trigger_price, time_in_force, expires_at, post_only, self_trade_prevention, display_quantity, outcome_index)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
             RETURNING 
             id, user_id, market_id,
             outcome as "outcome: Outcome",
@@ -87,15 +128,23 @@ impl Order {
             status as "status: OrderStatus",
             side as "side: OrderSide",            
             created_at, updated_at,
-            order_type as "order_type: OrderType"
+            order_type as "order_type: OrderType", trigger_price,
+            time_in_force as "time_in_force: TimeInForce", expires_at, post_only, status_reason, self_trade_prevention as "self_trade_prevention: SelfTradePrevention", outcome_index, display_quantity
             "#,
-            user_id,
-            market_id,
-            price,
-            quantity,
-            side as _,
-            outcome_side as _,
-            order_type as _,
+            new_order.user_id,
+            new_order.market_id,
+            new_order.price,
+            new_order.quantity,
+            new_order.side as _,
+            new_order.outcome as _,
+            new_order.order_type as _,
+            new_order.trigger_price,
+            new_order.time_in_force as _,
+            new_order.expires_at,
+            new_order.post_only,
+            new_order.self_trade_prevention as _,
+            new_order.display_quantity,
+            new_order.outcome_index,
         )
         .fetch_one(pool)
         .await?;
@@ -116,7 +165,8 @@ impl Order {
             price, quantity, filled_quantity,
             status as "status: OrderStatus",
             side as "side: OrderSide",
-            order_type as "order_type: OrderType",
+            order_type as "order_type: OrderType", trigger_price,
+            time_in_force as "time_in_force: TimeInForce", expires_at, post_only, status_reason, self_trade_prevention as "self_trade_prevention: SelfTradePrevention", outcome_index, display_quantity,
             created_at, updated_at
             "#,
             order_id
@@ -145,7 +195,8 @@ impl Order {
             price, quantity, filled_quantity,
             status as "status: OrderStatus",
             side as "side: OrderSide",
-            order_type as "order_type: OrderType",
+            order_type as "order_type: OrderType", trigger_price,
+            time_in_force as "time_in_force: TimeInForce", expires_at, post_only, status_reason, self_trade_prevention as "self_trade_prevention: SelfTradePrevention", outcome_index, display_quantity,
             created_at, updated_at
             "#,
             status as _,
@@ -171,7 +222,8 @@ impl Order {
             price, quantity, filled_quantity,
             status as "status: OrderStatus",
             side as "side: OrderSide",
-            order_type as "order_type: OrderType",
+            order_type as "order_type: OrderType", trigger_price,
+            time_in_force as "time_in_force: TimeInForce", expires_at, post_only, status_reason, self_trade_prevention as "self_trade_prevention: SelfTradePrevention", outcome_index, display_quantity,
             created_at, updated_at    
             FROM polymarket.orders
             WHERE id = $1
@@ -198,7 +250,8 @@ impl Order {
             price, quantity, filled_quantity,
             status as "status: OrderStatus",
             side as "side: OrderSide",
-            order_type as "order_type: OrderType",
+            order_type as "order_type: OrderType", trigger_price,
+            time_in_force as "time_in_force: TimeInForce", expires_at, post_only, status_reason, self_trade_prevention as "self_trade_prevention: SelfTradePrevention", outcome_index, display_quantity,
             created_at, updated_at            
             FROM polymarket.orders
             WHERE id = $1 AND status = $2
@@ -225,8 +278,9 @@ impl Order {
             o.price, o.quantity, o.filled_quantity,
             o.status as "status: OrderStatus",
             o.side as "side: OrderSide",
-            o.created_at, o.updated_at, m.liquidity_b,
-            o.order_type as "order_type: OrderType"
+            o.created_at, o.updated_at, m.liquidity_b, m.tick_size, m.min_quantity, m.lot_size, m.market_maker, m.price_model as "price_model: PriceModelKind", m.outcome_count, m.auction_opens_at, m.price_band, m.price_band_window,
+            o.order_type as "order_type: OrderType", o.trigger_price,
+            o.time_in_force as "time_in_force: TimeInForce", o.expires_at, o.post_only, o.status_reason, o.self_trade_prevention as "self_trade_prevention: SelfTradePrevention", o.outcome_index, o.display_quantity
             FROM polymarket.orders o
             LEFT JOIN polymarket.markets m ON o.market_id = m.id
             WHERE o.id = $1
@@ -250,8 +304,9 @@ impl Order {
             o.price, o.quantity, o.filled_quantity,
             o.status as "status: OrderStatus",
             o.side as "side: OrderSide",
-            o.created_at, o.updated_at, m.liquidity_b,
-            o.order_type as "order_type: OrderType"
+            o.created_at, o.updated_at, m.liquidity_b, m.tick_size, m.min_quantity, m.lot_size, m.market_maker, m.price_model as "price_model: PriceModelKind", m.outcome_count, m.auction_opens_at, m.price_band, m.price_band_window,
+            o.order_type as "order_type: OrderType", o.trigger_price,
+            o.time_in_force as "time_in_force: TimeInForce", o.expires_at, o.post_only, o.status_reason, o.self_trade_prevention as "self_trade_prevention: SelfTradePrevention", o.outcome_index, o.display_quantity
             FROM polymarket.orders o
             JOIN polymarket.markets m ON o.market_id = m.id
             WHERE o.status = 'open'::polymarket.order_status         
@@ -275,8 +330,9 @@ impl Order {
             o.price, o.quantity, o.filled_quantity,
             o.status as "status: OrderStatus",
             o.side as "side: OrderSide",
-            o.created_at, o.updated_at, m.liquidity_b,
-            o.order_type as "order_type: OrderType"
+            o.created_at, o.updated_at, m.liquidity_b, m.tick_size, m.min_quantity, m.lot_size, m.market_maker, m.price_model as "price_model: PriceModelKind", m.outcome_count, m.auction_opens_at, m.price_band, m.price_band_window,
+            o.order_type as "order_type: OrderType", o.trigger_price,
+            o.time_in_force as "time_in_force: TimeInForce", o.expires_at, o.post_only, o.status_reason, o.self_trade_prevention as "self_trade_prevention: SelfTradePrevention", o.outcome_index, o.display_quantity
             FROM polymarket.orders o
             JOIN polymarket.markets m ON o.market_id = m.id
             WHERE o.status IN ('open'::polymarket.order_status, 'unspecified'::polymarket.order_status)
@@ -301,8 +357,9 @@ impl Order {
                 o.price, o.quantity, o.filled_quantity,
                 o.status as "status: OrderStatus",
                 o.side as "side: OrderSide",
-                o.created_at, o.updated_at, m.liquidity_b,
-                o.order_type as "order_type: OrderType"
+                o.created_at, o.updated_at, m.liquidity_b, m.tick_size, m.min_quantity, m.lot_size, m.market_maker, m.price_model as "price_model: PriceModelKind", m.outcome_count, m.auction_opens_at, m.price_band, m.price_band_window,
+                o.order_type as "order_type: OrderType", o.trigger_price,
+                o.time_in_force as "time_in_force: TimeInForce", o.expires_at, o.post_only, o.status_reason, o.self_trade_prevention as "self_trade_prevention: SelfTradePrevention", o.outcome_index, o.display_quantity
             FROM polymarket.orders o
             JOIN polymarket.markets m ON o.market_id = m.id                
             WHERE o.status = $1
@@ -315,6 +372,54 @@ impl Order {
         Ok(orders)
     }
 
+    /// Returns ids of all orders which are (or should be) part of the order book
+    pub async fn get_live_order_ids(pool: &PgPool) -> Result<Vec<Uuid>, sqlx::Error> {
+        let order_ids = sqlx::query_scalar!(
+            r#"
+            SELECT id FROM polymarket.orders
+            WHERE status IN (
+                'open'::polymarket.order_status,
+                'pending_update'::polymarket.order_status,
+                'pending_cancel'::polymarket.order_status
+            )
+            "#
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(order_ids)
+    }
+
+    /// Returns good till date orders which are still live after their expiry time
+    pub async fn get_expired_orders(
+        pool: &PgPool,
+        current_time: NaiveDateTime,
+    ) -> Result<Vec<Order>, sqlx::Error> {
+        let orders = sqlx::query_as!(
+            Order,
+            r#"
+            SELECT
+            id, user_id, market_id,
+            outcome as "outcome: Outcome",
+            price, quantity, filled_quantity,
+            status as "status: OrderStatus",
+            side as "side: OrderSide",
+            order_type as "order_type: OrderType", trigger_price,
+            time_in_force as "time_in_force: TimeInForce", expires_at, post_only, status_reason, self_trade_prevention as "self_trade_prevention: SelfTradePrevention", outcome_index, display_quantity,
+            created_at, updated_at
+            FROM polymarket.orders
+            WHERE time_in_force = 'gtd'::polymarket.time_in_force
+                AND expires_at <= $1
+                AND status IN ('open'::polymarket.order_status, 'pending_update'::polymarket.order_status)
+            "#,
+            current_time
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(orders)
+    }
+
     pub async fn update(&self, pool: &PgPool) -> Result<Order, sqlx::Error> {
         let order = sqlx::query_as!(
             Order,
@@ -329,15 +434,17 @@ impl Order {
                 quantity = $6,
                 filled_quantity = $7,
                 status = $8,
-                order_type = $9
-            WHERE id = $10
+                order_type = $9,
+                status_reason = $10
+            WHERE id = $11
             RETURNING 
             id, user_id, market_id,
             outcome as "outcome: Outcome",
             price, quantity, filled_quantity,
             status as "status: OrderStatus",
             side as "side: OrderSide",
-            order_type as "order_type: OrderType",
+            order_type as "order_type: OrderType", trigger_price,
+            time_in_force as "time_in_force: TimeInForce", expires_at, post_only, status_reason, self_trade_prevention as "self_trade_prevention: SelfTradePrevention", outcome_index, display_quantity,
             created_at, updated_at
             "#,
             self.user_id,
@@ -349,6 +456,7 @@ impl Order {
             self.filled_quantity,
             self.status as _,
             self.order_type as _,
+            self.status_reason,
             self.id,
         )
         .fetch_one(pool)
@@ -413,7 +521,8 @@ impl Order {
             price, quantity, filled_quantity,
             status as "status: OrderStatus",
             side as "side: OrderSide",
-            order_type as "order_type: OrderType",
+            order_type as "order_type: OrderType", trigger_price,
+            time_in_force as "time_in_force: TimeInForce", expires_at, post_only, status_reason, self_trade_prevention as "self_trade_prevention: SelfTradePrevention", outcome_index, display_quantity,
             created_at, updated_at
             "#,
             order_status as _,
@@ -463,7 +572,8 @@ impl Order {
                 filled_quantity,
                 status as "status: OrderStatus",
                 side as "side: OrderSide",
-                order_type as "order_type: OrderType",
+                order_type as "order_type: OrderType", trigger_price,
+                time_in_force as "time_in_force: TimeInForce", expires_at, post_only, status_reason, self_trade_prevention as "self_trade_prevention: SelfTradePrevention", outcome_index, display_quantity,
                 created_at,
                 updated_at
             FROM polymarket.orders
@@ -517,7 +627,8 @@ impl Order {
                         filled_quantity,
                         status as "status: OrderStatus",
                         side as "side: OrderSide",
-                        order_type as "order_type: OrderType",
+                        order_type as "order_type: OrderType", trigger_price,
+                        time_in_force as "time_in_force: TimeInForce", expires_at, post_only, status_reason, self_trade_prevention as "self_trade_prevention: SelfTradePrevention", outcome_index, display_quantity,
                         created_at, updated_at
                     FROM polymarket.orders
                     WHERE user_id = $1 AND market_id = $2 AND status = $3
@@ -545,7 +656,8 @@ impl Order {
                         filled_quantity,
                         status as "status: OrderStatus",
                         side as "side: OrderSide",
-                        order_type as "order_type: OrderType",
+                        order_type as "order_type: OrderType", trigger_price,
+                        time_in_force as "time_in_force: TimeInForce", expires_at, post_only, status_reason, self_trade_prevention as "self_trade_prevention: SelfTradePrevention", outcome_index, display_quantity,
                         created_at, updated_at
                     FROM polymarket.orders
                     WHERE user_id = $1 AND market_id = $2
@@ -580,7 +692,8 @@ impl Order {
             outcome as "outcome: Outcome",
             price, quantity, filled_quantity,
             status as "status: OrderStatus",
-            order_type as "order_type: OrderType",
+            order_type as "order_type: OrderType", trigger_price,
+            time_in_force as "time_in_force: TimeInForce", expires_at, post_only, status_reason, self_trade_prevention as "self_trade_prevention: SelfTradePrevention", outcome_index, display_quantity,
             side as "side: OrderSide",
             created_at, updated_at
             "#,
@@ -607,8 +720,8 @@ impl Order {
                 Order,
                 r#"
                 INSERT INTO "polymarket"."orders"
-                (user_id, market_id, price, quantity, side, outcome, order_type, status)
-                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                (user_id, market_id, price, quantity, side, outcome, order_type, status, outcome_index)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
                 RETURNING 
                 id, user_id, market_id,
                 outcome as "outcome: Outcome",
@@ -616,7 +729,8 @@ impl Order {
                 status as "status: OrderStatus",
                 side as "side: OrderSide",
                 created_at, updated_at,
-                order_type as "order_type: OrderType"        
+                order_type as "order_type: OrderType", trigger_price,
+                time_in_force as "time_in_force: TimeInForce", expires_at, post_only, status_reason, self_trade_prevention as "self_trade_prevention: SelfTradePrevention", outcome_index, display_quantity        
                 "#,
                 order.user_id,
                 order.market_id,
@@ -626,6 +740,7 @@ impl Order {
                 order.outcome as _,
                 order.order_type as _,
                 order.status as _,
+                order.outcome_index,
             )
             .fetch_one(&mut *transaction)
             .await?;
@@ -688,7 +803,10 @@ mod tests {
     use utility_helpers::types::GoogleClaims;
 
     use super::*;
-    use crate::schema::{market::Market, users::User};
+    use crate::schema::{
+        market::{Market, NewMarket},
+        users::User,
+    };
 
     #[tokio::test]
     // #[ignore = "just like this"]
@@ -715,12 +833,21 @@ mod tests {
         let market_expiry = date_time.naive_utc();
 
         let market = Market::create_new_market(
-            "Test Market 0".to_string(),
-            "Test Description".to_string(),
-            "Test Logo".to_string(),
-            Decimal::new(100, 2),
-            market_expiry,
             &pool,
+            &NewMarket {
+                name: "Test Market 0".to_string(),
+                description: "Test Description".to_string(),
+                logo: "Test Logo".to_string(),
+                liquidity_b: Decimal::new(100, 2),
+                tick_size: Decimal::new(1, 2),
+                min_quantity: Decimal::new(1, 2),
+                lot_size: Decimal::new(1, 2),
+                market_maker: false,
+                price_model: PriceModelKind::LmsrWeighted,
+                outcomes: vec!["Yes".to_string(), "No".to_string()],
+                scalar_range: None,
+                market_expiry,
+            },
         )
         .await
         .unwrap();
@@ -734,14 +861,23 @@ mod tests {
         let side = OrderSide::BUY;
 
         let order = Order::create_order(
-            user_id,
-            market_id,
-            price,
-            quantity,
-            side.clone(),
-            Outcome::YES,
-            OrderType::LIMIT,
             &pool,
+            &NewOrder {
+                user_id,
+                market_id,
+                price,
+                quantity,
+                side: side.clone(),
+                outcome: Outcome::YES,
+                order_type: OrderType::LIMIT,
+                trigger_price: None,
+                time_in_force: TimeInForce::GTC,
+                expires_at: None,
+                post_only: false,
+                self_trade_prevention: SelfTradePrevention::CancelNewest,
+                display_quantity: None,
+                outcome_index: 0,
+            },
         )
         .await
         .unwrap();
@@ -814,12 +950,21 @@ mod tests {
         let market_expiry = date_time.naive_utc();
 
         let market = Market::create_new_market(
-            "Test Market 0".to_string(),
-            "Test Description".to_string(),
-            "Test Logo".to_string(),
-            Decimal::new(100, 2),
-            market_expiry,
             &pool,
+            &NewMarket {
+                name: "Test Market 0".to_string(),
+                description: "Test Description".to_string(),
+                logo: "Test Logo".to_string(),
+                liquidity_b: Decimal::new(100, 2),
+                tick_size: Decimal::new(1, 2),
+                min_quantity: Decimal::new(1, 2),
+                lot_size: Decimal::new(1, 2),
+                market_maker: false,
+                price_model: PriceModelKind::LmsrWeighted,
+                outcomes: vec!["Yes".to_string(), "No".to_string()],
+                scalar_range: None,
+                market_expiry,
+            },
         )
         .await
         .unwrap();
@@ -831,14 +976,23 @@ mod tests {
         let quantity = Decimal::from_str("1.0").unwrap();
         let side = OrderSide::BUY;
         let order = Order::create_order(
-            user_id,
-            market_id,
-            price,
-            quantity,
-            side.clone(),
-            Outcome::YES,
-            OrderType::LIMIT,
             &pool,
+            &NewOrder {
+                user_id,
+                market_id,
+                price,
+                quantity,
+                side: side.clone(),
+                outcome: Outcome::YES,
+                order_type: OrderType::LIMIT,
+                trigger_price: None,
+                time_in_force: TimeInForce::GTC,
+                expires_at: None,
+                post_only: false,
+                self_trade_prevention: SelfTradePrevention::CancelNewest,
+                display_quantity: None,
+                outcome_index: 0,
+            },
         )
         .await
         .unwrap();
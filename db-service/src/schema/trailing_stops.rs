@@ -0,0 +1,286 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::{
+    enums::{OrderSide, OrderStatus, OrderType, Outcome, SelfTradePrevention, TimeInForce},
+    orders::Order,
+};
+
+/// Trailing state of the stop loss order, trigger price of the order follows the best price seen since it's placement
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct TrailingStop {
+    pub order_id: Uuid,
+    pub trail_amount: Option<Decimal>, // fixed offset from the best price (0.00-1.00)
+    pub trail_percent: Option<Decimal>, // offset in percentage of the best price (0-100)
+    pub best_price: Option<Decimal>, // highest price for sells, lowest for buys (none until the order reaches the book)
+}
+
+pub struct NewTrailingStopOrder {
+    pub user_id: Uuid,
+    pub market_id: Uuid,
+    pub side: OrderSide,
+    pub outcome: Outcome,
+    pub outcome_index: i32,
+    pub quantity: Decimal,
+    pub self_trade_prevention: SelfTradePrevention,
+    pub trail_amount: Option<Decimal>,
+    pub trail_percent: Option<Decimal>,
+}
+
+impl TrailingStop {
+    /// Creates the stop loss order (executed as market order) along with it's trailing state, trigger price is set once the order reaches the book
+    pub async fn create_trailing_stop_order(
+        pool: &PgPool,
+        new_order: &NewTrailingStopOrder,
+    ) -> Result<(Order, TrailingStop), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        let order = sqlx::query_as!(
+            Order,
+            r#"
+            INSERT INTO "polymarket"."orders"
+            (user_id, market_id, price, quantity, side, outcome, order_type, self_trade_prevention, outcome_index)
+            VALUES ($1, $2, 0, $3, $4, $5, 'stop_loss'::polymarket.order_type, $6, $7)
+            RETURNING
+            id, user_id, market_id,
+            outcome as "outcome: Outcome",
+            price, quantity, filled_quantity,
+            status as "status: OrderStatus",
+            side as "side: OrderSide",
+            created_at, updated_at,
+            order_type as "order_type: OrderType", trigger_price,
+            time_in_force as "time_in_force: TimeInForce", expires_at, post_only, status_reason, self_trade_prevention as "self_trade_prevention: SelfTradePrevention", outcome_index, display_quantity
+            "#,
+            new_order.user_id,
+            new_order.market_id,
+            new_order.quantity,
+            new_order.side as _,
+            new_order.outcome as _,
+            new_order.self_trade_prevention as _,
+            new_order.outcome_index,
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let trailing_stop = sqlx::query_as!(
+            TrailingStop,
+            r#"
+            INSERT INTO polymarket.trailing_stops (order_id, trail_amount, trail_percent)
+            VALUES ($1, $2, $3)
+            RETURNING order_id, trail_amount, trail_percent, best_price
+            "#,
+            order.id,
+            new_order.trail_amount,
+            new_order.trail_percent,
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok((order, trailing_stop))
+    }
+
+    pub async fn get_trailing_stop_by_order_id(
+        pool: &PgPool,
+        order_id: &Uuid,
+    ) -> Result<Option<TrailingStop>, sqlx::Error> {
+        let trailing_stop = sqlx::query_as!(
+            TrailingStop,
+            r#"
+            SELECT order_id, trail_amount, trail_percent, best_price
+            FROM polymarket.trailing_stops
+            WHERE order_id = $1
+            "#,
+            order_id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(trailing_stop)
+    }
+
+    /// Trailing stops whose orders are not triggered (or cancelled) yet, used to restore the order book
+    pub async fn get_live_trailing_stops(pool: &PgPool) -> Result<Vec<TrailingStop>, sqlx::Error> {
+        let trailing_stops = sqlx::query_as!(
+            TrailingStop,
+            r#"
+            SELECT t.order_id, t.trail_amount, t.trail_percent, t.best_price
+            FROM polymarket.trailing_stops t
+            JOIN polymarket.orders o ON o.id = t.order_id
+            WHERE o.order_type = 'stop_loss'::polymarket.order_type
+                AND o.status IN ('open'::polymarket.order_status, 'unspecified'::polymarket.order_status)
+            "#
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(trailing_stops)
+    }
+
+    /// Persists the moved trailing stops, best price of the trailing state along with the trigger price of it's order
+    pub async fn save_trailing_stops(
+        pool: &PgPool,
+        moved: &[(TrailingStop, Decimal)],
+    ) -> Result<(), sqlx::Error> {
+        if moved.is_empty() {
+            return Ok(());
+        }
+        let order_ids = moved.iter().map(|(t, _)| t.order_id).collect::<Vec<_>>();
+        let best_prices = moved
+            .iter()
+            .map(|(t, _)| t.best_price.unwrap_or_default())
+            .collect::<Vec<_>>();
+        let trigger_prices = moved.iter().map(|(_, price)| *price).collect::<Vec<_>>();
+
+        let mut tx = pool.begin().await?;
+        sqlx::query!(
+            r#"
+            UPDATE polymarket.trailing_stops t
+            SET best_price = m.best_price, updated_at = CURRENT_TIMESTAMP
+            FROM UNNEST($1::uuid[], $2::decimal[]) AS m(order_id, best_price)
+            WHERE t.order_id = m.order_id
+            "#,
+            &order_ids,
+            &best_prices,
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            r#"
+            UPDATE polymarket.orders o
+            SET trigger_price = m.trigger_price
+            FROM UNNEST($1::uuid[], $2::decimal[]) AS m(order_id, trigger_price)
+            WHERE o.id = m.order_id
+            "#,
+            &order_ids,
+            &trigger_prices,
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::DateTime;
+    use rust_decimal_macros::dec;
+    use utility_helpers::types::GoogleClaims;
+
+    use super::*;
+    use crate::schema::{
+        enums::PriceModelKind,
+        market::{Market, NewMarket},
+        users::User,
+    };
+
+    #[tokio::test]
+    async fn test_trailing_stop_state() {
+        dotenv::dotenv().ok();
+        let pool = PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+
+        let user = User::create_new_user(
+            &pool,
+            &GoogleClaims {
+                email: "trailing_stops@gmail.com".to_string(),
+                exp: 0,
+                name: "temp".to_string(),
+                picture: "temp".to_string(),
+                sub: "trailing_stops".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+
+        let market_expiry = DateTime::parse_from_rfc3339("2030-01-01T00:00:00Z")
+            .unwrap()
+            .naive_utc();
+        let market = Market::create_new_market(
+            &pool,
+            &NewMarket {
+                name: "Test Market Trailing Stops".to_string(),
+                description: "Test Description".to_string(),
+                logo: "Test Logo".to_string(),
+                liquidity_b: dec!(100),
+                tick_size: dec!(0.01),
+                min_quantity: dec!(0.01),
+                lot_size: dec!(0.01),
+                market_maker: false,
+                price_model: PriceModelKind::Midpoint,
+                outcomes: vec!["Yes".to_string(), "No".to_string()],
+                scalar_range: None,
+                market_expiry,
+            },
+        )
+        .await
+        .unwrap();
+
+        let (order, trailing_stop) = TrailingStop::create_trailing_stop_order(
+            &pool,
+            &NewTrailingStopOrder {
+                user_id: user.id,
+                market_id: market.id,
+                side: OrderSide::SELL,
+                outcome: Outcome::YES,
+                outcome_index: 0,
+                quantity: dec!(10),
+                self_trade_prevention: SelfTradePrevention::CancelNewest,
+                trail_amount: Some(dec!(0.05)),
+                trail_percent: None,
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(order.order_type, OrderType::StopLoss);
+        assert_eq!(order.trigger_price, None);
+        assert_eq!(trailing_stop.best_price, None);
+        assert!(
+            TrailingStop::get_live_trailing_stops(&pool)
+                .await
+                .unwrap()
+                .contains(&trailing_stop)
+        );
+
+        // trailing state survives along with the trigger price of the order
+        let moved = TrailingStop {
+            best_price: Some(dec!(0.62)),
+            ..trailing_stop
+        };
+        TrailingStop::save_trailing_stops(&pool, &[(moved, dec!(0.57))])
+            .await
+            .unwrap();
+        let saved = TrailingStop::get_trailing_stop_by_order_id(&pool, &order.id)
+            .await
+            .unwrap();
+        assert_eq!(saved, Some(moved));
+        let order = Order::find_order_by_id(order.id, &pool)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(order.trigger_price, Some(dec!(0.57)));
+
+        // Clean up (trailing state is deleted along with the order)
+        sqlx::query(r#"DELETE FROM "polymarket"."orders" WHERE market_id = $1"#)
+            .bind(market.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(r#"DELETE FROM "polymarket"."markets" WHERE id = $1"#)
+            .bind(market.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(r#"DELETE FROM "polymarket"."users" WHERE id = $1"#)
+            .bind(user.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        pool.close().await;
+    }
+}
@@ -16,6 +16,7 @@ pub struct UserHoldings {
     pub market_id: Uuid,
     pub shares: Decimal,
     pub outcome: Outcome,
+    pub outcome_index: i32, // outcome of the categorical market, 0 for binary markets
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
 }
@@ -34,6 +35,7 @@ pub struct UserIdWithShares {
 pub struct UserHoldingWithMarket {
     pub market_id: Uuid,
     pub outcome: Outcome,
+    pub outcome_index: i32,
     pub shares: Decimal,
 
     pub market_name: String,
@@ -66,7 +68,8 @@ impl UserHoldings {
                 shares, 
                 created_at, 
                 updated_at, 
-                outcome as "outcome: Outcome";
+                outcome as "outcome: Outcome",
+                outcome_index;
             "#,
             user_id,
             market_id,
@@ -91,7 +94,7 @@ impl UserHoldings {
             r#"
             INSERT INTO polymarket.user_holdings (user_id, market_id, shares, outcome)
             VALUES ($1, $2, $3, $4)
-            ON CONFLICT (user_id, market_id, outcome)
+            ON CONFLICT (user_id, market_id, outcome, outcome_index)
             DO UPDATE SET shares = polymarket.user_holdings.shares + $3,
             updated_at = NOW()            
             RETURNING 
@@ -101,7 +104,8 @@ impl UserHoldings {
                 shares, 
                 created_at, 
                 updated_at, 
-                outcome as "outcome: Outcome";
+                outcome as "outcome: Outcome",
+                outcome_index;
             "#,
             user_id,
             market_id,
@@ -120,13 +124,14 @@ impl UserHoldings {
         market_id: Uuid,
         quantity: Decimal,
         outcome: Outcome,
+        outcome_index: i32,
     ) -> Result<UserHoldings, sqlx::error::Error> {
         let holding = sqlx::query_as!(
             UserHoldings,
             r#"
-            INSERT INTO polymarket.user_holdings (user_id, market_id, shares, outcome)
-            VALUES ($1, $2, $3, $4)
-            ON CONFLICT (user_id, market_id, outcome)
+            INSERT INTO polymarket.user_holdings (user_id, market_id, shares, outcome, outcome_index)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (user_id, market_id, outcome, outcome_index)
             DO UPDATE SET shares = polymarket.user_holdings.shares + $3,
             updated_at = NOW()
             RETURNING 
@@ -136,12 +141,14 @@ impl UserHoldings {
                 shares, 
                 created_at, 
                 updated_at, 
-                outcome as "outcome: Outcome";
+                outcome as "outcome: Outcome",
+                outcome_index;
             "#,
             user_id,
             market_id,
             quantity,
-            outcome as _
+            outcome as _,
+            outcome_index
         )
         .fetch_one(executor)
         .await?;
@@ -154,6 +161,7 @@ impl UserHoldings {
         user_id: Uuid,
         market_id: Uuid,
         outcome: Outcome,
+        outcome_index: i32,
     ) -> Result<UserHoldings, sqlx::error::Error> {
         let mut tx = db_pool.begin().await?;
         println!("Outcome: {:?}", outcome);
@@ -161,13 +169,14 @@ impl UserHoldings {
         // making sure the user holding exists, as on initial new order creation, it might not exist
         sqlx::query!(
             r#"
-            INSERT INTO polymarket.user_holdings (user_id, market_id, shares, outcome)
-            VALUES ($1, $2, 0, $3)
-            ON CONFLICT (user_id, market_id, outcome) DO NOTHING
+            INSERT INTO polymarket.user_holdings (user_id, market_id, shares, outcome, outcome_index)
+            VALUES ($1, $2, 0, $3, $4)
+            ON CONFLICT (user_id, market_id, outcome, outcome_index) DO NOTHING
             "#,
             user_id,
             market_id,
-            outcome as _
+            outcome as _,
+            outcome_index
         )
         .execute(&mut *tx)
         .await?;
@@ -175,13 +184,14 @@ impl UserHoldings {
         let holdings = sqlx::query_as!(
             UserHoldings,
             r#"
-            SELECT id, user_id, market_id, shares, created_at, updated_at, outcome as "outcome: Outcome"
+            SELECT id, user_id, market_id, shares, created_at, updated_at, outcome as "outcome: Outcome", outcome_index
             FROM polymarket.user_holdings
-            WHERE user_id = $1 AND market_id = $2 AND outcome = $3
+            WHERE user_id = $1 AND market_id = $2 AND outcome = $3 AND outcome_index = $4
             "#,
             user_id,
             market_id,
-            outcome as _
+            outcome as _,
+            outcome_index
         )
         .fetch_one(&mut *tx)
         .await?;
@@ -199,7 +209,7 @@ impl UserHoldings {
         sqlx::query_as!(
             UserHoldings,
             r#"
-            SELECT id, user_id, market_id, shares, created_at, updated_at, outcome as "outcome: Outcome"
+            SELECT id, user_id, market_id, shares, created_at, updated_at, outcome as "outcome: Outcome", outcome_index
             FROM polymarket.user_holdings
             WHERE user_id = $1 AND market_id = $2
             "#,
@@ -292,6 +302,7 @@ impl UserHoldings {
             SELECT 
                 uh.market_id,
                 uh.outcome AS "outcome: Outcome",
+                uh.outcome_index,
                 uh.shares,
                 
                 m.name AS market_name,
@@ -21,6 +21,7 @@ pub struct UserTrades {
     market_id: Uuid,
     trade_type: OrderSide,
     outcome: Outcome,
+    outcome_index: i32, // outcome of the categorical market, 0 for binary markets
     price: Decimal,
     quantity: Decimal,
     timestamp: NaiveDateTime,
@@ -39,6 +40,7 @@ pub struct UserTradesWithMarket {
     // trades
     pub trade_type: OrderSide,
     pub trade_outcome: Outcome,
+    pub trade_outcome_index: i32,
     pub trade_price: Decimal,
     pub trade_quantity: Decimal,
 }
@@ -64,6 +66,7 @@ impl UserTrades {
         user_id: Uuid,
         market_id: Uuid,
         outcome: Outcome,
+        outcome_index: i32,
         price: Decimal,
         quantity: Decimal,
         trade_type: OrderSide,
@@ -71,10 +74,10 @@ impl UserTrades {
         let trade = sqlx::query_as!(
             UserTrades,
             r#"
-            INSERT INTO polymarket.user_trades (buy_order_id, sell_order_id, user_id, market_id, outcome, price, quantity, trade_type)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            INSERT INTO polymarket.user_trades (buy_order_id, sell_order_id, user_id, market_id, outcome, outcome_index, price, quantity, trade_type)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
             RETURNING id, buy_order_id, sell_order_id, user_id, market_id,
-            outcome as "outcome: Outcome",
+            outcome as "outcome: Outcome", outcome_index,
             price, quantity, timestamp, created_at, updated_at,
             trade_type as "trade_type: OrderSide"
             "#,
@@ -83,6 +86,7 @@ impl UserTrades {
             user_id,
             market_id,
             outcome as Outcome,
+            outcome_index,
             price,
             quantity,
             trade_type as OrderSide,
@@ -175,6 +179,7 @@ impl UserTrades {
 
                 t.trade_type AS "trade_type: OrderSide",
                 t.outcome AS "trade_outcome: Outcome",
+                t.outcome_index AS trade_outcome_index,
                 t.price AS trade_price,
                 t.quantity AS trade_quantity
             FROM polymarket.user_trades t
@@ -8,7 +8,7 @@ use uuid::Uuid;
 
 use utility_helpers::{log_info, symmetric::encrypt, types::GoogleClaims};
 
-use crate::schema::enums::OrderSide;
+use crate::schema::enums::{OrderSide, SelfTradePrevention};
 
 #[derive(Debug, Serialize, Default, Deserialize)]
 pub struct User {
@@ -27,6 +27,9 @@ pub struct User {
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
     pub balance: Decimal,
+
+    // default self trade prevention mode of the user's orders
+    pub self_trade_prevention: SelfTradePrevention,
 }
 
 #[derive(Debug, Serialize, Default)]
@@ -93,7 +96,10 @@ impl User {
                 private_key
             ) VALUES (
                 $1, $2, $3, $4, $5, $6
-            ) RETURNING *                
+            ) RETURNING
+            id, google_id, email, name, avatar, last_login, public_key, private_key,
+            created_at, updated_at, balance,
+            self_trade_prevention as "self_trade_prevention: SelfTradePrevention"
             "#,
             claims.sub,
             claims.email,
@@ -117,7 +123,10 @@ impl User {
         let existing_user = sqlx::query_as!(
             User,
             r#"
-            SELECT * FROM "polymarket"."users" WHERE google_id = $1
+            SELECT id, google_id, email, name, avatar, last_login, public_key, private_key,
+            created_at, updated_at, balance,
+            self_trade_prevention as "self_trade_prevention: SelfTradePrevention"
+            FROM "polymarket"."users" WHERE google_id = $1
             "#,
             claims.sub
         )
@@ -134,7 +143,10 @@ impl User {
                     avatar = $3,
                     last_login = CURRENT_TIMESTAMP
                 WHERE id = $4
-                RETURNING *
+                RETURNING
+                id, google_id, email, name, avatar, last_login, public_key, private_key,
+                created_at, updated_at, balance,
+                self_trade_prevention as "self_trade_prevention: SelfTradePrevention"
                 "#,
                 claims.email,
                 claims.name,
@@ -161,7 +173,10 @@ impl User {
         let user = sqlx::query_as!(
             User,
             r#"
-            SELECT * FROM "polymarket"."users" WHERE id = $1
+            SELECT id, google_id, email, name, avatar, last_login, public_key, private_key,
+            created_at, updated_at, balance,
+            self_trade_prevention as "self_trade_prevention: SelfTradePrevention"
+            FROM "polymarket"."users" WHERE id = $1
             "#,
             user_id
         )
@@ -244,6 +259,97 @@ impl User {
         Ok(())
     }
 
+    /// Updates balance of two users on the same side of trade (both buyers pay for minted share pair, both sellers receive payout of merged pair)
+    pub async fn update_two_users_balance_same_side<'a>(
+        executor: impl Executor<'a, Database = Postgres>,
+        user_1_id: Uuid,
+        user_1_amount: Decimal,
+        user_2_id: Uuid,
+        user_2_amount: Decimal,
+        side: OrderSide,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE polymarket.users
+            SET balance = balance + (
+                (CASE WHEN id = $1 THEN $2::numeric ELSE $4::numeric END)
+                * (CASE WHEN $5 = 'buy'::polymarket.order_side THEN -1 ELSE 1 END)
+            )
+            WHERE id IN ($1, $3);
+            "#,
+            user_1_id,
+            user_1_amount,
+            user_2_id,
+            user_2_amount,
+            side as _,
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Updates balance of the user trading against the market maker (buyer pays the amount, seller receives it)
+    pub async fn update_user_balance<'a>(
+        executor: impl Executor<'a, Database = Postgres>,
+        user_id: Uuid,
+        amount: Decimal,
+        side: OrderSide,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE polymarket.users
+            SET balance = balance + ($2::numeric * (CASE WHEN $3 = 'buy'::polymarket.order_side THEN -1 ELSE 1 END))
+            WHERE id = $1;
+            "#,
+            user_id,
+            amount,
+            side as _,
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Account default self trade prevention mode, used for orders which don't specify it
+    pub async fn get_self_trade_prevention(
+        executor: impl Executor<'_, Database = Postgres>,
+        user_id: Uuid,
+    ) -> Result<SelfTradePrevention, sqlx::Error> {
+        let self_trade_prevention = sqlx::query_scalar!(
+            r#"
+            SELECT self_trade_prevention as "self_trade_prevention: SelfTradePrevention"
+            FROM polymarket.users WHERE id = $1
+            "#,
+            user_id
+        )
+        .fetch_one(executor)
+        .await?;
+
+        Ok(self_trade_prevention)
+    }
+
+    pub async fn update_self_trade_prevention(
+        pool: &PgPool,
+        user_id: Uuid,
+        self_trade_prevention: SelfTradePrevention,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE polymarket.users
+            SET self_trade_prevention = $1
+            WHERE id = $2
+            "#,
+            self_trade_prevention as _,
+            user_id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
     pub async fn get_all_user_ids(pool: &PgPool) -> Result<Vec<Uuid>, sqlx::Error> {
         let user_ids = sqlx::query!(
             r#"
@@ -282,7 +388,10 @@ impl User {
                 avatar = EXCLUDED.avatar,
                 last_login = CURRENT_TIMESTAMP,
                 balance = EXCLUDED.balance
-            RETURNING *
+            RETURNING
+            id, google_id, email, name, avatar, last_login, public_key, private_key,
+            created_at, updated_at, balance,
+            self_trade_prevention as "self_trade_prevention: SelfTradePrevention"
             "#,
             admin_google_id,
             admin_email,
@@ -575,6 +684,42 @@ mod tests {
         pool.close().await;
     }
 
+    #[tokio::test]
+    async fn test_update_self_trade_prevention() {
+        dotenv::dotenv().ok();
+
+        let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let pool = PgPool::connect(&database_url).await.unwrap();
+
+        let unique_id = Uuid::new_v4();
+        let google_claims = GoogleClaims {
+            sub: format!("test_google_id_{}", unique_id),
+            email: format!("test_{}@gmail.com", unique_id),
+            exp: 60 * 60 * 24 * 3,
+            name: "Test User STP".to_string(),
+            picture: "https://example.com/avatar_stp.png".to_string(),
+        };
+        let user = User::create_new_user(&pool, &google_claims).await.unwrap();
+
+        // new accounts cancel the incoming order by default
+        let self_trade_prevention = User::get_self_trade_prevention(&pool, user.id)
+            .await
+            .unwrap();
+        assert_eq!(self_trade_prevention, SelfTradePrevention::CancelNewest);
+
+        User::update_self_trade_prevention(&pool, user.id, SelfTradePrevention::CancelBoth)
+            .await
+            .unwrap();
+        let self_trade_prevention = User::get_self_trade_prevention(&pool, user.id)
+            .await
+            .unwrap();
+        assert_eq!(self_trade_prevention, SelfTradePrevention::CancelBoth);
+
+        // Clean up
+        cleanup_test_user(&pool, user.id).await;
+        pool.close().await;
+    }
+
     #[tokio::test]
     async fn test_get_user_by_id_nonexistent() {
         dotenv::dotenv().ok();
@@ -77,6 +77,35 @@ pub struct OrderBook {
 }
 #[derive(serde::Serialize, serde::Deserialize)]
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct OutcomeBook {
+    /// Index of the outcome (0 = YES, 1 = NO for binary market)
+    #[prost(uint32, tag = "1")]
+    pub outcome_index: u32,
+    /// Order book of the outcome
+    #[prost(message, optional, tag = "2")]
+    pub book: ::core::option::Option<OrderBook>,
+    /// Name of the outcome
+    #[prost(string, tag = "3")]
+    pub name: ::prost::alloc::string::String,
+    /// Latest price of the outcome
+    #[prost(double, tag = "4")]
+    pub price: f64,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MarketOutcome {
+    /// Index of the outcome (0 = YES, 1 = NO for binary market)
+    #[prost(uint32, tag = "1")]
+    pub outcome_index: u32,
+    /// Name of the outcome
+    #[prost(string, tag = "2")]
+    pub name: ::prost::alloc::string::String,
+    /// Latest price of the outcome
+    #[prost(double, tag = "3")]
+    pub price: f64,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GetMarketBookResponse {
     /// ID of the market
     #[prost(string, tag = "1")]
@@ -87,6 +116,9 @@ pub struct GetMarketBookResponse {
     /// Order book for NO outcome
     #[prost(message, optional, tag = "3")]
     pub no_book: ::core::option::Option<OrderBook>,
+    /// Order books of all outcomes
+    #[prost(message, repeated, tag = "4")]
+    pub outcome_books: ::prost::alloc::vec::Vec<OutcomeBook>,
 }
 #[derive(serde::Serialize, serde::Deserialize)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -129,6 +161,28 @@ pub struct Market {
     pub yes_price: f32,
     #[prost(float, tag = "12")]
     pub no_price: f32,
+    #[prost(enumeration = "MarketType", tag = "13")]
+    pub market_type: i32,
+    /// set for scalar markets only
+    #[prost(message, optional, tag = "14")]
+    pub scalar: ::core::option::Option<ScalarMetadata>,
+    /// All outcomes of the market, binary market has Yes / No
+    #[prost(message, repeated, tag = "15")]
+    pub outcomes: ::prost::alloc::vec::Vec<MarketOutcome>,
+}
+/// LONG (YES) pays linearly between the bounds, SHORT (NO) pays the rest
+#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ScalarMetadata {
+    #[prost(double, tag = "1")]
+    pub lower_bound: f64,
+    #[prost(double, tag = "2")]
+    pub upper_bound: f64,
+    #[prost(string, tag = "3")]
+    pub unit: ::prost::alloc::string::String,
+    /// set once the market is settled
+    #[prost(double, optional, tag = "4")]
+    pub resolved_value: ::core::option::Option<f64>,
 }
 #[derive(serde::Serialize, serde::Deserialize)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -161,6 +215,9 @@ pub struct MarketPrice {
     pub latest_yes_price: f64,
     #[prost(double, tag = "3")]
     pub latest_no_price: f64,
+    /// Latest prices of all outcomes by their index
+    #[prost(double, repeated, tag = "4")]
+    pub latest_outcome_prices: ::prost::alloc::vec::Vec<f64>,
 }
 #[derive(serde::Serialize, serde::Deserialize)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -212,6 +269,10 @@ pub enum MarketStatus {
     Open = 1,
     Closed = 2,
     Settled = 3,
+    Draft = 4,
+    Paused = 5,
+    Resolving = 6,
+    Voided = 7,
 }
 impl MarketStatus {
     /// String value of the enum field names used in the ProtoBuf definition.
@@ -224,6 +285,10 @@ impl MarketStatus {
             Self::Open => "OPEN",
             Self::Closed => "CLOSED",
             Self::Settled => "SETTLED",
+            Self::Draft => "DRAFT",
+            Self::Paused => "PAUSED",
+            Self::Resolving => "RESOLVING",
+            Self::Voided => "VOIDED",
         }
     }
     /// Creates an enum from field names used in the ProtoBuf definition.
@@ -233,6 +298,43 @@ impl MarketStatus {
             "OPEN" => Some(Self::Open),
             "CLOSED" => Some(Self::Closed),
             "SETTLED" => Some(Self::Settled),
+            "DRAFT" => Some(Self::Draft),
+            "PAUSED" => Some(Self::Paused),
+            "RESOLVING" => Some(Self::Resolving),
+            "VOIDED" => Some(Self::Voided),
+            _ => None,
+        }
+    }
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum MarketType {
+    UnspecifiedMarketType = 0,
+    Binary = 1,
+    Categorical = 2,
+    Scalar = 3,
+}
+impl MarketType {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Self::UnspecifiedMarketType => "UNSPECIFIED_MARKET_TYPE",
+            Self::Binary => "BINARY",
+            Self::Categorical => "CATEGORICAL",
+            Self::Scalar => "SCALAR",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "UNSPECIFIED_MARKET_TYPE" => Some(Self::UnspecifiedMarketType),
+            "BINARY" => Some(Self::Binary),
+            "CATEGORICAL" => Some(Self::Categorical),
+            "SCALAR" => Some(Self::Scalar),
             _ => None,
         }
     }
@@ -1,3 +1,4 @@
 pub mod price;
 pub mod markets;
 pub mod common;
+pub mod quote;
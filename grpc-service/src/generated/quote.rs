@@ -0,0 +1,253 @@
+// This file is @generated by prost-build.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QuoteMarketOrderRequest {
+    /// ID of the market
+    #[prost(string, tag = "1")]
+    pub market_id: ::prost::alloc::string::String,
+    #[prost(enumeration = "super::markets::TradeType", tag = "2")]
+    pub side: i32,
+    #[prost(enumeration = "super::markets::Outcome", tag = "3")]
+    pub outcome: i32,
+    /// outcome of the categorical market (yes shares of it are traded)
+    #[prost(uint32, tag = "4")]
+    pub outcome_index: u32,
+    #[prost(oneof = "quote_market_order_request::Size", tags = "5, 6")]
+    pub size: ::core::option::Option<quote_market_order_request::Size>,
+}
+/// Nested message and enum types in `QuoteMarketOrderRequest`.
+pub mod quote_market_order_request {
+    #[derive(serde::Serialize, serde::Deserialize)]
+    #[derive(Clone, Copy, PartialEq, ::prost::Oneof)]
+    pub enum Size {
+        /// shares to buy or sell
+        #[prost(double, tag = "5")]
+        Quantity(f64),
+        /// amount to spend (or to receive for sells)
+        #[prost(double, tag = "6")]
+        Budget(f64),
+    }
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct QuoteFill {
+    /// Price of the level
+    #[prost(double, tag = "1")]
+    pub price: f64,
+    /// Shares filled at this price
+    #[prost(double, tag = "2")]
+    pub quantity: f64,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QuoteMarketOrderResponse {
+    #[prost(string, tag = "1")]
+    pub market_id: ::prost::alloc::string::String,
+    /// expected fills per price level, in the order of execution
+    #[prost(message, repeated, tag = "2")]
+    pub fills: ::prost::alloc::vec::Vec<QuoteFill>,
+    #[prost(double, tag = "3")]
+    pub filled_quantity: f64,
+    #[prost(double, tag = "4")]
+    pub total_cost: f64,
+    /// 0 if nothing is filled
+    #[prost(double, tag = "5")]
+    pub average_price: f64,
+    /// midpoint of the quoted outcome after the execution
+    #[prost(double, tag = "6")]
+    pub midpoint: f64,
+    /// prices after the execution
+    #[prost(double, tag = "7")]
+    pub current_yes_price: f64,
+    #[prost(double, tag = "8")]
+    pub current_no_price: f64,
+    /// prices of all outcomes by their index after the execution
+    #[prost(double, repeated, tag = "9")]
+    pub outcome_prices: ::prost::alloc::vec::Vec<f64>,
+    /// why the order would not be (fully) filled, empty otherwise
+    #[prost(string, tag = "10")]
+    pub reason: ::prost::alloc::string::String,
+}
+/// Generated server implementations.
+pub mod quote_service_server {
+    #![allow(
+        unused_variables,
+        dead_code,
+        missing_docs,
+        clippy::wildcard_imports,
+        clippy::let_unit_value,
+    )]
+    use tonic::codegen::*;
+    /// Generated trait containing gRPC methods that should be implemented for use with QuoteServiceServer.
+    #[async_trait]
+    pub trait QuoteService: std::marker::Send + std::marker::Sync + 'static {
+        async fn quote_market_order(
+            &self,
+            request: tonic::Request<super::QuoteMarketOrderRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::QuoteMarketOrderResponse>,
+            tonic::Status,
+        >;
+    }
+    #[derive(Debug)]
+    pub struct QuoteServiceServer<T> {
+        inner: Arc<T>,
+        accept_compression_encodings: EnabledCompressionEncodings,
+        send_compression_encodings: EnabledCompressionEncodings,
+        max_decoding_message_size: Option<usize>,
+        max_encoding_message_size: Option<usize>,
+    }
+    impl<T> QuoteServiceServer<T> {
+        pub fn new(inner: T) -> Self {
+            Self::from_arc(Arc::new(inner))
+        }
+        pub fn from_arc(inner: Arc<T>) -> Self {
+            Self {
+                inner,
+                accept_compression_encodings: Default::default(),
+                send_compression_encodings: Default::default(),
+                max_decoding_message_size: None,
+                max_encoding_message_size: None,
+            }
+        }
+        pub fn with_interceptor<F>(
+            inner: T,
+            interceptor: F,
+        ) -> InterceptedService<Self, F>
+        where
+            F: tonic::service::Interceptor,
+        {
+            InterceptedService::new(Self::new(inner), interceptor)
+        }
+        /// Enable decompressing requests with the given encoding.
+        #[must_use]
+        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.accept_compression_encodings.enable(encoding);
+            self
+        }
+        /// Compress responses with the given encoding, if the client supports it.
+        #[must_use]
+        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.send_compression_encodings.enable(encoding);
+            self
+        }
+        /// Limits the maximum size of a decoded message.
+        ///
+        /// Default: `4MB`
+        #[must_use]
+        pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
+            self.max_decoding_message_size = Some(limit);
+            self
+        }
+        /// Limits the maximum size of an encoded message.
+        ///
+        /// Default: `usize::MAX`
+        #[must_use]
+        pub fn max_encoding_message_size(mut self, limit: usize) -> Self {
+            self.max_encoding_message_size = Some(limit);
+            self
+        }
+    }
+    impl<T, B> tonic::codegen::Service<http::Request<B>> for QuoteServiceServer<T>
+    where
+        T: QuoteService,
+        B: Body + std::marker::Send + 'static,
+        B::Error: Into<StdError> + std::marker::Send + 'static,
+    {
+        type Response = http::Response<tonic::body::Body>;
+        type Error = std::convert::Infallible;
+        type Future = BoxFuture<Self::Response, Self::Error>;
+        fn poll_ready(
+            &mut self,
+            _cx: &mut Context<'_>,
+        ) -> Poll<std::result::Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+        fn call(&mut self, req: http::Request<B>) -> Self::Future {
+            match req.uri().path() {
+                "/quote.QuoteService/QuoteMarketOrder" => {
+                    #[allow(non_camel_case_types)]
+                    struct QuoteMarketOrderSvc<T: QuoteService>(pub Arc<T>);
+                    impl<
+                        T: QuoteService,
+                    > tonic::server::UnaryService<super::QuoteMarketOrderRequest>
+                    for QuoteMarketOrderSvc<T> {
+                        type Response = super::QuoteMarketOrderResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::QuoteMarketOrderRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as QuoteService>::quote_market_order(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = QuoteMarketOrderSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                _ => {
+                    Box::pin(async move {
+                        let mut response = http::Response::new(
+                            tonic::body::Body::default(),
+                        );
+                        let headers = response.headers_mut();
+                        headers
+                            .insert(
+                                tonic::Status::GRPC_STATUS,
+                                (tonic::Code::Unimplemented as i32).into(),
+                            );
+                        headers
+                            .insert(
+                                http::header::CONTENT_TYPE,
+                                tonic::metadata::GRPC_CONTENT_TYPE,
+                            );
+                        Ok(response)
+                    })
+                }
+            }
+        }
+    }
+    impl<T> Clone for QuoteServiceServer<T> {
+        fn clone(&self) -> Self {
+            let inner = self.inner.clone();
+            Self {
+                inner,
+                accept_compression_encodings: self.accept_compression_encodings,
+                send_compression_encodings: self.send_compression_encodings,
+                max_decoding_message_size: self.max_decoding_message_size,
+                max_encoding_message_size: self.max_encoding_message_size,
+            }
+        }
+    }
+    /// Generated gRPC service name
+    pub const SERVICE_NAME: &str = "quote.QuoteService";
+    impl<T> tonic::server::NamedService for QuoteServiceServer<T> {
+        const NAME: &'static str = SERVICE_NAME;
+    }
+}
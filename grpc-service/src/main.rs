@@ -4,8 +4,12 @@ use grpc_service::{
     generated::{
         markets::market_service_server::MarketServiceServer,
         price::price_service_server::PriceServiceServer,
+        quote::quote_service_server::QuoteServiceServer,
+    },
+    procedures::{
+        market_services::MarketServiceStub, price_services::PriceServiceStub,
+        quote_services::QuoteServiceStub,
     },
-    procedures::{market_services::MarketServiceStub, price_services::PriceServiceStub},
     state::AppState,
 };
 use tonic::transport::Server;
@@ -35,6 +39,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let pair_service_layer = PriceServiceStub {
         state: state.clone(),
     };
+    let quote_service_layer = QuoteServiceStub {
+        state: state.clone(),
+    };
 
     log_info!("GRPC server running on port grpc://localhost:5010");
 
@@ -47,6 +54,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .add_service(reflector_service)
         .add_service(MarketServiceServer::new(market_service_layer))
         .add_service(PriceServiceServer::new(pair_service_layer))
+        .add_service(QuoteServiceServer::new(quote_service_layer))
         .serve(addr)
         .await?;
 
@@ -1,8 +1,8 @@
 use std::str::FromStr;
 
 use db_service::schema::{
-    enums::MarketStatus, market::Market as SchemaMarket, user_holdings::UserHoldings,
-    user_trades::UserTrades,
+    enums::MarketStatus, market::Market as SchemaMarket, market_outcomes::MarketOutcome,
+    user_holdings::UserHoldings, user_trades::UserTrades,
 };
 use sqlx::types::Uuid;
 use tonic::{Request, Response, Status};
@@ -19,9 +19,10 @@ use crate::{
     state::SafeState,
     utils::{
         clickhouse_queries::{
-            MARKET_LATEST_PRICE_QUERY, MARKET_VOLUME_BASE_QUERY, ORDER_BOOK_INITIALS,
+            MARKET_LATEST_PRICE_QUERY, MARKET_VOLUME_BASE_QUERY, ORDER_BOOK_DELTAS,
+            ORDER_BOOK_INITIALS,
         },
-        clickhouse_schema::{GetOrderBook, MarketPriceResponse, VolumeData},
+        clickhouse_schema::{GetOrderBook, MarketPriceResponse, OrderBookDelta, VolumeData},
     },
     validate_numbers, validate_strings,
 };
@@ -49,6 +50,10 @@ impl MarketService for MarketServiceStub {
             1 => MarketStatus::OPEN,
             2 => MarketStatus::CLOSED,
             3 => MarketStatus::SETTLED,
+            4 => MarketStatus::DRAFT,
+            5 => MarketStatus::PAUSED,
+            6 => MarketStatus::RESOLVING,
+            7 => MarketStatus::VOIDED,
             _ => return Err(Status::invalid_argument("Invalid market status")),
         };
 
@@ -81,11 +86,23 @@ impl MarketService for MarketServiceStub {
             .await
             .map_err(|e| Status::internal(format!("Failed to get market {e}")))?;
 
+        let market_ids = markets.items.iter().map(|item| item.id).collect::<Vec<_>>();
+        let outcomes = MarketOutcome::get_outcomes_by_market_ids(&self.state.db_pool, &market_ids)
+            .await
+            .map_err(|e| Status::internal(format!("Failed to get market outcomes {e}")))?;
+
         let response = GetPaginatedMarketResponse {
             markets: markets
                 .items
                 .iter()
-                .map(|item| from_db_market(item, 0.5, 0.5))
+                .map(|item| {
+                    let market_outcomes = outcomes
+                        .iter()
+                        .filter(|outcome| outcome.market_id == item.id)
+                        .cloned()
+                        .collect::<Vec<_>>();
+                    from_db_market(item, 0.5, 0.5, &market_outcomes, &[])
+                })
                 .collect(),
             page_info: Some(markets.page_info.into()),
         };
@@ -119,8 +136,6 @@ impl MarketService for MarketServiceStub {
             .map_err(|e| Status::internal(format!("Failed to get market id {e}")))?;
 
         if let Some(market) = market {
-            let market = from_db_market(&market, 0.5, 0.5);
-
             // we are not caching the volume info in Redis, as it changes frequently
 
             let get_volume_info_future = self
@@ -136,10 +151,15 @@ impl MarketService for MarketServiceStub {
                 .query(MARKET_LATEST_PRICE_QUERY)
                 .bind(market_id)
                 .fetch_optional::<MarketPriceResponse>();
+            let get_outcomes_future =
+                MarketOutcome::get_outcomes_by_market_id(&self.state.db_pool, market_id);
 
-            let (volume_info, market_price) =
-                tokio::try_join!(get_volume_info_future, get_market_price_future)
-                    .map_err(|e| Status::internal(format!("Failed to fetch market data: {}", e)))?;
+            let (volume_info, market_price, outcomes) = tokio::try_join!(
+                async { get_volume_info_future.await.map_err(|e| e.to_string()) },
+                async { get_market_price_future.await.map_err(|e| e.to_string()) },
+                async { get_outcomes_future.await.map_err(|e| e.to_string()) },
+            )
+            .map_err(|e| Status::internal(format!("Failed to fetch market data: {}", e)))?;
 
             let (volume_info_resp, market_price_resp) =
                 if let (Some(volume_info), Some(market_price)) = (volume_info, market_price) {
@@ -148,6 +168,14 @@ impl MarketService for MarketServiceStub {
                     (VolumeData::default(), MarketPriceResponse::default())
                 };
 
+            let market = from_db_market(
+                &market,
+                0.5,
+                0.5,
+                &outcomes,
+                &market_price_resp.latest_outcome_prices,
+            );
+
             let response = GetMarketByIdResponse {
                 market: Some(market),
                 volume_info: Some(volume_info_resp.into()),
@@ -173,26 +201,56 @@ impl MarketService for MarketServiceStub {
         let market_id = Uuid::from_str(&market_id)
             .map_err(|_| Status::invalid_argument("Invalid market id"))?;
 
-        let order_book_initials = self
+        let get_order_book_future = self
             .state
             .clickhouse_client
             .query(ORDER_BOOK_INITIALS)
-            .bind(depth)
-            .bind(depth)
-            .bind(depth)
-            .bind(depth)
             .bind(market_id)
-            .fetch_optional::<GetOrderBook>()
-            .await
-            .map_err(|e| Status::internal(format!("Failed to fetch market book: {}", e)))?;
+            .fetch_optional::<GetOrderBook>();
+        let get_market_price_future = self
+            .state
+            .clickhouse_client
+            .query(MARKET_LATEST_PRICE_QUERY)
+            .bind(market_id)
+            .fetch_optional::<MarketPriceResponse>();
+        let get_outcomes_future =
+            MarketOutcome::get_outcomes_by_market_id(&self.state.db_pool, market_id);
+
+        let (order_book_initials, market_price, outcomes) = tokio::try_join!(
+            async { get_order_book_future.await.map_err(|e| e.to_string()) },
+            async { get_market_price_future.await.map_err(|e| e.to_string()) },
+            async { get_outcomes_future.await.map_err(|e| e.to_string()) },
+        )
+        .map_err(|e| Status::internal(format!("Failed to fetch market book: {}", e)))?;
 
-        if order_book_initials.is_none() {
+        let Some(mut order_book_initials) = order_book_initials else {
             return Err(Status::not_found(format!(
                 "Market book for market id {market_id} not found"
             )));
-        }
+        };
 
-        let order_book = to_resp_for_market_book(order_book_initials.unwrap());
+        // books are published as the periodic snapshots and the deltas in between
+        let deltas = self
+            .state
+            .clickhouse_client
+            .query(ORDER_BOOK_DELTAS)
+            .bind(market_id)
+            .bind(order_book_initials.epoch)
+            .bind(order_book_initials.sequence)
+            .fetch_all::<OrderBookDelta>()
+            .await
+            .map_err(|e| Status::internal(format!("Failed to fetch market book deltas: {}", e)))?;
+        order_book_initials.apply_deltas(&deltas);
+
+        let outcome_prices = market_price
+            .map(|price| price.latest_outcome_prices)
+            .unwrap_or_default();
+        let order_book = to_resp_for_market_book(
+            order_book_initials,
+            depth as usize,
+            &outcomes,
+            &outcome_prices,
+        );
         let response = Response::new(order_book);
 
         Ok(response)
@@ -273,7 +331,10 @@ mod tests {
 
     use sqlx::types::Uuid;
 
-    use crate::utils::clickhouse_schema::{GetOrderBook, MarketPriceResponse, VolumeData};
+    use crate::utils::{
+        clickhouse_queries::{ORDER_BOOK_DELTAS, ORDER_BOOK_INITIALS},
+        clickhouse_schema::{GetOrderBook, MarketPriceResponse, OrderBookDelta, VolumeData},
+    };
 
     #[tokio::test]
     #[ignore = "Requires market id"]
@@ -284,37 +345,9 @@ mod tests {
             .with_user("polyMarket")
             .with_password("polyMarket");
         let market_id = Uuid::from_str("91afed7f-6004-4968-984f-cdc968ae6013").unwrap();
-        let depth = 10;
 
         let resp = client
-            .query(
-                r#"
-                 SELECT
-                    market_id,
-                    ts,
-                    created_at,
-
-                    CAST(arraySlice(
-                        arrayFilter(x -> x.2 > 0, yes_bids), 1, ?
-                        ) AS Array(Tuple(price Float64, shares Float64, users UInt32))) AS yes_bids,
-                    CAST(arraySlice(
-                        arrayFilter(x -> x.2 > 0, yes_asks), 1, ?
-                        ) AS Array(Tuple(price Float64, shares Float64, users UInt32))) AS yes_asks,
-                    CAST(arraySlice(
-                        arrayFilter(x -> x.2 > 0, no_bids), 1, ?
-                        ) AS Array(Tuple(price Float64, shares Float64, users UInt32))) AS no_bids,
-                    CAST(arraySlice(
-                        arrayFilter(x -> x.2 > 0, no_asks), 1, ?
-                        ) AS Array(Tuple(price Float64, shares Float64, users UInt32))) AS no_asks
-                FROM market_order_book WHERE market_id = ?
-                ORDER BY ts DESC
-                LIMIT 1
-            "#,
-            )
-            .bind(depth)
-            .bind(depth)
-            .bind(depth)
-            .bind(depth)
+            .query(ORDER_BOOK_INITIALS)
             .bind(market_id)
             .fetch_optional::<GetOrderBook>()
             .await
@@ -324,6 +357,22 @@ mod tests {
             .unwrap();
 
         assert!(resp.is_some(), "Response should not be empty");
+
+        let mut order_book = resp.unwrap();
+        let deltas = client
+            .query(ORDER_BOOK_DELTAS)
+            .bind(market_id)
+            .bind(order_book.epoch)
+            .bind(order_book.sequence)
+            .fetch_all::<OrderBookDelta>()
+            .await
+            .unwrap();
+        order_book.apply_deltas(&deltas);
+        assert!(
+            deltas
+                .last()
+                .is_none_or(|delta| delta.sequence == order_book.sequence)
+        );
     }
 
     #[tokio::test]
@@ -396,7 +445,8 @@ mod tests {
                 SELECT
                     market_id,
                     toFloat64(argMax(yes_price, ts)) AS latest_yes_price,
-                    toFloat64(argMax(no_price, ts)) AS latest_no_price
+                    toFloat64(argMax(no_price, ts)) AS latest_no_price,
+                    arrayMap(price -> toFloat64(price), argMax(outcome_prices, ts)) AS latest_outcome_prices
                 FROM market_price_data
                 WHERE market_id = ?
                 GROUP BY market_id
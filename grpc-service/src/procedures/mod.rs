@@ -1,7 +1,8 @@
 use db_service::{
     pagination::PageInfo as DbPageInfo,
     schema::{
-        market::Market as DbMarket, user_holdings::UserIdWithShares, user_trades::MarketTrades,
+        market::Market as DbMarket, market_outcomes::MarketOutcome as DbMarketOutcome,
+        user_holdings::UserIdWithShares, user_trades::MarketTrades,
     },
 };
 use utility_helpers::to_f64_verbose;
@@ -10,19 +11,26 @@ use crate::{
     generated::{
         common::PageInfo,
         markets::{
-            GetMarketBookResponse, Market, MarketTrade as GeneratedMarketTrade, OrderBook,
-            OrderLevel, UserWithTotalHoldings,
+            GetMarketBookResponse, Market, MarketOutcome, MarketTrade as GeneratedMarketTrade,
+            OrderBook, OrderLevel, OutcomeBook, ScalarMetadata, UserWithTotalHoldings,
         },
     },
-    utils::clickhouse_schema::GetOrderBook,
+    utils::clickhouse_schema::{GetOrderBook, OrderBook as ClickhouseOrderBook},
 };
 
 pub mod market_services;
 pub mod price_services;
+pub mod quote_services;
 
 // all type conversations.....
 
-pub fn from_db_market(value: &DbMarket, yes_price: f32, no_price: f32) -> Market {
+pub fn from_db_market(
+    value: &DbMarket,
+    yes_price: f32,
+    no_price: f32,
+    outcomes: &[DbMarketOutcome],
+    outcome_prices: &[f64],
+) -> Market {
     Market {
         created_at: value.created_at.to_string(),
         description: value.description.clone(),
@@ -36,9 +44,36 @@ pub fn from_db_market(value: &DbMarket, yes_price: f32, no_price: f32) -> Market
         market_expiry: value.market_expiry.to_string(),
         no_price,
         yes_price,
+        market_type: value.market_type as i32,
+        scalar: value.scalar_range().map(|range| ScalarMetadata {
+            lower_bound: to_f64_verbose(range.lower_bound),
+            upper_bound: to_f64_verbose(range.upper_bound),
+            unit: range.unit,
+            resolved_value: value.resolved_value.map(to_f64_verbose),
+        }),
+        outcomes: outcomes
+            .iter()
+            .map(|outcome| MarketOutcome {
+                outcome_index: outcome.outcome_index as u32,
+                name: outcome.name.clone(),
+                price: outcome_price(
+                    outcome_prices,
+                    outcome.outcome_index as usize,
+                    outcomes.len(),
+                ),
+            })
+            .collect(),
     }
 }
 
+/// Latest price of the outcome, outcomes without any trade are at the even price (`1 / outcome_count`)
+fn outcome_price(outcome_prices: &[f64], outcome_index: usize, outcome_count: usize) -> f64 {
+    outcome_prices
+        .get(outcome_index)
+        .copied()
+        .unwrap_or_else(|| 1.0 / outcome_count.max(1) as f64)
+}
+
 impl From<DbPageInfo> for PageInfo {
     fn from(value: DbPageInfo) -> Self {
         PageInfo {
@@ -84,48 +119,63 @@ impl From<UserIdWithShares> for UserWithTotalHoldings {
     }
 }
 
-pub fn to_resp_for_market_book(data: GetOrderBook) -> GetMarketBookResponse {
+/// Order books of all outcomes (binary market included) along with their names and latest prices, `depth` levels of each side
+pub fn to_resp_for_market_book(
+    data: GetOrderBook,
+    depth: usize,
+    outcomes: &[DbMarketOutcome],
+    outcome_prices: &[f64],
+) -> GetMarketBookResponse {
+    let outcome_count = outcomes.len().max(data.outcome_bids.len());
+    let outcome_books = data
+        .outcome_bids
+        .into_iter()
+        .zip(data.outcome_asks)
+        .enumerate()
+        .map(|(outcome_index, (bids, asks))| OutcomeBook {
+            outcome_index: outcome_index as u32,
+            book: Some(to_order_book(bids, asks, depth)),
+            name: outcomes
+                .iter()
+                .find(|outcome| outcome.outcome_index as usize == outcome_index)
+                .map(|outcome| outcome.name.clone())
+                .unwrap_or_default(),
+            price: outcome_price(outcome_prices, outcome_index, outcome_count),
+        })
+        .collect::<Vec<_>>();
+
+    // yes / no books are the first two outcomes of the binary market
+    let get_book = |outcome_index: usize| {
+        outcome_books
+            .get(outcome_index)
+            .and_then(|outcome_book| outcome_book.book.clone())
+            .or_else(|| Some(OrderBook::default()))
+    };
+
     GetMarketBookResponse {
         market_id: data.market_id.to_string(),
-        yes_book: Some(OrderBook {
-            bids: data
-                .yes_bids
-                .into_iter()
-                .map(|(price, shares, users)| OrderLevel {
-                    price,
-                    shares,
-                    users,
-                })
-                .collect(),
-            asks: data
-                .yes_asks
-                .into_iter()
-                .map(|(price, shares, users)| OrderLevel {
-                    price,
-                    shares,
-                    users,
-                })
-                .collect(),
-        }),
-        no_book: Some(OrderBook {
-            bids: data
-                .no_bids
-                .into_iter()
-                .map(|(price, shares, users)| OrderLevel {
-                    price,
-                    shares,
-                    users,
-                })
-                .collect(),
-            asks: data
-                .no_asks
-                .into_iter()
-                .map(|(price, shares, users)| OrderLevel {
-                    price,
-                    shares,
-                    users,
-                })
-                .collect(),
-        }),
+        yes_book: get_book(0),
+        no_book: get_book(1),
+        outcome_books,
+    }
+}
+
+fn to_order_book(bids: ClickhouseOrderBook, asks: ClickhouseOrderBook, depth: usize) -> OrderBook {
+    let to_order_levels = |levels: ClickhouseOrderBook| {
+        levels
+            .into_iter()
+            .filter(|(_, shares, _)| *shares > 0.0)
+            .take(depth)
+            .map(|(price, shares, users)| OrderLevel {
+                price,
+                shares,
+                users,
+            })
+            .collect()
+    };
+
+    OrderBook {
+        bids: to_order_levels(bids),
+        asks: to_order_levels(asks),
     }
 }
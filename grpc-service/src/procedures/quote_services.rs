@@ -0,0 +1,122 @@
+use std::time::Duration;
+
+use db_service::schema::enums::{OrderSide, Outcome as DbOutcome};
+use rust_decimal::{Decimal, prelude::FromPrimitive};
+use sqlx::types::Uuid;
+use tonic::{Request, Response, Status};
+use utility_helpers::{
+    message_pack_helper::{deserialize_from_message_pack, serialize_to_message_pack},
+    nats_helper::{
+        NatsSubjects,
+        types::{QuoteRequestMessage, QuoteResponseMessage},
+    },
+    to_f64_verbose,
+};
+
+use crate::{
+    generated::{
+        markets::{Outcome, TradeType},
+        quote::{
+            QuoteFill, QuoteMarketOrderRequest, QuoteMarketOrderResponse,
+            quote_market_order_request::Size, quote_service_server::QuoteService,
+        },
+    },
+    state::SafeState,
+    validate_strings,
+};
+
+const QUOTE_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub struct QuoteServiceStub {
+    pub state: SafeState,
+}
+
+#[tonic::async_trait]
+impl QuoteService for QuoteServiceStub {
+    async fn quote_market_order(
+        &self,
+        request: Request<QuoteMarketOrderRequest>,
+    ) -> Result<Response<QuoteMarketOrderResponse>, Status> {
+        let req = request.into_inner();
+        let market_id = req.market_id;
+
+        validate_strings!(market_id);
+
+        let market_id = Uuid::parse_str(&market_id)
+            .map_err(|_| Status::invalid_argument("Invalid market_id format"))?;
+        let side = match TradeType::try_from(req.side) {
+            Ok(TradeType::Buy) => OrderSide::BUY,
+            Ok(TradeType::Sell) => OrderSide::SELL,
+            _ => return Err(Status::invalid_argument("side must be BUY or SELL")),
+        };
+        let outcome = match Outcome::try_from(req.outcome) {
+            Ok(Outcome::Yes) => DbOutcome::YES,
+            Ok(Outcome::No) => DbOutcome::NO,
+            _ => return Err(Status::invalid_argument("outcome must be YES or NO")),
+        };
+        let (quantity, budget) = match req.size {
+            Some(Size::Quantity(quantity)) if quantity > 0.0 => (Decimal::from_f64(quantity), None),
+            Some(Size::Budget(budget)) if budget > 0.0 => (None, Decimal::from_f64(budget)),
+            _ => {
+                return Err(Status::invalid_argument(
+                    "Either quantity or budget must be greater than 0",
+                ));
+            }
+        };
+
+        let quote_request = QuoteRequestMessage {
+            market_id,
+            side,
+            outcome,
+            outcome_index: req.outcome_index as i32,
+            quantity,
+            budget,
+        };
+        let payload = serialize_to_message_pack(&quote_request)
+            .map_err(|e| Status::internal(format!("Failed to serialize quote request: {}", e)))?;
+
+        // quote is simulated by the order service on it's in memory order book
+        let reply = tokio::time::timeout(
+            QUOTE_TIMEOUT,
+            self.state
+                .nats_client
+                .request(NatsSubjects::OrderBookQuote.to_string(), payload.into()),
+        )
+        .await
+        .map_err(|_| Status::deadline_exceeded("Order service did not respond to the quote"))?
+        .map_err(|e| Status::unavailable(format!("Failed to request quote: {}", e)))?;
+
+        let quote =
+            deserialize_from_message_pack::<Result<QuoteResponseMessage, String>>(&reply.payload)
+                .map_err(|e| Status::internal(format!("Failed to deserialize quote: {}", e)))?
+                .map_err(Status::not_found)?;
+
+        Ok(Response::new(to_quote_response(quote)))
+    }
+}
+
+fn to_quote_response(quote: QuoteResponseMessage) -> QuoteMarketOrderResponse {
+    QuoteMarketOrderResponse {
+        market_id: quote.market_id.to_string(),
+        fills: quote
+            .fills
+            .into_iter()
+            .map(|fill| QuoteFill {
+                price: to_f64_verbose(fill.price),
+                quantity: to_f64_verbose(fill.quantity),
+            })
+            .collect(),
+        filled_quantity: to_f64_verbose(quote.filled_quantity),
+        total_cost: to_f64_verbose(quote.total_cost),
+        average_price: quote.average_price.map(to_f64_verbose).unwrap_or_default(),
+        midpoint: to_f64_verbose(quote.midpoint),
+        current_yes_price: to_f64_verbose(quote.current_yes_price),
+        current_no_price: to_f64_verbose(quote.current_no_price),
+        outcome_prices: quote
+            .outcome_prices
+            .into_iter()
+            .map(to_f64_verbose)
+            .collect(),
+        reason: quote.reason.unwrap_or_default(),
+    }
+}
@@ -8,6 +8,7 @@ pub struct AppState {
     pub db_pool: PgPool,
     pub redis_helper: RedisHelper,
     pub clickhouse_client: clickhouse::Client,
+    pub nats_client: async_nats::Client, // quotes are requested from the order service
     pub admin_username: String,
 }
 
@@ -28,11 +29,15 @@ impl AppState {
         let admin_username = env_config.admin_username.clone();
         log_info!("Connected to ClickHouse");
 
+        let nats_client = async_nats::connect(&env_config.nc_url).await?;
+        log_info!("Connected to NATS");
+
         Ok(AppState {
             admin_username,
             db_pool,
             redis_helper,
             clickhouse_client,
+            nats_client,
         })
     }
 }
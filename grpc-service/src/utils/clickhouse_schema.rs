@@ -29,11 +29,45 @@ pub struct GetOrderBook {
     #[serde(with = "clickhouse::serde::chrono::datetime")]
     pub created_at: DateTime<Utc>,
 
-    pub yes_bids: OrderBook,
-    pub yes_asks: OrderBook,
+    pub epoch: u64,
+    pub sequence: u64,
 
-    pub no_bids: OrderBook,
-    pub no_asks: OrderBook,
+    // books of all outcomes by their index (yes / no for binary market)
+    pub outcome_bids: Vec<OrderBook>,
+    pub outcome_asks: Vec<OrderBook>,
+}
+
+/// Changed levels of the outcomes' books, level with 0 shares is removed
+#[derive(Row, Deserialize, Debug)]
+pub struct OrderBookDelta {
+    pub sequence: u64,
+    pub outcome_bid_deltas: Vec<OrderBook>,
+    pub outcome_ask_deltas: Vec<OrderBook>,
+}
+
+impl GetOrderBook {
+    /// Applies the deltas published after the snapshot (in their sequence)
+    pub fn apply_deltas(&mut self, deltas: &[OrderBookDelta]) {
+        let apply = |levels: &mut OrderBook, changes: &OrderBook| {
+            for change in changes {
+                levels.retain(|level| level.0 != change.0);
+                if change.1 > 0.0 {
+                    levels.push(*change);
+                }
+            }
+            levels.sort_by(|a, b| a.0.total_cmp(&b.0));
+        };
+
+        for delta in deltas {
+            for (book, changes) in self.outcome_bids.iter_mut().zip(&delta.outcome_bid_deltas) {
+                apply(book, changes);
+            }
+            for (book, changes) in self.outcome_asks.iter_mut().zip(&delta.outcome_ask_deltas) {
+                apply(book, changes);
+            }
+            self.sequence = delta.sequence;
+        }
+    }
 }
 
 #[derive(Row, Deserialize, Debug, Default)]
@@ -61,6 +95,7 @@ pub struct MarketPriceResponse {
 
     pub latest_yes_price: f64,
     pub latest_no_price: f64,
+    pub latest_outcome_prices: Vec<f64>,
 }
 
 impl From<MarketPriceResponse> for MarketPrice {
@@ -69,6 +104,7 @@ impl From<MarketPriceResponse> for MarketPrice {
             market_id: data.market_id.to_string(),
             latest_yes_price: data.latest_yes_price,
             latest_no_price: data.latest_no_price,
+            latest_outcome_prices: data.latest_outcome_prices,
         }
     }
 }
@@ -79,6 +115,7 @@ impl Default for MarketPriceResponse {
             market_id: Uuid::default(),
             latest_yes_price: 0.5,
             latest_no_price: 0.5,
+            latest_outcome_prices: Vec::new(), // even price of the outcomes
         }
     }
 }
@@ -98,3 +135,41 @@ impl From<VolumeData> for VolumeInfo {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deltas_are_applied_on_snapshot() {
+        let mut order_book = GetOrderBook {
+            market_id: Uuid::new_v4(),
+            ts: Utc::now(),
+            created_at: Utc::now(),
+            epoch: 1,
+            sequence: 100,
+            outcome_bids: vec![vec![(0.3, 10.0, 1), (0.4, 5.0, 1)], vec![]],
+            outcome_asks: vec![vec![(0.6, 5.0, 1)], vec![(0.7, 2.0, 1)]],
+        };
+        let deltas = vec![
+            OrderBookDelta {
+                sequence: 101,
+                outcome_bid_deltas: vec![vec![(0.35, 4.0, 1), (0.4, 0.0, 0)], vec![]],
+                outcome_ask_deltas: vec![vec![], vec![(0.7, 3.0, 2)]],
+            },
+            OrderBookDelta {
+                sequence: 102,
+                outcome_bid_deltas: vec![vec![], vec![(0.2, 1.0, 1)]],
+                outcome_ask_deltas: vec![vec![(0.6, 0.0, 0)], vec![]],
+            },
+        ];
+
+        order_book.apply_deltas(&deltas);
+        assert_eq!(order_book.sequence, 102);
+        assert_eq!(
+            order_book.outcome_bids,
+            vec![vec![(0.3, 10.0, 1), (0.35, 4.0, 1)], vec![(0.2, 1.0, 1)]]
+        );
+        assert_eq!(order_book.outcome_asks, vec![vec![], vec![(0.7, 3.0, 2)]]);
+    }
+}
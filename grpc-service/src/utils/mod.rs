@@ -3,29 +3,30 @@ pub mod macros;
 pub mod timeframe;
 
 pub mod clickhouse_queries {
+    // latest snapshot of the books, deltas after it are applied by `ORDER_BOOK_DELTAS`
     pub const ORDER_BOOK_INITIALS: &str = r#"
             SELECT
                 market_id,
                 ts,
                 created_at,
-
-                CAST(arraySlice(
-                    arrayFilter(x -> x.2 > 0, yes_bids), 1, ?
-                    ) AS Array(Tuple(price Float64, shares Float64, users UInt32))) AS yes_bids,
-                CAST(arraySlice(
-                    arrayFilter(x -> x.2 > 0, yes_asks), 1, ?
-                    ) AS Array(Tuple(price Float64, shares Float64, users UInt32))) AS yes_asks,
-                CAST(arraySlice(
-                    arrayFilter(x -> x.2 > 0, no_bids), 1, ?
-                    ) AS Array(Tuple(price Float64, shares Float64, users UInt32))) AS no_bids,
-                CAST(arraySlice(
-                    arrayFilter(x -> x.2 > 0, no_asks), 1, ?
-                    ) AS Array(Tuple(price Float64, shares Float64, users UInt32))) AS no_asks
-            FROM market_order_book WHERE market_id = ?
-            ORDER BY ts DESC
+                epoch,
+                sequence,
+                outcome_bids,
+                outcome_asks
+            FROM market_order_book WHERE market_id = ? AND is_snapshot
+            ORDER BY epoch DESC, sequence DESC
             LIMIT 1
         "#;
 
+    pub const ORDER_BOOK_DELTAS: &str = r#"
+            SELECT
+                sequence,
+                outcome_bid_deltas,
+                outcome_ask_deltas
+            FROM market_order_book WHERE market_id = ? AND epoch = ? AND sequence > ?
+            ORDER BY sequence
+        "#;
+
     pub const MARKET_PRICE_BASE_QUERY: &str = r#"
             SELECT
                 market_id,
@@ -67,7 +68,8 @@ pub mod clickhouse_queries {
             SELECT
                 market_id,
                 toFloat64(argMax(yes_price, ts)) AS latest_yes_price,
-                toFloat64(argMax(no_price, ts)) AS latest_no_price
+                toFloat64(argMax(no_price, ts)) AS latest_no_price,
+                arrayMap(price -> toFloat64(price), argMax(outcome_prices, ts)) AS latest_outcome_prices
             FROM market_price_data
             WHERE market_id = ?
             GROUP BY market_id
@@ -0,0 +1,94 @@
+use std::{sync::Arc, time::Duration};
+
+use db_service::schema::{enums::OrderStatus, market::Market, orders::Order};
+use rust_decimal::Decimal;
+use utility_helpers::{log_error, log_info};
+use uuid::Uuid;
+
+use crate::{
+    order_book::outcome_book::OrderBookMatchedOutput,
+    state::AppState,
+    utils::{
+        OrderServiceError, process_trigger_orders::process_trigger_orders,
+        update_matched_orders::update_matched_orders, update_services::update_service_state,
+    },
+};
+
+const AUCTION_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Timer which uncrosses the opening call auction of the markets once their `auction_opens_at` is passed
+pub async fn handle_call_auctions(app_state: Arc<AppState>) -> Result<(), OrderServiceError> {
+    let mut interval = tokio::time::interval(AUCTION_CHECK_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let current_time = chrono::Utc::now().naive_utc();
+        let due_markets = app_state.order_book.get_due_auctions(current_time);
+
+        for market_id in due_markets {
+            if let Err(e) = uncross_auction(app_state.clone(), market_id).await {
+                log_error!("Failed to uncross call auction of market {market_id} {e}");
+            }
+        }
+    }
+}
+
+async fn uncross_auction(
+    app_state: Arc<AppState>,
+    market_id: Uuid,
+) -> Result<(), OrderServiceError> {
+    let matches = {
+        // sync block
+        {
+            let order_book = &app_state.order_book;
+            order_book.uncross_auction(&market_id)
+        }
+    };
+
+    Market::end_auction(&app_state.db_pool, &market_id)
+        .await
+        .map_err(|e| format!("Failed to end call auction: {:#?}", e))?;
+
+    log_info!(
+        "Call auction of market {market_id} uncrossed with {} matches",
+        matches.len()
+    );
+
+    // demand order is the current order of it's matches
+    let mut order_matches: Vec<(Uuid, Vec<OrderBookMatchedOutput>)> = Vec::new();
+    for match_item in matches {
+        match order_matches.last_mut() {
+            Some((order_id, matches)) if *order_id == match_item.order_id => {
+                matches.push(match_item)
+            }
+            _ => order_matches.push((match_item.order_id, vec![match_item])),
+        }
+    }
+
+    for (order_id, matches) in order_matches {
+        let order = Order::find_order_by_id(order_id, &app_state.db_pool)
+            .await
+            .map_err(|e| format!("Failed to find auction order {:#?}", e))?;
+        let Some(mut order) = order else {
+            return Err(format!("Order {order_id} of the call auction not found").into());
+        };
+
+        order.filled_quantity += matches.iter().map(|m| m.matched_quantity).sum::<Decimal>();
+        if order.filled_quantity >= order.quantity {
+            order.status = OrderStatus::FILLED;
+        }
+        let order = order
+            .update(&app_state.db_pool)
+            .await
+            .map_err(|e| format!("Failed to update auction order: {:#?}", e))?;
+
+        tokio::try_join!(
+            update_matched_orders(matches, app_state.clone(), &order),
+            update_service_state(app_state.clone(), &order)
+        )?;
+    }
+
+    // trigger orders are waiting for the continuous trading
+    process_trigger_orders(app_state, market_id).await
+}
@@ -0,0 +1,23 @@
+use std::sync::Arc;
+
+use utility_helpers::log_error;
+
+use crate::{
+    state::AppState,
+    utils::{OrderServiceError, update_services::publish_book_update},
+};
+
+/// Publishes the book updates queued by the global book one by one, so kafka and nats consumers get them in the order of their sequence
+pub async fn handle_book_updates(app_state: Arc<AppState>) -> Result<(), OrderServiceError> {
+    let mut book_updates = app_state.book_updates.lock().await;
+
+    while let Some(market_book_update) = book_updates.recv().await {
+        let market_id = market_book_update.market_id;
+        let sequence = market_book_update.book_update.sequence;
+        if let Err(e) = publish_book_update(&app_state, market_book_update).await {
+            log_error!("Failed to publish book update {sequence} of market {market_id} {e}");
+        }
+    }
+
+    Ok(())
+}
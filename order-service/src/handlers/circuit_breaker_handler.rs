@@ -0,0 +1,71 @@
+use std::{sync::Arc, time::Duration};
+
+use chrono::NaiveDateTime;
+use db_service::schema::market::Market;
+use utility_helpers::{log_error, log_info};
+use uuid::Uuid;
+
+use crate::{
+    state::AppState,
+    utils::{
+        OrderServiceError, process_trigger_orders::process_trigger_orders,
+        trading_status::publish_trading_status,
+    },
+};
+
+const CIRCUIT_BREAKER_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Timer which rolls the price bands of the markets and resumes their trading once the halt's cool-down is over
+pub async fn handle_circuit_breakers(app_state: Arc<AppState>) -> Result<(), OrderServiceError> {
+    let mut interval = tokio::time::interval(CIRCUIT_BREAKER_CHECK_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let current_time = chrono::Utc::now().naive_utc();
+        for market_id in app_state.order_book.get_due_price_bands(current_time) {
+            app_state
+                .order_book
+                .roll_price_band(&market_id, current_time);
+        }
+
+        let markets_to_resume =
+            match Market::get_markets_to_resume(&app_state.db_pool, current_time).await {
+                Ok(market_ids) => market_ids,
+                Err(e) => {
+                    log_error!("Failed to get markets to resume {e:#?}");
+                    continue;
+                }
+            };
+
+        for market_id in markets_to_resume {
+            if let Err(e) = resume_trading(app_state.clone(), market_id, current_time).await {
+                log_error!("Failed to resume trading of market {market_id} {e}");
+            }
+        }
+    }
+}
+
+async fn resume_trading(
+    app_state: Arc<AppState>,
+    market_id: Uuid,
+    current_time: NaiveDateTime,
+) -> Result<(), OrderServiceError> {
+    {
+        // sync block
+        {
+            let order_book = &app_state.order_book;
+            order_book.resume_trading(&market_id, current_time);
+        }
+    }
+
+    Market::resume_trading(&app_state.db_pool, &market_id)
+        .await
+        .map_err(|e| format!("Failed to resume trading: {:#?}", e))?;
+
+    log_info!("Trading of market {market_id} is resumed");
+    publish_trading_status(app_state.clone(), market_id, None).await?;
+
+    // trigger orders whose trigger price was hit before the halt are waiting for the trading
+    process_trigger_orders(app_state, market_id).await
+}
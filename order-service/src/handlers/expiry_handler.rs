@@ -0,0 +1,121 @@
+use std::{sync::Arc, time::Duration};
+
+use db_service::schema::{
+    enums::{OrderStatus, OrderType},
+    market::Market,
+    market_status_transitions::MarketStatusTransition,
+    orders::Order,
+};
+use utility_helpers::{
+    log_error, log_info,
+    message_pack_helper::serialize_to_message_pack,
+    nats_helper::{NatsSubjects, types::MarketStatusUpdateMessage},
+};
+
+use crate::{
+    state::AppState,
+    utils::{
+        OrderServiceError, order_groups::process_order_groups,
+        update_services::update_service_state,
+    },
+};
+
+const EXPIRY_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Timer which expires good till date orders once their `expires_at` is passed and closes the expired markets
+pub async fn handle_expired_orders(app_state: Arc<AppState>) -> Result<(), OrderServiceError> {
+    let mut interval = tokio::time::interval(EXPIRY_CHECK_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let current_time = chrono::Utc::now().naive_utc();
+        match Market::close_expired_markets(&app_state.db_pool, current_time).await {
+            Ok(transitions) => {
+                for transition in transitions {
+                    if let Err(e) = announce_market_close(&app_state, transition).await {
+                        log_error!("Failed to announce market close {e}");
+                    }
+                }
+            }
+            Err(e) => log_error!("Failed to close expired markets {e:#?}"),
+        }
+
+        let expired_orders = match Order::get_expired_orders(&app_state.db_pool, current_time).await
+        {
+            Ok(orders) => orders,
+            Err(e) => {
+                log_error!("Failed to get expired orders {e:#?}");
+                continue;
+            }
+        };
+
+        for order in expired_orders {
+            if let Err(e) = expire_order(app_state.clone(), order).await {
+                log_error!("Failed to expire order {e}");
+            }
+        }
+    }
+}
+
+async fn expire_order(app_state: Arc<AppState>, mut order: Order) -> Result<(), OrderServiceError> {
+    let removed = {
+        // sync block
+        {
+            let order_book = &app_state.order_book;
+
+            if matches!(
+                order.order_type,
+                OrderType::StopLoss | OrderType::TakeProfit
+            ) {
+                order_book.remove_trigger_order(&order.market_id, order.id)
+            } else {
+                order_book.remove_order(order.market_id, order.id)
+            }
+        }
+    };
+
+    if !removed {
+        // order is already matched or removed from the book
+        return Ok(());
+    }
+
+    Order::update_order_status(order.id, OrderStatus::EXPIRED, &app_state.db_pool)
+        .await
+        .map_err(|e| format!("Failed to update order status: {:#?}", e))?;
+    order.status = OrderStatus::EXPIRED;
+
+    log_info!("Order {} expired at {:?}", order.id, order.expires_at);
+
+    update_service_state(app_state.clone(), &order).await?;
+
+    // expiry of the group's order cancels / activates it's linked orders
+    process_order_groups(app_state, order.market_id).await
+}
+
+/// Closed market is frozen by it's market worker, so the close is ordered with the messages of the market
+async fn announce_market_close(
+    app_state: &AppState,
+    transition: MarketStatusTransition,
+) -> Result<(), OrderServiceError> {
+    log_info!("Market {} expired, closing it", transition.market_id);
+
+    let message = MarketStatusUpdateMessage {
+        market_id: transition.market_id,
+        from_status: transition.from_status,
+        to_status: transition.to_status,
+        changed_by: transition.changed_by,
+        timestamp: transition.created_at.and_utc().to_rfc3339(),
+    };
+    let message_pack_encoded = serialize_to_message_pack(&message)?;
+    app_state
+        .jetstream
+        .publish(
+            NatsSubjects::MarketStatusUpdate.to_string(),
+            message_pack_encoded.into(),
+        )
+        .await
+        .map_err(|e| format!("Failed to publish market status update: {e:#?}"))?;
+
+    Ok(())
+}
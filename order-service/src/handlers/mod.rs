@@ -1,2 +1,7 @@
+pub mod auction_handler;
+pub mod book_update_handler;
+pub mod circuit_breaker_handler;
+pub mod expiry_handler;
 pub mod nats_handler;
+pub mod quote_handler;
 pub mod ws_handler;
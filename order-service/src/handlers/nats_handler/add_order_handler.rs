@@ -6,7 +6,10 @@ use utility_helpers::log_error;
 
 use crate::{
     state::AppState,
-    utils::{OrderServiceError, update_services::update_service_state},
+    utils::{
+        OrderServiceError, market_resolver::get_orders_market_rules,
+        update_services::update_service_state,
+    },
 };
 
 pub async fn add_order_handler(
@@ -14,13 +17,22 @@ pub async fn add_order_handler(
     orders: &Vec<Order>,
     liquidity_b: Decimal,
 ) -> Result<(), OrderServiceError> {
-    // synchronous write lock to the order book
-    {
-        let mut order_book_guard = state.order_book.write();
+    let rules = get_orders_market_rules(&state, orders).await?;
 
-        for order in orders.iter() {
-            order_book_guard.add_order(order, liquidity_b);
-        }
+    // orders already restored by the snapshot replay are not added again on redelivery
+    let orders = orders
+        .iter()
+        .filter(|order| {
+            state
+                .order_book
+                .find_order(&order.market_id, order.id)
+                .is_none()
+        })
+        .collect::<Vec<_>>();
+
+    // synchronous block, market shard is locked per order
+    for order in orders.iter() {
+        state.order_book.add_order(order, liquidity_b, rules);
     }
 
     // asynchronous service state update
@@ -1,12 +1,18 @@
 use std::sync::Arc;
 
-use db_service::schema::{enums::OrderStatus, orders::Order};
+use db_service::schema::{
+    enums::{OrderStatus, OrderType},
+    orders::Order,
+};
 use utility_helpers::log_warn;
 use uuid::Uuid;
 
 use crate::{
     state::AppState,
-    utils::{OrderServiceError, update_services::update_service_state},
+    utils::{
+        OrderServiceError, process_trigger_orders::process_trigger_orders,
+        update_services::update_service_state,
+    },
 };
 
 pub async fn cancel_order_handler(
@@ -37,15 +43,16 @@ pub async fn cancel_order_handler(
     let update_flag = {
         // sync block
         {
-            let mut order_book = app_state.order_book.write();
-
-            order_book.remove_order(
-                order.market_id,
-                order_id,
-                order.side,
-                order.outcome,
-                order.price,
-            )
+            let order_book = &app_state.order_book;
+
+            if matches!(
+                order.order_type,
+                OrderType::StopLoss | OrderType::TakeProfit
+            ) {
+                order_book.remove_trigger_order(&order.market_id, order_id)
+            } else {
+                order_book.remove_order(order.market_id, order_id)
+            }
         }
     };
 
@@ -59,5 +66,7 @@ pub async fn cancel_order_handler(
     // ws publish remaining if required...
 
     // update market state
-    update_service_state(app_state.clone(), &order).await
+    update_service_state(app_state.clone(), &order).await?;
+
+    process_trigger_orders(app_state, order.market_id).await
 }
@@ -1,22 +1,29 @@
 use std::sync::Arc;
 
-use db_service::schema::{enums::OrderStatus, orders::Order};
-use rust_decimal::Decimal;
-use utility_helpers::log_info;
+use db_service::schema::{
+    enums::{OrderStatus, OrderType, TimeInForce},
+    orders::Order,
+    trailing_stops::TrailingStop,
+};
+use utility_helpers::{log_info, nats_helper::types::MarketOrderCreateMessage};
 use uuid::Uuid;
 
 use crate::{
+    order_book::outcome_book::{MarketOrderLimits, MarketRules},
     state::AppState,
     utils::{
-        OrderServiceError, update_matched_orders::update_matched_orders,
-        update_services::update_service_state,
+        OrderServiceError, market_order_execution::save_market_order_execution,
+        order_groups::process_order_groups, process_trigger_orders::process_trigger_orders,
+        update_matched_orders::update_matched_orders, update_services::update_service_state,
     },
 };
 
+const EXPIRED_BEFORE_BOOK_REASON: &str = "Order expired before reaching the book";
+
 pub async fn create_order_handler(
     app_state: Arc<AppState>,
     order_id: Uuid,
-    market_order_budget_opt: Option<Decimal>,
+    market_order_opt: Option<MarketOrderCreateMessage>,
 ) -> Result<(), OrderServiceError> {
     let order = Order::find_order_by_id_with_market(order_id, &app_state.db_pool)
         .await
@@ -28,10 +35,75 @@ pub async fn create_order_handler(
         return Ok(());
     }
 
+    let market_id = order.market_id;
+
+    // good till date order which is already expired before reaching the book
+    if order.time_in_force == TimeInForce::GTD
+        && order
+            .expires_at
+            .is_some_and(|expires_at| expires_at <= chrono::Utc::now().naive_utc())
+    {
+        log_info!("Order {} is already expired", order_id);
+        let mut expired_order: Order = order.into();
+        expired_order.status = OrderStatus::EXPIRED;
+        expired_order.status_reason = Some(EXPIRED_BEFORE_BOOK_REASON.to_string());
+        expired_order
+            .update(&app_state.db_pool)
+            .await
+            .map_err(|e| format!("Failed to update order: {:#?}", e))?;
+
+        update_service_state(app_state.clone(), &expired_order).await?;
+
+        // expiry of the group's order cancels / activates it's linked orders
+        return process_order_groups(app_state, market_id).await;
+    }
+
+    // stop loss and take profit orders are kept out of the book until they are triggered
+    if matches!(
+        order.order_type,
+        OrderType::StopLoss | OrderType::TakeProfit
+    ) {
+        let liquidity_b = order.liquidity_b;
+        let rules = MarketRules::from_order_with_market(&order);
+        let mut trigger_order: Order = order.into();
+        trigger_order.status = OrderStatus::OPEN;
+
+        trigger_order
+            .update(&app_state.db_pool)
+            .await
+            .map_err(|e| format!("Failed to update order: {:#?}", e))?;
+
+        // stop loss order with trailing state is a trailing stop
+        let trailing_stop = if trigger_order.order_type == OrderType::StopLoss {
+            TrailingStop::get_trailing_stop_by_order_id(&app_state.db_pool, &order_id)
+                .await
+                .map_err(|e| format!("Failed to find trailing stop: {:#?}", e))?
+        } else {
+            None
+        };
+
+        let added = {
+            let order_book = &app_state.order_book;
+            match trailing_stop {
+                Some(trailing_stop) => {
+                    order_book.add_trailing_stop(trigger_order, trailing_stop, liquidity_b, rules)
+                }
+                None => order_book.add_trigger_order(trigger_order, liquidity_b, rules),
+            }
+        };
+        if !added {
+            return Err(format!("Failed to add trigger order {order_id} in book").into());
+        }
+
+        // order might be already triggered by the current market price
+        return process_trigger_orders(app_state, market_id).await;
+    }
+
     // working on unspecified status order
     let (matched_order, updated_raw_order) = {
         // sync block
         {
+            let rules = MarketRules::from_order_with_market(&order);
             let mut order_raw = Order {
                 id: order.id,
                 status: OrderStatus::OPEN,
@@ -45,17 +117,26 @@ pub async fn create_order_handler(
                 updated_at: order.updated_at,
                 user_id: order.user_id,
                 order_type: order.order_type,
+                trigger_price: order.trigger_price,
+                time_in_force: order.time_in_force,
+                expires_at: order.expires_at,
+                post_only: order.post_only,
+                status_reason: order.status_reason,
+                self_trade_prevention: order.self_trade_prevention,
+                outcome_index: order.outcome_index,
+                display_quantity: order.display_quantity,
             };
 
-            let mut order_book = app_state.order_book.write();
-            let matches = if let Some(market_order_budget) = market_order_budget_opt {
+            let order_book = &app_state.order_book;
+            let matches = if let Some(market_order) = market_order_opt.as_ref() {
                 order_book.create_market_order(
                     &order.market_id,
                     &mut order_raw,
-                    market_order_budget,
+                    market_order.budget,
+                    MarketOrderLimits::from(market_order),
                 )
             } else {
-                order_book.process_order(&mut order_raw, order.liquidity_b)
+                order_book.process_order(&mut order_raw, order.liquidity_b, rules)
             };
 
             // updating current order filled quantity and status
@@ -67,6 +148,11 @@ pub async fn create_order_handler(
         .await
         .map_err(|e| format!("Failed to update order: {:#?}", e))?;
 
+    if let Some(market_order) = market_order_opt.as_ref() {
+        save_market_order_execution(&app_state, &updated_raw_order, market_order, &matched_order)
+            .await?;
+    }
+
     let update_matched_order_future =
         update_matched_orders(matched_order, app_state.clone(), &updated_raw_order);
 
@@ -79,7 +165,7 @@ pub async fn create_order_handler(
     update_service_state_result
         .map_err(|e| format!("Error while updating service states {e:#?}"))?;
 
-    Ok(())
+    process_trigger_orders(app_state, market_id).await
 }
 
 #[cfg(test)]
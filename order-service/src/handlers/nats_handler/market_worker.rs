@@ -0,0 +1,404 @@
+/*
+ * Market workers process the jetstream messages of a single market sequentially, messages of different markets are processed in parallel.
+ *
+ * 1. Nats handler resolves the market of every message from it's payload (without the db) and sends it to the market's worker over it's command channel, worker is spawned on the first message of the market.
+ * 2. Every message is acknowledged by the worker after it's processed, so acks are out of order across markets (ack floor of the consumer only moves once all previous messages are processed).
+ * 3. Snapshot of the market is taken by it's worker in between two messages, tagged with the last stream sequence applied on the market.
+ */
+
+use std::{collections::HashMap, sync::Arc};
+
+use async_nats::jetstream::Message;
+use db_service::schema::{enums::MarketStatus, orders::Order};
+use futures_util::future::BoxFuture;
+use tokio::sync::{mpsc, oneshot};
+use utility_helpers::{
+    log_error, log_info,
+    message_pack_helper::deserialize_from_message_pack,
+    nats_helper::{
+        NatsSubjects,
+        types::{
+            InitializeOrderBookMessage, MarketOrderCreateMessage, MarketStatusUpdateMessage,
+            OrderMessage, UpdateOrderMessage,
+        },
+    },
+};
+use uuid::Uuid;
+
+use crate::{
+    handlers::nats_handler::{
+        add_order_handler::add_order_handler, cancel_order_handler::cancel_order_handler,
+        create_order_handler::create_order_handler, update_order_handler::update_order_handler,
+    },
+    order_book::global_book::GlobalMarketBook,
+    state::AppState,
+    utils::{
+        OrderServiceError, market_resolver::parse_uuid, order_book_snapshot::MarketSnapshot,
+        process_trigger_orders::process_trigger_orders,
+    },
+};
+
+pub enum MarketCommand<M> {
+    Message(M),
+    Snapshot(oneshot::Sender<Option<MarketSnapshot>>),
+}
+
+/// Processes the message of the market, returns the stream sequence of the message once it's acknowledged
+pub type ProcessMessage<M> = Arc<dyn Fn(Uuid, M) -> BoxFuture<'static, Option<u64>> + Send + Sync>;
+
+pub struct MarketWorkers<M = Box<Message>> {
+    order_book: Arc<GlobalMarketBook>,
+    process_message: ProcessMessage<M>,
+    workers: HashMap<Uuid, mpsc::UnboundedSender<MarketCommand<M>>>,
+}
+
+impl MarketWorkers {
+    /// Workers of the jetstream messages, every message is acknowledged once it's processed
+    pub fn new(app_state: Arc<AppState>) -> Self {
+        let order_book = app_state.order_book.clone();
+        Self::with_processor(
+            order_book,
+            Arc::new(move |market_id, message| {
+                Box::pin(process_jetstream_message(
+                    app_state.clone(),
+                    market_id,
+                    message,
+                ))
+            }),
+        )
+    }
+}
+
+impl<M: Send + 'static> MarketWorkers<M> {
+    pub fn with_processor(
+        order_book: Arc<GlobalMarketBook>,
+        process_message: ProcessMessage<M>,
+    ) -> Self {
+        Self {
+            order_book,
+            process_message,
+            workers: HashMap::new(),
+        }
+    }
+
+    /// Sends the message to the worker of the market, worker is (re)spawned if the market doesn't have a running one
+    pub fn dispatch(&mut self, market_id: Uuid, message: M) {
+        let command = MarketCommand::Message(message);
+
+        let command = match self.workers.get(&market_id) {
+            Some(worker) => match worker.send(command) {
+                Ok(()) => return,
+                Err(mpsc::error::SendError(command)) => {
+                    log_error!("Worker of market {market_id} is stopped, respawning");
+                    command
+                }
+            },
+            None => command,
+        };
+
+        let worker = self.spawn_worker(market_id);
+        if worker.send(command).is_err() {
+            log_error!("Failed to dispatch message to the worker of market {market_id}");
+        }
+    }
+
+    /// Stops the worker once it processes the already dispatched messages
+    pub fn stop(&mut self, market_id: &Uuid) {
+        self.workers.remove(market_id);
+    }
+
+    /// Requests the snapshot of every market in the book, markets without worker are snapshotted right away
+    pub fn request_snapshots(&self) -> Vec<(Uuid, oneshot::Receiver<Option<MarketSnapshot>>)> {
+        let mut receivers = Vec::new();
+
+        for (market_id, market) in self.order_book.get_markets() {
+            let (tx, rx) = oneshot::channel();
+
+            match self.workers.get(&market_id) {
+                Some(worker) => {
+                    let _ = worker.send(MarketCommand::Snapshot(tx));
+                }
+                None => {
+                    let _ = tx.send(Some(MarketSnapshot {
+                        market_book: market.lock().clone(),
+                        stream_sequence: 0,
+                    }));
+                }
+            }
+            receivers.push((market_id, rx));
+        }
+
+        receivers
+    }
+
+    fn spawn_worker(&mut self, market_id: Uuid) -> &mpsc::UnboundedSender<MarketCommand<M>> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let order_book = self.order_book.clone();
+        let process_message = self.process_message.clone();
+
+        tokio::spawn(async move {
+            run_market_worker(order_book, process_message, market_id, rx).await;
+        });
+        log_info!("Spawned worker for market {market_id}");
+
+        self.workers.insert(market_id, tx);
+        &self.workers[&market_id]
+    }
+}
+
+async fn run_market_worker<M>(
+    order_book: Arc<GlobalMarketBook>,
+    process_message: ProcessMessage<M>,
+    market_id: Uuid,
+    mut rx: mpsc::UnboundedReceiver<MarketCommand<M>>,
+) {
+    let mut last_stream_sequence = 0;
+
+    while let Some(command) = rx.recv().await {
+        match command {
+            MarketCommand::Message(message) => {
+                if let Some(stream_sequence) = process_message(market_id, message).await {
+                    last_stream_sequence = stream_sequence;
+                }
+            }
+            MarketCommand::Snapshot(tx) => {
+                // market is removed from the book once it's finalized
+                let snapshot = order_book
+                    .get_market(&market_id)
+                    .map(|market| MarketSnapshot {
+                        market_book: market.lock().clone(),
+                        stream_sequence: last_stream_sequence,
+                    });
+                let _ = tx.send(snapshot);
+            }
+        }
+    }
+
+    log_info!("Worker of market {market_id} stopped");
+}
+
+async fn process_jetstream_message(
+    app_state: Arc<AppState>,
+    market_id: Uuid,
+    message: Box<Message>,
+) -> Option<u64> {
+    if let Err(e) = process_message(&app_state, &message).await {
+        log_error!("Error occur while processing message of market {market_id} {e}");
+    }
+
+    // sending ack in either case...
+    if message.ack().await.is_err() {
+        log_error!("Failed to acknowledge message of market {market_id}");
+    }
+    message.info().ok().map(|info| info.stream_sequence)
+}
+
+async fn process_message(
+    app_state: &Arc<AppState>,
+    message: &Message,
+) -> Result<(), OrderServiceError> {
+    let subject = NatsSubjects::from_string(message.subject.as_str())
+        .ok_or_else(|| format!("Invalid subject: {}", message.subject))?;
+
+    match subject {
+        NatsSubjects::OrderCreate => {
+            let order_id =
+                deserialize_from_message_pack::<OrderMessage>(&message.payload)?.order_id;
+            log_info!("Received order ID: {}", order_id);
+            create_order_handler(app_state.clone(), order_id, None)
+                .await
+                .map_err(|e| format!("Error occur while adding order in book {e}"))?;
+        }
+        NatsSubjects::OrderCancel => {
+            let order_id =
+                deserialize_from_message_pack::<OrderMessage>(&message.payload)?.order_id;
+            cancel_order_handler(app_state.clone(), order_id)
+                .await
+                .map_err(|e| format!("Error occur while cancelling order {e}"))?;
+        }
+        NatsSubjects::OrderUpdate => {
+            let deserialized_message =
+                deserialize_from_message_pack::<UpdateOrderMessage>(&message.payload)?;
+
+            update_order_handler(app_state.clone(), deserialized_message)
+                .await
+                .map_err(|e| format!("Error occur while updating order {e}"))?;
+        }
+        NatsSubjects::MarketOrderCreate => {
+            let deserialized_message =
+                deserialize_from_message_pack::<MarketOrderCreateMessage>(&message.payload)?;
+
+            create_order_handler(
+                app_state.clone(),
+                deserialized_message.order_id,
+                Some(deserialized_message),
+            )
+            .await
+            .map_err(|e| format!("Error occur while adding market order in book {e}"))?;
+        }
+        NatsSubjects::InitializeOrderBook => {
+            let deserialized_message = deserialize_from_message_pack::<
+                InitializeOrderBookMessage<Order>,
+            >(&message.payload)?;
+
+            add_order_handler(
+                app_state.clone(),
+                &deserialized_message.orders,
+                deserialized_message.liquidity_b,
+            )
+            .await
+            .map_err(|e| format!("Error occur while initializing order book {e}"))?;
+        }
+        NatsSubjects::FinalizeMarket => {
+            let market_id = parse_uuid(&message.payload)?;
+
+            if app_state.order_book.remove_market(&market_id) {
+                log_info!("Market with ID {} removed from global book", market_id);
+            } else {
+                log_error!(
+                    "Failed to remove market with ID {} from global book",
+                    market_id
+                );
+            }
+        }
+        NatsSubjects::MarketStatusUpdate => {
+            let deserialized_message = deserialize_from_message_pack::<
+                MarketStatusUpdateMessage<MarketStatus>,
+            >(&message.payload)?;
+            let market_id = deserialized_message.market_id;
+            let status = deserialized_message.to_status;
+
+            if !app_state.order_book.apply_market_status(&market_id, status) {
+                log_info!("Market {market_id} is not in the global book, skipping {status:?}");
+                return Ok(());
+            }
+            log_info!(
+                "Market {market_id} moved from {:?} to {status:?}",
+                deserialized_message.from_status
+            );
+
+            // trigger orders waiting during the freeze are executed once the market is reopened
+            if status.is_tradable() {
+                process_trigger_orders(app_state.clone(), market_id)
+                    .await
+                    .map_err(|e| format!("Error occur while processing trigger orders {e}"))?;
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use rust_decimal_macros::dec;
+    use tokio::sync::Notify;
+
+    use super::*;
+
+    fn get_workers(
+        order_book: Arc<GlobalMarketBook>,
+        processed_tx: mpsc::UnboundedSender<(Uuid, u64)>,
+        blocked_market: Option<(Uuid, Arc<Notify>)>,
+    ) -> MarketWorkers<u64> {
+        MarketWorkers::with_processor(
+            order_book,
+            Arc::new(move |market_id, sequence| {
+                let processed_tx = processed_tx.clone();
+                let blocked_market = blocked_market.clone();
+                Box::pin(async move {
+                    if let Some((blocked_market_id, release)) = blocked_market
+                        && blocked_market_id == market_id
+                    {
+                        release.notified().await;
+                    }
+                    // giving other workers a chance to run in between the messages
+                    tokio::task::yield_now().await;
+                    processed_tx.send((market_id, sequence)).unwrap();
+                    Some(sequence)
+                })
+            }),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_messages_of_market_are_processed_in_order() {
+        let (processed_tx, mut processed_rx) = mpsc::unbounded_channel();
+        let mut workers = get_workers(Arc::new(GlobalMarketBook::new()), processed_tx, None);
+        let market_ids = [Uuid::new_v4(), Uuid::new_v4()];
+
+        for sequence in 1..=50 {
+            workers.dispatch(market_ids[sequence as usize % 2], sequence);
+        }
+
+        let mut processed = HashMap::<Uuid, Vec<u64>>::new();
+        for _ in 1..=50 {
+            let (market_id, sequence) = processed_rx.recv().await.unwrap();
+            processed.entry(market_id).or_default().push(sequence);
+        }
+
+        for (index, market_id) in market_ids.iter().enumerate() {
+            let expected = (1..=50)
+                .filter(|sequence| *sequence as usize % 2 == index)
+                .collect::<Vec<_>>();
+            assert_eq!(processed[market_id], expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_blocked_market_does_not_block_other_markets() {
+        let (processed_tx, mut processed_rx) = mpsc::unbounded_channel();
+        let blocked_market_id = Uuid::new_v4();
+        let other_market_id = Uuid::new_v4();
+        let release = Arc::new(Notify::new());
+        let mut workers = get_workers(
+            Arc::new(GlobalMarketBook::new()),
+            processed_tx,
+            Some((blocked_market_id, release.clone())),
+        );
+
+        workers.dispatch(blocked_market_id, 1);
+        workers.dispatch(other_market_id, 2);
+        workers.dispatch(other_market_id, 3);
+
+        // messages of the other market are processed while the first message is still being processed
+        let processed = tokio::time::timeout(Duration::from_secs(1), async {
+            [
+                processed_rx.recv().await.unwrap(),
+                processed_rx.recv().await.unwrap(),
+            ]
+        })
+        .await
+        .unwrap();
+        assert_eq!(processed, [(other_market_id, 2), (other_market_id, 3)]);
+
+        release.notify_one();
+        assert_eq!(processed_rx.recv().await.unwrap(), (blocked_market_id, 1));
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_is_taken_after_the_dispatched_messages() {
+        let (processed_tx, mut processed_rx) = mpsc::unbounded_channel();
+        let order_book = Arc::new(GlobalMarketBook::new());
+        let market_id = Uuid::new_v4();
+        order_book.add_order(
+            &Order {
+                market_id,
+                ..Default::default()
+            },
+            dec!(100),
+            Default::default(),
+        );
+        let mut workers = get_workers(order_book, processed_tx, None);
+
+        workers.dispatch(market_id, 7);
+        let (_, rx) = workers.request_snapshots().pop().unwrap();
+        let snapshot = rx.await.unwrap().unwrap();
+
+        assert_eq!(processed_rx.recv().await.unwrap(), (market_id, 7));
+        assert_eq!(snapshot.stream_sequence, 7);
+    }
+}
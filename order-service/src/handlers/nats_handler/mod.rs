@@ -1,31 +1,30 @@
-use std::sync::Arc;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use async_nats::jetstream;
-use db_service::schema::orders::Order;
 use futures_util::StreamExt;
-use utility_helpers::{
-    log_error, log_info,
-    message_pack_helper::deserialize_from_message_pack,
-    nats_helper::{
-        NatsSubjects,
-        types::{InitializeOrderBookMessage, MarketOrderCreateMessage, UpdateOrderMessage},
-    },
-};
+use utility_helpers::{log_error, log_warn, nats_helper::NatsSubjects};
 
 use crate::{
-    handlers::nats_handler::{
-        add_order_handler::add_order_handler, cancel_order_handler::cancel_order_handler,
-        create_order_handler::create_order_handler, update_order_handler::update_order_handler,
-    },
+    handlers::nats_handler::market_worker::MarketWorkers,
     state::AppState,
-    utils::OrderServiceError,
+    utils::{
+        OrderServiceError,
+        market_resolver::{get_message_market_id, is_final_market_message},
+        order_book_snapshot::save_order_book_snapshot,
+    },
 };
 
 pub mod add_order_handler;
 pub mod cancel_order_handler;
 pub mod create_order_handler;
+pub mod market_worker;
 pub mod update_order_handler;
 
+const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(60);
+
 pub async fn handle_nats_message(app_state: Arc<AppState>) -> Result<(), OrderServiceError> {
     let stream_guard = app_state.jetstream.clone();
     let stream = stream_guard
@@ -37,7 +36,7 @@ pub async fn handle_nats_message(app_state: Arc<AppState>) -> Result<(), OrderSe
         })
         .await?;
 
-    let consumer = stream
+    let mut consumer = stream
         .create_consumer(jetstream::consumer::pull::Config {
             durable_name: Some("order_os".to_string()),
             ..Default::default()
@@ -45,6 +44,8 @@ pub async fn handle_nats_message(app_state: Arc<AppState>) -> Result<(), OrderSe
         .await?;
 
     let mut messages = consumer.messages().await?;
+    let mut market_workers = MarketWorkers::new(app_state.clone());
+    let mut last_snapshot_at = Instant::now();
 
     while let Some(Ok(message)) = messages.next().await {
         let subject = message.subject.clone();
@@ -52,99 +53,59 @@ pub async fn handle_nats_message(app_state: Arc<AppState>) -> Result<(), OrderSe
         let subject = NatsSubjects::from_string(subject_str)
             .ok_or_else(|| format!("Invalid subject: {}", subject))?;
 
-        match subject {
-            NatsSubjects::OrderCreate => {
-                let order_id = String::from_utf8(message.payload.to_vec())
-                    .map_err(|_| "Failed to convert payload to string".to_string())?;
-                log_info!("Received order ID: {}", order_id);
-                let order_id = uuid::Uuid::parse_str(&order_id)
-                    .map_err(|_| "Failed to parse order ID from string".to_string())?;
-                let _ = create_order_handler(app_state.clone(), order_id, None)
-                    .await
-                    .map_err(|e| {
-                        log_error!("Error occur while adding order in book {e}");
-                    });
-            }
-            NatsSubjects::OrderCancel => {
-                let order_id = String::from_utf8(message.payload.to_vec())
-                    .map_err(|_| "Failed to convert payload to string".to_string())?;
+        // book updates are published by the order service itself (for the websocket service)
+        if matches!(subject, NatsSubjects::MarketBookUpdate(_)) {
+            message
+                .ack()
+                .await
+                .map_err(|_| "Failed to acknowledge message".to_string())?;
+            continue;
+        }
 
-                let order_id = uuid::Uuid::parse_str(&order_id)
-                    .map_err(|_| "Failed to parse order ID from string".to_string())?;
-                let _ = cancel_order_handler(app_state.clone(), order_id)
-                    .await
-                    .map_err(|e| {
-                        log_error!("Error occur while cancelling order {e}");
-                    });
-            }
-            NatsSubjects::OrderUpdate => {
-                let serialized_message = message.payload.to_vec();
-                let deserialized_message = deserialize_from_message_pack::<UpdateOrderMessage>(
-                    &serialized_message.as_slice(),
-                )?;
+        // messages are processed by the worker of their market, so markets are matched in parallel
+        let market_id = get_message_market_id(&subject, &message.payload).unwrap_or_else(|e| {
+            log_error!("Failed to resolve market of {subject_str} message {e}");
+            None
+        });
 
-                let _ = update_order_handler(app_state.clone(), deserialized_message)
-                    .await
-                    .map_err(|e| {
-                        log_error!("Error occur while updating order {e}");
-                    });
-            }
-            NatsSubjects::MarketOrderCreate => {
-                let serialized_message = message.payload.to_vec();
-                let deserialized_message =
-                    deserialize_from_message_pack::<MarketOrderCreateMessage>(&serialized_message)?;
+        match market_id {
+            Some(market_id) => {
+                let is_final = is_final_market_message(&subject, &message.payload);
+                market_workers.dispatch(market_id, Box::new(message));
 
-                let _ = create_order_handler(
-                    app_state.clone(),
-                    deserialized_message.order_id,
-                    Some(deserialized_message.budget),
-                )
-                .await
-                .map_err(|e| {
-                    log_error!("Error occur while adding market order in book {e}");
-                });
+                // no more messages are expected after the market is finalized
+                if is_final {
+                    market_workers.stop(&market_id);
+                }
             }
-            NatsSubjects::InitializeOrderBook => {
-                let serialized_message = message.payload.to_vec();
-                let deserialized_message = deserialize_from_message_pack::<
-                    InitializeOrderBookMessage<Order>,
-                >(&serialized_message)?;
-
-                let _ = add_order_handler(
-                    app_state.clone(),
-                    &deserialized_message.orders,
-                    deserialized_message.liquidity_b,
-                )
-                .await
-                .map_err(|e| {
-                    log_error!("Error occur while initializing order book {e}");
-                    e
-                })?;
+            None => {
+                log_warn!("Market of {subject_str} message not found, skipping");
+                message
+                    .ack()
+                    .await
+                    .map_err(|_| "Failed to acknowledge message".to_string())?;
             }
-            NatsSubjects::FinalizeMarket => {
-                let market_id = String::from_utf8(message.payload.to_vec())
-                    .map_err(|_| "Failed to convert payload to string".to_string())?;
-                let market_id = uuid::Uuid::parse_str(&market_id)
-                    .map_err(|_| "Failed to parse market ID from string".to_string())?;
+        }
 
-                let mut global_book_guard = app_state.order_book.write();
-                if global_book_guard.remove_market(&market_id) {
-                    log_info!("Market with ID {} removed from global book", market_id);
-                } else {
-                    log_error!(
-                        "Failed to remove market with ID {} from global book",
-                        market_id
-                    );
+        // snapshot covers every message till the ack floor, messages after it are tagged per market by their workers
+        if last_snapshot_at.elapsed() >= SNAPSHOT_INTERVAL {
+            match consumer.info().await {
+                Ok(info) => {
+                    let ack_floor = info.ack_floor.stream_sequence;
+                    let snapshots = market_workers.request_snapshots();
+                    let app_state = app_state.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) =
+                            save_order_book_snapshot(&app_state, ack_floor, snapshots).await
+                        {
+                            log_error!("Failed to save order book snapshot {e}");
+                        }
+                    });
                 }
+                Err(e) => log_error!("Failed to get consumer info for snapshot {e}"),
             }
-            _ => {}
+            last_snapshot_at = Instant::now();
         }
-
-        // sending ack in either case...
-        message
-            .ack()
-            .await
-            .map_err(|_| "Failed to acknowledge message".to_string())?;
     }
 
     Ok(())
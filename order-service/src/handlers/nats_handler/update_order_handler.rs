@@ -4,10 +4,11 @@ use db_service::schema::{enums::OrderStatus, orders::Order};
 use utility_helpers::{log_error, nats_helper::types::UpdateOrderMessage};
 
 use crate::{
+    order_book::trigger_book::is_trigger_order,
     state::AppState,
     utils::{
-        OrderServiceError, update_matched_orders::update_matched_orders,
-        update_services::update_service_state,
+        OrderServiceError, process_trigger_orders::process_trigger_orders,
+        update_matched_orders::update_matched_orders, update_services::update_service_state,
     },
 };
 
@@ -38,17 +39,35 @@ pub async fn update_order_handler(
         return Ok(());
     }
 
+    // linked order of the group waiting for it's trigger is only resized, it's not in the order book yet
+    if is_trigger_order(&order)
+        && app_state
+            .order_book
+            .resize_trigger_order(&order.market_id, order.id, data.new_quantity)
+    {
+        order.quantity = data.new_quantity;
+        order.status = OrderStatus::OPEN;
+        order
+            .update(&app_state.db_pool)
+            .await
+            .map_err(|e| format!("Failed to update order: {e:#?}"))?;
+        return update_service_state(app_state, &order).await;
+    }
+
     // sync block
     let matches = {
-        let mut order_book = app_state.order_book.write();
-
-        let flg = order_book.update_order(&mut order, data.new_price, data.new_quantity);
-
-        if flg {
-            order_book.process_order_without_liquidity(&mut order)
-        } else {
-            Vec::new()
-        }
+        let order_book = &app_state.order_book;
+        order_book.update_order(&mut order, data.new_price, data.new_quantity)
+    };
+    let Some(matches) = matches else {
+        // amendment is rejected, order is resting in the book as it was
+        log_error!("Order with ID {} is not amended", data.order_id);
+        order.status = OrderStatus::OPEN;
+        order
+            .update(&app_state.db_pool)
+            .await
+            .map_err(|e| format!("Failed to update order: {e:#?}"))?;
+        return Ok(());
     };
 
     order
@@ -56,10 +75,16 @@ pub async fn update_order_handler(
         .await
         .map_err(|e| format!("Failed to update order: {e:#?}"))?;
 
-    tokio::try_join!(
-        update_matched_orders(matches, app_state.clone(), &order),
-        update_service_state(app_state.clone(), &order)
-    )?;
+    let update_matched_order_future = update_matched_orders(matches, app_state.clone(), &order);
+
+    let update_service_state_future = update_service_state(app_state.clone(), &order);
+
+    let (update_matched_orders_result, update_service_state_result) =
+        tokio::join!(update_matched_order_future, update_service_state_future);
+
+    update_matched_orders_result.map_err(|e| format!("Error while updating post order {e:#?}"))?;
+    update_service_state_result
+        .map_err(|e| format!("Error while updating service states {e:#?}"))?;
 
-    Ok(())
+    process_trigger_orders(app_state, order.market_id).await
 }
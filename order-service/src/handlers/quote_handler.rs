@@ -0,0 +1,117 @@
+use std::sync::Arc;
+
+use async_nats::Message;
+use db_service::schema::{
+    enums::{OrderSide, OrderStatus, OrderType, Outcome, SelfTradePrevention, TimeInForce},
+    orders::Order,
+};
+use futures_util::StreamExt;
+use rust_decimal::Decimal;
+use utility_helpers::{
+    log_error,
+    message_pack_helper::{deserialize_from_message_pack, serialize_to_message_pack},
+    nats_helper::{
+        NatsSubjects,
+        types::{QuoteFillLevel, QuoteRequestMessage, QuoteResponseMessage},
+    },
+};
+use uuid::Uuid;
+
+use crate::{
+    order_book::market_book::{NO_OUTCOME, QuoteSize, YES_OUTCOME},
+    state::AppState,
+    utils::OrderServiceError,
+};
+
+/// Replies to the quote requests of the market orders (nats request-reply), quotes are read-only so they are not queued with the market's messages
+pub async fn handle_quote_requests(app_state: Arc<AppState>) -> Result<(), OrderServiceError> {
+    let mut subscriber = app_state
+        .nats_client
+        .subscribe(NatsSubjects::OrderBookQuote.to_string())
+        .await?;
+
+    while let Some(message) = subscriber.next().await {
+        let Some(reply) = message.reply.clone() else {
+            continue; // nobody is waiting for the quote
+        };
+        let response = get_quote(&app_state, &message);
+        let payload = match serialize_to_message_pack(&response) {
+            Ok(payload) => payload,
+            Err(e) => {
+                log_error!("Failed to serialize quote response {e:#?}");
+                continue;
+            }
+        };
+        if let Err(e) = app_state.nats_client.publish(reply, payload.into()).await {
+            log_error!("Failed to reply to quote request {e:#?}");
+        }
+    }
+
+    Ok(())
+}
+
+fn get_quote(app_state: &AppState, message: &Message) -> Result<QuoteResponseMessage, String> {
+    let request =
+        deserialize_from_message_pack::<QuoteRequestMessage<OrderSide, Outcome>>(&message.payload)
+            .map_err(|e| format!("Invalid quote request {e}"))?;
+
+    let size = match (request.quantity, request.budget) {
+        (Some(quantity), None) if quantity > Decimal::ZERO => QuoteSize::Quantity(quantity),
+        (None, Some(budget)) if budget > Decimal::ZERO => QuoteSize::Budget(budget),
+        _ => return Err("Either quantity or budget is required".to_string()),
+    };
+
+    let order = get_quote_order(&request);
+    let quote = app_state
+        .order_book
+        .quote_market_order(&request.market_id, &order, size)
+        .ok_or_else(|| "Market or it's outcome is not found in the order book".to_string())?;
+
+    let price_of = |index: usize| quote.outcome_prices.get(index).copied().unwrap_or_default();
+    Ok(QuoteResponseMessage {
+        market_id: request.market_id,
+        fills: quote
+            .fills
+            .iter()
+            .map(|(price, quantity)| QuoteFillLevel {
+                price: *price,
+                quantity: *quantity,
+            })
+            .collect(),
+        filled_quantity: quote.filled_quantity,
+        total_cost: quote.total_cost,
+        average_price: quote.average_price(),
+        midpoint: quote.midpoint,
+        current_yes_price: price_of(YES_OUTCOME),
+        current_no_price: price_of(NO_OUTCOME),
+        outcome_prices: quote.outcome_prices.clone(),
+        reason: quote.reason.clone(),
+    })
+}
+
+// order of nobody, so the quote is not affected by the self trade prevention
+fn get_quote_order(request: &QuoteRequestMessage<OrderSide, Outcome>) -> Order {
+    let now = chrono::Utc::now().naive_utc();
+    Order {
+        id: Uuid::new_v4(),
+        user_id: Uuid::nil(),
+        market_id: request.market_id,
+        outcome: request.outcome,
+        price: Decimal::ZERO,
+        quantity: request.quantity.unwrap_or_default(),
+        filled_quantity: Decimal::ZERO,
+        status: OrderStatus::UNSPECIFIED,
+        side: request.side,
+        created_at: now,
+        updated_at: now,
+        order_type: OrderType::MARKET,
+        trigger_price: None,
+        time_in_force: TimeInForce::IOC,
+        expires_at: None,
+        post_only: false,
+        status_reason: None,
+        self_trade_prevention: SelfTradePrevention::CancelNewest,
+        outcome_index: request.outcome_index,
+        display_quantity: None,
+    }
+}
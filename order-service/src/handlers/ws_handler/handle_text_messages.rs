@@ -30,6 +30,8 @@ pub(super) async fn handle_text_messages(state: &Arc<AppState>, message: &Client
                             let mut market_subs = state.market_subs.write();
                             market_subs.insert(market_id);
                         }
+                        // new subscriber has no books to apply the deltas on, snapshot is published with the other book updates
+                        state.order_book.publish_book_snapshot(&market_id);
 
                         log_info!("Subscribed to order book updates for market: {market_id}");
                     }
@@ -1,9 +1,27 @@
-use db_service::schema::{enums::OrderStatus, orders::Order};
+use db_service::schema::{
+    enums::{OrderStatus, OrderType},
+    market::Market,
+    market_maker_accounts::MarketMakerAccount,
+    orders::Order,
+    trailing_stops::TrailingStop,
+};
 use state::AppState;
-use std::sync::Arc;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 use utility_helpers::{log_error, log_info};
 
-use crate::handlers::{nats_handler::handle_nats_message, ws_handler::handle_ws_messages};
+use crate::{
+    handlers::{
+        auction_handler::handle_call_auctions, book_update_handler::handle_book_updates,
+        circuit_breaker_handler::handle_circuit_breakers, expiry_handler::handle_expired_orders,
+        nats_handler::handle_nats_message, quote_handler::handle_quote_requests,
+        ws_handler::handle_ws_messages,
+    },
+    order_book::{journal::replay_journal, lmsr::LmsrMarketMaker, outcome_book::MarketRules},
+    utils::order_book_snapshot::restore_order_book,
+};
 
 mod handlers;
 mod order_book;
@@ -14,9 +32,30 @@ mod utils;
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     tracing_subscriber::fmt::init();
 
+    // `order-service replay-journal <path>` rebuilds the book from the matching journal and verifies it's outputs (no services are required)
+    let args = std::env::args().collect::<Vec<String>>();
+    if args.get(1).map(String::as_str) == Some("replay-journal") {
+        let journal_path = args
+            .get(2)
+            .ok_or("Journal path is required - order-service replay-journal <path>")?;
+        let report = replay_journal(journal_path)
+            .map_err(|e| format!("Failed to replay matching journal - {e}"))?;
+        log_info!(
+            "Journal replayed successfully, {} entries and {} matches are identical",
+            report.entries,
+            report.matches
+        );
+        return Ok(());
+    }
+
     let app_state = initialize_app().await?;
     let nats_app_state = Arc::clone(&app_state);
     let ws_app_state = Arc::clone(&app_state);
+    let expiry_app_state = Arc::clone(&app_state);
+    let auction_app_state = Arc::clone(&app_state);
+    let circuit_breaker_app_state = Arc::clone(&app_state);
+    let quote_app_state = Arc::clone(&app_state);
+    let book_update_app_state = Arc::clone(&app_state);
 
     let ws_handler_join = tokio::spawn(async move {
         if let Err(e) = handle_ws_messages(ws_app_state).await {
@@ -29,7 +68,45 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     });
 
-    tokio::try_join!(nats_handler_join, ws_handler_join)?;
+    let expiry_handler_join = tokio::spawn(async move {
+        if let Err(e) = handle_expired_orders(expiry_app_state).await {
+            log_error!("Error in expiry handler: {}", e);
+        }
+    });
+
+    let auction_handler_join = tokio::spawn(async move {
+        if let Err(e) = handle_call_auctions(auction_app_state).await {
+            log_error!("Error in auction handler: {}", e);
+        }
+    });
+
+    let circuit_breaker_handler_join = tokio::spawn(async move {
+        if let Err(e) = handle_circuit_breakers(circuit_breaker_app_state).await {
+            log_error!("Error in circuit breaker handler: {}", e);
+        }
+    });
+
+    let quote_handler_join = tokio::spawn(async move {
+        if let Err(e) = handle_quote_requests(quote_app_state).await {
+            log_error!("Error in quote handler: {}", e);
+        }
+    });
+
+    let book_update_handler_join = tokio::spawn(async move {
+        if let Err(e) = handle_book_updates(book_update_app_state).await {
+            log_error!("Error in book update handler: {}", e);
+        }
+    });
+
+    tokio::try_join!(
+        nats_handler_join,
+        ws_handler_join,
+        expiry_handler_join,
+        auction_handler_join,
+        circuit_breaker_handler_join,
+        quote_handler_join,
+        book_update_handler_join
+    )?;
 
     Ok(())
 }
@@ -37,35 +114,112 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 async fn initialize_app() -> Result<Arc<AppState>, Box<dyn std::error::Error>> {
     let app_state = Arc::new(AppState::new().await?);
 
+    let restored = restore_order_book(&app_state).await.unwrap_or_else(|e| {
+        log_error!("Failed to restore order book from snapshot, loading open orders - {e}");
+        app_state.order_book.restore_markets(Default::default());
+        false
+    });
+    if restored {
+        return Ok(app_state);
+    }
+
     let open_orders_future = Order::get_order_by_status(&app_state.db_pool, OrderStatus::OPEN);
     let partially_updated_orders_future =
         Order::get_order_by_status(&app_state.db_pool, OrderStatus::PendingUpdate);
     let unspecified_orders_future =
         Order::get_order_by_status(&app_state.db_pool, OrderStatus::UNSPECIFIED);
 
-    let (mut open_orders, mut partially_updated_orders, mut unspecified_orders) = tokio::try_join!(
+    let live_markets_future = Market::get_all_live_markets(&app_state.db_pool);
+    let market_maker_accounts_future =
+        MarketMakerAccount::get_live_market_accounts(&app_state.db_pool);
+    let trailing_stops_future = TrailingStop::get_live_trailing_stops(&app_state.db_pool);
+
+    let (
+        mut open_orders,
+        mut partially_updated_orders,
+        mut unspecified_orders,
+        live_markets,
+        market_maker_accounts,
+        trailing_stops,
+    ) = tokio::try_join!(
         open_orders_future,
         partially_updated_orders_future,
-        unspecified_orders_future
+        unspecified_orders_future,
+        live_markets_future,
+        market_maker_accounts_future,
+        trailing_stops_future
     )?;
+    let mut trailing_stops = trailing_stops
+        .into_iter()
+        .map(|trailing_stop| (trailing_stop.order_id, trailing_stop))
+        .collect::<HashMap<_, _>>();
 
     // combine open and partially updated orders
     open_orders.append(&mut partially_updated_orders);
     open_orders.append(&mut unspecified_orders);
 
-    // synchronous block, to prevent guard from being blocked
+    // synchronous block, market shards are locked per order
     {
-        let mut global_book = app_state.order_book.write();
+        let global_book = &app_state.order_book;
+
+        // market makers are restored before the orders, so their markets are priced by the persisted inventory
+        for account in market_maker_accounts {
+            let Some(market) = live_markets.iter().find(|m| m.id == account.market_id) else {
+                continue;
+            };
+            let market_maker = LmsrMarketMaker {
+                liquidity_b: account.liquidity_b,
+                q_yes: account.q_yes,
+                q_no: account.q_no,
+                cash: account.cash,
+            };
+            global_book.restore_market_maker(
+                account.market_id,
+                MarketRules::from_market(market),
+                market_maker,
+            );
+        }
+
+        // orders of paused, closed and resolving markets are not matched, their books are frozen once loaded
+        let frozen_market_ids = live_markets
+            .iter()
+            .filter(|market| !market.status.is_tradable())
+            .map(|market| market.id)
+            .collect::<HashSet<_>>();
 
         let mut order_ctn = 0;
         // iterate over open orders
         for db_order in open_orders {
             let liquidity_b = db_order.liquidity_b.clone();
+            let rules = MarketRules::from_order_with_market(&db_order);
             let mut order: Order = db_order.into();
-            global_book.process_order(&mut order, liquidity_b);
+            // stop loss and take profit orders are waiting for their trigger price
+            if matches!(
+                order.order_type,
+                OrderType::StopLoss | OrderType::TakeProfit
+            ) {
+                // trailing stops continue from their persisted best price
+                match trailing_stops.remove(&order.id) {
+                    Some(trailing_stop) => {
+                        global_book.add_trailing_stop(order, trailing_stop, liquidity_b, rules)
+                    }
+                    None => global_book.add_trigger_order(order, liquidity_b, rules),
+                };
+            } else if frozen_market_ids.contains(&order.market_id)
+                || (rules.market_maker && order.status != OrderStatus::UNSPECIFIED)
+            {
+                // resting orders are not matched again, they would trade against the restored market maker
+                global_book.add_order(&order, liquidity_b, rules);
+            } else {
+                global_book.process_order(&mut order, liquidity_b, rules);
+            }
             order_ctn += 1;
         }
         log_info!("Loaded {} open orders into the global book", order_ctn);
+
+        for market_id in frozen_market_ids {
+            global_book.freeze_market(&market_id);
+        }
     }
     Ok(app_state)
 }
@@ -0,0 +1,216 @@
+/*
+ * Published state of the market's order books
+ *
+ * 1. Every update of the market gets the next sequence number of the market (starting at 1), sequence is not persisted so it restarts on every boot and the updates are tagged with the boot epoch of the global book.
+ * 2. Updates carry only the changed price levels (delta) of the outcomes, removed level is sent with 0 shares and users.
+ * 3. Every `SNAPSHOT_INTERVAL`th update (first one, and the one after a snapshot is requested) carries the full books.
+ * 4. Checksum of the full books is sent with every update, so clients can detect the missed (or misapplied) updates and wait for the next snapshot.
+ */
+
+use std::collections::BTreeMap;
+
+use rust_decimal::Decimal;
+use utility_helpers::types::{OrderBookDataStruct, OrderLevel, get_books_checksum};
+use uuid::Uuid;
+
+pub(crate) const SNAPSHOT_INTERVAL: u64 = 100;
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct BookFeed {
+    sequence: u64,
+    books: Vec<OrderBookDataStruct>, // books of the outcomes as of the last update
+    last_snapshot: u64,              // sequence of the last snapshot
+    snapshot_requested: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct BookUpdate {
+    pub(crate) epoch: u64,
+    pub(crate) sequence: u64,
+    pub(crate) is_snapshot: bool,
+    pub(crate) books: Vec<OrderBookDataStruct>, // full books of the outcomes
+    pub(crate) deltas: Vec<OrderBookDataStruct>, // changed levels of the outcomes (empty for snapshots)
+    pub(crate) checksum: u32,
+}
+
+/// Book update of the market queued for the publisher
+#[derive(Debug, Clone)]
+pub(crate) struct MarketBookUpdate {
+    pub(crate) market_id: Uuid,
+    pub(crate) user_id: Option<Uuid>, // owner of the order which changed the books
+    pub(crate) book_update: BookUpdate,
+}
+
+impl BookFeed {
+    /// Next update of the feed for the current `books` of the market
+    pub(crate) fn next_update(&mut self, books: Vec<OrderBookDataStruct>) -> BookUpdate {
+        self.sequence += 1;
+        let is_snapshot = self.last_snapshot == 0
+            || self.snapshot_requested
+            || self.sequence - self.last_snapshot >= SNAPSHOT_INTERVAL
+            || self.books.len() != books.len();
+
+        let deltas = if is_snapshot {
+            self.last_snapshot = self.sequence;
+            self.snapshot_requested = false;
+            Vec::new()
+        } else {
+            self.books
+                .iter()
+                .zip(books.iter())
+                .map(|(previous, current)| OrderBookDataStruct {
+                    bids: get_level_changes(&previous.bids, &current.bids),
+                    asks: get_level_changes(&previous.asks, &current.asks),
+                })
+                .collect()
+        };
+
+        let checksum = get_books_checksum(&books);
+        self.books = books.clone();
+        BookUpdate {
+            epoch: 0, // tagged by the global book
+            sequence: self.sequence,
+            is_snapshot,
+            books,
+            deltas,
+            checksum,
+        }
+    }
+
+    /// Next update carries the full books (new subscriber has nothing to apply the deltas on)
+    pub(crate) fn request_snapshot(&mut self) {
+        self.snapshot_requested = true;
+    }
+}
+
+// levels which are added, changed or removed (with 0 shares) in ascending price
+fn get_level_changes(previous: &[OrderLevel], current: &[OrderLevel]) -> Vec<OrderLevel> {
+    let mut levels = previous
+        .iter()
+        .map(|level| {
+            let removed = OrderLevel {
+                price: level.price,
+                shares: Decimal::ZERO,
+                users: 0,
+            };
+            (level.price, (Some(level), removed))
+        })
+        .collect::<BTreeMap<_, _>>();
+    for level in current {
+        levels
+            .entry(level.price)
+            .and_modify(|(_, changed)| *changed = level.clone())
+            .or_insert((None, level.clone()));
+    }
+
+    levels
+        .into_values()
+        .filter(|(previous, changed)| *previous != Some(changed))
+        .map(|(_, changed)| changed)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    fn get_level(price: Decimal, shares: Decimal) -> OrderLevel {
+        OrderLevel {
+            price,
+            shares,
+            users: 1,
+        }
+    }
+
+    // applies the deltas as the clients do
+    fn apply_deltas(books: &mut [OrderBookDataStruct], deltas: &[OrderBookDataStruct]) {
+        let apply = |levels: &mut Vec<OrderLevel>, changes: &[OrderLevel]| {
+            for change in changes {
+                levels.retain(|level| level.price != change.price);
+                if change.shares > Decimal::ZERO {
+                    levels.push(change.clone());
+                }
+            }
+            levels.sort_by_key(|level| level.price);
+        };
+        for (book, delta) in books.iter_mut().zip(deltas) {
+            apply(&mut book.bids, &delta.bids);
+            apply(&mut book.asks, &delta.asks);
+        }
+    }
+
+    #[test]
+    fn test_deltas_rebuild_books() {
+        let mut feed = BookFeed::default();
+        let mut books = vec![OrderBookDataStruct::default(); 2];
+        books[0].bids = vec![
+            get_level(dec!(0.3), dec!(10)),
+            get_level(dec!(0.4), dec!(5)),
+        ];
+        books[0].asks = vec![get_level(dec!(0.6), dec!(5))];
+
+        let first = feed.next_update(books.clone());
+        assert_eq!(first.sequence, 1);
+        assert!(first.is_snapshot);
+        assert!(first.deltas.is_empty());
+        let mut client_books = first.books.clone();
+
+        // bid at 0.4 is filled, bid at 0.3 is reduced and ask at 0.55 is added
+        books[0].bids = vec![get_level(dec!(0.3), dec!(8))];
+        books[0].asks = vec![
+            get_level(dec!(0.55), dec!(2)),
+            get_level(dec!(0.6), dec!(5)),
+        ];
+        let second = feed.next_update(books.clone());
+        assert_eq!(second.sequence, 2);
+        assert!(!second.is_snapshot);
+        assert_eq!(
+            second.deltas[0].bids,
+            vec![
+                get_level(dec!(0.3), dec!(8)),
+                OrderLevel {
+                    price: dec!(0.4),
+                    shares: Decimal::ZERO,
+                    users: 0,
+                },
+            ]
+        );
+        assert_eq!(second.deltas[0].asks, vec![get_level(dec!(0.55), dec!(2))]);
+        assert_eq!(second.deltas[1], OrderBookDataStruct::default());
+
+        apply_deltas(&mut client_books, &second.deltas);
+        assert_eq!(client_books, books);
+        assert_eq!(get_books_checksum(&client_books), second.checksum);
+        assert_ne!(first.checksum, second.checksum);
+
+        // unchanged books are sent as an empty delta
+        let third = feed.next_update(books.clone());
+        assert_eq!(third.sequence, 3);
+        assert!(
+            third
+                .deltas
+                .iter()
+                .all(|delta| delta.bids.is_empty() && delta.asks.is_empty())
+        );
+        assert_eq!(third.checksum, second.checksum);
+    }
+
+    #[test]
+    fn test_periodic_and_requested_snapshots() {
+        let mut feed = BookFeed::default();
+        let books = vec![OrderBookDataStruct::default(); 2];
+
+        let snapshots = (0..SNAPSHOT_INTERVAL * 2)
+            .map(|_| feed.next_update(books.clone()))
+            .filter(|update| update.is_snapshot)
+            .map(|update| update.sequence)
+            .collect::<Vec<_>>();
+        assert_eq!(snapshots, vec![1, SNAPSHOT_INTERVAL + 1]);
+
+        feed.request_snapshot();
+        assert!(feed.next_update(books.clone()).is_snapshot);
+        assert!(!feed.next_update(books).is_snapshot);
+    }
+}
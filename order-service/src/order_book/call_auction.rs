@@ -0,0 +1,237 @@
+/*
+ * Opening call auction of the market, orders are collected without matching till the scheduled open and then uncrossed at a single price.
+ *
+ * ## Auction rules
+ *
+ * 1. While the auction runs (`MarketRules::auction_opens_at`), limit orders rest in the book without matching, market and immediate (IOC / FOK) orders are cancelled.
+ * 2. Clearing price is one of the limit prices which maximizes the executable volume, ties are broken by the smallest imbalance, then by the price closest to the reference price and then by the lower price.
+ * 3. Every crossing order is executed at the clearing price, demand is filled by the limit price (best first) and then by the time priority of the book.
+ * 4. Orders of the same user are never paired, so the executed volume might be lower than the indicated one.
+ *
+ * ## Binary market
+ *
+ * Both outcome books are auctioned together in YES terms, buying NO at `q` is the supply of YES at `1 - q` and selling NO at `q` is the demand of YES at `1 - q`.
+ * So YES / NO buyers are paired by minting share pairs and YES / NO sellers by merging them, NO shares are executed at `1 - clearing price`.
+ */
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Remaining quantity (including hidden quantity of iceberg orders) of the resting order taking part in the auction
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct AuctionOrder {
+    pub order_id: Uuid,
+    pub user_id: Uuid,
+    pub outcome_index: usize, // book of the order, binary market auctions both books together
+    pub limit_price: Decimal, // in terms of the auctioned outcome
+    pub quantity: Decimal,
+}
+
+/// Indicative result of the auction if it's uncrossed right now
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub(crate) struct AuctionIndication {
+    pub price: Decimal,
+    pub volume: Decimal,
+    pub imbalance: Decimal, // demand - supply at the price, positive if buyers are left over
+}
+
+/// Quantity executed between the demand and supply order (indices of the auction orders)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct AuctionFill {
+    pub demand: usize,
+    pub supply: usize,
+    pub quantity: Decimal,
+}
+
+/// Clearing price of the auction, `None` if demand and supply don't cross
+pub(crate) fn get_clearing_price(
+    demand: &[AuctionOrder],
+    supply: &[AuctionOrder],
+    reference_price: Decimal,
+) -> Option<AuctionIndication> {
+    let mut candidates = demand
+        .iter()
+        .chain(supply.iter())
+        .map(|order| order.limit_price)
+        .collect::<Vec<_>>();
+    candidates.sort();
+    candidates.dedup();
+
+    candidates
+        .into_iter()
+        .filter_map(|price| {
+            let demand_quantity = demand
+                .iter()
+                .filter(|order| order.limit_price >= price)
+                .map(|order| order.quantity)
+                .sum::<Decimal>();
+            let supply_quantity = supply
+                .iter()
+                .filter(|order| order.limit_price <= price)
+                .map(|order| order.quantity)
+                .sum::<Decimal>();
+            let volume = demand_quantity.min(supply_quantity);
+
+            (volume > Decimal::ZERO).then_some(AuctionIndication {
+                price,
+                volume,
+                imbalance: demand_quantity - supply_quantity,
+            })
+        })
+        // candidates are ascending, so `min_by` keeps the lower price on a tie
+        .min_by(|a, b| {
+            b.volume
+                .cmp(&a.volume)
+                .then(a.imbalance.abs().cmp(&b.imbalance.abs()))
+                .then(
+                    (a.price - reference_price)
+                        .abs()
+                        .cmp(&(b.price - reference_price).abs()),
+                )
+        })
+}
+
+/// Pairs the demand and supply orders crossing the clearing price, orders are expected in the book order (time priority)
+pub(crate) fn pair_orders(
+    demand: &[AuctionOrder],
+    supply: &[AuctionOrder],
+    price: Decimal,
+) -> Vec<AuctionFill> {
+    let mut demand_slots = (0..demand.len())
+        .filter(|index| demand[*index].limit_price >= price)
+        .collect::<Vec<_>>();
+    let mut supply_slots = (0..supply.len())
+        .filter(|index| supply[*index].limit_price <= price)
+        .collect::<Vec<_>>();
+    // stable sort, so the book order is kept within the same limit price
+    demand_slots.sort_by(|a, b| demand[*b].limit_price.cmp(&demand[*a].limit_price));
+    supply_slots.sort_by(|a, b| supply[*a].limit_price.cmp(&supply[*b].limit_price));
+
+    let mut supply_remaining = supply.iter().map(|o| o.quantity).collect::<Vec<_>>();
+    let mut fills = Vec::new();
+
+    for demand_index in demand_slots {
+        let demand_order = &demand[demand_index];
+        let mut remaining = demand_order.quantity;
+
+        for supply_index in supply_slots.iter().copied() {
+            if remaining <= Decimal::ZERO {
+                break;
+            }
+            let available = supply_remaining[supply_index];
+            if available <= Decimal::ZERO || supply[supply_index].user_id == demand_order.user_id {
+                continue;
+            }
+
+            let quantity = remaining.min(available);
+            supply_remaining[supply_index] -= quantity;
+            remaining -= quantity;
+            fills.push(AuctionFill {
+                demand: demand_index,
+                supply: supply_index,
+                quantity,
+            });
+        }
+    }
+
+    fills
+}
+
+#[cfg(test)]
+mod test {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    fn get_auction_order(limit_price: Decimal, quantity: Decimal) -> AuctionOrder {
+        AuctionOrder {
+            order_id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            outcome_index: 0,
+            limit_price,
+            quantity,
+        }
+    }
+
+    #[test]
+    fn test_clearing_price_maximizes_volume() {
+        let demand = [
+            get_auction_order(dec!(0.6), dec!(10)),
+            get_auction_order(dec!(0.55), dec!(10)),
+            get_auction_order(dec!(0.5), dec!(10)),
+        ];
+        let supply = [
+            get_auction_order(dec!(0.45), dec!(5)),
+            get_auction_order(dec!(0.5), dec!(10)),
+            get_auction_order(dec!(0.58), dec!(20)),
+        ];
+
+        // 0.5 -> min(30, 15), 0.55 -> min(20, 15), 0.58 -> min(10, 35)
+        let indication = get_clearing_price(&demand, &supply, dec!(0.5)).unwrap();
+        assert_eq!(indication.price, dec!(0.55));
+        assert_eq!(indication.volume, dec!(15));
+        assert_eq!(indication.imbalance, dec!(5));
+    }
+
+    #[test]
+    fn test_clearing_price_tie_breakers() {
+        // 10 shares are executed at any price between 0.4 and 0.6 with no imbalance
+        let demand = [get_auction_order(dec!(0.6), dec!(10))];
+        let supply = [get_auction_order(dec!(0.4), dec!(10))];
+
+        let indication = get_clearing_price(&demand, &supply, dec!(0.58)).unwrap();
+        assert_eq!(indication.price, dec!(0.6));
+
+        // reference price is in the middle, lower price is taken
+        let indication = get_clearing_price(&demand, &supply, dec!(0.5)).unwrap();
+        assert_eq!(indication.price, dec!(0.4));
+        assert_eq!(indication.imbalance, Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_not_crossing_auction() {
+        let demand = [get_auction_order(dec!(0.4), dec!(10))];
+        let supply = [get_auction_order(dec!(0.5), dec!(10))];
+
+        assert_eq!(get_clearing_price(&demand, &supply, dec!(0.5)), None);
+        assert!(pair_orders(&demand, &supply, dec!(0.45)).is_empty());
+    }
+
+    #[test]
+    fn test_pair_orders() {
+        let demand = [
+            get_auction_order(dec!(0.5), dec!(10)),
+            get_auction_order(dec!(0.6), dec!(4)),
+            get_auction_order(dec!(0.4), dec!(10)),
+        ];
+        let mut supply = [
+            get_auction_order(dec!(0.5), dec!(6)),
+            get_auction_order(dec!(0.45), dec!(6)),
+        ];
+        // same user's orders are never paired
+        supply[1].user_id = demand[1].user_id;
+
+        let fills = pair_orders(&demand, &supply, dec!(0.5));
+        assert_eq!(
+            fills,
+            vec![
+                AuctionFill {
+                    demand: 1,
+                    supply: 0,
+                    quantity: dec!(4)
+                },
+                AuctionFill {
+                    demand: 0,
+                    supply: 1,
+                    quantity: dec!(6)
+                },
+                AuctionFill {
+                    demand: 0,
+                    supply: 0,
+                    quantity: dec!(2)
+                },
+            ]
+        );
+    }
+}
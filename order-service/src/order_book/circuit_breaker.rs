@@ -0,0 +1,139 @@
+/*
+ * Volatility circuit breaker of the market, orders are only executed within a price band around the reference price.
+ *
+ * ## Price band
+ *
+ * 1. Reference price of every outcome is it's last trade price (current price if it was never traded), it's rolled every `MarketRules::price_band_window` seconds.
+ * 2. Band of the outcome is `reference * (1 ± MarketRules::price_band)`, clamped to the valid prices (0, 1).
+ * 3. Limit orders priced outside the band are cancelled, they don't halt the trading.
+ * 4. Market orders are matched up to the band edge, market order which would be executed beyond it breaches the band and halts the trading.
+ *
+ * ## Trading halt
+ *
+ * 1. Halted market cancels every new order, resting orders stay in the book and trigger orders wait in the trigger book.
+ * 2. Cool-down of the halt is tracked by the database (`halted_until`), once it's over the trading is resumed and the band is rolled to the current prices.
+ * 3. Opening call auction is the price discovery of the market, so the band is not applied to it and is re-referenced once it's uncrossed.
+ */
+
+use std::collections::BTreeMap;
+
+use chrono::NaiveDateTime;
+use db_service::schema::enums::OrderSide;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+pub(crate) const PRICE_OUTSIDE_BAND_REASON: &str = "Order price is outside the price band";
+pub(crate) const PRICE_BAND_BREACH_REASON: &str =
+    "Market order breached the price band, trading is halted";
+pub(crate) const TRADING_HALTED_REASON: &str = "Trading of the market is halted";
+
+/// Reference prices of the band by the outcome index
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct PriceBand {
+    pub reference_prices: BTreeMap<usize, Decimal>,
+    pub window_start: Option<NaiveDateTime>, // none till the band is rolled by the timer
+}
+
+impl PriceBand {
+    pub(crate) fn new(
+        reference_prices: BTreeMap<usize, Decimal>,
+        window_start: Option<NaiveDateTime>,
+    ) -> Self {
+        Self {
+            reference_prices,
+            window_start,
+        }
+    }
+
+    /// Lowest and highest price accepted for the outcome, `None` if the outcome has no reference price
+    pub(crate) fn bounds(&self, outcome_index: usize, band: Decimal) -> Option<(Decimal, Decimal)> {
+        let reference_price = self.reference_prices.get(&outcome_index)?;
+        let lower = (reference_price * (Decimal::ONE - band)).max(Decimal::ZERO);
+        let upper = (reference_price * (Decimal::ONE + band)).min(Decimal::ONE);
+        Some((lower, upper))
+    }
+
+    pub(crate) fn contains(&self, outcome_index: usize, band: Decimal, price: Decimal) -> bool {
+        self.bounds(outcome_index, band)
+            .is_none_or(|(lower, upper)| price >= lower && price <= upper)
+    }
+
+    /// Worst price an order of the side is executed at, upper edge for buys and lower edge for sells
+    pub(crate) fn worst_price(
+        &self,
+        outcome_index: usize,
+        band: Decimal,
+        side: OrderSide,
+    ) -> Option<Decimal> {
+        let (lower, upper) = self.bounds(outcome_index, band)?;
+        Some(match side {
+            OrderSide::BUY => upper,
+            OrderSide::SELL => lower,
+        })
+    }
+
+    /// Band is rolled once it's window (in seconds) is over
+    pub(crate) fn is_due(&self, window: u32, current_time: NaiveDateTime) -> bool {
+        self.window_start.is_none_or(|window_start| {
+            window_start + chrono::Duration::seconds(i64::from(window)) <= current_time
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    fn get_price_band(window_start: Option<NaiveDateTime>) -> PriceBand {
+        PriceBand::new(
+            BTreeMap::from([(0, dec!(0.5)), (1, dec!(0.9))]),
+            window_start,
+        )
+    }
+
+    #[test]
+    fn test_price_band_bounds() {
+        let price_band = get_price_band(None);
+
+        assert_eq!(
+            price_band.bounds(0, dec!(0.15)),
+            Some((dec!(0.425), dec!(0.575)))
+        );
+        // clamped to the valid prices
+        assert_eq!(
+            price_band.bounds(1, dec!(0.2)),
+            Some((dec!(0.72), Decimal::ONE))
+        );
+        assert_eq!(price_band.bounds(2, dec!(0.15)), None);
+
+        assert!(price_band.contains(0, dec!(0.15), dec!(0.575)));
+        assert!(!price_band.contains(0, dec!(0.15), dec!(0.58)));
+        assert!(!price_band.contains(0, dec!(0.15), dec!(0.42)));
+        // outcome without the reference price is not limited
+        assert!(price_band.contains(2, dec!(0.15), dec!(0.99)));
+
+        assert_eq!(
+            price_band.worst_price(0, dec!(0.15), OrderSide::BUY),
+            Some(dec!(0.575))
+        );
+        assert_eq!(
+            price_band.worst_price(0, dec!(0.15), OrderSide::SELL),
+            Some(dec!(0.425))
+        );
+    }
+
+    #[test]
+    fn test_price_band_window() {
+        let window_start = chrono::DateTime::parse_from_rfc3339("2025-06-01T10:00:00Z")
+            .unwrap()
+            .naive_utc();
+
+        assert!(get_price_band(None).is_due(300, window_start));
+
+        let price_band = get_price_band(Some(window_start));
+        assert!(!price_band.is_due(300, window_start + chrono::Duration::seconds(299)));
+        assert!(price_band.is_due(300, window_start + chrono::Duration::seconds(300)));
+    }
+}
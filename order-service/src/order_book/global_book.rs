@@ -1,129 +1,662 @@
-use db_service::schema::{
-    enums::{OrderSide, Outcome},
-    orders::Order,
-};
+/*
+ * Global book is a registry of market shards, every market book is guarded by it's own lock.
+ *
+ * 1. Markets are matched in parallel by their market workers (see `handlers::nats_handler::market_worker`), orders of the same market are processed sequentially by it's worker.
+ * 2. Registry lock is only written when a market is created, restored or removed, so matching of one market never blocks the others.
+ * 3. Lock order is registry -> market -> journal, registry lock is never acquired while holding a market lock.
+ * 4. Book updates are sequenced and queued for the publisher under the market lock, so they are published in the order of their sequence.
+ */
+
+use chrono::NaiveDateTime;
+use db_service::schema::{enums::MarketStatus, orders::Order, trailing_stops::TrailingStop};
+use parking_lot::{Mutex, RwLock};
 use rust_decimal::Decimal;
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+use tokio::sync::mpsc::UnboundedSender;
+use utility_helpers::log_error;
 use uuid::Uuid;
 
-use crate::order_book::outcome_book::{OrderBookMatchedOutput, OutcomeBook};
+use crate::order_book::outcome_book::{
+    MarketOrderLimits, MarketRules, OrderBookEntry, OrderBookMatchedOutput, PostOnlyMode,
+};
+
+use super::{
+    book_feed::{BookUpdate, MarketBookUpdate},
+    call_auction::AuctionIndication,
+    journal::{JournalCommand, MatchingJournal},
+    lmsr::LmsrMarketMaker,
+    market_book::{MarketBook, MarketQuote, QuoteSize},
+};
 
-use super::market_book::MarketBook;
+pub(crate) type MarketShard = Arc<Mutex<MarketBook>>;
 
 #[derive(Debug)]
 pub(crate) struct GlobalMarketBook {
-    pub(crate) markets: HashMap<Uuid, MarketBook>,
+    pub(crate) markets: RwLock<HashMap<Uuid, MarketShard>>,
+    post_only_mode: PostOnlyMode,
+    journal: Option<Mutex<MatchingJournal>>,
+    book_epoch: u64, // boot time (millis) tagged on the book updates, their sequence restarts on every boot
+    book_publisher: Option<UnboundedSender<MarketBookUpdate>>,
 }
 
 impl GlobalMarketBook {
     pub(crate) fn new() -> Self {
         Self {
-            markets: HashMap::new(),
+            markets: RwLock::new(HashMap::new()),
+            post_only_mode: PostOnlyMode::default(),
+            journal: None,
+            book_epoch: u64::try_from(chrono::Utc::now().timestamp_millis()).unwrap_or_default(),
+            book_publisher: None,
         }
     }
 
+    /// Queues every book update of the markets for the publisher (kafka and nats)
+    pub(crate) fn with_book_publisher(
+        mut self,
+        book_publisher: UnboundedSender<MarketBookUpdate>,
+    ) -> Self {
+        self.book_publisher = Some(book_publisher);
+        self
+    }
+
+    /// Sets how post only orders crossing the book are handled in newly created markets
+    pub(crate) fn with_post_only_mode(mut self, post_only_mode: PostOnlyMode) -> Self {
+        self.set_post_only_mode(post_only_mode);
+        self
+    }
+
+    /// Records every command applied on the book (and it's matches) in the journal
+    pub(crate) fn with_journal(mut self, journal: MatchingJournal) -> Self {
+        self.journal = Some(Mutex::new(journal));
+        let command = self.journal_command(|| JournalCommand::Configure {
+            post_only_mode: self.post_only_mode,
+        });
+        self.record(command, &[]);
+        self
+    }
+
+    pub(crate) fn set_post_only_mode(&mut self, post_only_mode: PostOnlyMode) {
+        self.post_only_mode = post_only_mode;
+    }
+
     pub(crate) fn process_order(
-        &mut self,
+        &self,
         order: &mut Order,
         liquidity_b: Decimal,
+        rules: MarketRules,
     ) -> Vec<OrderBookMatchedOutput> {
-        let market_id = order.market_id;
-        let market_book = self.get_or_create_market(market_id, liquidity_b);
-        market_book.process_order(order)
+        let command = self.journal_command(|| JournalCommand::ProcessOrder {
+            order: order.clone(),
+            liquidity_b,
+            rules,
+        });
+
+        let market = self.get_or_create_market(order.market_id, liquidity_b, rules);
+        let mut market_book = market.lock();
+        let matches = market_book.process_order(order);
+
+        self.record(command, &matches);
+        matches
     }
 
+    // matches the order that is already in the book, amended orders are matched by `update_order` now
+    // so it's only replayed for the older journals
     pub(crate) fn process_order_without_liquidity(
-        &mut self,
+        &self,
         order: &mut Order,
     ) -> Vec<OrderBookMatchedOutput> {
-        let market_id = order.market_id;
-        if let Some(market_book) = self.markets.get_mut(&market_id) {
-            let matches = market_book.process_order(order);
-            matches
-        } else {
-            Vec::new()
-        }
+        let command = self.journal_command(|| JournalCommand::ProcessOrderWithoutLiquidity {
+            order: order.clone(),
+        });
+
+        let market = self.get_market(&order.market_id);
+        let mut market_book = market.as_ref().map(|market| market.lock());
+        let matches = market_book
+            .as_mut()
+            .map(|market_book| market_book.process_order(order))
+            .unwrap_or_default();
+
+        self.record(command, &matches);
+        matches
     }
 
-    pub(crate) fn add_order(&mut self, order: &Order, liquidity_b: Decimal) {
-        let market_id = order.market_id;
-        let market_book = self.get_or_create_market(market_id, liquidity_b);
+    pub(crate) fn add_order(&self, order: &Order, liquidity_b: Decimal, rules: MarketRules) {
+        let command = self.journal_command(|| JournalCommand::AddOrder {
+            order: order.clone(),
+            liquidity_b,
+            rules,
+        });
+
+        let market = self.get_or_create_market(order.market_id, liquidity_b, rules);
+        let mut market_book = market.lock();
         market_book.add_order(order);
+
+        self.record(command, &[]);
     }
 
-    pub(crate) fn get_market_price(&self, market_id: &Uuid, outcome: Outcome) -> Option<Decimal> {
-        self.markets.get(market_id).map(|market| match outcome {
-            Outcome::YES => market.current_yes_price,
-            Outcome::NO => market.current_no_price,
-            _ => Decimal::ZERO,
-        })
+    /// Current prices of the market's outcomes by their index
+    pub(crate) fn get_market_prices(&self, market_id: &Uuid) -> Option<Vec<Decimal>> {
+        self.get_market(market_id)
+            .map(|market| market.lock().current_prices())
     }
 
-    pub(crate) fn get_orders(&self, market_id: &Uuid, outcome: Outcome) -> Option<&OutcomeBook> {
-        self.markets
-            .get(market_id)
-            .and_then(|market| market.get_order_book(outcome))
+    /// Publishes the next update of the market's books (delta of the changed levels), `user_id` is the owner of the order which changed them
+    pub(crate) fn publish_book_update(
+        &self,
+        market_id: &Uuid,
+        user_id: Option<Uuid>,
+    ) -> Option<BookUpdate> {
+        self.next_book_update(market_id, user_id, false)
+    }
+
+    /// Publishes the full books of the market as the next book update, for the new subscribers which have nothing to apply the deltas on
+    pub(crate) fn publish_book_snapshot(&self, market_id: &Uuid) -> Option<BookUpdate> {
+        self.next_book_update(market_id, None, true)
+    }
+
+    /// Quotes the market order against the current state of the market, nothing is changed (so it's not journaled)
+    pub(crate) fn quote_market_order(
+        &self,
+        market_id: &Uuid,
+        order: &Order,
+        size: QuoteSize,
+    ) -> Option<MarketQuote> {
+        self.get_market(market_id)
+            .and_then(|market| market.lock().quote_market_order(order, size))
     }
 
     pub(crate) fn create_market_order(
-        &mut self,
+        &self,
         market_id: &Uuid,
         order: &mut Order,
         budget: Decimal,
+        limits: MarketOrderLimits,
     ) -> Vec<OrderBookMatchedOutput> {
-        if let Some(market_book) = self.markets.get_mut(market_id) {
-            market_book.create_market_order(order, budget)
-        } else {
-            Vec::new()
-        }
+        let command = self.journal_command(|| JournalCommand::CreateMarketOrder {
+            market_id: *market_id,
+            order: order.clone(),
+            budget,
+            limits,
+        });
+
+        let market = self.get_market(market_id);
+        let mut market_book = market.as_ref().map(|market| market.lock());
+        let matches = market_book
+            .as_mut()
+            .map(|market_book| market_book.create_market_order(order, budget, limits))
+            .unwrap_or_default();
+
+        self.record(command, &matches);
+        matches
     }
 
-    pub(crate) fn remove_order(
-        &mut self,
-        market_id: Uuid,
-        order_id: Uuid,
-        market_side: OrderSide,
-        outcome: Outcome,
-        price: Decimal,
-    ) -> bool {
-        if let Some(market_book) = self.markets.get_mut(&market_id) {
-            market_book.remove_order(order_id, market_side, outcome, price)
-        } else {
-            false
-        }
+    pub(crate) fn remove_order(&self, market_id: Uuid, order_id: Uuid) -> bool {
+        let command = self.journal_command(|| JournalCommand::RemoveOrder {
+            market_id,
+            order_id,
+        });
+
+        let market = self.get_market(&market_id);
+        let mut market_book = market.as_ref().map(|market| market.lock());
+        let removed = market_book
+            .as_mut()
+            .is_some_and(|market_book| market_book.remove_order(order_id));
+
+        self.record(command, &[]);
+        removed
     }
 
     pub(crate) fn update_order(
-        &mut self,
+        &self,
         order: &mut Order,
         new_price: Decimal,
         new_total_quantity: Decimal,
+    ) -> Option<Vec<OrderBookMatchedOutput>> {
+        let command = self.journal_command(|| JournalCommand::UpdateOrder {
+            order: order.clone(),
+            new_price,
+            new_quantity: new_total_quantity,
+        });
+
+        let market = self.get_market(&order.market_id);
+        let mut market_book = market.as_ref().map(|market| market.lock());
+        let matches = market_book
+            .as_mut()
+            .and_then(|market_book| market_book.update_order(order, new_total_quantity, new_price));
+
+        self.record(command, matches.as_deref().unwrap_or_default());
+        matches
+    }
+
+    pub(crate) fn add_trigger_order(
+        &self,
+        order: Order,
+        liquidity_b: Decimal,
+        rules: MarketRules,
     ) -> bool {
-        if let Some(market_book) = self.markets.get_mut(&order.market_id) {
-            market_book.update_order(order, new_total_quantity, new_price)
-        } else {
-            false
+        let command = self.journal_command(|| JournalCommand::AddTriggerOrder {
+            order: order.clone(),
+            liquidity_b,
+            rules,
+        });
+
+        let market = self.get_or_create_market(order.market_id, liquidity_b, rules);
+        let mut market_book = market.lock();
+        let added = market_book.add_trigger_order(order);
+
+        self.record(command, &[]);
+        added
+    }
+
+    pub(crate) fn add_trailing_stop(
+        &self,
+        order: Order,
+        trailing_stop: TrailingStop,
+        liquidity_b: Decimal,
+        rules: MarketRules,
+    ) -> bool {
+        let command = self.journal_command(|| JournalCommand::AddTrailingStop {
+            order: order.clone(),
+            trailing_stop,
+            liquidity_b,
+            rules,
+        });
+
+        let market = self.get_or_create_market(order.market_id, liquidity_b, rules);
+        let mut market_book = market.lock();
+        let added = market_book.add_trailing_stop(order, trailing_stop);
+
+        self.record(command, &[]);
+        added
+    }
+
+    /// Trailing stops of the market moved since the last call, along with their trigger price
+    pub(crate) fn take_moved_trailing_stops(
+        &self,
+        market_id: &Uuid,
+    ) -> Vec<(TrailingStop, Decimal)> {
+        self.get_market(market_id)
+            .map(|market| market.lock().take_moved_trailing_stops())
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn remove_trigger_order(&self, market_id: &Uuid, order_id: Uuid) -> bool {
+        let command = self.journal_command(|| JournalCommand::RemoveTriggerOrder {
+            market_id: *market_id,
+            order_id,
+        });
+
+        let market = self.get_market(market_id);
+        let mut market_book = market.as_ref().map(|market| market.lock());
+        let removed = market_book
+            .as_mut()
+            .is_some_and(|market_book| market_book.remove_trigger_order(order_id));
+
+        self.record(command, &[]);
+        removed
+    }
+
+    /// Resizes the stop loss / take profit order waiting for it's trigger, returns false if it's not in the trigger book (e.g. already triggered)
+    pub(crate) fn resize_trigger_order(
+        &self,
+        market_id: &Uuid,
+        order_id: Uuid,
+        quantity: Decimal,
+    ) -> bool {
+        let command = self.journal_command(|| JournalCommand::ResizeTriggerOrder {
+            market_id: *market_id,
+            order_id,
+            quantity,
+        });
+
+        let market = self.get_market(market_id);
+        let mut market_book = market.as_ref().map(|market| market.lock());
+        let resized = market_book
+            .as_mut()
+            .is_some_and(|market_book| market_book.resize_trigger_order(order_id, quantity));
+
+        self.record(command, &[]);
+        resized
+    }
+
+    pub(crate) fn execute_triggered_orders(
+        &self,
+        market_id: &Uuid,
+    ) -> Vec<(Order, Vec<OrderBookMatchedOutput>)> {
+        let command = self.journal_command(|| JournalCommand::ExecuteTriggeredOrders {
+            market_id: *market_id,
+        });
+
+        let market = self.get_market(market_id);
+        let mut market_book = market.as_ref().map(|market| market.lock());
+        let executed = market_book
+            .as_mut()
+            .map(|market_book| market_book.execute_triggered_orders())
+            .unwrap_or_default();
+
+        if command.is_some() {
+            let matches = executed
+                .iter()
+                .flat_map(|(_, matches)| matches.iter().cloned())
+                .collect::<Vec<_>>();
+            self.record(command, &matches);
+        }
+        executed
+    }
+
+    /// Markets whose call auction is due to be uncrossed at `now`
+    pub(crate) fn get_due_auctions(&self, now: NaiveDateTime) -> Vec<Uuid> {
+        self.get_markets()
+            .into_iter()
+            .filter(|(_, market)| {
+                let market_book = market.lock();
+                !market_book.is_frozen()
+                    && market_book
+                        .auction_opens_at()
+                        .is_some_and(|opens_at| opens_at <= now)
+            })
+            .map(|(market_id, _)| market_id)
+            .collect()
+    }
+
+    /// Indicative clearing price, volume and imbalance of the market's outcomes while it's in the call auction
+    pub(crate) fn get_auction_indications(
+        &self,
+        market_id: &Uuid,
+    ) -> Option<Vec<(usize, AuctionIndication)>> {
+        self.get_market(market_id)
+            .and_then(|market| market.lock().auction_indications())
+    }
+
+    /// Ends the call auction of the market, crossing orders are executed at the clearing price
+    pub(crate) fn uncross_auction(&self, market_id: &Uuid) -> Vec<OrderBookMatchedOutput> {
+        let command = self.journal_command(|| JournalCommand::UncrossAuction {
+            market_id: *market_id,
+        });
+
+        let market = self.get_market(market_id);
+        let mut market_book = market.as_ref().map(|market| market.lock());
+        let matches = market_book
+            .as_mut()
+            .map(|market_book| market_book.uncross_auction())
+            .unwrap_or_default();
+
+        self.record(command, &matches);
+        matches
+    }
+
+    pub(crate) fn is_trading_halted(&self, market_id: &Uuid) -> bool {
+        self.get_market(market_id)
+            .is_some_and(|market| market.lock().is_trading_halted())
+    }
+
+    /// Markets whose price band is due to be rolled at `now`
+    pub(crate) fn get_due_price_bands(&self, now: NaiveDateTime) -> Vec<Uuid> {
+        self.get_markets()
+            .into_iter()
+            .filter(|(_, market)| market.lock().is_price_band_due(now))
+            .map(|(market_id, _)| market_id)
+            .collect()
+    }
+
+    /// Starts the new window of the market's price band, referenced to the current prices
+    pub(crate) fn roll_price_band(&self, market_id: &Uuid, now: NaiveDateTime) {
+        let command = self.journal_command(|| JournalCommand::RollPriceBand {
+            market_id: *market_id,
+            now,
+        });
+
+        if let Some(market) = self.get_market(market_id) {
+            market.lock().roll_price_band(now);
+        }
+
+        self.record(command, &[]);
+    }
+
+    /// Ends the trading halt of the market once it's cool-down is over
+    pub(crate) fn resume_trading(&self, market_id: &Uuid, now: NaiveDateTime) {
+        let command = self.journal_command(|| JournalCommand::ResumeTrading {
+            market_id: *market_id,
+            now,
+        });
+
+        if let Some(market) = self.get_market(market_id) {
+            market.lock().resume_trading(now);
+        }
+
+        self.record(command, &[]);
+    }
+
+    /// Applies the lifecycle status of the market on it's book, returns false if the market is not in the book
+    pub(crate) fn apply_market_status(&self, market_id: &Uuid, status: MarketStatus) -> bool {
+        match status {
+            MarketStatus::OPEN => self.unfreeze_market(market_id),
+            MarketStatus::PAUSED | MarketStatus::CLOSED | MarketStatus::RESOLVING => {
+                self.freeze_market(market_id)
+            }
+            MarketStatus::SETTLED | MarketStatus::VOIDED => self.remove_market(market_id),
+            MarketStatus::DRAFT => false, // draft market has no book yet
+        }
+    }
+
+    /// Freezes the market once it's not open for trading anymore (paused, closed or being resolved)
+    pub(crate) fn freeze_market(&self, market_id: &Uuid) -> bool {
+        self.set_market_frozen(market_id, true)
+    }
+
+    /// Unfreezes the market once it's reopened, returns false if the market is not in the book
+    pub(crate) fn unfreeze_market(&self, market_id: &Uuid) -> bool {
+        self.set_market_frozen(market_id, false)
+    }
+
+    /// Replaces all markets with the restored ones (from snapshot), current post only mode is applied on them
+    pub(crate) fn restore_markets(&self, markets: HashMap<Uuid, MarketBook>) {
+        let command = self.journal_command(|| JournalCommand::RestoreMarkets {
+            markets: markets.clone(),
+        });
+
+        let post_only_mode = self.post_only_mode;
+        let markets = markets
+            .into_iter()
+            .map(|(market_id, market_book)| {
+                let market_book = market_book.with_post_only_mode(post_only_mode);
+                (market_id, Arc::new(Mutex::new(market_book)))
+            })
+            .collect();
+
+        // replaced markets are locked, so their commands in progress are journaled before the restore
+        let mut registry = self.markets.write();
+        let replaced_markets = registry.values().cloned().collect::<Vec<_>>();
+        let _replaced_books = replaced_markets
+            .iter()
+            .map(|market| market.lock())
+            .collect::<Vec<_>>();
+        *registry = markets;
+
+        self.record(command, &[]);
+    }
+
+    /// Restores the persisted state of the market's market maker, market is created if it's not in the book yet
+    pub(crate) fn restore_market_maker(
+        &self,
+        market_id: Uuid,
+        rules: MarketRules,
+        market_maker: LmsrMarketMaker,
+    ) {
+        let command = self.journal_command(|| JournalCommand::RestoreMarketMaker {
+            market_id,
+            rules,
+            market_maker: market_maker.clone(),
+        });
+
+        let market = self.get_or_create_market(market_id, market_maker.liquidity_b, rules);
+        let mut market_book = market.lock();
+        market_book.restore_market_maker(market_maker);
+
+        self.record(command, &[]);
+    }
+
+    pub(crate) fn find_order(
+        &self,
+        market_id: &Uuid,
+        order_id: Uuid,
+    ) -> Option<(Decimal, OrderBookEntry)> {
+        self.get_market(market_id).and_then(|market| {
+            market
+                .lock()
+                .find_order(order_id)
+                .map(|(price, entry)| (price, entry.clone()))
+        })
+    }
+
+    /// Removes all orders of every market which are not live anymore, returns the number of removed orders
+    pub(crate) fn retain_orders(&self, live_order_ids: &HashSet<Uuid>) -> usize {
+        let command = self.journal_command(|| JournalCommand::RetainOrders {
+            order_ids: live_order_ids.iter().copied().collect(),
+        });
+
+        // registry is held so no market is created in between, command is recorded while all markets are locked
+        let keep = |order_id: &Uuid| live_order_ids.contains(order_id);
+        let registry = self.markets.read();
+        let mut market_books = registry
+            .values()
+            .map(|market| market.lock())
+            .collect::<Vec<_>>();
+        let removed = market_books
+            .iter_mut()
+            .map(|market_book| market_book.retain_orders(&keep))
+            .sum();
+
+        self.record(command, &[]);
+        removed
+    }
+
+    pub(crate) fn remove_market(&self, market_id: &Uuid) -> bool {
+        let command = self.journal_command(|| JournalCommand::RemoveMarket {
+            market_id: *market_id,
+        });
+
+        let mut registry = self.markets.write();
+        let market = registry.remove(market_id);
+        let _market_book = market.as_ref().map(|market| market.lock());
+
+        self.record(command, &[]);
+        market.is_some()
+    }
+
+    pub(crate) fn get_market(&self, market_id: &Uuid) -> Option<MarketShard> {
+        self.markets.read().get(market_id).cloned()
+    }
+
+    /// Returns the shards of all markets, registry lock is released before any market is locked
+    pub(crate) fn get_markets(&self) -> Vec<(Uuid, MarketShard)> {
+        self.markets
+            .read()
+            .iter()
+            .map(|(market_id, market)| (*market_id, market.clone()))
+            .collect()
+    }
+
+    fn set_market_frozen(&self, market_id: &Uuid, frozen: bool) -> bool {
+        let command = self.journal_command(|| JournalCommand::FreezeMarket {
+            market_id: *market_id,
+            frozen,
+        });
+
+        let Some(market) = self.get_market(market_id) else {
+            return false;
+        };
+        let mut market_book = market.lock();
+        market_book.set_frozen(frozen);
+
+        self.record(command, &[]);
+        true
+    }
+
+    // command is only built when journal is enabled
+    fn journal_command(&self, command: impl FnOnce() -> JournalCommand) -> Option<JournalCommand> {
+        self.journal.as_ref().map(|_| command())
+    }
+
+    // must be called while holding the market lock, so commands of the same market are journaled in the order they are applied
+    fn record(&self, command: Option<JournalCommand>, outputs: &[OrderBookMatchedOutput]) {
+        if let (Some(journal), Some(command)) = (self.journal.as_ref(), command) {
+            journal.lock().record(command, outputs);
         }
     }
 
-    pub(crate) fn remove_market(&mut self, market_id: &Uuid) -> bool {
-        self.markets.remove(market_id).is_some()
+    // sequence is taken and the update is queued under the market lock, so the updates are published in the order of the book changes
+    fn next_book_update(
+        &self,
+        market_id: &Uuid,
+        user_id: Option<Uuid>,
+        snapshot: bool,
+    ) -> Option<BookUpdate> {
+        let market = self.get_market(market_id)?;
+        let mut market_book = market.lock();
+        if snapshot {
+            market_book.request_book_snapshot();
+        }
+        let book_update = BookUpdate {
+            epoch: self.book_epoch,
+            ..market_book.get_book_update()
+        };
+
+        if let Some(book_publisher) = &self.book_publisher {
+            let market_book_update = MarketBookUpdate {
+                market_id: *market_id,
+                user_id,
+                book_update: book_update.clone(),
+            };
+            if book_publisher.send(market_book_update).is_err() {
+                log_error!(
+                    "Book publisher is closed, update {} of market {market_id} is dropped",
+                    book_update.sequence
+                );
+            }
+        }
+
+        Some(book_update)
     }
 
-    fn get_or_create_market(&mut self, market_id: Uuid, liquidity_b: Decimal) -> &mut MarketBook {
+    // liquidity and rules are only used when the market is created
+    fn get_or_create_market(
+        &self,
+        market_id: Uuid,
+        liquidity_b: Decimal,
+        rules: MarketRules,
+    ) -> MarketShard {
+        if let Some(market) = self.get_market(&market_id) {
+            return market;
+        }
+
+        let post_only_mode = self.post_only_mode;
         self.markets
+            .write()
             .entry(market_id)
-            .or_insert(MarketBook::new(liquidity_b))
+            .or_insert_with(|| {
+                let market_book = MarketBook::new(liquidity_b)
+                    .with_post_only_mode(post_only_mode)
+                    .with_rules(rules);
+                Arc::new(Mutex::new(market_book))
+            })
+            .clone()
     }
 }
 
 #[cfg(test)]
 mod test {
     use chrono::NaiveDateTime;
-    use db_service::schema::enums::{OrderSide, OrderStatus, OrderType, Outcome};
+    use db_service::schema::enums::{
+        OrderSide, OrderStatus, OrderType, Outcome, SelfTradePrevention, TimeInForce,
+    };
     use rust_decimal_macros::dec;
 
     use super::*;
+    use crate::order_book::market_book::{NO_OUTCOME, YES_OUTCOME};
 
     fn get_created_at() -> NaiveDateTime {
         chrono::Utc::now().naive_local()
@@ -134,23 +667,24 @@ mod test {
 
     #[test]
     fn test_global_market_book_creation() {
-        let mut global_market_book = GlobalMarketBook::new();
+        let global_market_book = GlobalMarketBook::new();
 
-        assert_eq!(global_market_book.markets.len(), 0);
+        assert_eq!(global_market_book.markets.read().len(), 0);
 
         let market_id = Uuid::new_v4();
         global_market_book
             .markets
-            .insert(market_id, MarketBook::new(dec!(100)));
+            .write()
+            .insert(market_id, Arc::new(Mutex::new(MarketBook::new(dec!(100)))));
 
-        assert_eq!(global_market_book.markets.len(), 1);
+        assert_eq!(global_market_book.markets.read().len(), 1);
 
-        let market_book = global_market_book.markets.get(&market_id);
+        let market_book = global_market_book.get_market(&market_id);
         assert!(market_book.is_some());
 
-        if let Some(book) = market_book {
-            assert_eq!(book.current_no_price, dec!(0.5));
-            assert_eq!(book.current_yes_price, dec!(0.5));
+        if let Some(book) = market_book.as_ref().map(|market| market.lock()) {
+            assert_eq!(book.current_price(NO_OUTCOME), dec!(0.5));
+            assert_eq!(book.current_price(YES_OUTCOME), dec!(0.5));
             assert_eq!(book.liquidity_b, dec!(100));
         } else {
             panic!("Market book should exist for the given market ID");
@@ -158,13 +692,64 @@ mod test {
     }
 
     #[test]
-    fn test_process_order() {
-        let mut global_market_book = GlobalMarketBook::new();
+    fn test_book_updates_are_tagged_with_boot_epoch() {
+        let (book_publisher, mut book_updates) = tokio::sync::mpsc::unbounded_channel();
+        let global_market_book = GlobalMarketBook::new().with_book_publisher(book_publisher);
         let market_id = Uuid::new_v4();
-        let liquidity_b = dec!(100);
+        let user_id = Uuid::new_v4();
         global_market_book
             .markets
-            .insert(market_id, MarketBook::new(liquidity_b));
+            .write()
+            .insert(market_id, Arc::new(Mutex::new(MarketBook::new(dec!(100)))));
+
+        let first = global_market_book
+            .publish_book_update(&market_id, Some(user_id))
+            .unwrap();
+        let second = global_market_book
+            .publish_book_update(&market_id, Some(user_id))
+            .unwrap();
+        assert_eq!(first.epoch, global_market_book.book_epoch);
+        assert!(first.is_snapshot && !second.is_snapshot);
+
+        // new subscriber gets the full books right away, sequence continues
+        let snapshot = global_market_book
+            .publish_book_snapshot(&market_id)
+            .unwrap();
+        assert_eq!(snapshot.epoch, global_market_book.book_epoch);
+        assert_eq!(snapshot.sequence, 3);
+        assert!(snapshot.is_snapshot);
+        assert!(
+            global_market_book
+                .publish_book_snapshot(&Uuid::new_v4())
+                .is_none()
+        );
+
+        // every update (snapshot included) is queued for the publisher in the order of it's sequence
+        let mut queued = Vec::new();
+        while let Ok(market_book_update) = book_updates.try_recv() {
+            assert_eq!(market_book_update.market_id, market_id);
+            queued.push(market_book_update);
+        }
+        assert_eq!(
+            queued
+                .iter()
+                .map(|update| update.book_update.clone())
+                .collect::<Vec<_>>(),
+            vec![first, second, snapshot]
+        );
+        assert_eq!(queued[0].user_id, Some(user_id));
+        assert_eq!(queued[2].user_id, None);
+    }
+
+    #[test]
+    fn test_process_order() {
+        let global_market_book = GlobalMarketBook::new();
+        let market_id = Uuid::new_v4();
+        let liquidity_b = dec!(100);
+        global_market_book.markets.write().insert(
+            market_id,
+            Arc::new(Mutex::new(MarketBook::new(liquidity_b))),
+        );
 
         let mut buy_order = Order {
             created_at: get_created_at(),
@@ -179,9 +764,18 @@ mod test {
             updated_at: get_created_at(),
             user_id: get_random_uuid(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
-        let results = global_market_book.process_order(&mut buy_order, liquidity_b);
+        let results =
+            global_market_book.process_order(&mut buy_order, liquidity_b, MarketRules::default());
 
         assert!(results.is_empty());
 
@@ -199,14 +793,77 @@ mod test {
             updated_at: get_created_at(),
             user_id: get_random_uuid(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
-        let results = global_market_book.process_order(&mut sell_order, liquidity_b);
+        let results =
+            global_market_book.process_order(&mut sell_order, liquidity_b, MarketRules::default());
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].order_id, sell_order.id);
         assert_eq!(results[0].opposite_order_id, buy_order.id);
         assert_eq!(results[0].matched_quantity, dec!(10)); // Matched quantity
         assert_eq!(results[0].price, dec!(0.5)); // Matched price
-        assert_eq!(global_market_book.markets.len(), 1);
+        assert_eq!(global_market_book.markets.read().len(), 1);
+    }
+
+    #[test]
+    fn test_markets_are_locked_independently() {
+        let global_market_book = GlobalMarketBook::new();
+        let liquidity_b = dec!(100);
+        let locked_market_id = Uuid::new_v4();
+        let market_id = Uuid::new_v4();
+
+        let order = |market_id: Uuid, side: OrderSide| Order {
+            created_at: get_created_at(),
+            filled_quantity: Decimal::ZERO,
+            id: get_random_uuid(),
+            market_id,
+            outcome: Outcome::YES,
+            price: dec!(0.5),
+            quantity: dec!(10),
+            side,
+            status: OrderStatus::OPEN,
+            updated_at: get_created_at(),
+            user_id: get_random_uuid(),
+            order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
+        };
+
+        global_market_book.add_order(
+            &order(locked_market_id, OrderSide::BUY),
+            liquidity_b,
+            MarketRules::default(),
+        );
+        let locked_market = global_market_book.get_market(&locked_market_id).unwrap();
+        let _guard = locked_market.lock();
+
+        // market being matched by another worker doesn't block the others (including creation of new markets)
+        global_market_book.add_order(
+            &order(market_id, OrderSide::BUY),
+            liquidity_b,
+            MarketRules::default(),
+        );
+        let matches = global_market_book.process_order(
+            &mut order(market_id, OrderSide::SELL),
+            liquidity_b,
+            MarketRules::default(),
+        );
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(global_market_book.get_markets().len(), 2);
     }
 }
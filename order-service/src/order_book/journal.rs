@@ -0,0 +1,478 @@
+/*
+ * Matching journal is an append-only log of every command applied on the global book along with the matches it produced.
+ *
+ * ## Journal format
+ *
+ * 1. Each line of the journal file is a json encoded `JournalEntry` with a strictly increasing sequence number.
+ * 2. Commands are recorded with the order state at the time they are applied (before matching), so the journal doesn't depend on db state.
+ * 3. Replay applies the commands on an empty book and compares the serialized matches of each entry with the recorded ones byte-for-byte.
+ */
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::Path,
+};
+
+use chrono::NaiveDateTime;
+use db_service::schema::{orders::Order, trailing_stops::TrailingStop};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use utility_helpers::log_error;
+use uuid::Uuid;
+
+use crate::utils::OrderServiceError;
+
+use super::{
+    global_book::GlobalMarketBook,
+    lmsr::LmsrMarketMaker,
+    market_book::MarketBook,
+    outcome_book::{MarketOrderLimits, MarketRules, OrderBookMatchedOutput, PostOnlyMode},
+};
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum JournalCommand {
+    Configure {
+        post_only_mode: PostOnlyMode,
+    },
+    ProcessOrder {
+        order: Order,
+        liquidity_b: Decimal,
+        #[serde(default)]
+        rules: MarketRules,
+    },
+    ProcessOrderWithoutLiquidity {
+        order: Order,
+    },
+    AddOrder {
+        order: Order,
+        liquidity_b: Decimal,
+        #[serde(default)]
+        rules: MarketRules,
+    },
+    CreateMarketOrder {
+        market_id: Uuid,
+        order: Order,
+        budget: Decimal,
+        #[serde(default)]
+        limits: MarketOrderLimits,
+    },
+    RemoveOrder {
+        market_id: Uuid,
+        order_id: Uuid,
+    },
+    UpdateOrder {
+        order: Order,
+        new_price: Decimal,
+        new_quantity: Decimal,
+    },
+    AddTriggerOrder {
+        order: Order,
+        liquidity_b: Decimal,
+        #[serde(default)]
+        rules: MarketRules,
+    },
+    AddTrailingStop {
+        order: Order,
+        trailing_stop: TrailingStop,
+        liquidity_b: Decimal,
+        #[serde(default)]
+        rules: MarketRules,
+    },
+    RemoveTriggerOrder {
+        market_id: Uuid,
+        order_id: Uuid,
+    },
+    ResizeTriggerOrder {
+        market_id: Uuid,
+        order_id: Uuid,
+        quantity: Decimal,
+    },
+    ExecuteTriggeredOrders {
+        market_id: Uuid,
+    },
+    UncrossAuction {
+        market_id: Uuid,
+    },
+    RollPriceBand {
+        market_id: Uuid,
+        now: NaiveDateTime,
+    },
+    ResumeTrading {
+        market_id: Uuid,
+        now: NaiveDateTime,
+    },
+    FreezeMarket {
+        market_id: Uuid,
+        frozen: bool,
+    },
+    RestoreMarkets {
+        markets: HashMap<Uuid, MarketBook>,
+    },
+    RestoreMarketMaker {
+        market_id: Uuid,
+        rules: MarketRules,
+        market_maker: LmsrMarketMaker,
+    },
+    RetainOrders {
+        order_ids: Vec<Uuid>,
+    },
+    RemoveMarket {
+        market_id: Uuid,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct JournalEntry {
+    pub sequence: u64,
+    pub command: JournalCommand,
+    pub outputs: Vec<OrderBookMatchedOutput>,
+}
+
+#[derive(Debug)]
+pub(crate) struct MatchingJournal {
+    file: File,
+    next_sequence: u64,
+}
+
+impl MatchingJournal {
+    /// Opens (or creates) the journal file, new entries are appended after the last recorded sequence
+    pub(crate) fn open(path: impl AsRef<Path>) -> Result<Self, OrderServiceError> {
+        let path = path.as_ref();
+        let mut last_sequence = 0;
+
+        if path.exists() {
+            let reader = BufReader::new(File::open(path)?);
+            for line in reader.lines() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let entry = serde_json::from_str::<JournalEntry>(&line)
+                    .map_err(|e| format!("Invalid journal entry after {last_sequence}: {e}"))?;
+                last_sequence = entry.sequence;
+            }
+        }
+
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+
+        Ok(Self {
+            file,
+            next_sequence: last_sequence + 1,
+        })
+    }
+
+    /// Appends the command and it's outputs to the journal, failures are logged instead of interrupting the matching
+    pub(crate) fn record(&mut self, command: JournalCommand, outputs: &[OrderBookMatchedOutput]) {
+        let entry = JournalEntryRef {
+            sequence: self.next_sequence,
+            command: &command,
+            outputs,
+        };
+
+        let result = serde_json::to_string(&entry)
+            .map_err(|e| e.to_string())
+            .and_then(|mut line| {
+                line.push('\n');
+                self.file
+                    .write_all(line.as_bytes())
+                    .map_err(|e| e.to_string())
+            });
+
+        match result {
+            Ok(()) => self.next_sequence += 1,
+            Err(e) => log_error!("Failed to write journal entry {} - {e}", entry.sequence),
+        }
+    }
+}
+
+// borrowed version of `JournalEntry`, to avoid cloning the outputs while recording
+#[derive(Serialize)]
+struct JournalEntryRef<'a> {
+    sequence: u64,
+    command: &'a JournalCommand,
+    outputs: &'a [OrderBookMatchedOutput],
+}
+
+#[derive(Debug)]
+pub(crate) struct ReplayReport {
+    pub entries: u64,
+    pub matches: u64,
+}
+
+/// Rebuilds the global book from the journal, fails on the first entry whose outputs are not identical to the recorded ones
+pub(crate) fn replay_journal(path: impl AsRef<Path>) -> Result<ReplayReport, OrderServiceError> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut global_book = GlobalMarketBook::new();
+    let mut report = ReplayReport {
+        entries: 0,
+        matches: 0,
+    };
+
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let entry = serde_json::from_str::<JournalEntry>(&line)
+            .map_err(|e| format!("Invalid journal entry after {}: {e}", report.entries))?;
+
+        let outputs = apply_command(&mut global_book, entry.command);
+
+        let recorded = serde_json::to_vec(&entry.outputs)?;
+        let replayed = serde_json::to_vec(&outputs)?;
+        if recorded != replayed {
+            return Err(format!(
+                "Journal entry {} diverged on replay, recorded outputs: {}, replayed outputs: {}",
+                entry.sequence,
+                String::from_utf8_lossy(&recorded),
+                String::from_utf8_lossy(&replayed)
+            )
+            .into());
+        }
+
+        report.entries += 1;
+        report.matches += outputs.len() as u64;
+    }
+
+    Ok(report)
+}
+
+fn apply_command(
+    global_book: &mut GlobalMarketBook,
+    command: JournalCommand,
+) -> Vec<OrderBookMatchedOutput> {
+    match command {
+        JournalCommand::Configure { post_only_mode } => {
+            global_book.set_post_only_mode(post_only_mode);
+            Vec::new()
+        }
+        JournalCommand::ProcessOrder {
+            mut order,
+            liquidity_b,
+            rules,
+        } => global_book.process_order(&mut order, liquidity_b, rules),
+        JournalCommand::ProcessOrderWithoutLiquidity { mut order } => {
+            global_book.process_order_without_liquidity(&mut order)
+        }
+        JournalCommand::AddOrder {
+            order,
+            liquidity_b,
+            rules,
+        } => {
+            global_book.add_order(&order, liquidity_b, rules);
+            Vec::new()
+        }
+        JournalCommand::CreateMarketOrder {
+            market_id,
+            mut order,
+            budget,
+            limits,
+        } => global_book.create_market_order(&market_id, &mut order, budget, limits),
+        JournalCommand::RemoveOrder {
+            market_id,
+            order_id,
+        } => {
+            global_book.remove_order(market_id, order_id);
+            Vec::new()
+        }
+        JournalCommand::UpdateOrder {
+            mut order,
+            new_price,
+            new_quantity,
+        } => global_book
+            .update_order(&mut order, new_price, new_quantity)
+            .unwrap_or_default(),
+        JournalCommand::AddTriggerOrder {
+            order,
+            liquidity_b,
+            rules,
+        } => {
+            global_book.add_trigger_order(order, liquidity_b, rules);
+            Vec::new()
+        }
+        JournalCommand::AddTrailingStop {
+            order,
+            trailing_stop,
+            liquidity_b,
+            rules,
+        } => {
+            global_book.add_trailing_stop(order, trailing_stop, liquidity_b, rules);
+            Vec::new()
+        }
+        JournalCommand::RemoveTriggerOrder {
+            market_id,
+            order_id,
+        } => {
+            global_book.remove_trigger_order(&market_id, order_id);
+            Vec::new()
+        }
+        JournalCommand::ResizeTriggerOrder {
+            market_id,
+            order_id,
+            quantity,
+        } => {
+            global_book.resize_trigger_order(&market_id, order_id, quantity);
+            Vec::new()
+        }
+        JournalCommand::ExecuteTriggeredOrders { market_id } => global_book
+            .execute_triggered_orders(&market_id)
+            .into_iter()
+            .flat_map(|(_, matches)| matches)
+            .collect(),
+        JournalCommand::UncrossAuction { market_id } => global_book.uncross_auction(&market_id),
+        JournalCommand::RollPriceBand { market_id, now } => {
+            global_book.roll_price_band(&market_id, now);
+            Vec::new()
+        }
+        JournalCommand::ResumeTrading { market_id, now } => {
+            global_book.resume_trading(&market_id, now);
+            Vec::new()
+        }
+        JournalCommand::FreezeMarket { market_id, frozen } => {
+            if frozen {
+                global_book.freeze_market(&market_id);
+            } else {
+                global_book.unfreeze_market(&market_id);
+            }
+            Vec::new()
+        }
+        JournalCommand::RestoreMarkets { markets } => {
+            global_book.restore_markets(markets);
+            Vec::new()
+        }
+        JournalCommand::RestoreMarketMaker {
+            market_id,
+            rules,
+            market_maker,
+        } => {
+            global_book.restore_market_maker(market_id, rules, market_maker);
+            Vec::new()
+        }
+        JournalCommand::RetainOrders { order_ids } => {
+            global_book.retain_orders(&order_ids.into_iter().collect::<HashSet<Uuid>>());
+            Vec::new()
+        }
+        JournalCommand::RemoveMarket { market_id } => {
+            global_book.remove_market(&market_id);
+            Vec::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use db_service::schema::enums::{
+        OrderSide, OrderStatus, OrderType, Outcome, SelfTradePrevention, TimeInForce,
+    };
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    fn get_journal_path() -> PathBuf {
+        std::env::temp_dir().join(format!("matching_journal_{}.jsonl", Uuid::new_v4()))
+    }
+
+    fn get_order(market_id: Uuid, side: OrderSide, price: Decimal, quantity: Decimal) -> Order {
+        Order {
+            created_at: chrono::Utc::now().naive_local(),
+            filled_quantity: Decimal::ZERO,
+            id: Uuid::new_v4(),
+            market_id,
+            outcome: Outcome::YES,
+            price,
+            quantity,
+            side,
+            status: OrderStatus::OPEN,
+            updated_at: chrono::Utc::now().naive_local(),
+            user_id: Uuid::new_v4(),
+            order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            display_quantity: None,
+            outcome_index: 0,
+        }
+    }
+
+    fn record_matching_session(path: &PathBuf) {
+        let market_id = Uuid::new_v4();
+        let global_book = GlobalMarketBook::new()
+            .with_post_only_mode(PostOnlyMode::Reprice)
+            .with_journal(MatchingJournal::open(path).unwrap());
+
+        let mut ask = get_order(market_id, OrderSide::SELL, dec!(0.6), dec!(10));
+        let mut bid = get_order(market_id, OrderSide::BUY, dec!(0.4), dec!(5));
+        global_book.process_order(&mut ask, dec!(100), MarketRules::default());
+        global_book.process_order(&mut bid, dec!(100), MarketRules::default());
+        global_book.remove_order(market_id, bid.id);
+
+        let mut buy_order = get_order(market_id, OrderSide::BUY, dec!(0.6), dec!(4));
+        let matches = global_book.process_order(&mut buy_order, dec!(100), MarketRules::default());
+        assert_eq!(matches.len(), 1);
+
+        let mut market_order = get_order(market_id, OrderSide::BUY, dec!(0), dec!(3));
+        market_order.order_type = OrderType::MARKET;
+        let matches = global_book.create_market_order(
+            &market_id,
+            &mut market_order,
+            dec!(3),
+            MarketOrderLimits::default(),
+        );
+        assert_eq!(matches.len(), 1);
+    }
+
+    #[test]
+    fn test_replay_journal() {
+        let path = get_journal_path();
+        record_matching_session(&path);
+
+        let report = replay_journal(&path).unwrap();
+        // configure, 3 orders, cancel and market order
+        assert_eq!(report.entries, 6);
+        assert_eq!(report.matches, 2);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_journal_sequence_continues_after_reopen() {
+        let path = get_journal_path();
+        record_matching_session(&path);
+        record_matching_session(&path);
+
+        let journal = MatchingJournal::open(&path).unwrap();
+        assert_eq!(journal.next_sequence, 13);
+
+        let report = replay_journal(&path).unwrap();
+        assert_eq!(report.entries, 12);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_replay_journal_detects_diverged_outputs() {
+        let path = get_journal_path();
+        record_matching_session(&path);
+
+        // tampering the recorded fill of the limit order
+        let journal = std::fs::read_to_string(&path).unwrap();
+        let tampered =
+            journal.replacen(r#""matched_quantity":"4""#, r#""matched_quantity":"5""#, 1);
+        assert_ne!(journal, tampered);
+        std::fs::write(&path, tampered).unwrap();
+
+        let err = replay_journal(&path).unwrap_err();
+        assert!(err.to_string().contains("Journal entry 5 diverged"));
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,300 @@
+/*
+ * LMSR (Logarithmic Market Scoring Rule) market maker, it's the liquidity source of last resort for the market.
+ *
+ * ## Pricing
+ *
+ * 1. Market maker sells (and buys back) shares of both outcomes, `q_yes` / `q_no` are the outstanding shares sold by it.
+ * 2. Cost of the outstanding shares is C(q) = b * ln(e^(q_yes / b) + e^(q_no / b)), a trade costs the change of C(q).
+ * 3. Price of an outcome is the derivative of C(q), e^(q_yes / b) / (e^(q_yes / b) + e^(q_no / b)), so prices of both outcomes sum up to 1.
+ *
+ * ## Subsidy
+ *
+ * 1. Market maker starts with q_yes = q_no = 0, loss of the market maker after the resolution is bounded by b * ln(2).
+ * 2. `cash` is the amount collected from the traders (buys - sells), P&L of the market maker is `cash - q_winner` once the market is resolved.
+ *
+ * Exponents are computed on non positive values only (log-sum-exp), so large inventories never overflow the decimal.
+ */
+
+use db_service::schema::enums::{OrderSide, Outcome};
+use rust_decimal::{Decimal, MathematicalOps, prelude::ToPrimitive};
+use serde::{Deserialize, Serialize};
+
+// e^-60 is below the precision of the prices
+const MIN_EXPONENT: Decimal = Decimal::from_parts(60, 0, 0, true, 0);
+// default tolerance of `exp` (2e-7) is too coarse for the cost function
+const EXP_TOLERANCE: Decimal = Decimal::from_parts(1, 0, 0, false, 26);
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct LmsrMarketMaker {
+    pub(crate) liquidity_b: Decimal,
+    pub(crate) q_yes: Decimal,
+    pub(crate) q_no: Decimal,
+    pub(crate) cash: Decimal,
+}
+
+impl LmsrMarketMaker {
+    pub(crate) fn new(liquidity_b: Decimal) -> Self {
+        Self {
+            liquidity_b,
+            ..Default::default()
+        }
+    }
+
+    /// Instantaneous price of the outcome
+    pub(crate) fn price(&self, outcome: Outcome) -> Decimal {
+        let (q_outcome, q_other) = self.get_quantities(outcome);
+        let difference = q_outcome - q_other;
+
+        let weight = exp_non_positive(-difference.abs() / self.liquidity_b);
+        if difference >= Decimal::ZERO {
+            Decimal::ONE / (Decimal::ONE + weight)
+        } else {
+            weight / (Decimal::ONE + weight)
+        }
+    }
+
+    /// Amount paid by the trader for buying (or received for selling) `quantity` shares of the outcome
+    pub(crate) fn quote(&self, outcome: Outcome, side: OrderSide, quantity: Decimal) -> Decimal {
+        let (q_outcome, q_other) = self.get_quantities(outcome);
+        let current_cost = self.cost(q_outcome, q_other);
+
+        match side {
+            OrderSide::BUY => self.cost(q_outcome + quantity, q_other) - current_cost,
+            OrderSide::SELL => current_cost - self.cost(q_outcome - quantity, q_other),
+        }
+    }
+
+    /// Quantity the market maker trades before it's price of the outcome reaches `price`, `None` if it's never reached
+    pub(crate) fn quantity_to_price(
+        &self,
+        outcome: Outcome,
+        side: OrderSide,
+        price: Decimal,
+    ) -> Option<Decimal> {
+        let (q_outcome, q_other) = self.get_quantities(outcome);
+
+        if price <= Decimal::ZERO || price >= Decimal::ONE {
+            // price only approaches 0 and 1
+            return match (side, price >= Decimal::ONE) {
+                (OrderSide::BUY, true) | (OrderSide::SELL, false) => None,
+                _ => Some(Decimal::ZERO),
+            };
+        }
+
+        // q_outcome where the price of the outcome is exactly `price`
+        let target = q_other + self.liquidity_b * (price / (Decimal::ONE - price)).ln();
+        let quantity = match side {
+            OrderSide::BUY => target - q_outcome,
+            OrderSide::SELL => q_outcome - target,
+        };
+
+        Some(quantity.max(Decimal::ZERO))
+    }
+
+    /// Quantity of the outcome `amount` buys (or has to be sold to receive `amount`), `None` if selling can't reach the amount
+    pub(crate) fn quantity_for_amount(
+        &self,
+        outcome: Outcome,
+        side: OrderSide,
+        amount: Decimal,
+    ) -> Option<Decimal> {
+        let (q_outcome, q_other) = self.get_quantities(outcome);
+        let current_cost = self.cost(q_outcome, q_other);
+
+        // C(q_outcome', q_other) = cost, solved as q_outcome' = q_other + s + b * ln(1 - e^(-s / b)) where s = cost - q_other
+        let spread = match side {
+            OrderSide::BUY => current_cost + amount - q_other,
+            OrderSide::SELL => current_cost - amount - q_other,
+        };
+        if spread <= Decimal::ZERO {
+            return None;
+        }
+
+        let remainder = Decimal::ONE - exp_non_positive(-spread / self.liquidity_b);
+        if remainder <= Decimal::ZERO {
+            return Some(Decimal::ZERO);
+        }
+        let target = q_other + spread + self.liquidity_b * remainder.ln();
+
+        let quantity = match side {
+            OrderSide::BUY => target - q_outcome,
+            OrderSide::SELL => q_outcome - target,
+        };
+        Some(quantity.max(Decimal::ZERO))
+    }
+
+    /// Trades `quantity` shares of the outcome with the market maker, returns the average execution price
+    pub(crate) fn execute(
+        &mut self,
+        outcome: Outcome,
+        side: OrderSide,
+        quantity: Decimal,
+    ) -> Decimal {
+        let amount = self.quote(outcome, side, quantity);
+        let signed_quantity = match side {
+            OrderSide::BUY => {
+                self.cash += amount;
+                quantity
+            }
+            OrderSide::SELL => {
+                self.cash -= amount;
+                -quantity
+            }
+        };
+
+        match outcome {
+            Outcome::YES => self.q_yes += signed_quantity,
+            _ => self.q_no += signed_quantity,
+        }
+
+        amount / quantity
+    }
+
+    ///// Helpers //////
+
+    fn get_quantities(&self, outcome: Outcome) -> (Decimal, Decimal) {
+        match outcome {
+            Outcome::YES => (self.q_yes, self.q_no),
+            _ => (self.q_no, self.q_yes),
+        }
+    }
+
+    // C(q) = max(q) + b * ln(1 + e^(-|q_yes - q_no| / b))
+    fn cost(&self, q_outcome: Decimal, q_other: Decimal) -> Decimal {
+        let weight = exp_non_positive(-(q_outcome - q_other).abs() / self.liquidity_b);
+        q_outcome.max(q_other) + self.liquidity_b * (Decimal::ONE + weight).ln()
+    }
+}
+
+// series of `exp` is only accurate around 0, so the exponent is split into steps of at most 1
+fn exp_non_positive(exponent: Decimal) -> Decimal {
+    if exponent < MIN_EXPONENT {
+        return Decimal::ZERO;
+    }
+    let steps = exponent.abs().ceil().max(Decimal::ONE);
+    (exponent / steps)
+        .exp_with_tolerance(EXP_TOLERANCE)
+        .powu(steps.to_u64().unwrap_or(1))
+}
+
+#[cfg(test)]
+mod test {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    fn assert_close(actual: Decimal, expected: Decimal) {
+        assert!(
+            (actual - expected).abs() < dec!(0.000000001),
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn test_initial_prices() {
+        let market_maker = LmsrMarketMaker::new(dec!(100));
+
+        assert_eq!(market_maker.price(Outcome::YES), dec!(0.5));
+        assert_eq!(market_maker.price(Outcome::NO), dec!(0.5));
+    }
+
+    #[test]
+    fn test_buy_moves_price() {
+        let mut market_maker = LmsrMarketMaker::new(dec!(100));
+
+        // b * ln(1 + e) - b * ln(2) = 62.0114506958
+        let price = market_maker.execute(Outcome::YES, OrderSide::BUY, dec!(100));
+        assert_close(price * dec!(100), dec!(62.0114506958));
+        assert_close(market_maker.cash, dec!(62.0114506958));
+
+        // e / (1 + e)
+        assert_close(market_maker.price(Outcome::YES), dec!(0.7310585786));
+        assert_close(
+            market_maker.price(Outcome::YES) + market_maker.price(Outcome::NO),
+            Decimal::ONE,
+        );
+    }
+
+    #[test]
+    fn test_round_trip_is_free() {
+        let mut market_maker = LmsrMarketMaker::new(dec!(50));
+
+        market_maker.execute(Outcome::NO, OrderSide::BUY, dec!(30));
+        market_maker.execute(Outcome::NO, OrderSide::SELL, dec!(30));
+
+        assert_close(market_maker.cash, Decimal::ZERO);
+        assert_close(market_maker.price(Outcome::NO), dec!(0.5));
+    }
+
+    #[test]
+    fn test_quantity_to_price() {
+        let market_maker = LmsrMarketMaker::new(dec!(100));
+
+        // b * ln(0.75 / 0.25)
+        let quantity = market_maker
+            .quantity_to_price(Outcome::YES, OrderSide::BUY, dec!(0.75))
+            .unwrap();
+        assert_close(quantity, dec!(109.8612288668));
+
+        let mut moved = market_maker.clone();
+        moved.execute(Outcome::YES, OrderSide::BUY, quantity);
+        assert_close(moved.price(Outcome::YES), dec!(0.75));
+
+        // price is already above the limit of the buyer
+        assert_eq!(
+            moved.quantity_to_price(Outcome::YES, OrderSide::BUY, dec!(0.6)),
+            Some(Decimal::ZERO)
+        );
+        assert_eq!(
+            market_maker.quantity_to_price(Outcome::YES, OrderSide::BUY, Decimal::ONE),
+            None
+        );
+    }
+
+    #[test]
+    fn test_quantity_for_amount() {
+        let mut market_maker = LmsrMarketMaker::new(dec!(100));
+        market_maker.execute(Outcome::YES, OrderSide::BUY, dec!(40));
+
+        let quantity = market_maker
+            .quantity_for_amount(Outcome::NO, OrderSide::BUY, dec!(25))
+            .unwrap();
+        assert_close(
+            market_maker.quote(Outcome::NO, OrderSide::BUY, quantity),
+            dec!(25),
+        );
+
+        let quantity = market_maker
+            .quantity_for_amount(Outcome::YES, OrderSide::SELL, dec!(10))
+            .unwrap();
+        assert_close(
+            market_maker.quote(Outcome::YES, OrderSide::SELL, quantity),
+            dec!(10),
+        );
+
+        // selling can't get more than the cost of the outstanding shares
+        assert_eq!(
+            market_maker.quantity_for_amount(Outcome::YES, OrderSide::SELL, dec!(1000)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_loss_is_bounded() {
+        let mut market_maker = LmsrMarketMaker::new(dec!(100));
+
+        // traders keep buying the winning outcome, far beyond the liquidity
+        for _ in 0..10 {
+            market_maker.execute(Outcome::YES, OrderSide::BUY, dec!(500));
+        }
+        market_maker.execute(Outcome::NO, OrderSide::BUY, dec!(20));
+        market_maker.execute(Outcome::NO, OrderSide::SELL, dec!(50));
+
+        // P&L is `cash - q_winner`, loss never exceeds b * ln(2)
+        let max_loss = market_maker.liquidity_b * Decimal::TWO.ln();
+        assert!(market_maker.price(Outcome::YES) > dec!(0.99));
+        assert!(market_maker.cash - market_maker.q_yes >= -max_loss);
+        assert!(market_maker.cash - market_maker.q_yes < Decimal::ZERO);
+        assert!(market_maker.cash - market_maker.q_no > Decimal::ZERO);
+    }
+}
@@ -1,62 +1,204 @@
+use std::collections::BTreeMap;
+
+use chrono::NaiveDateTime;
 use db_service::schema::{
-    enums::{OrderSide, OrderStatus, Outcome},
+    enums::{OrderSide, OrderStatus, OrderType, Outcome, TimeInForce},
     orders::Order,
+    trailing_stops::TrailingStop,
 };
 use rust_decimal::Decimal;
-use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use utility_helpers::log_info;
 use uuid::Uuid;
 
-use crate::order_book::outcome_book::OrderBookMatchedOutput;
+use crate::order_book::outcome_book::{Amendment, MatchType, OrderBookMatchedOutput};
+
+use super::{
+    book_feed::{BookFeed, BookUpdate},
+    call_auction::{AuctionIndication, AuctionOrder, get_clearing_price, pair_orders},
+    circuit_breaker::{
+        PRICE_BAND_BREACH_REASON, PRICE_OUTSIDE_BAND_REASON, PriceBand, TRADING_HALTED_REASON,
+    },
+    lmsr::LmsrMarketMaker,
+    outcome_book::{
+        MarketOrderLimits, MarketRules, OrderBookEntry, OutcomeBook, PRICE_LIMIT_REASON,
+        PostOnlyMode,
+    },
+    price_model::{MarketView, get_price_model},
+    trigger_book::TriggerBook,
+};
+
+/// Outcomes of the binary market, binary market is the categorical market with 2 outcomes
+pub(crate) const YES_OUTCOME: usize = 0;
+pub(crate) const NO_OUTCOME: usize = 1;
 
-use super::outcome_book::OutcomeBook;
+pub(crate) const AUCTION_REJECT_REASON: &str =
+    "Immediate orders are not accepted during the call auction";
+pub(crate) const MARKET_FROZEN_REASON: &str = "Market is not open for trading";
 
-#[derive(Debug)]
-pub(crate) struct MarketBook {
-    yes_order_book: OutcomeBook,
-    no_order_book: OutcomeBook,
+/// Size of the quoted market order
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum QuoteSize {
+    Quantity(Decimal),
+    Budget(Decimal),
+}
+
+/// Simulated execution of the market order against the current book, fills are aggregated per price level
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct MarketQuote {
+    pub(crate) fills: Vec<(Decimal, Decimal)>, // price and quantity, in the order of execution
+    pub(crate) filled_quantity: Decimal,
+    pub(crate) total_cost: Decimal,
+    pub(crate) midpoint: Decimal, // midpoint of the quoted outcome after the execution
+    pub(crate) outcome_prices: Vec<Decimal>, // prices of the outcomes after the execution
+    pub(crate) reason: Option<String>, // why the order would not be (fully) filled
+}
+
+impl MarketQuote {
+    pub(crate) fn average_price(&self) -> Option<Decimal> {
+        (self.filled_quantity > Decimal::ZERO).then(|| self.total_cost / self.filled_quantity)
+    }
+}
+
+/// Order book and price of one outcome of the market
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub(crate) struct OutcomeMarket {
+    pub(crate) order_book: OutcomeBook,
+    pub(crate) executed_buy_volume: Decimal,
+    pub(crate) current_price: Decimal,
+    pub(crate) last_trade_price: Option<Decimal>,
+}
 
-    pub(crate) executed_yes_buy_volume: Decimal,
-    pub(crate) executed_no_buy_volume: Decimal,
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct MarketBook {
+    /// Outcomes of the market by their index, prices of all outcomes sum up to 1
+    outcomes: BTreeMap<usize, OutcomeMarket>,
 
-    pub(crate) current_yes_price: Decimal,
-    pub(crate) current_no_price: Decimal,
+    /// Stop loss and take profit orders waiting for their trigger price
+    trigger_book: TriggerBook,
 
     /// Liquidity parameter of the market
     ///
     /// The higher `b` = more liquidity, slower price changes
     pub(crate) liquidity_b: Decimal,
+
+    /// LMSR market maker of the market (if enabled by the market rules), binary markets only
+    #[serde(default)]
+    pub(crate) market_maker: Option<LmsrMarketMaker>,
+
+    /// Scheduled open of the opening call auction, orders are collected without matching till it's uncrossed
+    #[serde(default)]
+    auction_opens_at: Option<NaiveDateTime>,
+
+    /// Reference prices of the circuit breaker (if enabled by the market rules)
+    #[serde(default)]
+    price_band: Option<PriceBand>,
+
+    /// Market order breached the price band, new orders are rejected till the trading is resumed
+    #[serde(default)]
+    trading_halted: bool,
+
+    /// Market is not open (paused, closed or being resolved), orders are kept but nothing is matched
+    #[serde(default)]
+    frozen: bool,
+
+    /// Books published to the subscribers, restored book starts with a snapshot
+    #[serde(skip)]
+    book_feed: BookFeed,
 }
 
 impl MarketBook {
     pub(super) fn new(liquidity_b: Decimal) -> Self {
         Self {
-            yes_order_book: OutcomeBook::default(),
-            no_order_book: OutcomeBook::default(),
+            outcomes: get_outcomes(2),
+            trigger_book: TriggerBook::default(),
+            liquidity_b,
+            market_maker: None,
+            auction_opens_at: None,
+            price_band: None,
+            trading_halted: false,
+            frozen: false,
+            book_feed: BookFeed::default(),
+        }
+    }
 
-            executed_yes_buy_volume: Decimal::ZERO,
-            executed_no_buy_volume: Decimal::ZERO,
+    pub(super) fn with_post_only_mode(mut self, post_only_mode: PostOnlyMode) -> Self {
+        for outcome in self.outcomes.values_mut() {
+            outcome.order_book.post_only_mode = post_only_mode;
+        }
+        self
+    }
 
-            current_no_price: Decimal::new(5, 1),  // initial 0.5
-            current_yes_price: Decimal::new(5, 1), // initial 0.5
-            liquidity_b,
+    pub(super) fn with_rules(mut self, rules: MarketRules) -> Self {
+        if rules.outcome_count != self.outcomes.len() {
+            let post_only_mode = self.get_rules_book().map(|book| book.post_only_mode);
+            self.outcomes = get_outcomes(rules.outcome_count);
+            if let Some(post_only_mode) = post_only_mode {
+                self = self.with_post_only_mode(post_only_mode);
+            }
+        }
+        for outcome in self.outcomes.values_mut() {
+            outcome.order_book.rules = rules;
+        }
+        if rules.market_maker && self.liquidity_b > Decimal::ZERO && self.is_binary() {
+            self.market_maker = Some(LmsrMarketMaker::new(self.liquidity_b));
         }
+        self.auction_opens_at = rules.auction_opens_at;
+        self.update_market_price();
+        self.price_band = rules
+            .price_band
+            .map(|_| PriceBand::new(self.get_reference_prices(), None));
+        self
+    }
+
+    /// Replaces the state of the market maker (outstanding shares and cash) with the persisted one
+    pub(super) fn restore_market_maker(&mut self, market_maker: LmsrMarketMaker) {
+        self.market_maker = Some(market_maker);
+        self.update_market_price();
     }
 
     pub(super) fn add_order(&mut self, order: &Order) {
-        match order.outcome {
-            Outcome::YES => self.yes_order_book.add_order(order),
-            Outcome::NO => self.no_order_book.add_order(order),
-            _ => {}
+        if let Some(outcome) =
+            get_outcome_index(order).and_then(|index| self.outcomes.get_mut(&index))
+        {
+            outcome.order_book.add_order(order);
         }
         self.update_market_price();
     }
 
     pub(super) fn process_order(&mut self, order: &mut Order) -> Vec<OrderBookMatchedOutput> {
-        let matches = match order.outcome {
-            Outcome::YES => self.yes_order_book.match_order(order),
-            Outcome::NO => self.no_order_book.match_order(order),
-            _ => Vec::new(),
+        let Some(index) = get_outcome_index(order) else {
+            return Vec::new();
         };
+        if self.frozen {
+            reject_order(order, MARKET_FROZEN_REASON);
+            return Vec::new();
+        }
+        if self.trading_halted {
+            reject_order(order, TRADING_HALTED_REASON);
+            return Vec::new();
+        }
+        if self.is_in_auction() {
+            self.collect_auction_order(index, order);
+            return Vec::new();
+        }
+        if !self.is_within_price_band(index, order.price) {
+            reject_order(order, PRICE_OUTSIDE_BAND_REASON);
+            return Vec::new();
+        }
+        // opposite outcome's book of the binary market is used for minting / merging share pairs
+        let complementary_index = self.get_complementary_index(index);
+        let matches = match get_outcome_pair_mut(&mut self.outcomes, index, complementary_index) {
+            Some((outcome, complementary_outcome)) => {
+                outcome.order_book.match_order_with_complementary(
+                    order,
+                    complementary_outcome.map(|outcome| &mut outcome.order_book),
+                    self.market_maker.as_mut(),
+                )
+            }
+            None => Vec::new(),
+        };
+        self.update_last_trade_price(index, &matches);
 
         if order.status == OrderStatus::OPEN || order.status == OrderStatus::PendingUpdate {
             self.add_order(order);
@@ -69,176 +211,881 @@ impl MarketBook {
         &mut self,
         order: &mut Order,
         budget: Decimal,
+        limits: MarketOrderLimits,
     ) -> Vec<OrderBookMatchedOutput> {
-        let matches = match order.outcome {
-            Outcome::YES => self.yes_order_book.create_market_order(order, budget),
-            Outcome::NO => self.no_order_book.create_market_order(order, budget),
-            _ => Vec::new(),
+        let Some(index) = get_outcome_index(order) else {
+            return Vec::new();
+        };
+        let (worst_price, is_band_binding) =
+            match self.get_market_order_price_limit(order, index, limits) {
+                Ok(price_limit) => price_limit,
+                Err(reason) => {
+                    reject_order(order, reason);
+                    return Vec::new();
+                }
+            };
+        let matches = match self.outcomes.get_mut(&index) {
+            Some(outcome) => execute_market_order(
+                outcome,
+                self.market_maker.as_mut(),
+                order,
+                budget,
+                worst_price,
+                limits.allow_partial,
+            ),
+            None => Vec::new(),
         };
 
-        if order.side == OrderSide::BUY && order.filled_quantity > Decimal::ZERO {
-            let executed_value = matches
-                .iter()
-                .map(|m| m.price * m.matched_quantity)
-                .sum::<Decimal>();
-
-            match order.outcome {
-                Outcome::YES => self.executed_yes_buy_volume += executed_value,
-                Outcome::NO => self.executed_no_buy_volume += executed_value,
-                _ => {}
-            }
+        if is_band_binding && order.status_reason.as_deref() == Some(PRICE_LIMIT_REASON) {
+            log_info!(
+                "Market order {} breached the price band, halting trading of market {}",
+                order.id,
+                order.market_id
+            );
+            order.status_reason = Some(PRICE_BAND_BREACH_REASON.to_string());
+            self.trading_halted = true;
         }
 
         self.update_market_price();
         matches
     }
 
+    /// Quotes the market order without changing the book (partial fills are quoted).
+    ///
+    /// Execution only changes the book of the quoted outcome and the market maker, so the order is executed on their copies and the rest of the market is borrowed
+    pub(super) fn quote_market_order(&self, order: &Order, size: QuoteSize) -> Option<MarketQuote> {
+        let index = get_outcome_index(order)?;
+        let mut order = order.clone();
+        order.order_type = OrderType::MARKET;
+        order.price = Decimal::ZERO;
+        order.filled_quantity = Decimal::ZERO;
+        let budget = match size {
+            QuoteSize::Budget(budget) => budget,
+            QuoteSize::Quantity(quantity) => {
+                order.quantity = quantity;
+                self.get_order_book(index)?
+                    .get_budget_for_quantity(&order, self.market_maker.as_ref())
+            }
+        };
+
+        // partial fills are quoted
+        let limits = MarketOrderLimits::default().with_allow_partial(true);
+        let mut outcome = self.outcomes.get(&index)?.clone();
+        let mut market_maker = self.market_maker.clone();
+        let matches = match self.get_market_order_price_limit(&order, index, limits) {
+            Ok((worst_price, is_band_binding)) => {
+                let matches = execute_market_order(
+                    &mut outcome,
+                    market_maker.as_mut(),
+                    &mut order,
+                    budget,
+                    worst_price,
+                    limits.allow_partial,
+                );
+                if is_band_binding && order.status_reason.as_deref() == Some(PRICE_LIMIT_REASON) {
+                    order.status_reason = Some(PRICE_BAND_BREACH_REASON.to_string());
+                }
+                matches
+            }
+            Err(reason) => {
+                reject_order(&mut order, reason);
+                Vec::new()
+            }
+        };
+
+        let mut quote = MarketQuote::default();
+        for matched in matches.iter() {
+            match quote.fills.last_mut() {
+                Some((price, quantity)) if *price == matched.price => {
+                    *quantity += matched.matched_quantity
+                }
+                _ => quote.fills.push((matched.price, matched.matched_quantity)),
+            }
+            quote.filled_quantity += matched.matched_quantity;
+            quote.total_cost += matched.price * matched.matched_quantity;
+        }
+        let quoted_market = QuotedMarket {
+            market_book: self,
+            outcome_index: index,
+            outcome: &outcome,
+            market_maker: market_maker.as_ref(),
+        };
+        quote.outcome_prices = self
+            .get_market_prices(&quoted_market)
+            .unwrap_or_else(|| self.current_prices());
+        quote.midpoint = outcome
+            .order_book
+            .midpoint()
+            .unwrap_or_else(|| quote.outcome_prices.get(index).copied().unwrap_or_default());
+        quote.reason = order.status_reason;
+        Some(quote)
+    }
+
+    /// Amends the resting order, returns `None` if it's not amended, otherwise matches of the repriced order
     pub(super) fn update_order(
         &mut self,
         order: &mut Order,
         new_quantity: Decimal,
         new_price: Decimal,
-    ) -> bool {
-        let result = match order.outcome {
-            Outcome::YES => self
-                .yes_order_book
-                .update_order(order, new_price, new_quantity),
-            Outcome::NO => self
-                .no_order_book
-                .update_order(order, new_price, new_quantity),
-            _ => false,
-        };
+    ) -> Option<Vec<OrderBookMatchedOutput>> {
+        let index = get_outcome_index(order)?;
+        if self.frozen {
+            log_info!("{MARKET_FROZEN_REASON}, not updating order {}", order.id);
+            return None;
+        }
+        if self.trading_halted && new_price != order.price {
+            // repriced order would be rejected by the halt after it's taken out of the book
+            log_info!("{TRADING_HALTED_REASON}, not repricing order {}", order.id);
+            return None;
+        }
+        if !self.is_within_price_band(index, new_price) {
+            log_info!(
+                "{PRICE_OUTSIDE_BAND_REASON}, not updating order {}",
+                order.id
+            );
+            return None;
+        }
+        let amendment = self.outcomes.get_mut(&index)?.order_book.update_order(
+            order,
+            new_price,
+            new_quantity,
+        )?;
+
+        if amendment == Amendment::Repriced {
+            // new price may cross the book, so it's matched like a new order
+            return Some(self.process_order(order));
+        }
+        self.update_market_price();
+        Some(Vec::new())
+    }
+
+    pub(super) fn remove_order(&mut self, order_id: Uuid) -> bool {
+        let result = self
+            .outcomes
+            .values_mut()
+            .any(|outcome| outcome.order_book.remove_order(order_id));
+
         if result {
             self.update_market_price();
         }
+
         result
     }
 
-    pub(super) fn remove_order(
-        &mut self,
-        order_id: Uuid,
-        side: OrderSide,
-        outcome: Outcome,
-        price: Decimal,
-    ) -> bool {
-        let result = match outcome {
-            Outcome::YES => self.yes_order_book.remove_order(order_id, side, price),
-            Outcome::NO => self.no_order_book.remove_order(order_id, side, price),
-            _ => false,
-        };
+    pub(crate) fn get_order_book(&self, outcome_index: usize) -> Option<&OutcomeBook> {
+        self.outcomes
+            .get(&outcome_index)
+            .map(|outcome| &outcome.order_book)
+    }
 
-        if result {
+    pub(crate) fn get_outcome(&self, outcome_index: usize) -> Option<&OutcomeMarket> {
+        self.outcomes.get(&outcome_index)
+    }
+
+    pub(crate) fn outcome_count(&self) -> usize {
+        self.outcomes.len()
+    }
+
+    /// Current price of the outcome, zero if the market has no such outcome
+    pub(crate) fn current_price(&self, outcome_index: usize) -> Decimal {
+        self.outcomes
+            .get(&outcome_index)
+            .map(|outcome| outcome.current_price)
+            .unwrap_or_default()
+    }
+
+    /// Next update of the published books (delta of the changed levels or the full snapshot)
+    pub(super) fn get_book_update(&mut self) -> BookUpdate {
+        let books = self
+            .outcomes
+            .values()
+            .map(|outcome| outcome.order_book.get_order_book())
+            .collect();
+        self.book_feed.next_update(books)
+    }
+
+    /// Next update of the published books carries the full books
+    pub(super) fn request_book_snapshot(&mut self) {
+        self.book_feed.request_snapshot();
+    }
+
+    /// Current prices of all outcomes by their index
+    pub(crate) fn current_prices(&self) -> Vec<Decimal> {
+        (0..self.outcome_count())
+            .map(|outcome_index| self.current_price(outcome_index))
+            .collect()
+    }
+
+    pub(super) fn find_order(&self, order_id: Uuid) -> Option<(Decimal, &OrderBookEntry)> {
+        self.outcomes
+            .values()
+            .find_map(|outcome| outcome.order_book.find_order(order_id))
+    }
+
+    /// Removes all orders (including trigger orders) for which `keep` returns false
+    pub(super) fn retain_orders(&mut self, keep: &impl Fn(&Uuid) -> bool) -> usize {
+        let removed = self
+            .outcomes
+            .values_mut()
+            .map(|outcome| outcome.order_book.retain_orders(keep))
+            .sum::<usize>()
+            + self.trigger_book.retain_orders(keep);
+
+        if removed > 0 {
             self.update_market_price();
         }
+        removed
+    }
 
-        result
+    pub(super) fn add_trigger_order(&mut self, order: Order) -> bool {
+        // limit price of the triggered order is checked once it reaches the book
+        let trigger_price = order.trigger_price.unwrap_or_default();
+        let Some(book) = get_outcome_index(&order).and_then(|index| self.get_order_book(index))
+        else {
+            log_info!(
+                "Market has no outcome of order {}, not adding order to trigger book",
+                order.id
+            );
+            return false;
+        };
+        if let Err(reason) = book.rules.validate(trigger_price, order.quantity) {
+            log_info!("{reason}, not adding order {} to trigger book", order.id);
+            return false;
+        }
+        self.trigger_book.add_order(order)
     }
 
-    pub(crate) fn get_order_book(&self, outcome: Outcome) -> Option<&OutcomeBook> {
-        match outcome {
-            Outcome::YES => Some(&self.yes_order_book),
-            Outcome::NO => Some(&self.no_order_book),
-            _ => None,
+    /// Adds the trailing stop, which starts trailing from the current reference price of it's outcome unless it already has a best price
+    pub(super) fn add_trailing_stop(&mut self, order: Order, trailing_stop: TrailingStop) -> bool {
+        let Some((outcome_index, book)) =
+            get_outcome_index(&order).and_then(|index| Some((index, self.get_order_book(index)?)))
+        else {
+            log_info!(
+                "Market has no outcome of order {}, not adding order to trigger book",
+                order.id
+            );
+            return false;
+        };
+        if let Err(reason) = book.rules.validate(Decimal::ZERO, order.quantity) {
+            log_info!("{reason}, not adding order {} to trigger book", order.id);
+            return false;
         }
+        let tick_size = book.rules.tick_size;
+        let reference_price = self
+            .get_reference_prices()
+            .get(&outcome_index)
+            .copied()
+            .unwrap_or_default();
+        self.trigger_book
+            .add_trailing_stop(order, trailing_stop, reference_price, tick_size)
     }
 
-    ///// Helpers //////
+    pub(super) fn remove_trigger_order(&mut self, order_id: Uuid) -> bool {
+        self.trigger_book.remove_order(order_id).is_some()
+    }
 
-    fn update_market_price(&mut self) {
-        // https://www.cultivatelabs.com/crowdsourced-forecasting-guide/how-does-logarithmic-market-scoring-rule-lmsr-work
-        // Refer above blogpost for better understanding on LMSR (Logarithmic Market Scoring Rule) price mechanism for prediction markets
-        if self.liquidity_b > Decimal::ZERO {
-            let funds_yes = self.calculate_total_funds(Outcome::YES);
-            let funds_no = self.calculate_total_funds(Outcome::NO);
-
-            let total_liquidity = self.liquidity_b * dec!(2); // 2 * b for both sides
-            let total_funds = funds_yes + funds_no;
-
-            if total_funds > Decimal::ZERO {
-                let yes_weight = (self.liquidity_b + funds_yes) / (total_liquidity + total_funds);
-                let no_weight = (self.liquidity_b + funds_no) / (total_liquidity + total_funds);
-
-                let total_weight = yes_weight + no_weight;
-                self.current_yes_price = yes_weight / total_weight;
-                self.current_no_price = no_weight / total_weight;
-            } else {
-                self.current_yes_price = dec!(0.5);
-                self.current_no_price = dec!(0.5);
+    pub(super) fn resize_trigger_order(&mut self, order_id: Uuid, quantity: Decimal) -> bool {
+        self.trigger_book.resize_order(order_id, quantity)
+    }
+
+    /// Trailing stops moved since the last call along with their trigger price, these are yet to be persisted
+    pub(super) fn take_moved_trailing_stops(&mut self) -> Vec<(TrailingStop, Decimal)> {
+        self.trigger_book.take_moved_trailing_stops()
+    }
+
+    /// Executes all stop loss / take profit orders whose trigger price is hit.
+    ///
+    /// Trailing stops are moved by the reference prices before they are checked.
+    /// Execution of triggered order moves the price, so it's repeated until no more orders are triggered
+    pub(super) fn execute_triggered_orders(&mut self) -> Vec<(Order, Vec<OrderBookMatchedOutput>)> {
+        let mut executed_orders = Vec::new();
+        if self.is_in_auction() || self.trading_halted || self.frozen {
+            return executed_orders; // trigger orders wait for the continuous trading
+        }
+
+        let tick_size = self
+            .get_rules_book()
+            .map(|book| book.rules.tick_size)
+            .unwrap_or_default();
+        loop {
+            let reference_prices = self.get_reference_prices();
+            let reference_price = |order: &Order| {
+                get_outcome_index(order)
+                    .and_then(|index| reference_prices.get(&index).copied())
+                    .unwrap_or_default()
+            };
+
+            self.trigger_book
+                .update_trailing_stops(reference_price, tick_size);
+            let triggered_orders = self.trigger_book.take_triggered_orders(reference_price);
+
+            if triggered_orders.is_empty() {
+                break;
             }
-        } else {
-            let yes_mid = self.calculate_midpoint_price(&self.yes_order_book);
-            let no_mid = self.calculate_midpoint_price(&self.no_order_book);
-
-            match (yes_mid, no_mid) {
-                (Some(yes_price), Some(no_price)) => {
-                    let total = yes_price + no_price;
-                    if total > Decimal::ZERO {
-                        self.current_yes_price = yes_price / total;
-                        self.current_no_price = no_price / total;
-                    } else {
-                        self.current_yes_price = dec!(0.5);
-                        self.current_no_price = dec!(0.5);
-                    }
-                }
 
-                (Some(yes_price), None) => {
-                    self.current_yes_price = yes_price.min(dec!(0.95)); // cap at 0.95
-                    self.current_no_price = dec!(1) - self.current_yes_price;
-                }
-                (None, Some(no_price)) => {
-                    self.current_no_price = no_price.min(dec!(0.95)); // cap at 0.95
-                    self.current_yes_price = dec!(1) - self.current_no_price;
-                }
-                (None, None) => {
-                    self.current_yes_price = dec!(0.5);
-                    self.current_no_price = dec!(0.5);
+            // orders triggered along with the one breaching the price band are rejected by the halt
+            for mut order in triggered_orders {
+                let matches = if order.order_type == OrderType::MARKET {
+                    let budget = get_outcome_index(&order)
+                        .and_then(|index| self.get_order_book(index))
+                        .map(|book| {
+                            book.get_budget_for_quantity(&order, self.market_maker.as_ref())
+                        })
+                        .unwrap_or_default();
+                    self.create_market_order(&mut order, budget, MarketOrderLimits::default())
+                } else {
+                    self.process_order(&mut order)
+                };
+                executed_orders.push((order, matches));
+            }
+
+            if self.trading_halted {
+                break;
+            }
+        }
+
+        executed_orders
+    }
+
+    pub(crate) fn auction_opens_at(&self) -> Option<NaiveDateTime> {
+        self.auction_opens_at
+    }
+
+    pub(crate) fn is_trading_halted(&self) -> bool {
+        self.trading_halted
+    }
+
+    pub(crate) fn is_frozen(&self) -> bool {
+        self.frozen
+    }
+
+    /// Freezes (or unfreezes) the market along with it's lifecycle, resting and trigger orders stay in the book
+    pub(super) fn set_frozen(&mut self, frozen: bool) {
+        self.frozen = frozen;
+    }
+
+    /// Reference prices of the price band are due to be rolled at `now`
+    pub(crate) fn is_price_band_due(&self, now: NaiveDateTime) -> bool {
+        match (&self.price_band, self.get_rules_book()) {
+            (Some(price_band), Some(book)) => price_band.is_due(book.rules.price_band_window, now),
+            _ => false,
+        }
+    }
+
+    /// Starts the new window of the price band at `now`, referenced to the current prices
+    pub(super) fn roll_price_band(&mut self, now: NaiveDateTime) {
+        if self.price_band.is_some() {
+            self.price_band = Some(PriceBand::new(self.get_reference_prices(), Some(now)));
+        }
+    }
+
+    /// Ends the trading halt once it's cool-down is over, price band is rolled to the prices of the halt
+    pub(super) fn resume_trading(&mut self, now: NaiveDateTime) {
+        self.trading_halted = false;
+        self.roll_price_band(now);
+    }
+
+    /// Indicative clearing price, volume and imbalance of every outcome, `None` if the market is not in the call auction
+    pub(crate) fn auction_indications(&self) -> Option<Vec<(usize, AuctionIndication)>> {
+        self.auction_opens_at?;
+
+        let indications = self
+            .get_auctions()
+            .into_iter()
+            .filter_map(|(index, demand, supply)| {
+                let reference_price = self.current_price(index);
+                get_clearing_price(&demand, &supply, reference_price)
+                    .map(|indication| (index, indication))
+            })
+            .flat_map(|(index, indication)| self.get_outcome_indications(index, indication))
+            .collect();
+        Some(indications)
+    }
+
+    /// Ends the call auction, crossing orders are executed at the clearing price and continuous matching starts
+    ///
+    /// Demand order (buyer of the auctioned outcome) is the current order of the match, binary market's NO seller is the demand of YES
+    pub(super) fn uncross_auction(&mut self) -> Vec<OrderBookMatchedOutput> {
+        let mut matches = Vec::new();
+        if self.auction_opens_at.is_none() || self.frozen {
+            return matches; // auction of the frozen market is uncrossed once it's reopened
+        }
+
+        for (index, demand, supply) in self.get_auctions() {
+            let Some(indication) = get_clearing_price(&demand, &supply, self.current_price(index))
+            else {
+                continue;
+            };
+
+            let fills = pair_orders(&demand, &supply, indication.price);
+            for fill in fills.iter() {
+                let (demand_order, supply_order) = (&demand[fill.demand], &supply[fill.supply]);
+                let is_no_seller = self.is_binary() && demand_order.outcome_index == NO_OUTCOME;
+                let match_type = match (
+                    demand_order.outcome_index == supply_order.outcome_index,
+                    is_no_seller,
+                ) {
+                    (true, _) => MatchType::Transfer,
+                    (false, false) => MatchType::Mint, // YES buyer + NO buyer
+                    (false, true) => MatchType::Merge, // NO seller + YES seller
+                };
+                let price = match is_no_seller {
+                    true => Decimal::ONE - indication.price,
+                    false => indication.price,
+                };
+
+                self.fill_auction_order(demand_order, fill.quantity);
+                let (total_quantity, filled_quantity) = self
+                    .fill_auction_order(supply_order, fill.quantity)
+                    .unwrap_or_default();
+
+                matches.push(OrderBookMatchedOutput {
+                    order_id: demand_order.order_id,
+                    opposite_order_id: supply_order.order_id,
+                    matched_quantity: fill.quantity,
+                    price,
+                    opposite_order_total_quantity: total_quantity,
+                    opposite_order_filled_quantity: filled_quantity,
+                    match_type,
+                });
+            }
+
+            if !fills.is_empty() {
+                for (outcome_index, outcome_indication) in
+                    self.get_outcome_indications(index, indication)
+                {
+                    if let Some(outcome) = self.outcomes.get_mut(&outcome_index) {
+                        outcome.last_trade_price = Some(outcome_indication.price);
+                    }
                 }
             }
         }
+
+        self.auction_opens_at = None;
+        for outcome in self.outcomes.values_mut() {
+            outcome.order_book.rules.auction_opens_at = None;
+        }
+        self.update_market_price();
+        // band is referenced to the prices discovered by the auction
+        let reference_prices = self.get_reference_prices();
+        if let Some(price_band) = self.price_band.as_mut() {
+            price_band.reference_prices = reference_prices;
+        }
+        matches
     }
 
-    fn calculate_total_funds(&self, outcome: Outcome) -> Decimal {
-        // iterating over bids, because buyers have put their money. sellers are putting stocks (not money, so funds = bids for this part)
-        let book_funds = match outcome {
-            Outcome::YES => self
-                .yes_order_book
-                .bids
-                .iter()
-                .map(|(p, price_level)| *p * price_level.total_quantity)
-                .sum(),
-            Outcome::NO => self
-                .no_order_book
-                .bids
-                .iter()
-                .map(|(p, price_level)| *p * price_level.total_quantity)
-                .sum(),
-            _ => Decimal::ZERO,
+    ///// Helpers //////
+
+    fn is_in_auction(&self) -> bool {
+        self.auction_opens_at.is_some()
+    }
+
+    // limit orders rest in the book till the auction is uncrossed
+    fn collect_auction_order(&mut self, outcome_index: usize, order: &mut Order) {
+        if order.status != OrderStatus::OPEN && order.status != OrderStatus::PendingUpdate {
+            return;
+        }
+        if order.order_type == OrderType::MARKET
+            || matches!(order.time_in_force, TimeInForce::IOC | TimeInForce::FOK)
+        {
+            reject_order(order, AUCTION_REJECT_REASON);
+            return;
+        }
+        let Some(book) = self.get_order_book(outcome_index) else {
+            return;
+        };
+        if let Err(reason) = book.rules.validate(order.price, order.quantity) {
+            log_info!("{reason}, cancelling order {}", order.id);
+            order.status = OrderStatus::CANCELLED;
+            order.status_reason = Some(reason);
+            return;
+        }
+        self.add_order(order);
+    }
+
+    /// Demand and supply of every auction (outcome index, demand, supply), binary market is auctioned in YES terms
+    fn get_auctions(&self) -> Vec<(usize, Vec<AuctionOrder>, Vec<AuctionOrder>)> {
+        let to_yes_terms = |order: AuctionOrder| AuctionOrder {
+            limit_price: Decimal::ONE - order.limit_price,
+            ..order
+        };
+
+        if self.is_binary() {
+            let (Some(yes_book), Some(no_book)) = (
+                self.get_order_book(YES_OUTCOME),
+                self.get_order_book(NO_OUTCOME),
+            ) else {
+                return Vec::new();
+            };
+            let mut demand = get_auction_orders(YES_OUTCOME, yes_book, OrderSide::BUY);
+            demand.extend(
+                get_auction_orders(NO_OUTCOME, no_book, OrderSide::SELL)
+                    .into_iter()
+                    .map(to_yes_terms),
+            );
+            let mut supply = get_auction_orders(YES_OUTCOME, yes_book, OrderSide::SELL);
+            supply.extend(
+                get_auction_orders(NO_OUTCOME, no_book, OrderSide::BUY)
+                    .into_iter()
+                    .map(to_yes_terms),
+            );
+            return vec![(YES_OUTCOME, demand, supply)];
+        }
+
+        self.outcomes
+            .iter()
+            .map(|(index, outcome)| {
+                let demand = get_auction_orders(*index, &outcome.order_book, OrderSide::BUY);
+                let supply = get_auction_orders(*index, &outcome.order_book, OrderSide::SELL);
+                (*index, demand, supply)
+            })
+            .collect()
+    }
+
+    // indication of the auctioned outcome, complementary outcome of binary market is indicated at `1 - price` with the opposite imbalance
+    fn get_outcome_indications(
+        &self,
+        outcome_index: usize,
+        indication: AuctionIndication,
+    ) -> Vec<(usize, AuctionIndication)> {
+        let mut indications = vec![(outcome_index, indication)];
+        if let Some(complementary_index) = self.get_complementary_index(outcome_index) {
+            let complementary_indication = AuctionIndication {
+                price: Decimal::ONE - indication.price,
+                volume: indication.volume,
+                imbalance: -indication.imbalance,
+            };
+            indications.push((complementary_index, complementary_indication));
+        }
+        indications
+    }
+
+    fn fill_auction_order(
+        &mut self,
+        order: &AuctionOrder,
+        quantity: Decimal,
+    ) -> Option<(Decimal, Decimal)> {
+        self.outcomes
+            .get_mut(&order.outcome_index)?
+            .order_book
+            .fill_order(order.order_id, quantity)
+    }
+
+    // last trade price of every outcome, current price if it was never traded
+    fn get_reference_prices(&self) -> BTreeMap<usize, Decimal> {
+        self.outcomes
+            .iter()
+            .map(|(index, outcome)| {
+                let price = outcome.last_trade_price.unwrap_or(outcome.current_price);
+                (*index, price)
+            })
+            .collect()
+    }
+
+    fn is_within_price_band(&self, outcome_index: usize, price: Decimal) -> bool {
+        match (&self.price_band, self.get_price_band_width()) {
+            (Some(price_band), Some(band)) => price_band.contains(outcome_index, band, price),
+            _ => true,
+        }
+    }
+
+    /// Last price level the market order can be matched at (stricter of it's own limits and the price band) and whether the band is binding,
+    /// error is the reason the order is rejected by the state of the market
+    fn get_market_order_price_limit(
+        &self,
+        order: &Order,
+        outcome_index: usize,
+        limits: MarketOrderLimits,
+    ) -> Result<(Option<Decimal>, bool), &'static str> {
+        if self.frozen {
+            return Err(MARKET_FROZEN_REASON);
+        }
+        if self.trading_halted {
+            return Err(TRADING_HALTED_REASON);
+        }
+        if self.is_in_auction() {
+            return Err(AUCTION_REJECT_REASON);
+        }
+        let worst_price = limits.worst_price(order.side, self.get_midpoint(outcome_index));
+        // band edge is applied as the price limit if it's stricter than the order's own limits
+        let band_price = self.get_band_worst_price(outcome_index, order.side);
+        let is_band_binding = match (worst_price, band_price) {
+            (_, None) => false,
+            (None, Some(_)) => true,
+            (Some(worst_price), Some(band_price)) => match order.side {
+                OrderSide::BUY => band_price < worst_price,
+                OrderSide::SELL => band_price > worst_price,
+            },
+        };
+        if is_band_binding {
+            Ok((band_price, true))
+        } else {
+            Ok((worst_price, false))
+        }
+    }
+
+    fn get_band_worst_price(&self, outcome_index: usize, side: OrderSide) -> Option<Decimal> {
+        let band = self.get_price_band_width()?;
+        self.price_band
+            .as_ref()?
+            .worst_price(outcome_index, band, side)
+    }
+
+    fn get_price_band_width(&self) -> Option<Decimal> {
+        self.get_rules_book()?.rules.price_band
+    }
+
+    fn is_binary(&self) -> bool {
+        self.outcomes.len() == 2
+    }
+
+    // only the outcomes of binary market are complementary (YES + NO = 1)
+    fn get_complementary_index(&self, outcome_index: usize) -> Option<usize> {
+        match outcome_index {
+            YES_OUTCOME if self.is_binary() => Some(NO_OUTCOME),
+            NO_OUTCOME if self.is_binary() => Some(YES_OUTCOME),
+            _ => None,
+        }
+    }
+
+    fn get_rules_book(&self) -> Option<&OutcomeBook> {
+        self.outcomes
+            .values()
+            .next()
+            .map(|outcome| &outcome.order_book)
+    }
+
+    /// Midpoint of the outcome's book, current market price of the outcome if any side of the book is empty
+    fn get_midpoint(&self, outcome_index: usize) -> Decimal {
+        self.outcomes
+            .get(&outcome_index)
+            .map(|outcome| {
+                outcome
+                    .order_book
+                    .midpoint()
+                    .unwrap_or(outcome.current_price)
+            })
+            .unwrap_or_default()
+    }
+
+    fn update_last_trade_price(
+        &mut self,
+        outcome_index: usize,
+        matches: &[OrderBookMatchedOutput],
+    ) {
+        // self trade prevention doesn't execute anything, so it doesn't move the price
+        let Some(last_match) = matches
+            .iter()
+            .rev()
+            .find(|m| m.match_type != MatchType::SelfTradePrevention)
+        else {
+            return;
         };
 
-        let executed_funds = match outcome {
-            Outcome::YES => self.executed_yes_buy_volume,
-            Outcome::NO => self.executed_no_buy_volume,
-            _ => Decimal::ZERO,
+        if let Some(outcome) = self.outcomes.get_mut(&outcome_index) {
+            outcome.last_trade_price = Some(last_match.price);
+        }
+
+        // mint and merge trades the opposite outcome at the complementary price as well
+        if matches!(last_match.match_type, MatchType::Mint | MatchType::Merge)
+            && let Some(complementary_outcome) = self
+                .get_complementary_index(outcome_index)
+                .and_then(|index| self.outcomes.get_mut(&index))
+        {
+            complementary_outcome.last_trade_price = Some(Decimal::ONE - last_match.price);
+        }
+    }
+
+    fn update_market_price(&mut self) {
+        let Some(prices) = self.get_market_prices(self) else {
+            return;
         };
 
-        book_funds + executed_funds
+        for (outcome, price) in self.outcomes.values_mut().zip(prices) {
+            outcome.current_price = price;
+        }
+    }
+
+    /// Prices of the `market` by the price model of this market, `None` if the market has no rules yet
+    fn get_market_prices(&self, market: &dyn MarketView) -> Option<Vec<Decimal>> {
+        let price_model = self
+            .get_rules_book()
+            .map(|book| get_price_model(book.rules.price_model))?;
+
+        // market which has nothing to be priced by is shown at uniform prices
+        let outcome_count = market.outcome_count();
+        let prices = price_model
+            .prices(market)
+            .unwrap_or_else(|| vec![Decimal::ONE / Decimal::from(outcome_count); outcome_count]);
+        Some(prices)
+    }
+}
+
+impl MarketView for MarketBook {
+    fn outcome_count(&self) -> usize {
+        self.outcomes.len()
+    }
+
+    fn get_outcome(&self, outcome_index: usize) -> Option<&OutcomeMarket> {
+        self.outcomes.get(&outcome_index)
+    }
+
+    fn market_maker(&self) -> Option<&LmsrMarketMaker> {
+        self.market_maker.as_ref()
+    }
+
+    fn liquidity_b(&self) -> Decimal {
+        self.liquidity_b
+    }
+}
+
+/// Market with the quoted outcome and market maker as they are after the quoted execution
+struct QuotedMarket<'a> {
+    market_book: &'a MarketBook,
+    outcome_index: usize,
+    outcome: &'a OutcomeMarket,
+    market_maker: Option<&'a LmsrMarketMaker>,
+}
+
+impl MarketView for QuotedMarket<'_> {
+    fn outcome_count(&self) -> usize {
+        self.market_book.outcome_count()
+    }
+
+    fn get_outcome(&self, outcome_index: usize) -> Option<&OutcomeMarket> {
+        if outcome_index == self.outcome_index {
+            return Some(self.outcome);
+        }
+        self.market_book.get_outcome(outcome_index)
+    }
+
+    fn market_maker(&self) -> Option<&LmsrMarketMaker> {
+        self.market_maker
+    }
+
+    fn liquidity_b(&self) -> Decimal {
+        self.market_book.liquidity_b
+    }
+}
+
+/// Matches the market order against the outcome's book and the market maker within the `worst_price`.
+///
+/// Market order is not matched with the complementary book, so only the outcome's own last trade price is moved
+fn execute_market_order(
+    outcome: &mut OutcomeMarket,
+    market_maker: Option<&mut LmsrMarketMaker>,
+    order: &mut Order,
+    budget: Decimal,
+    worst_price: Option<Decimal>,
+    allow_partial: bool,
+) -> Vec<OrderBookMatchedOutput> {
+    let matches = outcome.order_book.create_market_order(
+        order,
+        budget,
+        worst_price,
+        allow_partial,
+        market_maker,
+    );
+
+    // self trade prevention doesn't execute anything, so it doesn't move the price
+    if let Some(last_match) = matches
+        .iter()
+        .rev()
+        .find(|m| m.match_type != MatchType::SelfTradePrevention)
+    {
+        outcome.last_trade_price = Some(last_match.price);
+    }
+    if order.side == OrderSide::BUY && order.filled_quantity > Decimal::ZERO {
+        outcome.executed_buy_volume += matches
+            .iter()
+            .map(|m| m.price * m.matched_quantity)
+            .sum::<Decimal>();
+    }
+
+    matches
+}
+
+/// Index of the order's outcome in the market, binary market trades YES (0) and NO (1) shares,
+/// categorical market trades YES shares of the `outcome_index`
+pub(crate) fn get_outcome_index(order: &Order) -> Option<usize> {
+    match order.outcome {
+        Outcome::YES => usize::try_from(order.outcome_index).ok(),
+        Outcome::NO => Some(NO_OUTCOME),
+        Outcome::UNSPECIFIED => None,
     }
+}
+
+fn reject_order(order: &mut Order, reason: &str) {
+    log_info!("{reason}, cancelling order {}", order.id);
+    order.status = OrderStatus::CANCELLED;
+    order.status_reason = Some(reason.to_string());
+}
+
+// remaining quantity of the book side in the book order (best price first, then time priority)
+fn get_auction_orders(
+    outcome_index: usize,
+    book: &OutcomeBook,
+    side: OrderSide,
+) -> Vec<AuctionOrder> {
+    let price_levels: Box<dyn Iterator<Item = _>> = match side {
+        OrderSide::BUY => Box::new(book.bids.iter().rev()),
+        OrderSide::SELL => Box::new(book.asks.iter()),
+    };
+
+    price_levels
+        .flat_map(|(price, price_level)| {
+            price_level.orders.iter().map(|entry| AuctionOrder {
+                order_id: entry.order_id,
+                user_id: entry.user_id,
+                outcome_index,
+                limit_price: *price,
+                quantity: entry.total_quantity - entry.filled_quantity,
+            })
+        })
+        .filter(|order| order.quantity > Decimal::ZERO)
+        .collect()
+}
+
+fn get_outcomes(outcome_count: usize) -> BTreeMap<usize, OutcomeMarket> {
+    let initial_price = Decimal::ONE / Decimal::from(outcome_count.max(1));
+    (0..outcome_count)
+        .map(|index| {
+            let outcome = OutcomeMarket {
+                current_price: initial_price,
+                ..Default::default()
+            };
+            (index, outcome)
+        })
+        .collect()
+}
 
-    fn calculate_midpoint_price(&self, order_book: &OutcomeBook) -> Option<Decimal> {
-        match (order_book.best_bid(), order_book.best_ask()) {
-            (Some(bid), Some(ask)) => Some((bid + ask) / dec!(2)),
-            (Some(bid), None) => Some(bid),
-            (None, Some(ask)) => Some(ask),
-            (None, None) => None,
+fn get_outcome_pair_mut(
+    outcomes: &mut BTreeMap<usize, OutcomeMarket>,
+    outcome_index: usize,
+    complementary_index: Option<usize>,
+) -> Option<(&mut OutcomeMarket, Option<&mut OutcomeMarket>)> {
+    let mut outcome = None;
+    let mut complementary_outcome = None;
+    for (index, outcome_market) in outcomes.iter_mut() {
+        if *index == outcome_index {
+            outcome = Some(outcome_market);
+        } else if Some(*index) == complementary_index {
+            complementary_outcome = Some(outcome_market);
         }
     }
+
+    outcome.map(|outcome| (outcome, complementary_outcome))
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
     use chrono::NaiveDateTime;
-    use db_service::schema::enums::OrderType;
+    use db_service::schema::enums::{OrderType, SelfTradePrevention, TimeInForce};
+    use rust_decimal_macros::dec;
+    use utility_helpers::message_pack_helper::{
+        deserialize_from_message_pack, serialize_to_message_pack,
+    };
 
     fn get_created_at() -> NaiveDateTime {
         chrono::Utc::now().naive_local()
@@ -264,10 +1111,22 @@ mod test {
             updated_at: get_created_at(),
             user_id: get_random_uuid(),
             order_type: OrderType::MARKET,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         let budget = dec!(100); // Large budget but empty book
-        let matches = market_book.create_market_order(&mut market_order, budget);
+        let matches = market_book.create_market_order(
+            &mut market_order,
+            budget,
+            MarketOrderLimits::default(),
+        );
 
         // Results of empty book matching:
         assert_eq!(matches.len(), 0); // No matches
@@ -276,11 +1135,14 @@ mod test {
         assert_eq!(market_order.status, OrderStatus::CANCELLED); // Still "cancelled"
 
         // Prices remain at default
-        assert_eq!(market_book.current_yes_price, dec!(0.5));
-        assert_eq!(market_book.current_no_price, dec!(0.5));
+        assert_eq!(market_book.current_price(YES_OUTCOME), dec!(0.5));
+        assert_eq!(market_book.current_price(NO_OUTCOME), dec!(0.5));
 
         // No executed volume tracked
-        assert_eq!(market_book.executed_yes_buy_volume, dec!(0));
+        assert_eq!(
+            market_book.outcomes[&YES_OUTCOME].executed_buy_volume,
+            dec!(0)
+        );
     }
 
     #[test]
@@ -290,12 +1152,22 @@ mod test {
         let market_book = MarketBook::new(liquidity_b);
 
         assert_eq!(market_book.liquidity_b, liquidity_b);
-        assert_eq!(market_book.current_yes_price, Decimal::new(5, 1)); // 0.5
-        assert_eq!(market_book.current_no_price, Decimal::new(5, 1)); // 0.5
-        assert!(market_book.yes_order_book.bids.is_empty());
-        assert!(market_book.no_order_book.bids.is_empty());
-        assert!(market_book.yes_order_book.asks.is_empty());
-        assert!(market_book.no_order_book.asks.is_empty());
+        assert_eq!(market_book.current_price(YES_OUTCOME), Decimal::new(5, 1)); // 0.5
+        assert_eq!(market_book.current_price(NO_OUTCOME), Decimal::new(5, 1)); // 0.5
+        assert!(
+            market_book.outcomes[&YES_OUTCOME]
+                .order_book
+                .bids
+                .is_empty()
+        );
+        assert!(market_book.outcomes[&NO_OUTCOME].order_book.bids.is_empty());
+        assert!(
+            market_book.outcomes[&YES_OUTCOME]
+                .order_book
+                .asks
+                .is_empty()
+        );
+        assert!(market_book.outcomes[&NO_OUTCOME].order_book.asks.is_empty());
     }
 
     #[test]
@@ -313,6 +1185,14 @@ mod test {
             updated_at: get_created_at(),
             user_id: get_random_uuid(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
         let order_2 = Order {
             id: get_random_uuid(),
@@ -327,34 +1207,52 @@ mod test {
             updated_at: get_created_at(),
             user_id: get_random_uuid(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         let liquidity_b = Decimal::new(100, 0);
         let mut market_book = MarketBook::new(liquidity_b); // 100 units of liquidity
 
+        // orders are indexed by id, so same orders are re-added with new ids
+        let with_new_id = |order: &Order| Order {
+            id: get_random_uuid(),
+            ..order.clone()
+        };
         market_book.add_order(&order_1);
-        market_book.add_order(&order_1);
-        market_book.add_order(&order_2);
+        market_book.add_order(&with_new_id(&order_1));
         market_book.add_order(&order_2);
+        market_book.add_order(&with_new_id(&order_2));
 
-        assert_eq!(market_book.yes_order_book.bids.len(), 1);
-        assert!(market_book.yes_order_book.bids.contains_key(&order_1.price));
+        assert_eq!(market_book.outcomes[&YES_OUTCOME].order_book.bids.len(), 1);
+        assert!(
+            market_book.outcomes[&YES_OUTCOME]
+                .order_book
+                .bids
+                .contains_key(&order_1.price)
+        );
         assert_eq!(
-            market_book
-                .yes_order_book
+            market_book.outcomes[&YES_OUTCOME]
+                .order_book
                 .bids
                 .get(&order_1.price)
                 .unwrap()
                 .total_quantity,
             order_1.quantity * dec!(2)
         );
-        assert_eq!(market_book.current_yes_price, Decimal::new(5, 1)); //  0.5
-        assert_eq!(market_book.current_no_price, Decimal::new(5, 1)); // 0.5
+        assert_eq!(market_book.current_price(YES_OUTCOME), Decimal::new(5, 1)); //  0.5
+        assert_eq!(market_book.current_price(NO_OUTCOME), Decimal::new(5, 1)); // 0.5
 
-        market_book.add_order(&order_2); // adding another order on NO side to skew the price
+        market_book.add_order(&with_new_id(&order_2)); // adding another order on NO side to skew the price
 
-        assert_ne!(market_book.current_yes_price, Decimal::new(5, 1)); // != 0.5
-        assert_ne!(market_book.current_no_price, Decimal::new(5, 1)); // != 0.5
+        assert_ne!(market_book.current_price(YES_OUTCOME), Decimal::new(5, 1)); // != 0.5
+        assert_ne!(market_book.current_price(NO_OUTCOME), Decimal::new(5, 1)); // != 0.5
     }
 
     #[test]
@@ -372,6 +1270,14 @@ mod test {
             updated_at: get_created_at(),
             user_id: get_random_uuid(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         let mut sell_order_1_yes = Order {
@@ -387,6 +1293,14 @@ mod test {
             updated_at: get_created_at(),
             user_id: get_random_uuid(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         let mut buy_order_1_no = Order {
@@ -395,13 +1309,21 @@ mod test {
             id: get_random_uuid(),
             market_id: get_random_uuid(),
             outcome: Outcome::NO,
-            price: Decimal::new(50, 2), // 0.5
+            price: Decimal::new(45, 2), // 0.45 (yes and no bids at 0.5 would mint a share pair)
             quantity: Decimal::new(10, 0),
             side: OrderSide::BUY,
             status: OrderStatus::OPEN,
             updated_at: get_created_at(),
             user_id: get_random_uuid(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         let mut sell_order_1_no = Order {
@@ -410,13 +1332,21 @@ mod test {
             id: get_random_uuid(),
             market_id: get_random_uuid(),
             outcome: Outcome::NO,
-            price: Decimal::new(50, 2), // 0.5
+            price: Decimal::new(45, 2), // 0.45
             quantity: Decimal::new(5, 0),
             side: OrderSide::SELL,
             status: OrderStatus::OPEN,
             updated_at: get_created_at(),
             user_id: get_random_uuid(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         let mut market_book = MarketBook::new(dec!(100));
@@ -429,11 +1359,14 @@ mod test {
 
         assert_eq!(match_1.len(), 1);
         assert_eq!(match_2.len(), 1);
-        assert_eq!(match_1.get(0).unwrap().order_id, sell_order_1_no.id);
-        assert_eq!(match_2.get(0).unwrap().order_id, sell_order_1_yes.id);
-        assert_eq!(match_1.get(0).unwrap().opposite_order_id, buy_order_1_no.id);
+        assert_eq!(match_1.first().unwrap().order_id, sell_order_1_no.id);
+        assert_eq!(match_2.first().unwrap().order_id, sell_order_1_yes.id);
+        assert_eq!(
+            match_1.first().unwrap().opposite_order_id,
+            buy_order_1_no.id
+        );
         assert_eq!(
-            match_2.get(0).unwrap().opposite_order_id,
+            match_2.first().unwrap().opposite_order_id,
             buy_order_1_yes.id
         );
     }
@@ -455,17 +1388,25 @@ mod test {
             updated_at: get_created_at(),
             user_id: get_random_uuid(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         let mut market_book = MarketBook::new(dec!(100));
 
         market_book.add_order(&order);
 
-        assert_eq!(market_book.yes_order_book.bids.len(), 1);
+        assert_eq!(market_book.outcomes[&YES_OUTCOME].order_book.bids.len(), 1);
 
-        market_book.remove_order(id, OrderSide::BUY, Outcome::YES, price);
+        market_book.remove_order(id);
 
-        assert_eq!(market_book.yes_order_book.bids.len(), 0);
+        assert_eq!(market_book.outcomes[&YES_OUTCOME].order_book.bids.len(), 0);
     }
 
     #[test]
@@ -485,6 +1426,14 @@ mod test {
             updated_at: get_created_at(),
             user_id: get_random_uuid(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         let mut sell_order = Order {
@@ -500,6 +1449,14 @@ mod test {
             updated_at: get_created_at(),
             user_id: get_random_uuid(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         let mut outcome_book = OutcomeBook::default();
@@ -535,6 +1492,14 @@ mod test {
             updated_at: get_created_at(),
             user_id: get_random_uuid(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         let buy_order_2 = Order {
@@ -552,6 +1517,14 @@ mod test {
             updated_at: get_created_at(),
             user_id: get_random_uuid(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         let mut sell_order = Order {
@@ -567,6 +1540,14 @@ mod test {
             updated_at: get_created_at(),
             user_id: get_random_uuid(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         let mut outcome_book = OutcomeBook::default();
@@ -604,6 +1585,14 @@ mod test {
             updated_at: get_created_at(),
             user_id: get_random_uuid(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         let mut sell_order = Order {
@@ -619,6 +1608,14 @@ mod test {
             updated_at: get_created_at(),
             user_id: get_random_uuid(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         let mut outcome_book = OutcomeBook::default();
@@ -648,6 +1645,14 @@ mod test {
             updated_at: get_created_at(),
             user_id: get_random_uuid(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         let mut sell_order = Order {
@@ -663,6 +1668,14 @@ mod test {
             updated_at: get_created_at(),
             user_id: get_random_uuid(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         let mut outcome_book = OutcomeBook::default();
@@ -694,6 +1707,14 @@ mod test {
             updated_at: get_created_at(),
             user_id: get_random_uuid(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         let mut sell_order = Order {
@@ -709,6 +1730,14 @@ mod test {
             updated_at: get_created_at(),
             user_id: get_random_uuid(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         let mut outcome_book = OutcomeBook::default();
@@ -727,10 +1756,10 @@ mod test {
         let mut outcome_book = OutcomeBook::default();
 
         // Try to remove an order that doesn't exist
-        let result = outcome_book.remove_order(get_random_uuid(), OrderSide::BUY, dec!(0.5));
+        let result = outcome_book.remove_order(get_random_uuid());
         assert!(!result);
 
-        // Try to remove with wrong side
+        // Order is removed by it's id only, side and price are resolved from the index
         let id = get_random_uuid();
         let order = Order {
             created_at: get_created_at(),
@@ -745,13 +1774,21 @@ mod test {
             updated_at: get_created_at(),
             user_id: get_random_uuid(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         outcome_book.add_order(&order);
 
-        let result = outcome_book.remove_order(id, OrderSide::SELL, dec!(0.5));
-        assert!(!result);
-        assert_eq!(outcome_book.bids.len(), 1);
+        let result = outcome_book.remove_order(id);
+        assert!(result);
+        assert!(outcome_book.bids.is_empty());
     }
 
     #[test]
@@ -772,6 +1809,14 @@ mod test {
             updated_at: get_created_at(),
             user_id: get_random_uuid(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         // Process an order when book is empty
@@ -804,6 +1849,14 @@ mod test {
             updated_at: get_created_at(),
             user_id: seller_id,
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         outcome_book.add_order(&sell_order);
@@ -822,11 +1875,20 @@ mod test {
             updated_at: get_created_at(),
             user_id: buyer_id,
             order_type: OrderType::MARKET,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         // Budget for 5 shares: 0.25 * 5 = 1.25
         let budget = dec!(1.25);
-        let matches = outcome_book.create_market_order(&mut market_buy_order, budget);
+        let matches =
+            outcome_book.create_market_order(&mut market_buy_order, budget, None, false, None);
 
         // Verify the results
         assert_eq!(matches.len(), 1);
@@ -863,6 +1925,14 @@ mod test {
             updated_at: get_created_at(),
             user_id: buyer_id,
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         outcome_book.add_order(&buy_order);
@@ -881,11 +1951,20 @@ mod test {
             updated_at: get_created_at(),
             user_id: seller_id,
             order_type: OrderType::MARKET,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         // Budget for 5 shares: 0.75 * 5 = 3.75
         let budget = dec!(3.75);
-        let matches = outcome_book.create_market_order(&mut market_sell_order, budget);
+        let matches =
+            outcome_book.create_market_order(&mut market_sell_order, budget, None, false, None);
 
         // Verify the results
         assert_eq!(matches.len(), 1);
@@ -922,6 +2001,14 @@ mod test {
             updated_at: get_created_at(),
             user_id: seller_id,
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         let sell_order_2 = Order {
@@ -937,6 +2024,14 @@ mod test {
             updated_at: get_created_at(),
             user_id: seller_id,
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         let sell_order_3 = Order {
@@ -952,6 +2047,14 @@ mod test {
             updated_at: get_created_at(),
             user_id: seller_id,
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         outcome_book.add_order(&sell_order_1);
@@ -972,11 +2075,20 @@ mod test {
             updated_at: get_created_at(),
             user_id: buyer_id,
             order_type: OrderType::MARKET,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         // Budget enough for some matches: 0.20*3 + 0.30*4 + 0.40*2 = 0.6 + 1.2 + 0.8 = 2.6
         let budget = dec!(2.6);
-        let matches = outcome_book.create_market_order(&mut market_buy_order, budget);
+        let matches =
+            outcome_book.create_market_order(&mut market_buy_order, budget, None, false, None);
 
         // Verify the results
         assert_eq!(matches.len(), 3); // 3 price levels matched
@@ -1021,6 +2133,14 @@ mod test {
             updated_at: get_created_at(),
             user_id: buyer_id,
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         let buy_order_2 = Order {
@@ -1036,6 +2156,14 @@ mod test {
             updated_at: get_created_at(),
             user_id: buyer_id,
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         let buy_order_3 = Order {
@@ -1051,6 +2179,14 @@ mod test {
             updated_at: get_created_at(),
             user_id: buyer_id,
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         outcome_book.add_order(&buy_order_1);
@@ -1071,11 +2207,20 @@ mod test {
             updated_at: get_created_at(),
             user_id: seller_id,
             order_type: OrderType::MARKET,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         // Budget enough for some matches: 0.80*3 + 0.70*4 + 0.60*2 = 2.4 + 2.8 + 1.2 = 6.4
         let budget = dec!(6.4);
-        let matches = outcome_book.create_market_order(&mut market_sell_order, budget);
+        let matches =
+            outcome_book.create_market_order(&mut market_sell_order, budget, None, false, None);
 
         // Verify the results
         assert_eq!(matches.len(), 3); // 3 price levels matched
@@ -1120,6 +2265,14 @@ mod test {
             updated_at: get_created_at(),
             user_id: seller_id,
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         outcome_book.add_order(&sell_order);
@@ -1138,11 +2291,20 @@ mod test {
             updated_at: get_created_at(),
             user_id: buyer_id,
             order_type: OrderType::MARKET,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         // Budget for 0 shares
         let budget = dec!(0);
-        let matches = outcome_book.create_market_order(&mut market_buy_order, budget);
+        let matches =
+            outcome_book.create_market_order(&mut market_buy_order, budget, None, false, None);
 
         // Verify the results
         assert_eq!(matches.len(), 0);
@@ -1173,6 +2335,14 @@ mod test {
             updated_at: get_created_at(),
             user_id: seller_id,
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         outcome_book.add_order(&sell_order);
@@ -1191,11 +2361,20 @@ mod test {
             updated_at: get_created_at(),
             user_id: buyer_id,
             order_type: OrderType::MARKET,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         // Budget for exactly 10 shares: 0.50 * 10 = 5.0
         let budget = dec!(5.0);
-        let matches = outcome_book.create_market_order(&mut market_buy_order, budget);
+        let matches =
+            outcome_book.create_market_order(&mut market_buy_order, budget, None, false, None);
 
         // Verify the results
         assert_eq!(matches.len(), 1);
@@ -1230,6 +2409,14 @@ mod test {
             updated_at: get_created_at(),
             user_id, // Same user
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         outcome_book.add_order(&sell_order);
@@ -1248,10 +2435,19 @@ mod test {
             updated_at: get_created_at(),
             user_id, // Same user
             order_type: OrderType::MARKET,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         let budget = dec!(5.0);
-        let matches = outcome_book.create_market_order(&mut market_buy_order, budget);
+        let matches =
+            outcome_book.create_market_order(&mut market_buy_order, budget, None, false, None);
 
         // Verify no matches because same user can't match with themselves
         assert_eq!(matches.len(), 0);
@@ -1281,6 +2477,14 @@ mod test {
             updated_at: get_created_at(),
             user_id: seller_id,
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         outcome_book.add_order(&sell_order);
@@ -1299,10 +2503,19 @@ mod test {
             updated_at: get_created_at(),
             user_id: buyer_id,
             order_type: OrderType::LIMIT, // Wrong order type
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         let budget = dec!(5.0);
-        let matches = outcome_book.create_market_order(&mut limit_buy_order, budget);
+        let matches =
+            outcome_book.create_market_order(&mut limit_buy_order, budget, None, false, None);
 
         // Verify no matches because wrong order type
         assert_eq!(matches.len(), 0);
@@ -1331,6 +2544,14 @@ mod test {
             updated_at: get_created_at(),
             user_id: seller_id_1,
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         let sell_order_2 = Order {
@@ -1348,6 +2569,14 @@ mod test {
                 .unwrap(), // Later timestamp
             user_id: seller_id_2,
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         let sell_order_3 = Order {
@@ -1363,6 +2592,14 @@ mod test {
             updated_at: get_created_at(),
             user_id: seller_id_1,
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         outcome_book.add_order(&sell_order_1);
@@ -1383,11 +2620,20 @@ mod test {
             updated_at: get_created_at(),
             user_id: buyer_id,
             order_type: OrderType::MARKET,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         // Budget for all shares at 0.25 and 2 shares at 0.35: (0.25 * 7) + (0.35 * 2) = 1.75 + 0.70 = 2.45
         let budget = dec!(2.45);
-        let matches = outcome_book.create_market_order(&mut market_buy_order, budget);
+        let matches =
+            outcome_book.create_market_order(&mut market_buy_order, budget, None, false, None);
 
         // Verify the results
         assert_eq!(matches.len(), 3); // 3 order matches (2 at same price level, 1 at higher price)
@@ -1432,6 +2678,14 @@ mod test {
             updated_at: get_created_at(),
             user_id: seller_id,
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         outcome_book.add_order(&sell_order);
@@ -1450,11 +2704,20 @@ mod test {
             updated_at: get_created_at(),
             user_id: buyer_id,
             order_type: OrderType::MARKET,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         // Budget for exactly 5.25 shares: 0.50 * 5.25 = 2.625
         let budget = dec!(2.625);
-        let matches = outcome_book.create_market_order(&mut market_buy_order, budget);
+        let matches =
+            outcome_book.create_market_order(&mut market_buy_order, budget, None, false, None);
 
         // Verify the results
         assert_eq!(matches.len(), 1);
@@ -1491,6 +2754,14 @@ mod test {
             updated_at: get_created_at(),
             user_id: seller_id,
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         market_book.add_order(&sell_order);
@@ -1509,11 +2780,23 @@ mod test {
             updated_at: get_created_at(),
             user_id: buyer_id,
             order_type: OrderType::MARKET,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         // Budget for 5 shares: 0.30 * 5 = 1.5
         let budget = dec!(1.5);
-        let matches = market_book.create_market_order(&mut market_buy_order, budget);
+        let matches = market_book.create_market_order(
+            &mut market_buy_order,
+            budget,
+            MarketOrderLimits::default(),
+        );
 
         // Verify the results
         assert_eq!(matches.len(), 1);
@@ -1524,11 +2807,56 @@ mod test {
         assert_eq!(market_buy_order.status, OrderStatus::FILLED);
 
         // Verify executed_yes_buy_volume was updated
-        assert_eq!(market_book.executed_yes_buy_volume, dec!(1.5)); // 0.30 * 5 = 1.5
+        assert_eq!(
+            market_book.outcomes[&YES_OUTCOME].executed_buy_volume,
+            dec!(1.5)
+        ); // 0.30 * 5 = 1.5
 
         // Verify market price was updated
-        assert_ne!(market_book.current_yes_price, dec!(0.5)); // Price should have changed
-        assert_ne!(market_book.current_no_price, dec!(0.5));
+        assert_ne!(market_book.current_price(YES_OUTCOME), dec!(0.5)); // Price should have changed
+        assert_ne!(market_book.current_price(NO_OUTCOME), dec!(0.5));
+    }
+
+    #[test]
+    fn test_market_order_slippage_from_midpoint() {
+        let resting_order = |side: OrderSide, price: Decimal| Order {
+            id: get_random_uuid(),
+            user_id: get_random_uuid(),
+            outcome: Outcome::YES,
+            side,
+            price,
+            quantity: dec!(2),
+            status: OrderStatus::OPEN,
+            ..Default::default()
+        };
+        let market_buy_order = || Order {
+            id: get_random_uuid(),
+            user_id: get_random_uuid(),
+            outcome: Outcome::YES,
+            side: OrderSide::BUY,
+            status: OrderStatus::OPEN,
+            order_type: OrderType::MARKET,
+            ..Default::default()
+        };
+
+        let mut market_book = MarketBook::new(dec!(100));
+        market_book.add_order(&resting_order(OrderSide::BUY, dec!(0.4)));
+        market_book.add_order(&resting_order(OrderSide::SELL, dec!(0.5)));
+        market_book.add_order(&resting_order(OrderSide::SELL, dec!(0.7)));
+
+        // midpoint is 0.45, so 20% slippage stops the order at 0.54
+        let limits = MarketOrderLimits::new(None, Some(dec!(0.2)));
+
+        let mut order = market_buy_order();
+        let matches = market_book.create_market_order(&mut order, dec!(1.7), limits);
+        assert!(matches.is_empty());
+        assert_eq!(order.status, OrderStatus::CANCELLED);
+
+        let mut order = market_buy_order();
+        let matches = market_book.create_market_order(&mut order, dec!(1), limits);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].price, dec!(0.5));
+        assert_eq!(order.status, OrderStatus::FILLED);
     }
 
     #[test]
@@ -1552,6 +2880,14 @@ mod test {
             updated_at: get_created_at(),
             user_id,
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         let sell_order_yes = Order {
@@ -1567,6 +2903,14 @@ mod test {
             updated_at: get_created_at(),
             user_id,
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         // Add orders to NO book
@@ -1583,6 +2927,14 @@ mod test {
             updated_at: get_created_at(),
             user_id,
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         market_book.add_order(&buy_order_yes);
@@ -1590,7 +2942,7 @@ mod test {
         market_book.add_order(&buy_order_no);
 
         // Get the YES order book
-        let yes_book = market_book.get_order_book(Outcome::YES).unwrap();
+        let yes_book = market_book.get_order_book(YES_OUTCOME).unwrap();
 
         // Verify YES book structure
         assert_eq!(yes_book.bids.len(), 1);
@@ -1603,7 +2955,7 @@ mod test {
         assert_eq!(yes_ask_level.total_quantity, dec!(3));
 
         // Get the NO order book
-        let no_book = market_book.get_order_book(Outcome::NO).unwrap();
+        let no_book = market_book.get_order_book(NO_OUTCOME).unwrap();
 
         // Verify NO book structure
         assert_eq!(no_book.bids.len(), 1);
@@ -1613,7 +2965,7 @@ mod test {
         assert_eq!(no_bid_level.total_quantity, dec!(7));
 
         // Try to get an invalid order book
-        let invalid_book = market_book.get_order_book(Outcome::UNSPECIFIED);
+        let invalid_book = market_book.get_order_book(2);
         assert!(invalid_book.is_none());
     }
 
@@ -1638,6 +2990,14 @@ mod test {
             updated_at: get_created_at(),
             user_id,
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         market_book.add_order(&order);
@@ -1649,13 +3009,13 @@ mod test {
         let result = market_book.update_order(&mut order, new_quantity, new_price);
 
         // Verify the update was successful
-        assert!(result);
+        assert!(result.is_some_and(|matches| matches.is_empty()));
         assert_eq!(order.price, new_price);
         assert_eq!(order.quantity, new_quantity);
         assert_eq!(order.status, OrderStatus::OPEN);
 
         // Verify the order book was updated correctly
-        let yes_book = market_book.get_order_book(Outcome::YES).unwrap();
+        let yes_book = market_book.get_order_book(YES_OUTCOME).unwrap();
 
         // Old price level should be gone
         assert!(!yes_book.bids.contains_key(&dec!(0.40)));
@@ -1665,4 +3025,1136 @@ mod test {
         let price_level = yes_book.bids.get(&new_price).unwrap();
         assert_eq!(price_level.total_quantity, new_quantity);
     }
+
+    #[test]
+    fn test_update_order_queue_priority() {
+        let mut market_book = MarketBook::new(dec!(0));
+
+        let mut bid_1 = get_yes_order(OrderSide::BUY, OrderType::LIMIT, dec!(0.4), dec!(10), None);
+        let bid_2 = get_yes_order(OrderSide::BUY, OrderType::LIMIT, dec!(0.4), dec!(5), None);
+        market_book.add_order(&bid_1);
+        market_book.add_order(&bid_2);
+
+        // reducing the quantity keeps the place of the order in the queue
+        let matches = market_book.update_order(&mut bid_1, dec!(6), dec!(0.4));
+        assert!(matches.is_some_and(|matches| matches.is_empty()));
+        let yes_book = market_book.get_order_book(YES_OUTCOME).unwrap();
+        assert_eq!(
+            yes_book.bids.get(&dec!(0.4)).unwrap().total_quantity,
+            dec!(11)
+        );
+
+        let mut ask = get_yes_order(OrderSide::SELL, OrderType::LIMIT, dec!(0.4), dec!(3), None);
+        let matches = market_book.process_order(&mut ask);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].opposite_order_id, bid_1.id);
+
+        // raising the quantity sends the order to the back of the queue
+        bid_1.filled_quantity = dec!(3);
+        let matches = market_book.update_order(&mut bid_1, dec!(8), dec!(0.4));
+        assert!(matches.is_some_and(|matches| matches.is_empty()));
+
+        let mut ask = get_yes_order(OrderSide::SELL, OrderType::LIMIT, dec!(0.4), dec!(5), None);
+        let matches = market_book.process_order(&mut ask);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].opposite_order_id, bid_2.id);
+
+        let yes_book = market_book.get_order_book(YES_OUTCOME).unwrap();
+        let price_level = yes_book.bids.get(&dec!(0.4)).unwrap();
+        assert_eq!(price_level.total_quantity, dec!(5));
+        assert_eq!(price_level.orders.iter().next().unwrap().order_id, bid_1.id);
+    }
+
+    #[test]
+    fn test_repriced_order_is_matched() {
+        let mut market_book = MarketBook::new(dec!(0));
+
+        let ask = get_yes_order(OrderSide::SELL, OrderType::LIMIT, dec!(0.5), dec!(5), None);
+        let mut bid = get_yes_order(OrderSide::BUY, OrderType::LIMIT, dec!(0.4), dec!(5), None);
+        market_book.add_order(&ask);
+        market_book.add_order(&bid);
+
+        // new price crosses the resting ask
+        let matches = market_book
+            .update_order(&mut bid, dec!(5), dec!(0.5))
+            .unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].opposite_order_id, ask.id);
+        assert_eq!(matches[0].matched_quantity, dec!(5));
+        assert_eq!(bid.status, OrderStatus::FILLED);
+
+        let yes_book = market_book.get_order_book(YES_OUTCOME).unwrap();
+        assert!(yes_book.bids.is_empty());
+        assert!(yes_book.asks.is_empty());
+    }
+
+    fn get_yes_order(
+        side: OrderSide,
+        order_type: OrderType,
+        price: Decimal,
+        quantity: Decimal,
+        trigger_price: Option<Decimal>,
+    ) -> Order {
+        Order {
+            created_at: get_created_at(),
+            filled_quantity: Decimal::ZERO,
+            id: get_random_uuid(),
+            market_id: get_random_uuid(),
+            outcome: Outcome::YES,
+            price,
+            quantity,
+            side,
+            status: OrderStatus::OPEN,
+            updated_at: get_created_at(),
+            user_id: get_random_uuid(),
+            order_type,
+            trigger_price,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
+        }
+    }
+
+    #[test]
+    fn test_stop_loss_triggered_by_last_trade_price() {
+        let mut market_book = MarketBook::new(dec!(0));
+
+        let bid_1 = get_yes_order(OrderSide::BUY, OrderType::LIMIT, dec!(0.35), dec!(5), None);
+        let bid_2 = get_yes_order(OrderSide::BUY, OrderType::LIMIT, dec!(0.20), dec!(10), None);
+        let ask = get_yes_order(OrderSide::SELL, OrderType::LIMIT, dec!(0.40), dec!(5), None);
+        market_book.add_order(&bid_1);
+        market_book.add_order(&bid_2);
+        market_book.add_order(&ask);
+
+        // stop loss (market execution) for 4 shares if price falls to 0.30
+        let stop_order = get_yes_order(
+            OrderSide::SELL,
+            OrderType::StopLoss,
+            dec!(0),
+            dec!(4),
+            Some(dec!(0.30)),
+        );
+        let stop_order_id = stop_order.id;
+        assert!(market_book.add_trigger_order(stop_order));
+
+        // midpoint price is 0.375, nothing to trigger
+        assert!(market_book.execute_triggered_orders().is_empty());
+
+        // seller hits both bids, last trade price is 0.20
+        let mut sell_order =
+            get_yes_order(OrderSide::SELL, OrderType::LIMIT, dec!(0.20), dec!(7), None);
+        let matches = market_book.process_order(&mut sell_order);
+        assert_eq!(matches.len(), 2);
+        assert_eq!(
+            market_book.outcomes[&YES_OUTCOME].last_trade_price,
+            Some(dec!(0.20))
+        );
+
+        let executed = market_book.execute_triggered_orders();
+        assert_eq!(executed.len(), 1);
+
+        let (triggered_order, triggered_matches) = &executed[0];
+        assert_eq!(triggered_order.id, stop_order_id);
+        assert_eq!(triggered_order.order_type, OrderType::MARKET);
+        assert_eq!(triggered_order.status, OrderStatus::FILLED);
+        assert_eq!(triggered_order.filled_quantity, dec!(4));
+        assert_eq!(triggered_matches.len(), 1);
+        assert_eq!(triggered_matches[0].opposite_order_id, bid_2.id);
+        assert_eq!(triggered_matches[0].price, dec!(0.20));
+
+        let yes_book = market_book.get_order_book(YES_OUTCOME).unwrap();
+        assert_eq!(
+            yes_book.bids.get(&dec!(0.20)).unwrap().total_quantity,
+            dec!(4)
+        ); // 10 - 2 - 4
+
+        // already executed
+        assert!(!market_book.remove_trigger_order(stop_order_id));
+        assert!(market_book.execute_triggered_orders().is_empty());
+    }
+
+    #[test]
+    fn test_stop_limit_order_rests_in_book_after_trigger() {
+        let mut market_book = MarketBook::new(dec!(100));
+
+        // buy stop loss triggered when price rises to 0.45, current price is 0.5
+        let stop_order = get_yes_order(
+            OrderSide::BUY,
+            OrderType::StopLoss,
+            dec!(0.5),
+            dec!(10),
+            Some(dec!(0.45)),
+        );
+        let stop_order_id = stop_order.id;
+        assert!(market_book.add_trigger_order(stop_order));
+
+        let executed = market_book.execute_triggered_orders();
+        assert_eq!(executed.len(), 1);
+
+        let (triggered_order, triggered_matches) = &executed[0];
+        assert_eq!(triggered_order.id, stop_order_id);
+        assert_eq!(triggered_order.order_type, OrderType::LIMIT);
+        assert_eq!(triggered_order.status, OrderStatus::OPEN);
+        assert!(triggered_matches.is_empty());
+
+        let yes_book = market_book.get_order_book(YES_OUTCOME).unwrap();
+        assert_eq!(
+            yes_book.bids.get(&dec!(0.5)).unwrap().total_quantity,
+            dec!(10)
+        );
+    }
+
+    #[test]
+    fn test_quote_market_order_does_not_change_book() {
+        let mut market_book = MarketBook::new(dec!(0));
+
+        let bid = get_yes_order(OrderSide::BUY, OrderType::LIMIT, dec!(0.30), dec!(10), None);
+        let ask_1 = get_yes_order(OrderSide::SELL, OrderType::LIMIT, dec!(0.40), dec!(5), None);
+        let ask_2 = get_yes_order(
+            OrderSide::SELL,
+            OrderType::LIMIT,
+            dec!(0.45),
+            dec!(10),
+            None,
+        );
+        market_book.add_order(&bid);
+        market_book.add_order(&ask_1);
+        market_book.add_order(&ask_2);
+        let prices = market_book.current_prices();
+
+        let order = get_yes_order(OrderSide::BUY, OrderType::MARKET, dec!(0), dec!(0), None);
+        let quote = market_book
+            .quote_market_order(&order, QuoteSize::Quantity(dec!(8)))
+            .unwrap();
+        assert_eq!(
+            quote.fills,
+            vec![(dec!(0.40), dec!(5)), (dec!(0.45), dec!(3))]
+        );
+        assert_eq!(quote.filled_quantity, dec!(8));
+        assert_eq!(quote.total_cost, dec!(3.35));
+        assert_eq!(quote.average_price(), Some(dec!(0.41875)));
+        assert_eq!(quote.midpoint, dec!(0.375)); // (0.30 + 0.45) / 2
+        assert_eq!(quote.reason, None);
+
+        // budget larger than the book's liquidity is partially filled
+        let quote = market_book
+            .quote_market_order(&order, QuoteSize::Budget(dec!(10)))
+            .unwrap();
+        assert_eq!(
+            quote.fills,
+            vec![(dec!(0.40), dec!(5)), (dec!(0.45), dec!(10))]
+        );
+        assert_eq!(quote.total_cost, dec!(6.5));
+
+        // prices of the quote are the prices after the execution
+        let mut executed_book = market_book.clone();
+        let mut executed_order = order.clone();
+        executed_book.create_market_order(
+            &mut executed_order,
+            dec!(10),
+            MarketOrderLimits::default().with_allow_partial(true),
+        );
+        assert_eq!(quote.outcome_prices, executed_book.current_prices());
+        assert_eq!(quote.midpoint, executed_book.get_midpoint(YES_OUTCOME));
+        assert_ne!(quote.outcome_prices, prices);
+
+        // nothing is matched in the book
+        let yes_book = market_book.get_order_book(YES_OUTCOME).unwrap();
+        assert_eq!(
+            yes_book.asks.get(&dec!(0.40)).unwrap().total_quantity,
+            dec!(5)
+        );
+        assert_eq!(
+            yes_book.asks.get(&dec!(0.45)).unwrap().total_quantity,
+            dec!(10)
+        );
+        assert_eq!(market_book.outcomes[&YES_OUTCOME].last_trade_price, None);
+        assert_eq!(market_book.current_prices(), prices);
+    }
+
+    #[test]
+    fn test_quote_leaves_live_book_unchanged() {
+        let mut market_book = MarketBook::new(dec!(0));
+
+        let orders = [
+            get_yes_order(OrderSide::BUY, OrderType::LIMIT, dec!(0.30), dec!(10), None),
+            get_yes_order(OrderSide::BUY, OrderType::LIMIT, dec!(0.25), dec!(4), None),
+            get_yes_order(OrderSide::BUY, OrderType::LIMIT, dec!(0.25), dec!(6), None),
+            get_yes_order(OrderSide::SELL, OrderType::LIMIT, dec!(0.40), dec!(5), None),
+            get_yes_order(OrderSide::SELL, OrderType::LIMIT, dec!(0.45), dec!(3), None),
+            get_yes_order(OrderSide::SELL, OrderType::LIMIT, dec!(0.45), dec!(7), None),
+            get_yes_order(OrderSide::SELL, OrderType::LIMIT, dec!(0.50), dec!(8), None),
+        ];
+        for order in orders.iter() {
+            market_book.add_order(order);
+        }
+        let get_locations = |market_book: &MarketBook| {
+            orders
+                .iter()
+                .map(|order| {
+                    market_book
+                        .find_order(order.id)
+                        .map(|(price, entry)| (price, entry.total_quantity, entry.filled_quantity))
+                })
+                .collect::<Vec<_>>()
+        };
+        let locations = get_locations(&market_book);
+        let update = market_book.get_book_update();
+        let prices = market_book.current_prices();
+
+        // quotes sweeping several levels of both sides
+        let buy_order = get_yes_order(OrderSide::BUY, OrderType::MARKET, dec!(0), dec!(0), None);
+        let quote = market_book
+            .quote_market_order(&buy_order, QuoteSize::Quantity(dec!(12)))
+            .unwrap();
+        assert_eq!(
+            quote.fills,
+            vec![(dec!(0.40), dec!(5)), (dec!(0.45), dec!(7))]
+        );
+        let sell_order = get_yes_order(OrderSide::SELL, OrderType::MARKET, dec!(0), dec!(0), None);
+        let quote = market_book
+            .quote_market_order(&sell_order, QuoteSize::Quantity(dec!(15)))
+            .unwrap();
+        assert_eq!(
+            quote.fills,
+            vec![(dec!(0.30), dec!(10)), (dec!(0.25), dec!(5))]
+        );
+
+        // every order is still at it's level with it's quantity
+        assert_eq!(get_locations(&market_book), locations);
+        assert!(locations.iter().all(Option::is_some));
+        assert_eq!(market_book.current_prices(), prices);
+
+        // feed continues the sequence and has no changed level to publish
+        let next_update = market_book.get_book_update();
+        assert_eq!(next_update.sequence, update.sequence + 1);
+        assert!(!next_update.is_snapshot);
+        assert_eq!(next_update.books, update.books);
+        assert!(
+            next_update
+                .deltas
+                .iter()
+                .all(|delta| delta.bids.is_empty() && delta.asks.is_empty())
+        );
+    }
+
+    #[test]
+    fn test_trailing_stop_triggered_as_market_order() {
+        let mut market_book = MarketBook::new(dec!(0));
+
+        let bid_1 = get_yes_order(OrderSide::BUY, OrderType::LIMIT, dec!(0.35), dec!(5), None);
+        let bid_2 = get_yes_order(OrderSide::BUY, OrderType::LIMIT, dec!(0.20), dec!(10), None);
+        let ask = get_yes_order(OrderSide::SELL, OrderType::LIMIT, dec!(0.40), dec!(5), None);
+        market_book.add_order(&bid_1);
+        market_book.add_order(&bid_2);
+        market_book.add_order(&ask);
+
+        // sell trailing stop for 4 shares, 0.05 below the best price
+        let stop_order =
+            get_yes_order(OrderSide::SELL, OrderType::StopLoss, dec!(0), dec!(4), None);
+        let stop_order_id = stop_order.id;
+        let trailing_stop = TrailingStop {
+            order_id: stop_order_id,
+            trail_amount: Some(dec!(0.05)),
+            trail_percent: None,
+            best_price: None,
+        };
+        assert!(market_book.add_trailing_stop(stop_order, trailing_stop));
+        // trails from the midpoint price 0.375
+        assert_eq!(
+            market_book.take_moved_trailing_stops(),
+            vec![(
+                TrailingStop {
+                    best_price: Some(dec!(0.375)),
+                    ..trailing_stop
+                },
+                dec!(0.32)
+            )]
+        );
+
+        // buyer lifts the ask, trigger moves up with the last trade price
+        let mut buy_order =
+            get_yes_order(OrderSide::BUY, OrderType::LIMIT, dec!(0.40), dec!(5), None);
+        assert_eq!(market_book.process_order(&mut buy_order).len(), 1);
+        assert!(market_book.execute_triggered_orders().is_empty());
+        assert_eq!(
+            market_book.take_moved_trailing_stops(),
+            vec![(
+                TrailingStop {
+                    best_price: Some(dec!(0.40)),
+                    ..trailing_stop
+                },
+                dec!(0.35)
+            )]
+        );
+
+        // seller hits both bids, last trade price 0.20 is below the moved trigger
+        let mut sell_order =
+            get_yes_order(OrderSide::SELL, OrderType::LIMIT, dec!(0.20), dec!(7), None);
+        assert_eq!(market_book.process_order(&mut sell_order).len(), 2);
+
+        let executed = market_book.execute_triggered_orders();
+        assert_eq!(executed.len(), 1);
+        let (triggered_order, triggered_matches) = &executed[0];
+        assert_eq!(triggered_order.id, stop_order_id);
+        assert_eq!(triggered_order.order_type, OrderType::MARKET);
+        assert_eq!(triggered_order.status, OrderStatus::FILLED);
+        assert_eq!(triggered_order.filled_quantity, dec!(4));
+        assert_eq!(triggered_matches[0].opposite_order_id, bid_2.id);
+        assert!(market_book.take_moved_trailing_stops().is_empty());
+    }
+
+    #[test]
+    fn test_remove_trigger_order() {
+        let mut market_book = MarketBook::new(dec!(100));
+
+        let take_profit = get_yes_order(
+            OrderSide::SELL,
+            OrderType::TakeProfit,
+            dec!(0.8),
+            dec!(10),
+            Some(dec!(0.75)),
+        );
+        let take_profit_id = take_profit.id;
+        assert!(market_book.add_trigger_order(take_profit));
+
+        assert!(market_book.remove_trigger_order(take_profit_id));
+        assert!(!market_book.remove_trigger_order(take_profit_id));
+        assert!(market_book.execute_triggered_orders().is_empty());
+    }
+
+    /// Take profit and stop loss legs of an OCO group selling 10 shares, both are executed as market orders
+    fn get_oco_legs() -> (Order, Order) {
+        let take_profit = get_yes_order(
+            OrderSide::SELL,
+            OrderType::TakeProfit,
+            dec!(0),
+            dec!(10),
+            Some(dec!(0.6)),
+        );
+        let stop_loss = get_yes_order(
+            OrderSide::SELL,
+            OrderType::StopLoss,
+            dec!(0),
+            dec!(10),
+            Some(dec!(0.3)),
+        );
+        (take_profit, stop_loss)
+    }
+
+    #[test]
+    fn test_oco_fill_cancels_sibling() {
+        let mut market_book = MarketBook::new(dec!(0));
+        let bid = get_yes_order(OrderSide::BUY, OrderType::LIMIT, dec!(0.2), dec!(20), None);
+        let ask = get_yes_order(OrderSide::SELL, OrderType::LIMIT, dec!(0.7), dec!(20), None);
+        market_book.add_order(&bid);
+        market_book.add_order(&ask);
+
+        let (take_profit, stop_loss) = get_oco_legs();
+        let (take_profit_id, stop_loss_id) = (take_profit.id, stop_loss.id);
+        assert!(market_book.add_trigger_order(take_profit));
+        assert!(market_book.add_trigger_order(stop_loss));
+        assert!(market_book.execute_triggered_orders().is_empty());
+
+        // price falls to 0.2, stop loss is filled
+        let mut sell_order =
+            get_yes_order(OrderSide::SELL, OrderType::LIMIT, dec!(0.2), dec!(1), None);
+        market_book.process_order(&mut sell_order);
+        let executed = market_book.execute_triggered_orders();
+        assert_eq!(executed.len(), 1);
+        assert_eq!(executed[0].0.id, stop_loss_id);
+        assert_eq!(executed[0].0.status, OrderStatus::FILLED);
+
+        // group cancels the take profit, it's not executed once the price rises
+        assert!(market_book.remove_trigger_order(take_profit_id));
+        let mut buy_order =
+            get_yes_order(OrderSide::BUY, OrderType::LIMIT, dec!(0.7), dec!(1), None);
+        market_book.process_order(&mut buy_order);
+        assert_eq!(
+            market_book.outcomes[&YES_OUTCOME].last_trade_price,
+            Some(dec!(0.7))
+        );
+        assert!(market_book.execute_triggered_orders().is_empty());
+    }
+
+    #[test]
+    fn test_bracket_exits_activated_by_filled_entry() {
+        let mut market_book = MarketBook::new(dec!(0));
+        let bid = get_yes_order(OrderSide::BUY, OrderType::LIMIT, dec!(0.2), dec!(20), None);
+        market_book.add_order(&bid);
+
+        let mut entry = get_yes_order(OrderSide::BUY, OrderType::LIMIT, dec!(0.4), dec!(10), None);
+        assert!(market_book.process_order(&mut entry).is_empty());
+
+        let mut sell_order =
+            get_yes_order(OrderSide::SELL, OrderType::LIMIT, dec!(0.4), dec!(10), None);
+        let matches = market_book.process_order(&mut sell_order);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].opposite_order_id, entry.id);
+        assert_eq!(matches[0].matched_quantity, dec!(10));
+
+        // exits are sent to the book once the entry is filled, sized by the entry's fill
+        let (mut take_profit, mut stop_loss) = get_oco_legs();
+        take_profit.quantity = matches[0].matched_quantity;
+        stop_loss.quantity = matches[0].matched_quantity;
+        let stop_loss_id = stop_loss.id;
+        assert!(market_book.add_trigger_order(take_profit));
+        assert!(market_book.add_trigger_order(stop_loss));
+        assert!(market_book.execute_triggered_orders().is_empty());
+
+        let mut sell_order =
+            get_yes_order(OrderSide::SELL, OrderType::LIMIT, dec!(0.2), dec!(1), None);
+        market_book.process_order(&mut sell_order);
+        let executed = market_book.execute_triggered_orders();
+        assert_eq!(executed.len(), 1);
+        let (stop_loss, matches) = &executed[0];
+        assert_eq!(stop_loss.id, stop_loss_id);
+        assert_eq!(stop_loss.filled_quantity, dec!(10));
+        assert_eq!(matches[0].opposite_order_id, bid.id);
+
+        let yes_book = market_book.get_order_book(YES_OUTCOME).unwrap();
+        assert_eq!(
+            yes_book.bids.get(&dec!(0.2)).unwrap().total_quantity,
+            dec!(9)
+        ); // 20 - 1 - 10
+    }
+
+    #[test]
+    fn test_partial_fill_shrinks_linked_order() {
+        let mut market_book = MarketBook::new(dec!(0));
+        let bid = get_yes_order(OrderSide::BUY, OrderType::LIMIT, dec!(0.2), dec!(20), None);
+        let ask = get_yes_order(OrderSide::SELL, OrderType::LIMIT, dec!(0.65), dec!(1), None);
+        market_book.add_order(&bid);
+        market_book.add_order(&ask);
+
+        // take profit rests as a limit order once triggered
+        let (mut take_profit, stop_loss) = get_oco_legs();
+        take_profit.price = dec!(0.6);
+        let (take_profit_id, stop_loss_id) = (take_profit.id, stop_loss.id);
+        assert!(market_book.add_trigger_order(take_profit));
+        assert!(market_book.add_trigger_order(stop_loss));
+
+        let mut buy_order =
+            get_yes_order(OrderSide::BUY, OrderType::LIMIT, dec!(0.65), dec!(1), None);
+        market_book.process_order(&mut buy_order);
+        let executed = market_book.execute_triggered_orders();
+        assert_eq!(executed.len(), 1);
+        assert_eq!(executed[0].0.id, take_profit_id);
+        assert_eq!(executed[0].0.status, OrderStatus::OPEN);
+
+        let mut buy_order =
+            get_yes_order(OrderSide::BUY, OrderType::LIMIT, dec!(0.6), dec!(4), None);
+        let matches = market_book.process_order(&mut buy_order);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].opposite_order_id, take_profit_id);
+
+        // group shrinks the stop loss to the unfilled 6 shares of the take profit
+        assert!(market_book.resize_trigger_order(stop_loss_id, dec!(6)));
+
+        let mut sell_order =
+            get_yes_order(OrderSide::SELL, OrderType::LIMIT, dec!(0.2), dec!(1), None);
+        market_book.process_order(&mut sell_order);
+        let executed = market_book.execute_triggered_orders();
+        assert_eq!(executed.len(), 1);
+        let (stop_loss, _) = &executed[0];
+        assert_eq!(stop_loss.id, stop_loss_id);
+        assert_eq!(stop_loss.status, OrderStatus::FILLED);
+        assert_eq!(stop_loss.filled_quantity, dec!(6));
+
+        // already triggered, so it can't be resized anymore
+        assert!(!market_book.resize_trigger_order(stop_loss_id, dec!(3)));
+    }
+
+    #[test]
+    fn test_cancel_oco_leg_in_trigger_book() {
+        let mut market_book = MarketBook::new(dec!(0));
+        let bid = get_yes_order(OrderSide::BUY, OrderType::LIMIT, dec!(0.2), dec!(20), None);
+        let ask = get_yes_order(OrderSide::SELL, OrderType::LIMIT, dec!(0.7), dec!(20), None);
+        market_book.add_order(&bid);
+        market_book.add_order(&ask);
+
+        let (take_profit, stop_loss) = get_oco_legs();
+        let (take_profit_id, stop_loss_id) = (take_profit.id, stop_loss.id);
+        assert!(market_book.add_trigger_order(take_profit));
+        assert!(market_book.add_trigger_order(stop_loss));
+
+        // user cancels the take profit, stop loss is still waiting for it's trigger
+        assert!(market_book.remove_trigger_order(take_profit_id));
+        let mut buy_order =
+            get_yes_order(OrderSide::BUY, OrderType::LIMIT, dec!(0.7), dec!(1), None);
+        market_book.process_order(&mut buy_order);
+        assert!(market_book.execute_triggered_orders().is_empty());
+
+        // group cancels the stop loss, nothing is left to trigger
+        assert!(market_book.remove_trigger_order(stop_loss_id));
+        assert!(!market_book.remove_trigger_order(take_profit_id));
+        let mut sell_order =
+            get_yes_order(OrderSide::SELL, OrderType::LIMIT, dec!(0.2), dec!(1), None);
+        market_book.process_order(&mut sell_order);
+        assert!(market_book.execute_triggered_orders().is_empty());
+        assert_eq!(
+            market_book
+                .get_order_book(YES_OUTCOME)
+                .unwrap()
+                .bids
+                .get(&dec!(0.2))
+                .unwrap()
+                .total_quantity,
+            dec!(19)
+        );
+    }
+
+    #[test]
+    fn test_complementary_buy_orders_mint() {
+        let mut market_book = MarketBook::new(dec!(0));
+
+        let mut no_bid = get_yes_order(OrderSide::BUY, OrderType::LIMIT, dec!(0.4), dec!(10), None);
+        no_bid.outcome = Outcome::NO;
+        let no_bid_id = no_bid.id;
+        market_book.process_order(&mut no_bid);
+
+        // yes bid at 0.65 + no bid at 0.4 collateralize a new share pair
+        let mut yes_bid =
+            get_yes_order(OrderSide::BUY, OrderType::LIMIT, dec!(0.65), dec!(4), None);
+        let matches = market_book.process_order(&mut yes_bid);
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].opposite_order_id, no_bid_id);
+        assert_eq!(matches[0].match_type, MatchType::Mint);
+        assert_eq!(matches[0].matched_quantity, dec!(4));
+        assert_eq!(matches[0].price, dec!(0.6)); // resting no order is executed at it's own price
+        assert_eq!(yes_bid.status, OrderStatus::FILLED);
+
+        assert_eq!(
+            market_book.outcomes[&YES_OUTCOME].last_trade_price,
+            Some(dec!(0.6))
+        );
+        assert_eq!(
+            market_book.outcomes[&NO_OUTCOME].last_trade_price,
+            Some(dec!(0.4))
+        );
+
+        let no_book = market_book.get_order_book(NO_OUTCOME).unwrap();
+        assert_eq!(
+            no_book.bids.get(&dec!(0.4)).unwrap().total_quantity,
+            dec!(6)
+        );
+    }
+
+    #[test]
+    fn test_complementary_sell_orders_merge() {
+        let mut market_book = MarketBook::new(dec!(0));
+
+        let mut no_ask = get_yes_order(OrderSide::SELL, OrderType::LIMIT, dec!(0.3), dec!(5), None);
+        no_ask.outcome = Outcome::NO;
+        market_book.process_order(&mut no_ask);
+
+        let mut yes_ask =
+            get_yes_order(OrderSide::SELL, OrderType::LIMIT, dec!(0.6), dec!(8), None);
+        let matches = market_book.process_order(&mut yes_ask);
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].match_type, MatchType::Merge);
+        assert_eq!(matches[0].matched_quantity, dec!(5));
+        assert_eq!(matches[0].price, dec!(0.7));
+
+        // remaining quantity rests in the yes book
+        assert_eq!(yes_ask.filled_quantity, dec!(5));
+        assert_eq!(yes_ask.status, OrderStatus::OPEN);
+        let yes_book = market_book.get_order_book(YES_OUTCOME).unwrap();
+        assert_eq!(
+            yes_book.asks.get(&dec!(0.6)).unwrap().total_quantity,
+            dec!(3)
+        );
+        let no_book = market_book.get_order_book(NO_OUTCOME).unwrap();
+        assert!(no_book.asks.is_empty());
+    }
+
+    #[test]
+    fn test_direct_match_before_complementary_match() {
+        let mut market_book = MarketBook::new(dec!(0));
+
+        let mut yes_ask =
+            get_yes_order(OrderSide::SELL, OrderType::LIMIT, dec!(0.55), dec!(2), None);
+        let yes_ask_id = yes_ask.id;
+        market_book.process_order(&mut yes_ask);
+
+        let mut no_bid = get_yes_order(OrderSide::BUY, OrderType::LIMIT, dec!(0.45), dec!(5), None);
+        no_bid.outcome = Outcome::NO;
+        market_book.process_order(&mut no_bid);
+
+        let mut yes_bid =
+            get_yes_order(OrderSide::BUY, OrderType::LIMIT, dec!(0.55), dec!(4), None);
+        let matches = market_book.process_order(&mut yes_bid);
+
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].opposite_order_id, yes_ask_id);
+        assert_eq!(matches[0].match_type, MatchType::Transfer);
+        assert_eq!(matches[1].match_type, MatchType::Mint);
+        assert_eq!(matches[1].matched_quantity, dec!(2));
+        assert_eq!(yes_bid.status, OrderStatus::FILLED);
+    }
+
+    #[test]
+    fn test_complementary_orders_not_crossing() {
+        let mut market_book = MarketBook::new(dec!(0));
+
+        let mut no_bid = get_yes_order(OrderSide::BUY, OrderType::LIMIT, dec!(0.4), dec!(10), None);
+        no_bid.outcome = Outcome::NO;
+        market_book.process_order(&mut no_bid);
+
+        // 0.55 + 0.4 doesn't collateralize a share pair
+        let mut yes_bid =
+            get_yes_order(OrderSide::BUY, OrderType::LIMIT, dec!(0.55), dec!(4), None);
+        let matches = market_book.process_order(&mut yes_bid);
+
+        assert!(matches.is_empty());
+        assert_eq!(yes_bid.status, OrderStatus::OPEN);
+    }
+
+    #[test]
+    fn test_market_book_snapshot_roundtrip() {
+        let mut market_book = MarketBook::new(dec!(100));
+
+        let bid = get_yes_order(OrderSide::BUY, OrderType::LIMIT, dec!(0.4), dec!(10), None);
+        let ask = get_yes_order(OrderSide::SELL, OrderType::LIMIT, dec!(0.6), dec!(5), None);
+        let stop_order = get_yes_order(
+            OrderSide::SELL,
+            OrderType::StopLoss,
+            dec!(0),
+            dec!(4),
+            Some(dec!(0.30)),
+        );
+        market_book.add_order(&bid);
+        market_book.add_order(&ask);
+        let stop_order_id = stop_order.id;
+        market_book.add_trigger_order(stop_order);
+
+        let snapshot = serialize_to_message_pack(&market_book).unwrap();
+        let mut restored = deserialize_from_message_pack::<MarketBook>(&snapshot).unwrap();
+
+        assert_eq!(
+            restored.current_price(YES_OUTCOME),
+            market_book.current_price(YES_OUTCOME)
+        );
+        assert_eq!(restored.trigger_book.orders.len(), 1);
+        assert_eq!(restored.trigger_book.orders[0].id, stop_order_id);
+
+        let (price, entry) = restored.find_order(bid.id).unwrap();
+        assert_eq!(price, dec!(0.4));
+        assert_eq!(entry.total_quantity, dec!(10));
+
+        // only the bid is still live
+        let removed = restored.retain_orders(&|order_id| *order_id == bid.id);
+        assert_eq!(removed, 2);
+        assert!(restored.trigger_book.orders.is_empty());
+        assert!(restored.find_order(ask.id).is_none());
+    }
+
+    fn get_market_maker_book() -> MarketBook {
+        MarketBook::new(dec!(100)).with_rules(MarketRules::default().with_market_maker(true))
+    }
+
+    #[test]
+    fn test_market_maker_fills_limit_order_till_limit_price() {
+        let mut market_book = get_market_maker_book();
+        assert_eq!(market_book.current_price(YES_OUTCOME), dec!(0.5));
+
+        // b * ln(0.75 / 0.25) = 109.86.. shares move the price from 0.5 to 0.75
+        let mut yes_bid = get_yes_order(
+            OrderSide::BUY,
+            OrderType::LIMIT,
+            dec!(0.75),
+            dec!(200),
+            None,
+        );
+        let matches = market_book.process_order(&mut yes_bid);
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].match_type, MatchType::MarketMaker);
+        assert_eq!(matches[0].opposite_order_id, Uuid::nil());
+        assert_eq!(matches[0].matched_quantity, dec!(109.86));
+        assert!(matches[0].price > dec!(0.5) && matches[0].price < dec!(0.75));
+
+        // remaining quantity rests in the book
+        assert_eq!(yes_bid.filled_quantity, dec!(109.86));
+        assert_eq!(yes_bid.status, OrderStatus::OPEN);
+        let yes_book = market_book.get_order_book(YES_OUTCOME).unwrap();
+        assert_eq!(
+            yes_book.bids.get(&dec!(0.75)).unwrap().total_quantity,
+            dec!(90.14)
+        );
+
+        let market_maker = market_book.market_maker.as_ref().unwrap();
+        assert_eq!(market_maker.q_yes, dec!(109.86));
+        assert!(market_book.current_price(YES_OUTCOME) > dec!(0.749));
+        assert!(market_book.current_price(YES_OUTCOME) <= dec!(0.75));
+        assert_eq!(
+            market_book.current_price(YES_OUTCOME) + market_book.current_price(NO_OUTCOME),
+            Decimal::ONE
+        );
+    }
+
+    #[test]
+    fn test_book_is_matched_before_market_maker() {
+        let mut market_book = get_market_maker_book();
+
+        let mut yes_ask =
+            get_yes_order(OrderSide::SELL, OrderType::LIMIT, dec!(0.5), dec!(5), None);
+        let yes_ask_id = yes_ask.id;
+        market_book.process_order(&mut yes_ask);
+
+        let mut yes_bid =
+            get_yes_order(OrderSide::BUY, OrderType::LIMIT, dec!(0.55), dec!(10), None);
+        let matches = market_book.process_order(&mut yes_bid);
+
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].opposite_order_id, yes_ask_id);
+        assert_eq!(matches[0].match_type, MatchType::Transfer);
+        assert_eq!(matches[1].match_type, MatchType::MarketMaker);
+        assert_eq!(matches[1].matched_quantity, dec!(5));
+        assert_eq!(yes_bid.status, OrderStatus::FILLED);
+    }
+
+    #[test]
+    fn test_market_order_spends_budget_on_market_maker() {
+        let mut market_book = get_market_maker_book();
+
+        let mut order = get_yes_order(OrderSide::BUY, OrderType::MARKET, dec!(0), dec!(0), None);
+        let matches = market_book.create_market_order(
+            &mut order,
+            dec!(10),
+            MarketOrderLimits::new(None, None),
+        );
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].match_type, MatchType::MarketMaker);
+        assert_eq!(order.status, OrderStatus::FILLED);
+
+        let executed_value = matches[0].price * matches[0].matched_quantity;
+        assert!((executed_value - dec!(10)).abs() < dec!(0.000001));
+        assert_eq!(
+            market_book.market_maker.as_ref().unwrap().q_yes,
+            order.filled_quantity
+        );
+    }
+
+    #[test]
+    fn test_fok_and_post_only_orders_with_market_maker() {
+        let mut market_book = get_market_maker_book();
+
+        // market maker can't fill 200 shares under 0.75
+        let mut fok_order = get_yes_order(
+            OrderSide::BUY,
+            OrderType::LIMIT,
+            dec!(0.75),
+            dec!(200),
+            None,
+        );
+        fok_order.time_in_force = TimeInForce::FOK;
+        let matches = market_book.process_order(&mut fok_order);
+        assert!(matches.is_empty());
+        assert_eq!(fok_order.status, OrderStatus::CANCELLED);
+        assert_eq!(market_book.market_maker.as_ref().unwrap().q_yes, dec!(0));
+
+        let mut fok_order = get_yes_order(
+            OrderSide::BUY,
+            OrderType::LIMIT,
+            dec!(0.75),
+            dec!(100),
+            None,
+        );
+        fok_order.time_in_force = TimeInForce::FOK;
+        market_book.process_order(&mut fok_order);
+        assert_eq!(fok_order.status, OrderStatus::FILLED);
+
+        // post only order never takes liquidity from the market maker
+        let mut post_only_order =
+            get_yes_order(OrderSide::SELL, OrderType::LIMIT, dec!(0.5), dec!(10), None);
+        post_only_order.post_only = true;
+        let matches = market_book.process_order(&mut post_only_order);
+        assert!(matches.is_empty());
+        assert_eq!(post_only_order.status, OrderStatus::OPEN);
+        assert_eq!(market_book.market_maker.as_ref().unwrap().q_yes, dec!(100));
+    }
+
+    fn get_auction_book() -> MarketBook {
+        let rules = MarketRules::default().with_auction_opens_at(Some(get_created_at()));
+        MarketBook::new(dec!(0)).with_rules(rules)
+    }
+
+    #[test]
+    fn test_orders_rest_during_call_auction() {
+        let mut market_book = get_auction_book();
+
+        let mut ask = get_yes_order(OrderSide::SELL, OrderType::LIMIT, dec!(0.5), dec!(5), None);
+        let mut bid = get_yes_order(OrderSide::BUY, OrderType::LIMIT, dec!(0.6), dec!(5), None);
+        assert!(market_book.process_order(&mut ask).is_empty());
+        assert!(market_book.process_order(&mut bid).is_empty());
+        assert_eq!(bid.status, OrderStatus::OPEN);
+
+        // book stays crossed till the auction is uncrossed
+        let yes_book = market_book.get_order_book(YES_OUTCOME).unwrap();
+        assert_eq!(yes_book.best_bid(), Some(dec!(0.6)));
+        assert_eq!(yes_book.best_ask(), Some(dec!(0.5)));
+
+        let mut ioc_order =
+            get_yes_order(OrderSide::BUY, OrderType::LIMIT, dec!(0.6), dec!(2), None);
+        ioc_order.time_in_force = TimeInForce::IOC;
+        assert!(market_book.process_order(&mut ioc_order).is_empty());
+        assert_eq!(ioc_order.status, OrderStatus::CANCELLED);
+        assert_eq!(
+            ioc_order.status_reason.as_deref(),
+            Some(AUCTION_REJECT_REASON)
+        );
+
+        let mut market_order =
+            get_yes_order(OrderSide::BUY, OrderType::MARKET, dec!(0), dec!(2), None);
+        let matches =
+            market_book.create_market_order(&mut market_order, dec!(2), Default::default());
+        assert!(matches.is_empty());
+        assert_eq!(market_order.status, OrderStatus::CANCELLED);
+
+        let indications = market_book.auction_indications().unwrap();
+        assert_eq!(indications.len(), 2);
+        assert_eq!(indications[0].1.volume, dec!(5));
+    }
+
+    #[test]
+    fn test_uncross_binary_call_auction() {
+        let mut market_book = get_auction_book();
+
+        let mut no_ask = get_yes_order(OrderSide::SELL, OrderType::LIMIT, dec!(0.3), dec!(3), None);
+        no_ask.outcome = Outcome::NO;
+        let mut yes_bid =
+            get_yes_order(OrderSide::BUY, OrderType::LIMIT, dec!(0.6), dec!(10), None);
+        let mut low_yes_bid =
+            get_yes_order(OrderSide::BUY, OrderType::LIMIT, dec!(0.55), dec!(2), None);
+        let mut yes_ask =
+            get_yes_order(OrderSide::SELL, OrderType::LIMIT, dec!(0.5), dec!(4), None);
+        let mut no_bid = get_yes_order(OrderSide::BUY, OrderType::LIMIT, dec!(0.45), dec!(6), None);
+        no_bid.outcome = Outcome::NO;
+        for order in [
+            &mut no_ask,
+            &mut yes_bid,
+            &mut low_yes_bid,
+            &mut yes_ask,
+            &mut no_bid,
+        ] {
+            assert!(market_book.process_order(order).is_empty());
+        }
+
+        // in yes terms, demand 3 @ 0.7, 10 @ 0.6, 2 @ 0.55 and supply 4 @ 0.5, 6 @ 0.55
+        let indications = market_book.auction_indications().unwrap();
+        assert_eq!(indications[0].0, YES_OUTCOME);
+        assert_eq!(indications[0].1.price, dec!(0.6));
+        assert_eq!(indications[0].1.volume, dec!(10));
+        assert_eq!(indications[0].1.imbalance, dec!(3));
+        assert_eq!(indications[1].0, NO_OUTCOME);
+        assert_eq!(indications[1].1.price, dec!(0.4));
+        assert_eq!(indications[1].1.imbalance, dec!(-3));
+
+        let matches = market_book.uncross_auction();
+        assert_eq!(matches.len(), 3);
+
+        // no seller and yes seller merge a pair, no shares are executed at 1 - 0.6
+        assert_eq!(matches[0].order_id, no_ask.id);
+        assert_eq!(matches[0].opposite_order_id, yes_ask.id);
+        assert_eq!(matches[0].match_type, MatchType::Merge);
+        assert_eq!(matches[0].price, dec!(0.4));
+        assert_eq!(matches[0].matched_quantity, dec!(3));
+
+        assert_eq!(matches[1].order_id, yes_bid.id);
+        assert_eq!(matches[1].match_type, MatchType::Transfer);
+        assert_eq!(matches[1].price, dec!(0.6));
+        assert_eq!(matches[1].opposite_order_filled_quantity, dec!(4));
+
+        // no buyer below it's limit mints a pair with the yes buyer
+        assert_eq!(matches[2].opposite_order_id, no_bid.id);
+        assert_eq!(matches[2].match_type, MatchType::Mint);
+        assert_eq!(matches[2].price, dec!(0.6));
+        assert_eq!(matches[2].matched_quantity, dec!(6));
+
+        assert_eq!(market_book.auction_opens_at(), None);
+        assert_eq!(market_book.auction_indications(), None);
+        assert_eq!(
+            market_book.outcomes[&YES_OUTCOME].last_trade_price,
+            Some(dec!(0.6))
+        );
+        assert_eq!(
+            market_book.outcomes[&NO_OUTCOME].last_trade_price,
+            Some(dec!(0.4))
+        );
+
+        // remaining quantity of the yes buyer rests, the book isn't crossed anymore
+        let yes_book = market_book.get_order_book(YES_OUTCOME).unwrap();
+        assert_eq!(
+            yes_book.bids.get(&dec!(0.6)).unwrap().total_quantity,
+            dec!(3)
+        );
+        assert_eq!(yes_book.best_bid(), Some(dec!(0.6)));
+        assert!(yes_book.asks.is_empty());
+        assert!(
+            market_book
+                .get_order_book(NO_OUTCOME)
+                .unwrap()
+                .bids
+                .is_empty()
+        );
+
+        // continuous matching starts after the auction
+        let mut sell_order =
+            get_yes_order(OrderSide::SELL, OrderType::LIMIT, dec!(0.6), dec!(1), None);
+        let matches = market_book.process_order(&mut sell_order);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(sell_order.status, OrderStatus::FILLED);
+    }
+
+    fn get_price_band_book() -> MarketBook {
+        let rules = MarketRules::default().with_price_band(Some(dec!(0.15)), 300);
+        MarketBook::new(dec!(0)).with_rules(rules)
+    }
+
+    #[test]
+    fn test_limit_order_outside_price_band() {
+        let mut market_book = get_price_band_book();
+
+        // reference price of yes is 0.5, orders are accepted between 0.425 and 0.575
+        let mut bid = get_yes_order(OrderSide::BUY, OrderType::LIMIT, dec!(0.6), dec!(5), None);
+        assert!(market_book.process_order(&mut bid).is_empty());
+        assert_eq!(bid.status, OrderStatus::CANCELLED);
+        assert_eq!(
+            bid.status_reason.as_deref(),
+            Some(PRICE_OUTSIDE_BAND_REASON)
+        );
+
+        let mut ask = get_yes_order(OrderSide::SELL, OrderType::LIMIT, dec!(0.55), dec!(5), None);
+        assert!(market_book.process_order(&mut ask).is_empty());
+        assert_eq!(ask.status, OrderStatus::OPEN);
+        assert!(
+            market_book
+                .update_order(&mut ask, dec!(5), dec!(0.4))
+                .is_none()
+        );
+
+        // rejected limit orders don't halt the trading
+        assert!(!market_book.is_trading_halted());
+        let yes_book = market_book.get_order_book(YES_OUTCOME).unwrap();
+        assert_eq!(yes_book.best_ask(), Some(dec!(0.55)));
+    }
+
+    #[test]
+    fn test_market_order_breaching_price_band_halts_trading() {
+        let mut market_book = get_price_band_book();
+        // resting asks placed before the band, 0.6 is beyond the upper edge of 0.575
+        for price in [dec!(0.55), dec!(0.6)] {
+            market_book.add_order(&get_yes_order(
+                OrderSide::SELL,
+                OrderType::LIMIT,
+                price,
+                dec!(4),
+                None,
+            ));
+        }
+
+        let mut market_order =
+            get_yes_order(OrderSide::BUY, OrderType::MARKET, dec!(0), dec!(0), None);
+        let matches = market_book.create_market_order(
+            &mut market_order,
+            dec!(5),
+            MarketOrderLimits::default().with_allow_partial(true),
+        );
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].price, dec!(0.55));
+        assert_eq!(market_order.filled_quantity, dec!(4));
+        assert_eq!(
+            market_order.status_reason.as_deref(),
+            Some(PRICE_BAND_BREACH_REASON)
+        );
+        assert!(market_book.is_trading_halted());
+
+        // new orders are rejected while halted, resting orders stay in the book
+        let mut bid = get_yes_order(OrderSide::BUY, OrderType::LIMIT, dec!(0.6), dec!(1), None);
+        assert!(market_book.process_order(&mut bid).is_empty());
+        assert_eq!(bid.status_reason.as_deref(), Some(TRADING_HALTED_REASON));
+        let mut stop_order = get_yes_order(
+            OrderSide::BUY,
+            OrderType::StopLoss,
+            dec!(0),
+            dec!(1),
+            Some(dec!(0.5)),
+        );
+        stop_order.status = OrderStatus::OPEN;
+        assert!(market_book.add_trigger_order(stop_order));
+        assert!(market_book.execute_triggered_orders().is_empty());
+
+        // band is rolled to the last trade price, 0.55 * 1.15 = 0.6325
+        let resumed_at = get_created_at();
+        market_book.resume_trading(resumed_at);
+        assert!(!market_book.is_trading_halted());
+        assert!(!market_book.is_price_band_due(resumed_at));
+        assert!(market_book.is_price_band_due(resumed_at + chrono::Duration::seconds(300)));
+
+        // stop order triggered during the halt is executed once it's resumed
+        let executed = market_book.execute_triggered_orders();
+        assert_eq!(executed.len(), 1);
+        assert_eq!(executed[0].1[0].price, dec!(0.6));
+    }
+
+    #[test]
+    fn test_frozen_market() {
+        let mut market_book = MarketBook::new(dec!(0));
+        let mut ask = get_yes_order(OrderSide::SELL, OrderType::LIMIT, dec!(0.5), dec!(5), None);
+        market_book.process_order(&mut ask);
+        let mut stop_order = get_yes_order(
+            OrderSide::BUY,
+            OrderType::StopLoss,
+            dec!(0),
+            dec!(1),
+            Some(dec!(0.5)),
+        );
+        stop_order.status = OrderStatus::OPEN;
+        assert!(market_book.add_trigger_order(stop_order));
+
+        market_book.set_frozen(true);
+
+        // nothing is matched while the market is frozen
+        let mut bid = get_yes_order(OrderSide::BUY, OrderType::LIMIT, dec!(0.5), dec!(1), None);
+        assert!(market_book.process_order(&mut bid).is_empty());
+        assert_eq!(bid.status, OrderStatus::CANCELLED);
+        assert_eq!(bid.status_reason.as_deref(), Some(MARKET_FROZEN_REASON));
+
+        let mut market_order =
+            get_yes_order(OrderSide::BUY, OrderType::MARKET, dec!(0), dec!(0), None);
+        assert!(
+            market_book
+                .create_market_order(&mut market_order, dec!(5), Default::default())
+                .is_empty()
+        );
+        assert_eq!(
+            market_order.status_reason.as_deref(),
+            Some(MARKET_FROZEN_REASON)
+        );
+        assert!(
+            market_book
+                .update_order(&mut ask, dec!(5), dec!(0.45))
+                .is_none()
+        );
+        assert!(market_book.execute_triggered_orders().is_empty());
+
+        // resting orders are kept and can still be cancelled
+        let yes_book = market_book.get_order_book(YES_OUTCOME).unwrap();
+        assert_eq!(yes_book.best_ask(), Some(dec!(0.5)));
+
+        // trigger order waiting during the freeze is executed once the market is reopened
+        market_book.set_frozen(false);
+        let executed = market_book.execute_triggered_orders();
+        assert_eq!(executed.len(), 1);
+        assert_eq!(executed[0].1[0].price, dec!(0.5));
+        assert!(market_book.remove_order(ask.id));
+    }
 }
@@ -1,3 +1,11 @@
+pub(crate) mod book_feed;
+pub(crate) mod call_auction;
+pub(crate) mod circuit_breaker;
 pub(crate) mod global_book;
+pub(crate) mod journal;
+pub(crate) mod lmsr;
 pub(crate) mod market_book;
+pub(crate) mod order_queue;
 pub(crate) mod outcome_book;
+pub(crate) mod price_model;
+pub(crate) mod trigger_book;
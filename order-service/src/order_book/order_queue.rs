@@ -0,0 +1,257 @@
+/*
+ * Order queue keeps the orders of a price level in time priority (FIFO).
+ *
+ * 1. Entries are stored in the slots of a slab and linked in arrival order, so an order can be removed (or moved to the end) by it's slot in O(1).
+ * 2. Slot of an order never changes while it's in the queue, so it can be indexed by the outcome book. Freed slots are reused by new orders.
+ * 3. Queue is serialized as a plain list of entries in time priority, slots are reassigned on deserialize.
+ */
+
+use serde::{Deserialize, Serialize};
+
+use super::outcome_book::OrderBookEntry;
+
+#[derive(Debug, Clone)]
+struct Node {
+    entry: OrderBookEntry,
+    prev: Option<usize>,
+    next: Option<usize>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(from = "Vec<OrderBookEntry>", into = "Vec<OrderBookEntry>")]
+pub(crate) struct OrderQueue {
+    slots: Vec<Option<Node>>,
+    free_slots: Vec<usize>,
+    head: Option<usize>,
+    tail: Option<usize>,
+    len: usize,
+}
+
+impl OrderQueue {
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Adds the entry at the end of the queue, returns it's slot
+    pub(crate) fn push_back(&mut self, entry: OrderBookEntry) -> usize {
+        let node = Node {
+            entry,
+            prev: self.tail,
+            next: None,
+        };
+        let slot = match self.free_slots.pop() {
+            Some(slot) => {
+                self.slots[slot] = Some(node);
+                slot
+            }
+            None => {
+                self.slots.push(Some(node));
+                self.slots.len() - 1
+            }
+        };
+
+        match self.tail {
+            Some(tail) => self.node_mut(tail).next = Some(slot),
+            None => self.head = Some(slot),
+        }
+        self.tail = Some(slot);
+        self.len += 1;
+        slot
+    }
+
+    pub(crate) fn remove(&mut self, slot: usize) -> Option<OrderBookEntry> {
+        let node = self.slots.get_mut(slot)?.take()?;
+        self.unlink(node.prev, node.next);
+        self.free_slots.push(slot);
+        self.len -= 1;
+        Some(node.entry)
+    }
+
+    /// Moves the entry to the end of the queue (entry loses it's time priority), slot of the entry is kept
+    pub(crate) fn move_to_back(&mut self, slot: usize) {
+        if self.tail == Some(slot) {
+            return;
+        }
+        let Some((prev, next)) = self
+            .slots
+            .get(slot)
+            .and_then(Option::as_ref)
+            .map(|node| (node.prev, node.next))
+        else {
+            return;
+        };
+        self.unlink(prev, next);
+
+        let tail = self.tail;
+        let node = self.node_mut(slot);
+        node.prev = tail;
+        node.next = None;
+        match tail {
+            Some(tail) => self.node_mut(tail).next = Some(slot),
+            None => self.head = Some(slot),
+        }
+        self.tail = Some(slot);
+    }
+
+    pub(crate) fn get(&self, slot: usize) -> Option<&OrderBookEntry> {
+        self.slots.get(slot)?.as_ref().map(|node| &node.entry)
+    }
+
+    pub(crate) fn get_mut(&mut self, slot: usize) -> Option<&mut OrderBookEntry> {
+        self.slots
+            .get_mut(slot)?
+            .as_mut()
+            .map(|node| &mut node.entry)
+    }
+
+    pub(crate) fn first_slot(&self) -> Option<usize> {
+        self.head
+    }
+
+    pub(crate) fn next_slot(&self, slot: usize) -> Option<usize> {
+        self.slots.get(slot)?.as_ref()?.next
+    }
+
+    /// Iterates over the entries in time priority
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &OrderBookEntry> {
+        self.iter_slots().map(|(_, entry)| entry)
+    }
+
+    /// Iterates over the slots and entries in time priority
+    pub(crate) fn iter_slots(&self) -> impl Iterator<Item = (usize, &OrderBookEntry)> {
+        std::iter::successors(self.head, |slot| self.next_slot(*slot))
+            .filter_map(|slot| self.get(slot).map(|entry| (slot, entry)))
+    }
+
+    fn unlink(&mut self, prev: Option<usize>, next: Option<usize>) {
+        match prev {
+            Some(prev) => self.node_mut(prev).next = next,
+            None => self.head = next,
+        }
+        match next {
+            Some(next) => self.node_mut(next).prev = prev,
+            None => self.tail = prev,
+        }
+    }
+
+    fn node_mut(&mut self, slot: usize) -> &mut Node {
+        self.slots[slot]
+            .as_mut()
+            .expect("linked slot of order queue must be occupied")
+    }
+}
+
+impl From<Vec<OrderBookEntry>> for OrderQueue {
+    fn from(entries: Vec<OrderBookEntry>) -> Self {
+        let mut queue = OrderQueue::default();
+        for entry in entries {
+            queue.push_back(entry);
+        }
+        queue
+    }
+}
+
+impl From<OrderQueue> for Vec<OrderBookEntry> {
+    fn from(queue: OrderQueue) -> Self {
+        queue.iter().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rust_decimal::Decimal;
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn get_entry() -> OrderBookEntry {
+        OrderBookEntry {
+            user_id: Uuid::new_v4(),
+            order_id: Uuid::new_v4(),
+            total_quantity: Decimal::ONE,
+            filled_quantity: Decimal::ZERO,
+            display_quantity: None,
+            slice_quantity: Decimal::ZERO,
+        }
+    }
+
+    fn get_order_ids(queue: &OrderQueue) -> Vec<Uuid> {
+        queue.iter().map(|entry| entry.order_id).collect()
+    }
+
+    #[test]
+    fn test_remove_from_middle() {
+        let mut queue = OrderQueue::default();
+        let entries = [get_entry(), get_entry(), get_entry()];
+        let slots = entries
+            .iter()
+            .map(|entry| queue.push_back(entry.clone()))
+            .collect::<Vec<_>>();
+
+        let removed = queue.remove(slots[1]).unwrap();
+        assert_eq!(removed.order_id, entries[1].order_id);
+        assert!(queue.remove(slots[1]).is_none());
+        assert_eq!(queue.len(), 2);
+        assert_eq!(
+            get_order_ids(&queue),
+            vec![entries[0].order_id, entries[2].order_id]
+        );
+
+        // freed slot is reused, new entry is still at the end
+        let entry = get_entry();
+        assert_eq!(queue.push_back(entry.clone()), slots[1]);
+        assert_eq!(
+            get_order_ids(&queue),
+            vec![entries[0].order_id, entries[2].order_id, entry.order_id]
+        );
+    }
+
+    #[test]
+    fn test_move_to_back_keeps_slot() {
+        let mut queue = OrderQueue::default();
+        let entries = [get_entry(), get_entry(), get_entry()];
+        let slots = entries
+            .iter()
+            .map(|entry| queue.push_back(entry.clone()))
+            .collect::<Vec<_>>();
+
+        queue.move_to_back(slots[0]);
+        assert_eq!(
+            get_order_ids(&queue),
+            vec![
+                entries[1].order_id,
+                entries[2].order_id,
+                entries[0].order_id
+            ]
+        );
+        assert_eq!(queue.get(slots[0]).unwrap().order_id, entries[0].order_id);
+        assert_eq!(queue.iter().next().unwrap().order_id, entries[1].order_id);
+
+        queue.remove(slots[1]);
+        queue.remove(slots[2]);
+        assert_eq!(queue.first_slot(), Some(slots[0]));
+        assert_eq!(queue.next_slot(slots[0]), None);
+    }
+
+    #[test]
+    fn test_serialized_in_time_priority() {
+        let mut queue = OrderQueue::default();
+        let entries = [get_entry(), get_entry(), get_entry()];
+        let slots = entries
+            .iter()
+            .map(|entry| queue.push_back(entry.clone()))
+            .collect::<Vec<_>>();
+        queue.move_to_back(slots[0]);
+        queue.remove(slots[1]);
+
+        let serialized = serde_json::to_string(&queue).unwrap();
+        let restored = serde_json::from_str::<OrderQueue>(&serialized).unwrap();
+
+        assert_eq!(get_order_ids(&restored), get_order_ids(&queue));
+        assert_eq!(restored.len(), 2);
+    }
+}
@@ -7,52 +7,432 @@
  *
  * ## Market order rules
  *
- * 1. Partial matches are not allowed.
- * 2. If order is partially filled, then it's going to be cancelled.
+ * 1. Partial matches are not allowed by default, order which can't be filled for it's whole budget is cancelled.
+ * 2. Orders with `allow_partial` (`MarketOrderLimits::allow_partial`) fill what the liquidity allows, order is filled for the executed quantity and unused budget is never charged.
  * 3. Market orders are not going to be added in the order book.
+ * 4. Matching stops at the worst price of the order (`MarketOrderLimits`), order is cancelled if the budget can't be filled within it.
+ *
+ *
+ * ## Market maker rules
+ *
+ * 1. Markets with a market maker (`MarketRules::market_maker`) fill the remaining quantity of limit and market orders from the LMSR market maker once the book (including the complementary book) is exhausted.
+ * 2. Limit orders are filled by the market maker till it's price reaches the limit price, filled quantity is rounded down to the lot size. Post only orders never trade with it.
+ * 3. Market orders spend the remaining budget on the market maker within their worst price.
+ * 4. Market maker is executed at the average price of the filled quantity, it never rests orders in the book.
+ *
+ *
+ * ## Iceberg order rules
+ *
+ * 1. Only the current slice (up to `display_quantity`) of iceberg order is visible in the published book, rest of the order is hidden.
+ * 2. Once the slice is fully matched, it's refreshed from the hidden quantity and moved to the end of it's price level (loses time priority).
+ * 3. Hidden quantity is still matchable, incoming order matches refreshed slices after the other orders of the level.
+ *
+ *
+ * ## Order index
+ *
+ * 1. Every resting order is indexed by it's id (side, price and slot in the price level's queue), so orders are found, cancelled and amended in O(1).
+ * 2. Index is updated along with the price levels, it's not serialized and rebuilt from the price levels on deserialize.
  */
 
-use std::collections::BTreeMap;
+use std::{
+    collections::{BTreeMap, HashMap},
+    str::FromStr,
+};
 
+use chrono::NaiveDateTime;
 use db_service::schema::{
-    enums::{OrderSide, OrderStatus, OrderType},
-    orders::Order,
+    enums::{OrderSide, OrderStatus, OrderType, PriceModelKind, SelfTradePrevention, TimeInForce},
+    market::Market,
+    orders::{Order, OrderWithMarket},
 };
 use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
 use utility_helpers::{
     log_error, log_info,
+    nats_helper::types::MarketOrderCreateMessage,
     types::{OrderBookDataStruct, OrderLevel},
 };
 use uuid::Uuid;
 
-#[derive(Default, Debug)]
+use super::{lmsr::LmsrMarketMaker, order_queue::OrderQueue};
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
 pub(crate) struct PriceLevel {
-    pub(crate) orders: Vec<OrderBookEntry>, // should I consider using hashmap here for O(1) lookup
+    pub(crate) orders: OrderQueue,
     pub(crate) total_quantity: Decimal,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub(crate) struct OrderBookEntry {
     pub user_id: Uuid,
     pub order_id: Uuid,
     pub total_quantity: Decimal,
     pub filled_quantity: Decimal,
+
+    #[serde(default)]
+    pub display_quantity: Option<Decimal>, // slice size of iceberg order
+    #[serde(default)]
+    pub slice_quantity: Decimal, // unmatched quantity of the current iceberg slice
+}
+
+impl OrderBookEntry {
+    /// Remaining quantity which can be matched (and published) right now, iceberg order only exposes it's current slice
+    pub(crate) fn visible_quantity(&self) -> Decimal {
+        let remaining = self.total_quantity - self.filled_quantity;
+        match self.display_quantity {
+            Some(_) => self.slice_quantity.min(remaining),
+            None => remaining,
+        }
+    }
+
+    fn fill(&mut self, quantity: Decimal) {
+        self.filled_quantity += quantity;
+        if self.display_quantity.is_some() {
+            self.slice_quantity -= quantity;
+        }
+    }
+
+    // iceberg order whose slice is matched but still has hidden quantity
+    fn is_slice_exhausted(&self) -> bool {
+        self.display_quantity.is_some()
+            && self.slice_quantity <= Decimal::ZERO
+            && self.filled_quantity < self.total_quantity
+    }
+
+    fn refresh_slice(&mut self) {
+        if let Some(display_quantity) = self.display_quantity {
+            self.slice_quantity = display_quantity.min(self.total_quantity - self.filled_quantity);
+        }
+    }
+}
+
+impl PriceLevel {
+    /// Refreshes the exhausted iceberg slices and moves them to the end of the level, returns `false` if nothing is refreshed
+    fn refresh_iceberg_slices(&mut self) -> bool {
+        let exhausted_slots = self
+            .orders
+            .iter_slots()
+            .filter(|(_, entry)| entry.is_slice_exhausted())
+            .map(|(slot, _)| slot)
+            .collect::<Vec<_>>();
+
+        for slot in exhausted_slots.iter() {
+            if let Some(entry) = self.orders.get_mut(*slot) {
+                entry.refresh_slice();
+            }
+            self.orders.move_to_back(*slot);
+        }
+        !exhausted_slots.is_empty()
+    }
+
+    /// Removes the fully filled orders, returns their ids
+    fn remove_filled_orders(&mut self) -> Vec<Uuid> {
+        let filled_slots = self
+            .orders
+            .iter_slots()
+            .filter(|(_, entry)| entry.filled_quantity >= entry.total_quantity)
+            .map(|(slot, _)| slot)
+            .collect::<Vec<_>>();
+
+        let removed = filled_slots
+            .into_iter()
+            .filter_map(|slot| self.orders.remove(slot))
+            .map(|entry| entry.order_id)
+            .collect();
+
+        self.total_quantity = self
+            .orders
+            .iter()
+            .map(|o| o.total_quantity - o.filled_quantity)
+            .sum();
+        removed
+    }
+
+    /// Sum of the visible quantity of all orders, hidden quantity of iceberg orders is excluded
+    pub(super) fn visible_quantity(&self) -> Decimal {
+        self.orders
+            .iter()
+            .map(OrderBookEntry::visible_quantity)
+            .sum()
+    }
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(from = "OutcomeBookState")]
 pub(crate) struct OutcomeBook {
     pub(crate) bids: BTreeMap<Decimal, PriceLevel>, // buyers side
     pub(crate) asks: BTreeMap<Decimal, PriceLevel>, // sellers side
+
+    #[serde(skip)] // derived from the price levels
+    index: HashMap<Uuid, OrderLocation>,
+
+    #[serde(skip)] // service configuration, not part of the book state
+    pub(crate) post_only_mode: PostOnlyMode,
+
+    #[serde(default)] // snapshots taken before the market rules were introduced
+    pub(crate) rules: MarketRules,
+}
+
+/// Location of the resting order in the book
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct OrderLocation {
+    pub side: OrderSide,
+    pub price: Decimal,
+    pub slot: usize,
+}
+
+// serialized state of the outcome book, order index is rebuilt from it
+#[derive(Deserialize)]
+struct OutcomeBookState {
+    bids: BTreeMap<Decimal, PriceLevel>,
+    asks: BTreeMap<Decimal, PriceLevel>,
+    #[serde(default)]
+    rules: MarketRules,
+}
+
+impl From<OutcomeBookState> for OutcomeBook {
+    fn from(state: OutcomeBookState) -> Self {
+        let mut outcome_book = OutcomeBook {
+            bids: state.bids,
+            asks: state.asks,
+            rules: state.rules,
+            ..Default::default()
+        };
+        outcome_book.rebuild_index();
+        outcome_book
+    }
+}
+
+/// Trading rules of the market, orders off the price and quantity increments are never added to the book
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub(crate) struct MarketRules {
+    pub tick_size: Decimal,
+    pub min_quantity: Decimal,
+    pub lot_size: Decimal,
+    #[serde(default)]
+    pub market_maker: bool, // LMSR market maker fills what the book can't
+    #[serde(default)]
+    pub price_model: PriceModelKind, // displayed probability of the outcomes
+    #[serde(default = "default_outcome_count")]
+    pub outcome_count: usize, // 2 for binary markets
+    #[serde(default)]
+    pub auction_opens_at: Option<NaiveDateTime>, // orders are collected without matching till the opening call auction
+    #[serde(default)]
+    pub price_band: Option<Decimal>, // orders are accepted within ±price_band of the reference price (circuit breaker)
+    #[serde(default = "default_price_band_window")]
+    pub price_band_window: u32, // seconds after which the reference price of the band is rolled
+}
+
+fn default_outcome_count() -> usize {
+    2
+}
+
+fn default_price_band_window() -> u32 {
+    300
+}
+
+impl Default for MarketRules {
+    fn default() -> Self {
+        Self {
+            tick_size: PRICE_TICK,
+            min_quantity: PRICE_TICK,
+            lot_size: PRICE_TICK,
+            market_maker: false,
+            price_model: PriceModelKind::default(),
+            outcome_count: default_outcome_count(),
+            auction_opens_at: None,
+            price_band: None,
+            price_band_window: default_price_band_window(),
+        }
+    }
+}
+
+impl MarketRules {
+    pub(crate) fn new(tick_size: Decimal, min_quantity: Decimal, lot_size: Decimal) -> Self {
+        Self {
+            tick_size,
+            min_quantity,
+            lot_size,
+            market_maker: false,
+            price_model: PriceModelKind::default(),
+            outcome_count: default_outcome_count(),
+            auction_opens_at: None,
+            price_band: None,
+            price_band_window: default_price_band_window(),
+        }
+    }
+
+    pub(crate) fn with_market_maker(mut self, market_maker: bool) -> Self {
+        self.market_maker = market_maker;
+        self
+    }
+
+    pub(crate) fn with_price_model(mut self, price_model: PriceModelKind) -> Self {
+        self.price_model = price_model;
+        self
+    }
+
+    pub(crate) fn with_outcome_count(mut self, outcome_count: usize) -> Self {
+        self.outcome_count = outcome_count;
+        self
+    }
+
+    pub(crate) fn with_auction_opens_at(mut self, auction_opens_at: Option<NaiveDateTime>) -> Self {
+        self.auction_opens_at = auction_opens_at;
+        self
+    }
+
+    pub(crate) fn with_price_band(mut self, price_band: Option<Decimal>, window: i32) -> Self {
+        self.price_band = price_band;
+        self.price_band_window =
+            u32::try_from(window).unwrap_or_else(|_| default_price_band_window());
+        self
+    }
+
+    pub(crate) fn from_market(market: &Market) -> Self {
+        Self::new(market.tick_size, market.min_quantity, market.lot_size)
+            .with_market_maker(market.market_maker)
+            .with_price_model(market.price_model)
+            .with_outcome_count(usize::try_from(market.outcome_count).unwrap_or(2))
+            .with_auction_opens_at(market.auction_opens_at)
+            .with_price_band(market.price_band, market.price_band_window)
+    }
+
+    pub(crate) fn from_order_with_market(order: &OrderWithMarket) -> Self {
+        Self::new(order.tick_size, order.min_quantity, order.lot_size)
+            .with_market_maker(order.market_maker)
+            .with_price_model(order.price_model)
+            .with_outcome_count(usize::try_from(order.outcome_count).unwrap_or(2))
+            .with_auction_opens_at(order.auction_opens_at)
+            .with_price_band(order.price_band, order.price_band_window)
+    }
+
+    /// Returns the reason why given price and quantity are not allowed in the market
+    pub(crate) fn validate(&self, price: Decimal, quantity: Decimal) -> Result<(), String> {
+        if !(price % self.tick_size).is_zero() {
+            return Err(format!(
+                "Price {} is not a multiple of the tick size {}",
+                price, self.tick_size
+            ));
+        }
+        if quantity < self.min_quantity {
+            return Err(format!(
+                "Quantity {} is less than the min quantity {}",
+                quantity, self.min_quantity
+            ));
+        }
+        if !(quantity % self.lot_size).is_zero() {
+            return Err(format!(
+                "Quantity {} is not a multiple of the lot size {}",
+                quantity, self.lot_size
+            ));
+        }
+        Ok(())
+    }
 }
 
-#[derive(Debug)]
+/// Slippage protection of a market order (matching stops at the stricter of both bounds) and it's fill rule
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub(crate) struct MarketOrderLimits {
+    pub price_limit: Option<Decimal>, // max price for buys, min price for sells
+    pub max_slippage: Option<Decimal>, // relative to the midpoint, 0.05 is 5%
+    #[serde(default)]
+    pub allow_partial: bool, // fills what the liquidity allows instead of cancelling the order
+}
+
+impl MarketOrderLimits {
+    pub(crate) fn new(price_limit: Option<Decimal>, max_slippage: Option<Decimal>) -> Self {
+        Self {
+            price_limit,
+            max_slippage,
+            allow_partial: false,
+        }
+    }
+
+    pub(crate) fn with_allow_partial(mut self, allow_partial: bool) -> Self {
+        self.allow_partial = allow_partial;
+        self
+    }
+
+    /// Worst price the order of given side can be matched at, slippage is applied on the `midpoint`
+    pub(crate) fn worst_price(&self, side: OrderSide, midpoint: Decimal) -> Option<Decimal> {
+        let slippage_price = self.max_slippage.map(|slippage| match side {
+            OrderSide::BUY => midpoint * (Decimal::ONE + slippage),
+            OrderSide::SELL => midpoint * (Decimal::ONE - slippage),
+        });
+
+        match (self.price_limit, slippage_price) {
+            (Some(price_limit), Some(slippage_price)) => Some(match side {
+                OrderSide::BUY => price_limit.min(slippage_price),
+                OrderSide::SELL => price_limit.max(slippage_price),
+            }),
+            (price_limit, slippage_price) => price_limit.or(slippage_price),
+        }
+    }
+}
+
+impl From<&MarketOrderCreateMessage> for MarketOrderLimits {
+    fn from(message: &MarketOrderCreateMessage) -> Self {
+        Self::new(message.price_limit, message.max_slippage)
+            .with_allow_partial(message.allow_partial)
+    }
+}
+
+/// Action taken on a post only order which would match immediately against the book
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum PostOnlyMode {
+    #[default]
+    Reject,
+    Reprice, // order price is moved one tick behind the best opposite price
+}
+
+impl FromStr for PostOnlyMode {
+    type Err = String;
+
+    fn from_str(mode: &str) -> Result<Self, Self::Err> {
+        match mode {
+            "reject" => Ok(PostOnlyMode::Reject),
+            "reprice" => Ok(PostOnlyMode::Reprice),
+            _ => Err(format!("Invalid post only mode: {}", mode)),
+        }
+    }
+}
+
+const PRICE_TICK: Decimal = Decimal::from_parts(1, 0, 0, false, 2); // 0.01, default tick of the market
+pub(crate) const POST_ONLY_REJECT_REASON: &str = "Post only order would cross the book";
+pub(crate) const SELF_TRADE_PREVENTION_REASON: &str = "Cancelled by self trade prevention";
+pub(crate) const PRICE_LIMIT_REASON: &str = "Market order can't be filled within it's price limit";
+
+/// Regular matches transfer shares between users of the same outcome, complementary matches (YES against NO) mint or merge share pairs
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum MatchType {
+    #[default]
+    Transfer,
+    Mint,                // BUY YES + BUY NO, new YES/NO share pair is created
+    Merge,               // SELL YES + SELL NO, YES/NO share pair is burned
+    SelfTradePrevention, // resting order of the same user is reduced to `opposite_order_total_quantity`, nothing is matched
+    MarketMaker, // shares are traded with the LMSR market maker, there is no opposite order (nil id)
+}
+
+/// How the resting order is amended by `OutcomeBook::update_order`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Amendment {
+    KeptPriority, // quantity is reduced at the same price, order keeps it's place in the queue
+    LostPriority, // quantity is raised at the same price, order is moved to the back of the queue
+    Repriced,     // order is taken out of the book to be matched at the new price
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub(crate) struct OrderBookMatchedOutput {
     pub order_id: Uuid,
     pub opposite_order_id: Uuid,
     pub matched_quantity: Decimal,
-    pub price: Decimal,
+    pub price: Decimal, // execution price of the current order (opposite order is executed at `1 - price` for mint and merge)
     pub opposite_order_total_quantity: Decimal,
     pub opposite_order_filled_quantity: Decimal,
+    pub match_type: MatchType,
 }
 
 impl OutcomeBook {
@@ -72,6 +452,14 @@ impl OutcomeBook {
             );
             return; // price should be less than or equal to 1.0 (or 100%)
         }
+        if let Err(reason) = self.rules.validate(order.price, order.quantity) {
+            log_info!("{reason}, not adding order {} to book", order.id);
+            return;
+        }
+        if self.index.contains_key(&order.id) {
+            log_info!("Order with id {} is already in the book", order.id);
+            return;
+        }
         let side = match order.side {
             OrderSide::BUY => &mut self.bids,
             OrderSide::SELL => &mut self.asks,
@@ -79,15 +467,27 @@ impl OutcomeBook {
 
         let price_level = side.entry(order.price).or_default();
 
-        let entry = OrderBookEntry {
+        let mut entry = OrderBookEntry {
             filled_quantity: order.filled_quantity,
             order_id: order.id,
             total_quantity: order.quantity,
             user_id: order.user_id,
+            display_quantity: order.display_quantity,
+            slice_quantity: Decimal::ZERO,
         };
+        entry.refresh_slice();
 
-        price_level.orders.push(entry);
+        let slot = price_level.orders.push_back(entry);
         price_level.total_quantity += order.quantity - order.filled_quantity;
+
+        self.index.insert(
+            order.id,
+            OrderLocation {
+                side: order.side,
+                price: order.price,
+                slot,
+            },
+        );
     }
 
     pub(super) fn best_bid(&self) -> Option<Decimal> {
@@ -95,130 +495,254 @@ impl OutcomeBook {
         self.bids.keys().next_back().cloned()
     }
 
+    /// Midpoint of the best bid and ask, `None` if any side of the book is empty
+    pub(super) fn midpoint(&self) -> Option<Decimal> {
+        Some((self.best_bid()? + self.best_ask()?) / Decimal::TWO)
+    }
+
     pub(super) fn best_ask(&self) -> Option<Decimal> {
         // keys are sorted in ascending order, so lowest price from sellers to buyers is first
         self.asks.keys().next().cloned()
     }
 
-    pub(super) fn remove_order(&mut self, order_id: Uuid, side: OrderSide, price: Decimal) -> bool {
-        if price > Decimal::ONE {
-            log_info!(
-                "Order price should be less than or equal to 1.0, but got: {}",
-                price
-            );
-            return false; // price should be less than or equal to 1.0 (or 100%)
+    pub(super) fn remove_order(&mut self, order_id: Uuid) -> bool {
+        let Some(location) = self.index.remove(&order_id) else {
+            return false;
+        };
+        let price_side = match location.side {
+            OrderSide::BUY => &mut self.bids,
+            OrderSide::SELL => &mut self.asks,
+        };
+        let Some(price_level) = price_side.get_mut(&location.price) else {
+            return false;
+        };
+        let Some(removed_order) = price_level.orders.remove(location.slot) else {
+            return false;
+        };
+
+        price_level.total_quantity -= removed_order.total_quantity - removed_order.filled_quantity;
+        if price_level.orders.is_empty() {
+            price_side.remove(&location.price);
         }
-        let price_side = match side {
+        true
+    }
+
+    /// Returns the price level and book entry of the resting order
+    pub(super) fn find_order(&self, order_id: Uuid) -> Option<(Decimal, &OrderBookEntry)> {
+        let location = self.index.get(&order_id)?;
+        let price_side = match location.side {
+            OrderSide::BUY => &self.bids,
+            OrderSide::SELL => &self.asks,
+        };
+        price_side
+            .get(&location.price)?
+            .orders
+            .get(location.slot)
+            .map(|entry| (location.price, entry))
+    }
+
+    /// Fills the resting order by `quantity` without an incoming order (call auction), fully filled order is removed from the book.
+    ///
+    /// Returns the total and filled quantity of the order after the fill
+    pub(super) fn fill_order(
+        &mut self,
+        order_id: Uuid,
+        quantity: Decimal,
+    ) -> Option<(Decimal, Decimal)> {
+        let location = *self.index.get(&order_id)?;
+        let price_side = match location.side {
             OrderSide::BUY => &mut self.bids,
             OrderSide::SELL => &mut self.asks,
         };
-        if let Some(price_level) = price_side.get_mut(&price) {
-            if let Some(pos) = price_level
-                .orders
-                .iter()
-                .position(|order| order.order_id == order_id)
-            {
-                let removed_order = price_level.orders.remove(pos);
-                price_level.total_quantity -=
-                    removed_order.total_quantity - removed_order.filled_quantity;
+        let price_level = price_side.get_mut(&location.price)?;
+        let entry = price_level.orders.get_mut(location.slot)?;
 
-                if price_level.orders.is_empty() {
-                    price_side.remove(&price);
+        entry.fill(quantity);
+        if entry.is_slice_exhausted() {
+            entry.refresh_slice();
+        }
+        let (total_quantity, filled_quantity) = (entry.total_quantity, entry.filled_quantity);
+        price_level.total_quantity -= quantity;
+
+        if filled_quantity >= total_quantity {
+            self.remove_order(order_id);
+        }
+        Some((total_quantity, filled_quantity))
+    }
+
+    /// Removes all orders for which `keep` returns false, returns the number of removed orders
+    pub(super) fn retain_orders(&mut self, keep: &impl Fn(&Uuid) -> bool) -> usize {
+        let removed_order_ids = self
+            .index
+            .keys()
+            .filter(|order_id| !keep(order_id))
+            .copied()
+            .collect::<Vec<_>>();
+
+        removed_order_ids
+            .into_iter()
+            .filter(|order_id| self.remove_order(*order_id))
+            .count()
+    }
+
+    fn rebuild_index(&mut self) {
+        self.index.clear();
+        for (side, price_side) in [(OrderSide::BUY, &self.bids), (OrderSide::SELL, &self.asks)] {
+            for (price, price_level) in price_side {
+                for (slot, entry) in price_level.orders.iter_slots() {
+                    let location = OrderLocation {
+                        side,
+                        price: *price,
+                        slot,
+                    };
+                    self.index.insert(entry.order_id, location);
                 }
-                return true;
             }
         }
-        false
     }
 
-    // returns matched orders if updated order is matched with some order
+    /// Amends the resting order, returns `None` if the order can't be amended.
+    ///
+    /// Reducing the quantity at the same price keeps the queue position of the order, raising it sends
+    /// the order to the back of the queue. Repriced order is taken out of the book, it's matched at the new price by the caller
     pub(super) fn update_order(
         &mut self,
         order: &mut Order,
         updated_price: Decimal,
         new_quantity: Decimal,
-    ) -> bool {
+    ) -> Option<Amendment> {
         if order.price > Decimal::ONE {
             log_info!(
                 "Order price should be less than or equal to 1.0, but got: {}",
                 order.price
             );
-            return false; // invalid price
+            return None; // invalid price
         }
         if order.quantity == new_quantity && order.price == updated_price {
             log_info!("No changes in order, nothing to update");
-            return true; // no changes
+            return Some(Amendment::KeptPriority); // no changes
         }
-        // removing order
-        if !self.remove_order(order.id, order.side, order.price) {
-            log_info!("Order not found in book, cannot update");
-            return false; // order not found
+        if let Err(reason) = self.rules.validate(updated_price, new_quantity) {
+            log_info!("{reason}, cannot update order {}", order.id);
+            return None;
         }
+        let Some(location) = self.index.get(&order.id).copied() else {
+            log_info!("Order not found in book, cannot update");
+            return None; // order not found
+        };
+
+        let amendment = if location.price == updated_price {
+            let price_side = match location.side {
+                OrderSide::BUY => &mut self.bids,
+                OrderSide::SELL => &mut self.asks,
+            };
+            let price_level = price_side.get_mut(&location.price)?;
+            let entry = price_level.orders.get_mut(location.slot)?;
+            if new_quantity <= entry.filled_quantity {
+                log_info!(
+                    "Order {} is already filled by {}, cannot reduce it to {}",
+                    order.id,
+                    entry.filled_quantity,
+                    new_quantity
+                );
+                return None;
+            }
+            let raised = new_quantity > entry.total_quantity;
+            price_level.total_quantity += new_quantity - entry.total_quantity;
+            entry.total_quantity = new_quantity;
+            if let Some(display_quantity) = entry.display_quantity {
+                // visible slice can't be larger than the remaining quantity
+                entry.slice_quantity = entry
+                    .slice_quantity
+                    .min(display_quantity)
+                    .min(new_quantity - entry.filled_quantity);
+            }
+            if raised {
+                price_level.orders.move_to_back(location.slot);
+                Amendment::LostPriority
+            } else {
+                Amendment::KeptPriority
+            }
+        } else {
+            self.remove_order(order.id);
+            Amendment::Repriced
+        };
         order.price = updated_price;
         order.quantity = new_quantity;
         order.status = OrderStatus::OPEN; // resetting status to open
 
-        self.add_order(order);
-
-        true
+        Some(amendment)
     }
 
     pub(super) fn _update_order_filled_quantity(
         &mut self,
         order_id: Uuid,
-        side: OrderSide,
-        current_price: Decimal,
         new_filled_quantity: Decimal,
     ) -> bool {
-        let price_mapping = match side {
+        let Some(location) = self.index.get(&order_id).copied() else {
+            return false;
+        };
+        let price_mapping = match location.side {
             OrderSide::BUY => &mut self.bids,
             OrderSide::SELL => &mut self.asks,
         };
-        if let Some(price_level) = price_mapping.get_mut(&current_price) {
-            if let Some(order) = price_level
-                .orders
-                .iter_mut()
-                .find(|order| order.order_id == order_id)
-            {
-                /*
-                   35 price_level.total_quantity
+        let Some(price_level) = price_mapping.get_mut(&location.price) else {
+            return false;
+        };
+        let Some(order) = price_level.orders.get_mut(location.slot) else {
+            return false;
+        };
 
-                   10 order.total_quantity (already exists)
-                   5 order.filled_quantity
-                   5 order.remaining_quantity
+        /*
+           35 price_level.total_quantity
 
-                   price_level.total_quantity = 30 (30 - 5)
+           10 order.total_quantity (already exists)
+           5 order.filled_quantity
+           5 order.remaining_quantity
 
-                   update
-                   10 -> order.total_quantity
-                   5 -> order.filled_quantity
-                   7 -> new_filled_quantity
-                   prev_remaining = 10 - 5 = 5
-                   new_remaining = 10 - 7 = 3
+           price_level.total_quantity = 30 (30 - 5)
 
-                   price_level.total_quantity = 30 + 3 - 5 = 28
-                */
-                let prev_remaining = order.total_quantity - order.filled_quantity;
+           update
+           10 -> order.total_quantity
+           5 -> order.filled_quantity
+           7 -> new_filled_quantity
+           prev_remaining = 10 - 5 = 5
+           new_remaining = 10 - 7 = 3
 
-                let new_remaining = order.total_quantity - new_filled_quantity;
+           price_level.total_quantity = 30 + 3 - 5 = 28
+        */
+        let prev_remaining = order.total_quantity - order.filled_quantity;
 
-                price_level.total_quantity =
-                    price_level.total_quantity + new_remaining - prev_remaining;
-                order.filled_quantity = new_filled_quantity;
+        let new_remaining = order.total_quantity - new_filled_quantity;
 
-                if price_level.total_quantity <= Decimal::ZERO {
-                    price_mapping.remove(&current_price);
-                }
+        price_level.total_quantity = price_level.total_quantity + new_remaining - prev_remaining;
+        order.filled_quantity = new_filled_quantity;
 
-                return true;
+        if price_level.total_quantity <= Decimal::ZERO
+            && let Some(price_level) = price_mapping.remove(&location.price)
+        {
+            for entry in price_level.orders.iter() {
+                self.index.remove(&entry.order_id);
             }
         }
 
-        false
+        true
     }
 
     /// This function modifies the current order and opposite orders. It's not used for simulation
+    #[cfg(test)]
     pub(super) fn match_order(&mut self, order: &mut Order) -> Vec<OrderBookMatchedOutput> {
+        self.match_order_with_complementary(order, None, None)
+    }
+
+    /// Matches the order against this book, remaining quantity of limit order is matched against the
+    /// opposite outcome's book (`complementary_book`) by minting or merging share pairs and then against the `market_maker`
+    pub(super) fn match_order_with_complementary(
+        &mut self,
+        order: &mut Order,
+        complementary_book: Option<&mut OutcomeBook>,
+        market_maker: Option<&mut LmsrMarketMaker>,
+    ) -> Vec<OrderBookMatchedOutput> {
         // order id, opposite order id, matched quantity, price
         let mut matches: Vec<OrderBookMatchedOutput> = Vec::new();
 
@@ -240,6 +764,61 @@ impl OutcomeBook {
                 log_info!("Order quantity is zero, nothing to match");
                 return matches; // no quantity to match
             }
+
+            if let Err(reason) = self.rules.validate(order.price, order.quantity) {
+                log_info!("{reason}, cancelling order {}", order.id);
+                order.status = OrderStatus::CANCELLED;
+                order.status_reason = Some(reason);
+                return matches;
+            }
+        }
+
+        // post only orders must never take liquidity from the book
+        if order.post_only
+            && order.order_type == OrderType::LIMIT
+            && self.is_crossing(order, complementary_book.as_deref())
+        {
+            let repriced = match self.post_only_mode {
+                PostOnlyMode::Reject => None,
+                PostOnlyMode::Reprice => {
+                    self.get_post_only_price(order, complementary_book.as_deref())
+                }
+            };
+
+            match repriced {
+                Some(price) => {
+                    log_info!(
+                        "Post only order {} repriced from {} to {}",
+                        order.id,
+                        order.price,
+                        price
+                    );
+                    order.price = price;
+                }
+                None => {
+                    log_info!(
+                        "Post only order {} would cross the book, rejecting",
+                        order.id
+                    );
+                    order.status = OrderStatus::CANCELLED;
+                    order.status_reason = Some(POST_ONLY_REJECT_REASON.to_string());
+                    return matches;
+                }
+            }
+        }
+
+        // fill or kill orders must be fully fillable before touching the book
+        if order.order_type != OrderType::MARKET
+            && order.time_in_force == TimeInForce::FOK
+            && self.get_fok_fillable_quantity(
+                order,
+                complementary_book.as_deref(),
+                market_maker.as_deref(),
+            ) < order.quantity - order.filled_quantity
+        {
+            log_info!("FOK order {} can't be fully filled, cancelling", order.id);
+            order.status = OrderStatus::CANCELLED;
+            return matches;
         }
 
         let (book, is_buy) = match order.side {
@@ -271,74 +850,67 @@ impl OutcomeBook {
             }
 
             if let Some(price_level) = book.get_mut(&price) {
-                for opposite_order in price_level.orders.iter_mut() {
-                    if order.id == opposite_order.order_id
-                        || order.user_id == opposite_order.user_id
-                    {
-                        // skip matching with itself
-                        continue;
-                    }
-                    let opp_remaining =
-                        opposite_order.total_quantity - opposite_order.filled_quantity;
-                    if opp_remaining <= Decimal::ZERO {
-                        continue;
-                    }
-
-                    let match_qty = remaining.min(opp_remaining);
-
-                    ///// ATOMIC Operation START (trusting on parking lot's RWLock )
-                    opposite_order.filled_quantity += match_qty;
-
-                    order.filled_quantity += match_qty;
-                    remaining -= match_qty;
-
-                    matches.push(OrderBookMatchedOutput {
-                        order_id: order.id,
-                        opposite_order_id: opposite_order.order_id,
-                        matched_quantity: match_qty,
-                        price,
-                        // price: opposite_order.price, // price of matching order
-                        opposite_order_total_quantity: opposite_order.total_quantity,
-                        opposite_order_filled_quantity: opposite_order.filled_quantity,
-                    });
-
-                    if remaining == Decimal::ZERO {
-                        break;
-                    }
-                    ///// ATOMIC Operation END
+                let filled_order_ids = match_price_level(
+                    order,
+                    price_level,
+                    &mut remaining,
+                    &mut matches,
+                    price,
+                    MatchType::Transfer,
+                );
+                for order_id in filled_order_ids {
+                    self.index.remove(&order_id);
                 }
 
-                // removing orders (keep only those which are not fully filled)
-                price_level
-                    .orders
-                    .retain(|o| o.filled_quantity < o.total_quantity);
-
-                price_level.total_quantity = price_level
-                    .orders
-                    .iter()
-                    .map(|o| o.total_quantity - o.filled_quantity)
-                    .sum();
-
                 if price_level.orders.is_empty() {
                     book.remove(&price);
                 }
-                if remaining == Decimal::ZERO {
+                if remaining == Decimal::ZERO || order.status != OrderStatus::OPEN {
                     break;
                 }
             }
         }
 
-        if order.filled_quantity == order.quantity {
+        // remaining quantity of limit orders is matched against the opposite outcome
+        if remaining > Decimal::ZERO
+            && order.order_type == OrderType::LIMIT
+            && order.status == OrderStatus::OPEN
+            && let Some(complementary_book) = complementary_book
+        {
+            matches.extend(complementary_book.match_complementary_order(order));
+        }
+
+        // market maker fills what the books can't
+        if order.status == OrderStatus::OPEN
+            && !order.post_only
+            && let Some(market_maker) = market_maker
+        {
+            matches.extend(self.match_market_maker(order, market_maker));
+        }
+
+        if order.status != OrderStatus::OPEN {
+            // already cancelled by self trade prevention
+        } else if order.filled_quantity == order.quantity {
             order.status = OrderStatus::FILLED;
+        } else if order.order_type != OrderType::MARKET
+            && matches!(order.time_in_force, TimeInForce::IOC | TimeInForce::FOK)
+        {
+            // remaining quantity of immediate orders is not rested in the book
+            order.status = OrderStatus::CANCELLED;
         }
 
         matches
     }
 
+    /// Matches the market order within the budget, `worst_price` (if any) is the last price level the order can be matched at.
+    /// Order is cancelled if it can't be filled for it's whole budget, unless `allow_partial` is set
     pub(crate) fn create_market_order(
         &mut self,
         order: &mut Order,
         budget: Decimal,
+        worst_price: Option<Decimal>,
+        allow_partial: bool,
+        market_maker: Option<&mut LmsrMarketMaker>,
     ) -> Vec<OrderBookMatchedOutput> {
         if order.order_type != OrderType::MARKET {
             log_error!("Order type must be MARKET for create_market_order function");
@@ -347,11 +919,17 @@ impl OutcomeBook {
         // This function is used to create a market order, which will match with the best available orders in the book
         // It will not check the price of the order, but will match with the best available orders until the quantity is filled or no more orders are available
         // NOTE: Market orders are not added in the order book
-        let order_quantity = self.get_available_match_quantity(order, budget);
+        let order_quantity = self.get_available_match_quantity(
+            order,
+            budget,
+            worst_price,
+            allow_partial,
+            market_maker.as_deref(),
+        );
 
         order.quantity = order_quantity; // update order quantity to the available match quantity
         order.price = budget; // market orders do not have a price
-        self.match_order(order)
+        self.match_order_with_complementary(order, None, market_maker)
     }
 
     // Getters ///
@@ -366,7 +944,7 @@ impl OutcomeBook {
         for (price, level) in bids {
             let data = OrderLevel {
                 price: *price,
-                shares: level.total_quantity,
+                shares: level.visible_quantity(),
                 users: level.orders.len(),
             };
             if level.orders.is_empty() {
@@ -377,7 +955,7 @@ impl OutcomeBook {
         for (price, level) in asks {
             let data = OrderLevel {
                 price: *price,
-                shares: level.total_quantity,
+                shares: level.visible_quantity(),
                 users: level.orders.len(),
             };
             if level.orders.is_empty() {
@@ -392,7 +970,14 @@ impl OutcomeBook {
         }
     }
 
-    fn get_available_match_quantity(&mut self, order: &mut Order, budget: Decimal) -> Decimal {
+    fn get_available_match_quantity(
+        &mut self,
+        order: &mut Order,
+        budget: Decimal,
+        worst_price: Option<Decimal>,
+        allow_partial: bool,
+        market_maker: Option<&LmsrMarketMaker>,
+    ) -> Decimal {
         let mut available_quantity = Decimal::ZERO;
         if order.price != Decimal::ZERO {
             log_error!(
@@ -416,50 +1001,525 @@ impl OutcomeBook {
         }
 
         let mut remaining_budget = budget;
+        let mut stopped_by_self_trade = false;
+        let mut stopped_by_price_limit = false;
+
+        for price in keys {
+            if remaining_budget <= Decimal::ZERO {
+                // order is fully matched
+                break;
+            }
+            if let Some(worst_price) = worst_price
+                && match order.side {
+                    OrderSide::BUY => price > worst_price,
+                    OrderSide::SELL => price < worst_price,
+                }
+            {
+                stopped_by_price_limit = true;
+                break;
+            }
+
+            if let Some(level) = book.get(&price) {
+                let mut total_level_qty = Decimal::ZERO;
+
+                for entry in level.orders.iter() {
+                    let rem_qty = entry.total_quantity - entry.filled_quantity;
+                    if rem_qty <= Decimal::ZERO {
+                        continue;
+                    }
+                    if entry.user_id == order.user_id {
+                        match order.self_trade_prevention {
+                            // own order is removed from the book while matching
+                            SelfTradePrevention::CancelOldest => continue,
+                            // own quantity is decremented from the order without any cost
+                            SelfTradePrevention::DecrementAndCancel => {
+                                available_quantity += rem_qty;
+                                continue;
+                            }
+                            // order is cancelled once it reaches the own order
+                            SelfTradePrevention::CancelNewest | SelfTradePrevention::CancelBoth => {
+                                stopped_by_self_trade = true;
+                                break;
+                            }
+                        }
+                    }
+                    total_level_qty += rem_qty;
+                }
+
+                let cost_to_consume_level = price * total_level_qty;
+
+                if remaining_budget >= cost_to_consume_level {
+                    // consume full level
+                    available_quantity += total_level_qty;
+                    remaining_budget -= cost_to_consume_level;
+                } else {
+                    // partial consume
+                    let partial_qty = remaining_budget / price;
+                    available_quantity += partial_qty;
+                    remaining_budget -= price * partial_qty;
+                    break;
+                }
+
+                if stopped_by_self_trade {
+                    break;
+                }
+            }
+        }
+
+        // remaining budget is spent on the market maker within the worst price
+        if remaining_budget > Decimal::ZERO
+            && !stopped_by_self_trade
+            && let Some(market_maker) = market_maker
+        {
+            let budget_quantity =
+                market_maker.quantity_for_amount(order.outcome, order.side, remaining_budget);
+            let limit_quantity = worst_price.and_then(|worst_price| {
+                market_maker.quantity_to_price(order.outcome, order.side, worst_price)
+            });
+
+            let quantity = match (budget_quantity, limit_quantity) {
+                (Some(budget_quantity), Some(limit_quantity)) => {
+                    stopped_by_price_limit |= limit_quantity < budget_quantity;
+                    budget_quantity.min(limit_quantity)
+                }
+                (quantity, None) | (None, quantity) => quantity.unwrap_or_default(),
+            };
+
+            if quantity > Decimal::ZERO {
+                available_quantity += quantity;
+                remaining_budget = if Some(quantity) == budget_quantity {
+                    Decimal::ZERO // whole budget is spent (avoids the rounding dust of the cost function)
+                } else {
+                    remaining_budget - market_maker.quote(order.outcome, order.side, quantity)
+                };
+            }
+        }
+
+        let is_partially_filled = remaining_budget > Decimal::ZERO
+            && !(allow_partial && available_quantity > Decimal::ZERO);
+        if is_partially_filled || budget == Decimal::ZERO {
+            // order is not fully matched, but we have remaining budget
+            order.status = OrderStatus::CANCELLED;
+            if stopped_by_self_trade {
+                order.status_reason = Some(SELF_TRADE_PREVENTION_REASON.to_string());
+            } else if stopped_by_price_limit {
+                order.status_reason = Some(PRICE_LIMIT_REASON.to_string());
+            }
+        } else if stopped_by_price_limit && remaining_budget > Decimal::ZERO {
+            // IOC order is filled till the price limit, rest of it's budget is left unspent
+            order.status_reason = Some(PRICE_LIMIT_REASON.to_string());
+        }
+
+        available_quantity
+    }
+
+    /// Checks if the order would match immediately against the best opposite price
+    fn is_crossing(&self, order: &Order, complementary_book: Option<&OutcomeBook>) -> bool {
+        let Some(best_price) = self.get_best_opposite_price(order.side, complementary_book) else {
+            return false;
+        };
+
+        match order.side {
+            OrderSide::BUY => order.price >= best_price,
+            OrderSide::SELL => order.price <= best_price,
+        }
+    }
+
+    /// Returns the nearest price (one tick behind the best opposite price) where the post only order rests without crossing
+    fn get_post_only_price(
+        &self,
+        order: &Order,
+        complementary_book: Option<&OutcomeBook>,
+    ) -> Option<Decimal> {
+        let best_price = self.get_best_opposite_price(order.side, complementary_book)?;
+        let price = match order.side {
+            OrderSide::BUY => best_price - self.rules.tick_size,
+            OrderSide::SELL => best_price + self.rules.tick_size,
+        };
+
+        (price > Decimal::ZERO && price < Decimal::ONE).then_some(price)
+    }
+
+    /// Best price the order of given side can be executed at, including the complementary outcome's book
+    /// (BUY NO at 0.4 is equivalent to SELL YES at 0.6)
+    fn get_best_opposite_price(
+        &self,
+        side: OrderSide,
+        complementary_book: Option<&OutcomeBook>,
+    ) -> Option<Decimal> {
+        match side {
+            OrderSide::BUY => {
+                let complementary_ask = complementary_book
+                    .and_then(|book| book.best_bid())
+                    .map(|bid| Decimal::ONE - bid);
+                match (self.best_ask(), complementary_ask) {
+                    (Some(ask), Some(complementary_ask)) => Some(ask.min(complementary_ask)),
+                    (ask, complementary_ask) => ask.or(complementary_ask),
+                }
+            }
+            OrderSide::SELL => {
+                let complementary_bid = complementary_book
+                    .and_then(|book| book.best_ask())
+                    .map(|ask| Decimal::ONE - ask);
+                match (self.best_bid(), complementary_bid) {
+                    (Some(bid), Some(complementary_bid)) => Some(bid.max(complementary_bid)),
+                    (bid, complementary_bid) => bid.or(complementary_bid),
+                }
+            }
+        }
+    }
+
+    /// Matches the order of the opposite outcome against the same side of this book.
+    ///
+    /// BUY orders are matched against bids (mint) and SELL orders against asks (merge) when both prices sum up to 1 or better for the incoming order.
+    /// Resting order is executed at its own price and incoming order at the complement of it.
+    fn match_complementary_order(&mut self, order: &mut Order) -> Vec<OrderBookMatchedOutput> {
+        let mut matches: Vec<OrderBookMatchedOutput> = Vec::new();
+        let complementary_price = Decimal::ONE - order.price;
+
+        let (book, match_type) = match order.side {
+            OrderSide::BUY => (&mut self.bids, MatchType::Mint),
+            OrderSide::SELL => (&mut self.asks, MatchType::Merge),
+        };
+
+        // best price for incoming order first (highest bid for minting, lowest ask for merging)
+        let keys: Vec<Decimal> = match order.side {
+            OrderSide::BUY => book
+                .range(complementary_price..)
+                .rev()
+                .map(|(p, _)| *p)
+                .collect(),
+            OrderSide::SELL => book
+                .range(..=complementary_price)
+                .map(|(p, _)| *p)
+                .collect(),
+        };
+
+        let mut remaining = order.quantity - order.filled_quantity;
+
+        for price in keys {
+            if remaining <= Decimal::ZERO {
+                break;
+            }
+
+            if let Some(price_level) = book.get_mut(&price) {
+                let filled_order_ids = match_price_level(
+                    order,
+                    price_level,
+                    &mut remaining,
+                    &mut matches,
+                    Decimal::ONE - price,
+                    match_type,
+                );
+                for order_id in filled_order_ids {
+                    self.index.remove(&order_id);
+                }
+
+                if price_level.orders.is_empty() {
+                    book.remove(&price);
+                }
+                if order.status != OrderStatus::OPEN {
+                    break;
+                }
+            }
+        }
+
+        matches
+    }
+
+    /// Returns the quantity the fill or kill order can be filled with, matching stops at the user's own order unless it's cancelled by `CancelOldest`
+    fn get_fok_fillable_quantity(
+        &self,
+        order: &Order,
+        complementary_book: Option<&OutcomeBook>,
+        market_maker: Option<&LmsrMarketMaker>,
+    ) -> Decimal {
+        let (quantity, stopped) = self.get_fillable_quantity(order);
+        if stopped {
+            return quantity;
+        }
+
+        let (complementary_quantity, stopped) = complementary_book
+            .map_or((Decimal::ZERO, false), |book| {
+                book.get_complementary_fillable_quantity(order)
+            });
+        if stopped {
+            return quantity + complementary_quantity;
+        }
+
+        let remaining = order.quantity - order.filled_quantity - quantity - complementary_quantity;
+        quantity
+            + complementary_quantity
+            + market_maker.map_or(Decimal::ZERO, |market_maker| {
+                self.get_market_maker_fillable_quantity(order, market_maker, remaining)
+            })
+    }
+
+    /// Quantity of the limit order the market maker fills (up to `remaining`), rounded down to the lot size
+    fn get_market_maker_fillable_quantity(
+        &self,
+        order: &Order,
+        market_maker: &LmsrMarketMaker,
+        remaining: Decimal,
+    ) -> Decimal {
+        if remaining <= Decimal::ZERO {
+            return Decimal::ZERO;
+        }
+
+        let quantity = market_maker
+            .quantity_to_price(order.outcome, order.side, order.price)
+            .map_or(remaining, |quantity| quantity.min(remaining));
+        (quantity / self.rules.lot_size).floor() * self.rules.lot_size
+    }
+
+    /// Fills the remaining quantity of the order from the market maker, limit orders are filled till the market maker's price reaches the limit price
+    fn match_market_maker(
+        &self,
+        order: &mut Order,
+        market_maker: &mut LmsrMarketMaker,
+    ) -> Option<OrderBookMatchedOutput> {
+        let remaining = order.quantity - order.filled_quantity;
+        let quantity = match order.order_type {
+            OrderType::LIMIT => {
+                self.get_market_maker_fillable_quantity(order, market_maker, remaining)
+            }
+            // quantity of market order is already bounded by it's budget and worst price
+            OrderType::MARKET => remaining,
+            _ => Decimal::ZERO,
+        };
+        if quantity <= Decimal::ZERO {
+            return None;
+        }
+
+        let price = market_maker.execute(order.outcome, order.side, quantity);
+        order.filled_quantity += quantity;
+
+        Some(OrderBookMatchedOutput {
+            order_id: order.id,
+            opposite_order_id: Uuid::nil(),
+            matched_quantity: quantity,
+            price,
+            opposite_order_total_quantity: Decimal::ZERO,
+            opposite_order_filled_quantity: Decimal::ZERO,
+            match_type: MatchType::MarketMaker,
+        })
+    }
+
+    /// Returns the quantity of this book which can be minted or merged with the opposite outcome's order, and whether it's stopped by the user's own order
+    fn get_complementary_fillable_quantity(&self, order: &Order) -> (Decimal, bool) {
+        let complementary_price = Decimal::ONE - order.price;
+        let levels: Box<dyn Iterator<Item = (&Decimal, &PriceLevel)>> = match order.side {
+            OrderSide::BUY => Box::new(self.bids.range(complementary_price..).rev()),
+            OrderSide::SELL => Box::new(self.asks.range(..=complementary_price)),
+        };
+
+        get_fillable_quantity_before_self_trade(order, levels)
+    }
+
+    /// Returns the quantity available on the opposite side within the order's limit price, and whether it's stopped by the user's own order
+    fn get_fillable_quantity(&self, order: &Order) -> (Decimal, bool) {
+        let levels: Box<dyn Iterator<Item = (&Decimal, &PriceLevel)>> = match order.side {
+            OrderSide::BUY => Box::new(self.asks.range(..=order.price)),
+            OrderSide::SELL => Box::new(self.bids.range(order.price..).rev()),
+        };
+
+        get_fillable_quantity_before_self_trade(order, levels)
+    }
+
+    /// Calculates the budget required to match the order's remaining quantity against the current book (used to execute triggered orders as market orders)
+    ///
+    /// Quantity which is not available in the book is quoted by the market maker (if any), otherwise budget for the available liquidity is returned
+    pub(super) fn get_budget_for_quantity(
+        &self,
+        order: &Order,
+        market_maker: Option<&LmsrMarketMaker>,
+    ) -> Decimal {
+        let book = match order.side {
+            OrderSide::BUY => &self.asks,
+            OrderSide::SELL => &self.bids,
+        };
+
+        let mut remaining_quantity = order.quantity - order.filled_quantity;
+        let mut budget = Decimal::ZERO;
+
+        let mut keys: Vec<Decimal> = book.keys().cloned().collect();
+        if order.side == OrderSide::SELL {
+            keys.reverse(); // descending: sell to highest
+        }
+
+        for price in keys {
+            if remaining_quantity <= Decimal::ZERO {
+                break;
+            }
+            let Some(level) = book.get(&price) else {
+                continue;
+            };
+
+            let level_qty: Decimal = level
+                .orders
+                .iter()
+                .filter(|entry| entry.user_id != order.user_id)
+                .map(|entry| entry.total_quantity - entry.filled_quantity)
+                .filter(|qty| *qty > Decimal::ZERO)
+                .sum();
+
+            let match_qty = remaining_quantity.min(level_qty);
+            budget += price * match_qty;
+            remaining_quantity -= match_qty;
+        }
+
+        if remaining_quantity > Decimal::ZERO
+            && let Some(market_maker) = market_maker
+        {
+            budget += market_maker.quote(order.outcome, order.side, remaining_quantity);
+        }
+
+        budget
+    }
+}
 
-        for price in keys {
-            if remaining_budget <= Decimal::ZERO {
-                // order is fully matched
+/// Matches the order against the orders of the price level in time priority, iceberg slices refreshed
+/// while matching are matched after the other orders of the level. `price` is the execution price of the order
+///
+/// Returns the ids of the fully filled orders, which are removed from the level
+fn match_price_level(
+    order: &mut Order,
+    price_level: &mut PriceLevel,
+    remaining: &mut Decimal,
+    matches: &mut Vec<OrderBookMatchedOutput>,
+    price: Decimal,
+    match_type: MatchType,
+) -> Vec<Uuid> {
+    loop {
+        let mut cursor = price_level.orders.first_slot();
+        while let Some(slot) = cursor {
+            cursor = price_level.orders.next_slot(slot);
+            let Some(opposite_order) = price_level.orders.get_mut(slot) else {
+                break;
+            };
+            if order.id == opposite_order.order_id {
+                // skip matching with itself
+                continue;
+            }
+            let opp_remaining = opposite_order.visible_quantity();
+            if opp_remaining <= Decimal::ZERO {
+                continue;
+            }
+            if order.user_id == opposite_order.user_id {
+                if prevent_self_trade(order, opposite_order, remaining, matches) {
+                    continue;
+                }
                 break;
             }
 
-            if let Some(level) = book.get(&price) {
-                let mut total_level_qty = Decimal::ZERO;
+            let match_qty = (*remaining).min(opp_remaining);
 
-                for entry in &level.orders {
-                    if entry.user_id == order.user_id {
-                        // skip matching with itself
-                        continue;
-                    }
-                    let rem_qty = entry.total_quantity - entry.filled_quantity;
-                    if rem_qty > Decimal::ZERO {
-                        total_level_qty += rem_qty;
-                    }
-                }
+            ///// ATOMIC Operation START (trusting on parking lot's RWLock )
+            opposite_order.fill(match_qty);
 
-                let cost_to_consume_level = price * total_level_qty;
+            order.filled_quantity += match_qty;
+            *remaining -= match_qty;
 
-                if remaining_budget >= cost_to_consume_level {
-                    // consume full level
-                    available_quantity += total_level_qty;
-                    remaining_budget -= cost_to_consume_level;
-                } else {
-                    // partial consume
-                    let partial_qty = remaining_budget / price;
-                    available_quantity += partial_qty;
-                    remaining_budget -= price * partial_qty;
-                    break;
-                }
+            matches.push(OrderBookMatchedOutput {
+                order_id: order.id,
+                opposite_order_id: opposite_order.order_id,
+                matched_quantity: match_qty,
+                price,
+                opposite_order_total_quantity: opposite_order.total_quantity,
+                opposite_order_filled_quantity: opposite_order.filled_quantity,
+                match_type,
+            });
+
+            if *remaining == Decimal::ZERO {
+                break;
             }
+            ///// ATOMIC Operation END
         }
 
-        if remaining_budget > Decimal::ZERO || budget == Decimal::ZERO {
-            // order is not fully matched, but we have remaining budget
-            order.status = OrderStatus::CANCELLED;
+        // slices are refreshed even if the order is fully matched, so they lose their priority
+        let refreshed = price_level.refresh_iceberg_slices();
+        if !refreshed || *remaining == Decimal::ZERO || order.status != OrderStatus::OPEN {
+            break;
         }
+    }
 
-        available_quantity
+    // removing orders (keep only those which are not fully filled)
+    price_level.remove_filled_orders()
+}
+
+/// Applies the incoming order's self trade prevention mode on the resting order of the same user.
+///
+/// Returns `false` if the incoming order is cancelled and must not be matched any further
+fn prevent_self_trade(
+    order: &mut Order,
+    resting_order: &mut OrderBookEntry,
+    remaining: &mut Decimal,
+    matches: &mut Vec<OrderBookMatchedOutput>,
+) -> bool {
+    let resting_remaining = resting_order.total_quantity - resting_order.filled_quantity;
+
+    let (cancel_incoming, resting_decrement) = match order.self_trade_prevention {
+        SelfTradePrevention::CancelNewest => (true, Decimal::ZERO),
+        SelfTradePrevention::CancelOldest => (false, resting_remaining),
+        SelfTradePrevention::CancelBoth => (true, resting_remaining),
+        SelfTradePrevention::DecrementAndCancel => {
+            let decrement = (*remaining).min(resting_remaining);
+            order.quantity -= decrement;
+            *remaining -= decrement;
+            (*remaining == Decimal::ZERO, decrement)
+        }
+    };
+
+    if resting_decrement > Decimal::ZERO {
+        // fully decremented order is removed from the price level with the filled ones
+        resting_order.total_quantity -= resting_decrement;
+        matches.push(OrderBookMatchedOutput {
+            order_id: order.id,
+            opposite_order_id: resting_order.order_id,
+            matched_quantity: Decimal::ZERO,
+            price: Decimal::ZERO,
+            opposite_order_total_quantity: resting_order.total_quantity,
+            opposite_order_filled_quantity: resting_order.filled_quantity,
+            match_type: MatchType::SelfTradePrevention,
+        });
+    }
+
+    if cancel_incoming {
+        log_info!(
+            "Order {} cancelled by self trade prevention against order {}",
+            order.id,
+            resting_order.order_id
+        );
+        order.status = OrderStatus::CANCELLED;
+        order.status_reason = Some(SELF_TRADE_PREVENTION_REASON.to_string());
+    }
+
+    !cancel_incoming
+}
+
+/// Sums the remaining quantity of the levels (in matching order) till the user's own order is reached,
+/// own orders are skipped if they are going to be cancelled by `CancelOldest`
+fn get_fillable_quantity_before_self_trade<'a>(
+    order: &Order,
+    levels: impl Iterator<Item = (&'a Decimal, &'a PriceLevel)>,
+) -> (Decimal, bool) {
+    let mut quantity = Decimal::ZERO;
+
+    for entry in levels.flat_map(|(_, level)| level.orders.iter()) {
+        let rem_qty = entry.total_quantity - entry.filled_quantity;
+        if rem_qty <= Decimal::ZERO {
+            continue;
+        }
+        if entry.user_id == order.user_id {
+            if order.self_trade_prevention == SelfTradePrevention::CancelOldest {
+                continue;
+            }
+            return (quantity, true);
+        }
+        quantity += rem_qty;
     }
+
+    (quantity, false)
 }
 
 #[cfg(test)]
@@ -496,6 +1556,14 @@ mod test {
             status: OrderStatus::OPEN,
             updated_at: get_created_at(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
             user_id,
         }; // 0.25 buy - 10 qty (buy)
         let sell_order_2 = Order {
@@ -511,6 +1579,14 @@ mod test {
             updated_at: get_created_at(),
             user_id,
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         }; // 0.20 buy - 3 qty (buy)
 
         // market order
@@ -527,13 +1603,22 @@ mod test {
             updated_at: get_created_at(),
             user_id: another_user_id,
             order_type: OrderType::MARKET,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         outcome_book.add_order(&sell_order_1);
         outcome_book.add_order(&sell_order_2);
 
         let budget = dec!(2.1); // equivalent to 21
-        let matches = outcome_book.create_market_order(&mut market_buy_order, budget);
+        let matches =
+            outcome_book.create_market_order(&mut market_buy_order, budget, None, false, None);
 
         /*
          * 0.20 * 3 = 0.60
@@ -575,7 +1660,7 @@ mod test {
 
         assert_eq!(price_level.total_quantity, dec!(4)); // 10 - 6 = 4 left
         assert_eq!(price_level.orders.len(), 1); // only one order left at 0.25
-        let order_book_entry = price_level.orders.get(0).unwrap();
+        let order_book_entry = price_level.orders.iter().next().unwrap();
 
         assert_eq!(order_book_entry.user_id, user_id);
         assert_eq!(order_book_entry.order_id, sell_order_1.id);
@@ -604,6 +1689,14 @@ mod test {
             updated_at,
             user_id,
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         let mut outcome_book = OutcomeBook::default();
@@ -617,7 +1710,7 @@ mod test {
         assert_eq!(price_level.total_quantity, Decimal::new(10, 0));
         assert_eq!(price_level.orders.len(), 1);
 
-        let order_book_entry = price_level.orders.get(0).unwrap();
+        let order_book_entry = price_level.orders.iter().next().unwrap();
 
         assert_eq!(order_book_entry.user_id, user_id);
         assert_eq!(order_book_entry.order_id, id);
@@ -630,7 +1723,7 @@ mod test {
 
     #[test]
     fn test_remove_order() {
-        let price = Decimal::new(25, 3);
+        let price = Decimal::new(25, 2);
         let mut order_book = OutcomeBook::default();
 
         let order = Order {
@@ -647,6 +1740,14 @@ mod test {
             user_id: get_random_uuid(),
 
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
         order_book.add_order(&order);
         let price_level = order_book.bids.get(&price).unwrap();
@@ -654,7 +1755,7 @@ mod test {
         assert_eq!(order_book.bids.len(), 1);
         assert_eq!(price_level.total_quantity, Decimal::new(10, 0));
 
-        order_book.remove_order(order.id, OrderSide::BUY, price);
+        order_book.remove_order(order.id);
 
         let price_level = order_book.bids.get(&price);
 
@@ -684,6 +1785,14 @@ mod test {
             updated_at,
             user_id,
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         let mut outcome_book = OutcomeBook::default();
@@ -696,11 +1805,11 @@ mod test {
         assert_eq!(price_level.total_quantity, quantity);
 
         // updating order
-        outcome_book._update_order_filled_quantity(id, OrderSide::BUY, price, Decimal::new(5, 0));
+        outcome_book._update_order_filled_quantity(id, Decimal::new(5, 0));
 
         let price_level = outcome_book.bids.get(&price).unwrap();
         assert_eq!(price_level.total_quantity, Decimal::new(5, 0));
-        let price_order = price_level.orders.get(0).unwrap();
+        let price_order = price_level.orders.iter().next().unwrap();
         assert_eq!(price_order.filled_quantity, Decimal::new(5, 0));
     }
 
@@ -710,6 +1819,14 @@ mod test {
 
         let buy_order_1 = Order {
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
 
             created_at: get_created_at(),
             filled_quantity: Decimal::ZERO,
@@ -735,6 +1852,14 @@ mod test {
             status: OrderStatus::OPEN,
             updated_at: get_created_at(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
 
             user_id: get_random_uuid(),
         }; // 0.20 buy - 3 qty (buy)
@@ -750,6 +1875,14 @@ mod test {
             status: OrderStatus::OPEN,
             updated_at: get_created_at(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
 
             user_id: get_random_uuid(),
         }; // 0.23 buy - 4 qty (buy)
@@ -766,6 +1899,14 @@ mod test {
             status: OrderStatus::OPEN,
             updated_at: get_created_at(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
 
             user_id: get_random_uuid(),
         }; // 0.20 - 15 qty (sell)
@@ -781,7 +1922,15 @@ mod test {
         assert!(order_book_entry.is_some());
         let order_book_entry = order_book_entry.unwrap();
         assert!(order_book_entry.orders.len() == 1);
-        assert!(order_book_entry.orders[0].filled_quantity == dec!(1));
+        assert!(
+            order_book_entry
+                .orders
+                .iter()
+                .next()
+                .unwrap()
+                .filled_quantity
+                == dec!(1)
+        );
 
         assert_eq!(sell_order_1.status, OrderStatus::FILLED);
         assert_eq!(resp.len(), 3);
@@ -809,6 +1958,14 @@ mod test {
             updated_at: get_created_at(),
             user_id: get_random_uuid(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         let mut sell_order = Order {
@@ -824,6 +1981,14 @@ mod test {
             updated_at: get_created_at(),
             user_id: get_random_uuid(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         let mut outcome_book = OutcomeBook::default();
@@ -856,6 +2021,14 @@ mod test {
             updated_at: get_created_at(),
             user_id: get_random_uuid(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         let buy_order_2 = Order {
@@ -871,6 +2044,14 @@ mod test {
             updated_at: get_created_at(),
             user_id: get_random_uuid(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         let buy_order_3 = Order {
@@ -886,6 +2067,14 @@ mod test {
             updated_at: get_created_at(),
             user_id: get_random_uuid(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         let mut sell_order = Order {
@@ -901,6 +2090,14 @@ mod test {
             updated_at: get_created_at(),
             user_id: get_random_uuid(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         let mut outcome_book = OutcomeBook::default();
@@ -933,6 +2130,14 @@ mod test {
             updated_at: get_created_at(),
             user_id: get_random_uuid(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         let mut sell_order = Order {
@@ -948,6 +2153,14 @@ mod test {
             updated_at: get_created_at(),
             user_id: get_random_uuid(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         let mut outcome_book = OutcomeBook::default();
@@ -965,7 +2178,11 @@ mod test {
         let market_id = get_random_uuid();
         let user_id = get_random_uuid();
 
-        let mut outcome_book = OutcomeBook::default();
+        // prices of the orders are finer than the default tick
+        let mut outcome_book = OutcomeBook {
+            rules: MarketRules::new(dec!(0.0001), dec!(1), dec!(1)),
+            ..Default::default()
+        };
 
         // Add 1000 buy orders at different prices
         for i in 1..=1000 {
@@ -981,6 +2198,14 @@ mod test {
                 status: OrderStatus::OPEN,
                 updated_at: get_created_at(),
                 order_type: OrderType::LIMIT,
+                trigger_price: None,
+                time_in_force: TimeInForce::GTC,
+                expires_at: None,
+                post_only: false,
+                status_reason: None,
+                self_trade_prevention: SelfTradePrevention::CancelNewest,
+                outcome_index: 0,
+                display_quantity: None,
 
                 user_id,
             };
@@ -1000,6 +2225,14 @@ mod test {
             updated_at: get_created_at(),
             user_id: get_random_uuid(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         let resp = outcome_book.match_order(&mut sell_order);
@@ -1026,6 +2259,14 @@ mod test {
             updated_at: get_created_at(),
             user_id: get_random_uuid(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         let mut sell_order = Order {
@@ -1041,6 +2282,14 @@ mod test {
             updated_at: get_created_at(),
             user_id: get_random_uuid(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         let mut outcome_book = OutcomeBook::default();
@@ -1071,6 +2320,14 @@ mod test {
             updated_at: get_created_at(),
             user_id: get_random_uuid(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         let mut outcome_book = OutcomeBook::default();
@@ -1099,6 +2356,14 @@ mod test {
             updated_at: get_created_at(),
             user_id: get_random_uuid(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
         let buy_order_one_1 = Order {
             created_at: get_created_at(),
@@ -1113,6 +2378,14 @@ mod test {
             updated_at: get_created_at(),
             user_id: get_random_uuid(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
         let buy_order_one_2 = Order {
             created_at: get_created_at(),
@@ -1127,6 +2400,14 @@ mod test {
             updated_at: get_created_at(),
             user_id: get_random_uuid(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         outcome_book.add_order(&buy_order_one);
@@ -1146,6 +2427,14 @@ mod test {
             updated_at: get_created_at(),
             user_id: get_random_uuid(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
         let matches = outcome_book.match_order(&mut matching_sell_order);
         assert_eq!(matches.len(), 1);
@@ -1172,6 +2461,14 @@ mod test {
             status: OrderStatus::OPEN,
             updated_at: get_created_at(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
             user_id,
         };
 
@@ -1190,9 +2487,23 @@ mod test {
             updated_at: get_created_at(),
             user_id: another_user_id,
             order_type: OrderType::MARKET,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
-        let matches = outcome_book.create_market_order(&mut market_buy_order, Decimal::ZERO);
+        let matches = outcome_book.create_market_order(
+            &mut market_buy_order,
+            Decimal::ZERO,
+            None,
+            false,
+            None,
+        );
 
         assert_eq!(matches.len(), 0);
         assert_eq!(market_buy_order.quantity, Decimal::ZERO);
@@ -1219,6 +2530,14 @@ mod test {
             status: OrderStatus::OPEN,
             updated_at: get_created_at(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
             user_id,
         };
 
@@ -1237,10 +2556,19 @@ mod test {
             updated_at: get_created_at(),
             user_id: another_user_id,
             order_type: OrderType::MARKET,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         let budget = dec!(7.5); // 0.75 * 10 = 7.5 units
-        let matches = outcome_book.create_market_order(&mut market_sell_order, budget);
+        let matches =
+            outcome_book.create_market_order(&mut market_sell_order, budget, None, false, None);
 
         assert_eq!(matches.len(), 1);
         assert_eq!(market_sell_order.filled_quantity, dec!(10));
@@ -1268,6 +2596,14 @@ mod test {
             status: OrderStatus::OPEN,
             updated_at: get_created_at(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
             user_id,
         };
 
@@ -1286,9 +2622,18 @@ mod test {
             updated_at: get_created_at(),
             user_id: another_user_id,
             order_type: OrderType::MARKET,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
-        let matches = outcome_book.create_market_order(&mut market_buy_order, dec!(2.5));
+        let matches =
+            outcome_book.create_market_order(&mut market_buy_order, dec!(2.5), None, false, None);
 
         assert_eq!(matches.len(), 0); // Should fail because market orders must have price 0
     }
@@ -1313,6 +2658,14 @@ mod test {
             status: OrderStatus::OPEN,
             updated_at: get_created_at(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
             user_id,
         };
 
@@ -1328,6 +2681,14 @@ mod test {
             status: OrderStatus::OPEN,
             updated_at: get_created_at(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
             user_id,
         };
 
@@ -1343,6 +2704,14 @@ mod test {
             status: OrderStatus::OPEN,
             updated_at: get_created_at(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
             user_id,
         };
 
@@ -1363,11 +2732,20 @@ mod test {
             updated_at: get_created_at(),
             user_id: another_user_id,
             order_type: OrderType::MARKET,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         // Budget enough to fill all orders: 0.80*5 + 0.70*3 + 0.60*4 = 4.0 + 2.1 + 2.4 = 8.5
         let budget = dec!(8.5);
-        let matches = outcome_book.create_market_order(&mut market_sell_order, budget);
+        let matches =
+            outcome_book.create_market_order(&mut market_sell_order, budget, None, false, None);
 
         assert_eq!(matches.len(), 3);
         assert_eq!(market_sell_order.filled_quantity, dec!(12)); // 5 + 3 + 4 = 12
@@ -1402,6 +2780,14 @@ mod test {
             status: OrderStatus::OPEN,
             updated_at: get_created_at(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
             user_id,
         };
 
@@ -1420,11 +2806,20 @@ mod test {
             updated_at: get_created_at(),
             user_id: another_user_id,
             order_type: OrderType::MARKET,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         // Budget for only 3 shares: 0.50 * 3 = 1.5
         let budget = dec!(1.5);
-        let matches = outcome_book.create_market_order(&mut market_sell_order, budget);
+        let matches =
+            outcome_book.create_market_order(&mut market_sell_order, budget, None, false, None);
 
         assert_eq!(matches.len(), 1);
         assert_eq!(market_sell_order.filled_quantity, dec!(3));
@@ -1451,6 +2846,14 @@ mod test {
             status: OrderStatus::OPEN,
             updated_at: get_created_at(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
             user_id,
         };
 
@@ -1469,11 +2872,20 @@ mod test {
             updated_at: get_created_at(),
             user_id: another_user_id,
             order_type: OrderType::MARKET,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         // Budget for 1.25 shares: 0.25 * 1.25 = 0.3125
         let budget = dec!(0.3125);
-        let matches = outcome_book.create_market_order(&mut market_buy_order, budget);
+        let matches =
+            outcome_book.create_market_order(&mut market_buy_order, budget, None, false, None);
 
         assert_eq!(matches.len(), 1);
         assert_eq!(market_buy_order.filled_quantity, dec!(1.25));
@@ -1502,10 +2914,19 @@ mod test {
             updated_at: get_created_at(),
             user_id: another_user_id,
             order_type: OrderType::MARKET,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         let budget = dec!(5.0);
-        let matches = outcome_book.create_market_order(&mut market_buy_order, budget);
+        let matches =
+            outcome_book.create_market_order(&mut market_buy_order, budget, None, false, None);
 
         assert_eq!(matches.len(), 0);
         assert_eq!(market_buy_order.quantity, Decimal::ZERO);
@@ -1531,6 +2952,14 @@ mod test {
             status: OrderStatus::OPEN,
             updated_at: get_created_at(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
             user_id,
         };
 
@@ -1549,10 +2978,19 @@ mod test {
             updated_at: get_created_at(),
             user_id: another_user_id,
             order_type: OrderType::LIMIT, // Wrong order type
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         let budget = dec!(2.5);
-        let matches = outcome_book.create_market_order(&mut limit_buy_order, budget);
+        let matches =
+            outcome_book.create_market_order(&mut limit_buy_order, budget, None, false, None);
 
         assert_eq!(matches.len(), 0); // Should fail because only market orders can be used
     }
@@ -1578,6 +3016,14 @@ mod test {
             status: OrderStatus::OPEN,
             updated_at: get_created_at(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
             user_id,
         };
 
@@ -1593,6 +3039,14 @@ mod test {
             status: OrderStatus::OPEN,
             updated_at: get_created_at(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
             user_id,
         };
 
@@ -1612,20 +3066,46 @@ mod test {
             updated_at: get_created_at(),
             user_id: another_user_id,
             order_type: OrderType::MARKET,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         // Test with different budgets
         // Budget enough for all sell_order_1: 0.20 * 5 = 1.0
-        let quantity = outcome_book.get_available_match_quantity(&mut market_buy_order, dec!(1.0));
+        let quantity = outcome_book.get_available_match_quantity(
+            &mut market_buy_order,
+            dec!(1.0),
+            None,
+            false,
+            None,
+        );
         assert_eq!(quantity, dec!(5.0));
 
         // Budget enough for all sell_order_1 and part of sell_order_2
         // 0.20 * 5 + 0.30 * 3.33 = 1.0 + 1.0 = 2.0
-        let quantity = outcome_book.get_available_match_quantity(&mut market_buy_order, dec!(2.0));
+        let quantity = outcome_book.get_available_match_quantity(
+            &mut market_buy_order,
+            dec!(2.0),
+            None,
+            false,
+            None,
+        );
         assert_eq!(quantity, dec!(5) + dec!(1.0) / dec!(0.30));
 
         // Budget enough for all orders: 0.20 * 5 + 0.30 * 10 = 1.0 + 3.0 = 4.0
-        let quantity = outcome_book.get_available_match_quantity(&mut market_buy_order, dec!(4.0));
+        let quantity = outcome_book.get_available_match_quantity(
+            &mut market_buy_order,
+            dec!(4.0),
+            None,
+            false,
+            None,
+        );
         assert_eq!(quantity, dec!(15.0)); // 5 + 10 = 15
     }
 
@@ -1647,6 +3127,14 @@ mod test {
             status: OrderStatus::OPEN,
             updated_at: get_created_at(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
             user_id, // Same user ID
         };
 
@@ -1665,10 +3153,19 @@ mod test {
             updated_at: get_created_at(),
             user_id, // Same user ID
             order_type: OrderType::MARKET,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
         };
 
         let budget = dec!(2.5);
-        let matches = outcome_book.create_market_order(&mut market_buy_order, budget);
+        let matches =
+            outcome_book.create_market_order(&mut market_buy_order, budget, None, false, None);
 
         assert_eq!(matches.len(), 0); // Should not match with own orders
         assert_eq!(market_buy_order.quantity, Decimal::ZERO);
@@ -1694,6 +3191,14 @@ mod test {
             status: OrderStatus::OPEN,
             updated_at: get_created_at(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
             user_id,
         };
 
@@ -1709,6 +3214,14 @@ mod test {
             status: OrderStatus::OPEN,
             updated_at: get_created_at(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
             user_id,
         };
 
@@ -1725,6 +3238,14 @@ mod test {
             status: OrderStatus::OPEN,
             updated_at: get_created_at(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
             user_id,
         };
 
@@ -1740,6 +3261,14 @@ mod test {
             status: OrderStatus::OPEN,
             updated_at: get_created_at(),
             order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
             user_id,
         };
 
@@ -1791,4 +3320,723 @@ mod test {
             }
         }
     }
+
+    fn get_limit_order(
+        side: OrderSide,
+        price: Decimal,
+        quantity: Decimal,
+        time_in_force: TimeInForce,
+    ) -> Order {
+        Order {
+            created_at: get_created_at(),
+            filled_quantity: Decimal::ZERO,
+            id: get_random_uuid(),
+            market_id: get_random_uuid(),
+            outcome: Outcome::YES,
+            price,
+            quantity,
+            side,
+            status: OrderStatus::OPEN,
+            updated_at: get_created_at(),
+            user_id: get_random_uuid(),
+            order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
+        }
+    }
+
+    #[test]
+    fn test_ioc_order_partial_fill() {
+        let mut outcome_book = OutcomeBook::default();
+        let sell_order = get_limit_order(OrderSide::SELL, dec!(0.5), dec!(4), TimeInForce::GTC);
+        outcome_book.add_order(&sell_order);
+
+        let mut ioc_order = get_limit_order(OrderSide::BUY, dec!(0.5), dec!(10), TimeInForce::IOC);
+        let matches = outcome_book.match_order(&mut ioc_order);
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(ioc_order.filled_quantity, dec!(4));
+        // remaining quantity is cancelled instead of resting in the book
+        assert_eq!(ioc_order.status, OrderStatus::CANCELLED);
+        assert!(outcome_book.asks.is_empty());
+    }
+
+    #[test]
+    fn test_fok_order_not_fully_fillable() {
+        let mut outcome_book = OutcomeBook::default();
+        let sell_order_1 = get_limit_order(OrderSide::SELL, dec!(0.5), dec!(4), TimeInForce::GTC);
+        let sell_order_2 = get_limit_order(OrderSide::SELL, dec!(0.6), dec!(4), TimeInForce::GTC);
+        outcome_book.add_order(&sell_order_1);
+        outcome_book.add_order(&sell_order_2);
+
+        // only 4 shares are available within the limit price
+        let mut fok_order = get_limit_order(OrderSide::BUY, dec!(0.55), dec!(6), TimeInForce::FOK);
+        let matches = outcome_book.match_order(&mut fok_order);
+
+        assert!(matches.is_empty());
+        assert_eq!(fok_order.filled_quantity, Decimal::ZERO);
+        assert_eq!(fok_order.status, OrderStatus::CANCELLED);
+
+        // book is untouched
+        let level = outcome_book.asks.get(&dec!(0.5)).unwrap();
+        assert_eq!(level.total_quantity, dec!(4));
+        assert_eq!(
+            level.orders.iter().next().unwrap().filled_quantity,
+            Decimal::ZERO
+        );
+    }
+
+    #[test]
+    fn test_fok_order_fully_filled() {
+        let mut outcome_book = OutcomeBook::default();
+        let sell_order_1 = get_limit_order(OrderSide::SELL, dec!(0.5), dec!(4), TimeInForce::GTC);
+        let sell_order_2 = get_limit_order(OrderSide::SELL, dec!(0.6), dec!(4), TimeInForce::GTC);
+        outcome_book.add_order(&sell_order_1);
+        outcome_book.add_order(&sell_order_2);
+
+        let mut fok_order = get_limit_order(OrderSide::BUY, dec!(0.6), dec!(6), TimeInForce::FOK);
+        let matches = outcome_book.match_order(&mut fok_order);
+
+        assert_eq!(matches.len(), 2);
+        assert_eq!(fok_order.filled_quantity, dec!(6));
+        assert_eq!(fok_order.status, OrderStatus::FILLED);
+    }
+
+    #[test]
+    fn test_gtc_order_remains_open() {
+        let mut outcome_book = OutcomeBook::default();
+        let sell_order = get_limit_order(OrderSide::SELL, dec!(0.5), dec!(4), TimeInForce::GTC);
+        outcome_book.add_order(&sell_order);
+
+        let mut gtc_order = get_limit_order(OrderSide::BUY, dec!(0.5), dec!(10), TimeInForce::GTC);
+        outcome_book.match_order(&mut gtc_order);
+
+        assert_eq!(gtc_order.filled_quantity, dec!(4));
+        assert_eq!(gtc_order.status, OrderStatus::OPEN);
+    }
+
+    #[test]
+    fn test_post_only_order_rejected() {
+        let mut outcome_book = OutcomeBook::default();
+        let sell_order = get_limit_order(OrderSide::SELL, dec!(0.5), dec!(4), TimeInForce::GTC);
+        outcome_book.add_order(&sell_order);
+
+        let mut post_only_order =
+            get_limit_order(OrderSide::BUY, dec!(0.55), dec!(4), TimeInForce::GTC);
+        post_only_order.post_only = true;
+        let matches = outcome_book.match_order(&mut post_only_order);
+
+        assert!(matches.is_empty());
+        assert_eq!(post_only_order.status, OrderStatus::CANCELLED);
+        assert_eq!(
+            post_only_order.status_reason.as_deref(),
+            Some(POST_ONLY_REJECT_REASON)
+        );
+        assert_eq!(
+            outcome_book.asks.get(&dec!(0.5)).unwrap().total_quantity,
+            dec!(4)
+        );
+    }
+
+    #[test]
+    fn test_post_only_order_repriced() {
+        let mut outcome_book = OutcomeBook {
+            post_only_mode: PostOnlyMode::Reprice,
+            ..Default::default()
+        };
+        let sell_order = get_limit_order(OrderSide::SELL, dec!(0.5), dec!(4), TimeInForce::GTC);
+        let buy_order = get_limit_order(OrderSide::BUY, dec!(0.3), dec!(4), TimeInForce::GTC);
+        outcome_book.add_order(&sell_order);
+        outcome_book.add_order(&buy_order);
+
+        let mut post_only_buy =
+            get_limit_order(OrderSide::BUY, dec!(0.5), dec!(4), TimeInForce::GTC);
+        post_only_buy.post_only = true;
+        let matches = outcome_book.match_order(&mut post_only_buy);
+
+        assert!(matches.is_empty());
+        assert_eq!(post_only_buy.status, OrderStatus::OPEN);
+        assert_eq!(post_only_buy.price, dec!(0.49));
+
+        let mut post_only_sell =
+            get_limit_order(OrderSide::SELL, dec!(0.2), dec!(4), TimeInForce::GTC);
+        post_only_sell.post_only = true;
+        let matches = outcome_book.match_order(&mut post_only_sell);
+
+        assert!(matches.is_empty());
+        assert_eq!(post_only_sell.status, OrderStatus::OPEN);
+        assert_eq!(post_only_sell.price, dec!(0.31));
+    }
+
+    #[test]
+    fn test_post_only_order_reprice_out_of_range() {
+        let mut outcome_book = OutcomeBook {
+            post_only_mode: PostOnlyMode::Reprice,
+            ..Default::default()
+        };
+        let sell_order = get_limit_order(OrderSide::SELL, dec!(0.01), dec!(4), TimeInForce::GTC);
+        outcome_book.add_order(&sell_order);
+
+        // no valid price below the best ask
+        let mut post_only_order =
+            get_limit_order(OrderSide::BUY, dec!(0.2), dec!(4), TimeInForce::GTC);
+        post_only_order.post_only = true;
+        outcome_book.match_order(&mut post_only_order);
+
+        assert_eq!(post_only_order.status, OrderStatus::CANCELLED);
+        assert!(post_only_order.status_reason.is_some());
+    }
+
+    fn get_coarse_book() -> OutcomeBook {
+        OutcomeBook {
+            rules: MarketRules::new(dec!(0.05), dec!(10), dec!(5)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_order_off_tick_size_rejected() {
+        let mut outcome_book = get_coarse_book();
+        let sell_order = get_limit_order(OrderSide::SELL, dec!(0.52), dec!(10), TimeInForce::GTC);
+        outcome_book.add_order(&sell_order);
+        assert!(outcome_book.asks.is_empty());
+
+        let mut buy_order = get_limit_order(OrderSide::BUY, dec!(0.53), dec!(10), TimeInForce::GTC);
+        let matches = outcome_book.match_order(&mut buy_order);
+
+        assert!(matches.is_empty());
+        assert_eq!(buy_order.status, OrderStatus::CANCELLED);
+        assert!(
+            buy_order
+                .status_reason
+                .as_deref()
+                .is_some_and(|reason| reason.contains("tick size"))
+        );
+    }
+
+    #[test]
+    fn test_order_off_lot_size_rejected() {
+        let mut outcome_book = get_coarse_book();
+
+        let mut small_order = get_limit_order(OrderSide::BUY, dec!(0.5), dec!(5), TimeInForce::GTC);
+        outcome_book.match_order(&mut small_order);
+        assert_eq!(small_order.status, OrderStatus::CANCELLED);
+        assert!(
+            small_order
+                .status_reason
+                .as_deref()
+                .is_some_and(|reason| reason.contains("min quantity"))
+        );
+
+        let mut odd_lot_order =
+            get_limit_order(OrderSide::BUY, dec!(0.5), dec!(12), TimeInForce::GTC);
+        outcome_book.match_order(&mut odd_lot_order);
+        assert_eq!(odd_lot_order.status, OrderStatus::CANCELLED);
+
+        let mut order = get_limit_order(OrderSide::BUY, dec!(0.5), dec!(15), TimeInForce::GTC);
+        outcome_book.match_order(&mut order);
+        assert_eq!(order.status, OrderStatus::OPEN);
+        outcome_book.add_order(&order);
+
+        // update off the lot size is ignored
+        assert!(
+            outcome_book
+                .update_order(&mut order, dec!(0.5), dec!(17))
+                .is_none()
+        );
+        assert_eq!(
+            outcome_book.bids.get(&dec!(0.5)).unwrap().total_quantity,
+            dec!(15)
+        );
+    }
+
+    #[test]
+    fn test_post_only_order_repriced_by_market_tick() {
+        let mut outcome_book = OutcomeBook {
+            post_only_mode: PostOnlyMode::Reprice,
+            ..get_coarse_book()
+        };
+        let sell_order = get_limit_order(OrderSide::SELL, dec!(0.5), dec!(10), TimeInForce::GTC);
+        outcome_book.add_order(&sell_order);
+
+        let mut post_only_buy =
+            get_limit_order(OrderSide::BUY, dec!(0.6), dec!(10), TimeInForce::GTC);
+        post_only_buy.post_only = true;
+        outcome_book.match_order(&mut post_only_buy);
+
+        assert_eq!(post_only_buy.status, OrderStatus::OPEN);
+        assert_eq!(post_only_buy.price, dec!(0.45));
+    }
+
+    fn get_iceberg_order(side: OrderSide, price: Decimal, quantity: Decimal) -> Order {
+        let mut order = get_limit_order(side, price, quantity, TimeInForce::GTC);
+        order.display_quantity = Some(dec!(4));
+        order
+    }
+
+    #[test]
+    fn test_iceberg_order_hidden_quantity() {
+        let mut outcome_book = OutcomeBook::default();
+        outcome_book.add_order(&get_iceberg_order(OrderSide::SELL, dec!(0.5), dec!(10)));
+        outcome_book.add_order(&get_limit_order(
+            OrderSide::SELL,
+            dec!(0.5),
+            dec!(3),
+            TimeInForce::GTC,
+        ));
+
+        let order_book = outcome_book.get_order_book();
+        assert_eq!(order_book.asks.len(), 1);
+        assert_eq!(order_book.asks[0].shares, dec!(7)); // 4 visible + 3
+        assert_eq!(order_book.asks[0].users, 2);
+        assert_eq!(
+            outcome_book.asks.get(&dec!(0.5)).unwrap().total_quantity,
+            dec!(13)
+        );
+    }
+
+    #[test]
+    fn test_iceberg_slice_loses_time_priority() {
+        let mut outcome_book = OutcomeBook::default();
+        let iceberg_order = get_iceberg_order(OrderSide::SELL, dec!(0.5), dec!(10));
+        let sell_order = get_limit_order(OrderSide::SELL, dec!(0.5), dec!(3), TimeInForce::GTC);
+        outcome_book.add_order(&iceberg_order);
+        outcome_book.add_order(&sell_order);
+
+        // visible slice is matched first, refreshed slice is moved behind the other order
+        let mut buy_order = get_limit_order(OrderSide::BUY, dec!(0.5), dec!(4), TimeInForce::GTC);
+        let matches = outcome_book.match_order(&mut buy_order);
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].opposite_order_id, iceberg_order.id);
+        assert_eq!(buy_order.status, OrderStatus::FILLED);
+
+        let price_level = outcome_book.asks.get(&dec!(0.5)).unwrap();
+        assert_eq!(
+            price_level.orders.iter().next().unwrap().order_id,
+            sell_order.id
+        );
+        assert_eq!(
+            price_level.orders.iter().nth(1).unwrap().order_id,
+            iceberg_order.id
+        );
+        assert_eq!(
+            price_level.orders.iter().nth(1).unwrap().visible_quantity(),
+            dec!(4)
+        );
+        assert_eq!(outcome_book.get_order_book().asks[0].shares, dec!(7));
+
+        // hidden quantity is matched after the other order of the level
+        let mut buy_order = get_limit_order(OrderSide::BUY, dec!(0.5), dec!(9), TimeInForce::GTC);
+        let matches = outcome_book.match_order(&mut buy_order);
+
+        let matched = matches
+            .iter()
+            .map(|m| (m.opposite_order_id, m.matched_quantity))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            matched,
+            vec![
+                (sell_order.id, dec!(3)),
+                (iceberg_order.id, dec!(4)),
+                (iceberg_order.id, dec!(2)),
+            ]
+        );
+        assert_eq!(buy_order.status, OrderStatus::FILLED);
+        assert!(outcome_book.asks.is_empty());
+    }
+
+    #[test]
+    fn test_post_only_order_not_crossing() {
+        let mut outcome_book = OutcomeBook::default();
+        let sell_order = get_limit_order(OrderSide::SELL, dec!(0.5), dec!(4), TimeInForce::GTC);
+        outcome_book.add_order(&sell_order);
+
+        let mut post_only_order =
+            get_limit_order(OrderSide::BUY, dec!(0.45), dec!(4), TimeInForce::GTC);
+        post_only_order.post_only = true;
+        outcome_book.match_order(&mut post_only_order);
+
+        assert_eq!(post_only_order.status, OrderStatus::OPEN);
+        assert_eq!(post_only_order.price, dec!(0.45));
+        assert!(post_only_order.status_reason.is_none());
+    }
+
+    #[test]
+    fn test_fok_order_filled_with_complementary_book() {
+        let mut outcome_book = OutcomeBook::default();
+        let mut complementary_book = OutcomeBook::default();
+
+        let sell_order = get_limit_order(OrderSide::SELL, dec!(0.5), dec!(4), TimeInForce::GTC);
+        let complementary_bid =
+            get_limit_order(OrderSide::BUY, dec!(0.5), dec!(4), TimeInForce::GTC);
+        outcome_book.add_order(&sell_order);
+        complementary_book.add_order(&complementary_bid);
+
+        let mut fok_order = get_limit_order(OrderSide::BUY, dec!(0.5), dec!(8), TimeInForce::FOK);
+        let matches = outcome_book.match_order_with_complementary(
+            &mut fok_order,
+            Some(&mut complementary_book),
+            None,
+        );
+
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[1].match_type, MatchType::Mint);
+        assert_eq!(fok_order.status, OrderStatus::FILLED);
+        assert!(complementary_book.bids.is_empty());
+    }
+
+    #[test]
+    fn test_post_only_order_crossing_complementary_book() {
+        let mut outcome_book = OutcomeBook::default();
+        let mut complementary_book = OutcomeBook::default();
+
+        let complementary_bid =
+            get_limit_order(OrderSide::BUY, dec!(0.45), dec!(4), TimeInForce::GTC);
+        complementary_book.add_order(&complementary_bid);
+
+        // buying at 0.55 would mint against the 0.45 bid of opposite outcome
+        let mut post_only_order =
+            get_limit_order(OrderSide::BUY, dec!(0.55), dec!(4), TimeInForce::GTC);
+        post_only_order.post_only = true;
+        let matches = outcome_book.match_order_with_complementary(
+            &mut post_only_order,
+            Some(&mut complementary_book),
+            None,
+        );
+
+        assert!(matches.is_empty());
+        assert_eq!(post_only_order.status, OrderStatus::CANCELLED);
+    }
+
+    #[test]
+    fn test_retain_orders() {
+        let mut outcome_book = OutcomeBook::default();
+        let bid_1 = get_limit_order(OrderSide::BUY, dec!(0.4), dec!(4), TimeInForce::GTC);
+        let bid_2 = get_limit_order(OrderSide::BUY, dec!(0.4), dec!(6), TimeInForce::GTC);
+        let ask = get_limit_order(OrderSide::SELL, dec!(0.6), dec!(5), TimeInForce::GTC);
+        outcome_book.add_order(&bid_1);
+        outcome_book.add_order(&bid_2);
+        outcome_book.add_order(&ask);
+
+        let removed = outcome_book.retain_orders(&|order_id| *order_id == bid_2.id);
+
+        assert_eq!(removed, 2);
+        assert!(outcome_book.asks.is_empty());
+        let price_level = outcome_book.bids.get(&dec!(0.4)).unwrap();
+        assert_eq!(price_level.orders.len(), 1);
+        assert_eq!(price_level.total_quantity, dec!(6));
+
+        let (price, entry) = outcome_book.find_order(bid_2.id).unwrap();
+        assert_eq!(price, dec!(0.4));
+        assert_eq!(entry.total_quantity, dec!(6));
+        assert!(outcome_book.find_order(bid_1.id).is_none());
+    }
+
+    // resting asks of the same user (4 shares) and another user (6 shares) at the same price, own ask has time priority
+    fn get_self_trade_book(user_id: Uuid) -> (OutcomeBook, Order, Order) {
+        let mut outcome_book = OutcomeBook::default();
+        let mut own_ask = get_limit_order(OrderSide::SELL, dec!(0.5), dec!(4), TimeInForce::GTC);
+        own_ask.user_id = user_id;
+        let other_ask = get_limit_order(OrderSide::SELL, dec!(0.5), dec!(6), TimeInForce::GTC);
+        outcome_book.add_order(&own_ask);
+        outcome_book.add_order(&other_ask);
+        (outcome_book, own_ask, other_ask)
+    }
+
+    fn get_self_trade_order(user_id: Uuid, mode: SelfTradePrevention) -> Order {
+        let mut order = get_limit_order(OrderSide::BUY, dec!(0.5), dec!(8), TimeInForce::GTC);
+        order.user_id = user_id;
+        order.self_trade_prevention = mode;
+        order
+    }
+
+    #[test]
+    fn test_self_trade_prevention_cancel_newest() {
+        let user_id = get_random_uuid();
+        let (mut outcome_book, own_ask, _) = get_self_trade_book(user_id);
+
+        let mut order = get_self_trade_order(user_id, SelfTradePrevention::CancelNewest);
+        let matches = outcome_book.match_order(&mut order);
+
+        assert!(matches.is_empty());
+        assert_eq!(order.status, OrderStatus::CANCELLED);
+        assert_eq!(
+            order.status_reason.as_deref(),
+            Some(SELF_TRADE_PREVENTION_REASON)
+        );
+        let (_, entry) = outcome_book.find_order(own_ask.id).unwrap();
+        assert_eq!(entry.total_quantity, dec!(4));
+    }
+
+    #[test]
+    fn test_self_trade_prevention_cancel_oldest() {
+        let user_id = get_random_uuid();
+        let (mut outcome_book, own_ask, other_ask) = get_self_trade_book(user_id);
+
+        let mut order = get_self_trade_order(user_id, SelfTradePrevention::CancelOldest);
+        let matches = outcome_book.match_order(&mut order);
+
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].match_type, MatchType::SelfTradePrevention);
+        assert_eq!(matches[0].opposite_order_id, own_ask.id);
+        assert_eq!(matches[0].opposite_order_total_quantity, Decimal::ZERO);
+        assert_eq!(matches[1].opposite_order_id, other_ask.id);
+        assert_eq!(matches[1].matched_quantity, dec!(6));
+
+        // remaining quantity rests in the book without crossing the own ask
+        assert_eq!(order.status, OrderStatus::OPEN);
+        assert_eq!(order.filled_quantity, dec!(6));
+        assert!(outcome_book.asks.is_empty());
+    }
+
+    #[test]
+    fn test_self_trade_prevention_cancel_both() {
+        let user_id = get_random_uuid();
+        let (mut outcome_book, own_ask, other_ask) = get_self_trade_book(user_id);
+
+        let mut order = get_self_trade_order(user_id, SelfTradePrevention::CancelBoth);
+        let matches = outcome_book.match_order(&mut order);
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].match_type, MatchType::SelfTradePrevention);
+        assert_eq!(order.status, OrderStatus::CANCELLED);
+        assert!(outcome_book.find_order(own_ask.id).is_none());
+        assert!(outcome_book.find_order(other_ask.id).is_some());
+    }
+
+    #[test]
+    fn test_self_trade_prevention_decrement_and_cancel() {
+        let user_id = get_random_uuid();
+        let (mut outcome_book, own_ask, other_ask) = get_self_trade_book(user_id);
+
+        let mut order = get_self_trade_order(user_id, SelfTradePrevention::DecrementAndCancel);
+        let matches = outcome_book.match_order(&mut order);
+
+        // 8 shares are decremented by 4 on both sides, remaining 4 are matched with the other user
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].match_type, MatchType::SelfTradePrevention);
+        assert_eq!(matches[1].matched_quantity, dec!(4));
+        assert_eq!(order.quantity, dec!(4));
+        assert_eq!(order.status, OrderStatus::FILLED);
+        assert!(outcome_book.find_order(own_ask.id).is_none());
+
+        let (_, entry) = outcome_book.find_order(other_ask.id).unwrap();
+        assert_eq!(entry.filled_quantity, dec!(4));
+    }
+
+    #[test]
+    fn test_self_trade_prevention_fok_order() {
+        let user_id = get_random_uuid();
+        let (mut outcome_book, own_ask, _) = get_self_trade_book(user_id);
+
+        // own ask has time priority, so the order can't be filled without trading with itself
+        let mut order = get_self_trade_order(user_id, SelfTradePrevention::CancelNewest);
+        order.time_in_force = TimeInForce::FOK;
+        order.quantity = dec!(6);
+        let matches = outcome_book.match_order(&mut order);
+
+        assert!(matches.is_empty());
+        assert_eq!(order.status, OrderStatus::CANCELLED);
+        assert!(outcome_book.find_order(own_ask.id).is_some());
+    }
+
+    #[test]
+    fn test_cancel_order_by_id_keeps_time_priority() {
+        let mut outcome_book = OutcomeBook::default();
+        let orders = [dec!(2), dec!(3), dec!(4)].map(|quantity| {
+            get_limit_order(OrderSide::SELL, dec!(0.5), quantity, TimeInForce::GTC)
+        });
+        for order in orders.iter() {
+            outcome_book.add_order(order);
+        }
+
+        assert!(outcome_book.remove_order(orders[1].id));
+        assert!(!outcome_book.remove_order(orders[1].id));
+        assert!(outcome_book.find_order(orders[1].id).is_none());
+
+        let price_level = outcome_book.asks.get(&dec!(0.5)).unwrap();
+        assert_eq!(price_level.total_quantity, dec!(6));
+        assert_eq!(
+            price_level
+                .orders
+                .iter()
+                .map(|entry| entry.order_id)
+                .collect::<Vec<_>>(),
+            vec![orders[0].id, orders[2].id]
+        );
+
+        // buy order matches the remaining orders in time priority
+        let mut order = get_limit_order(OrderSide::BUY, dec!(0.5), dec!(3), TimeInForce::GTC);
+        let matches = outcome_book.match_order(&mut order);
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].opposite_order_id, orders[0].id);
+        assert_eq!(matches[1].opposite_order_id, orders[2].id);
+        assert!(outcome_book.find_order(orders[0].id).is_none());
+
+        let (price, entry) = outcome_book.find_order(orders[2].id).unwrap();
+        assert_eq!(price, dec!(0.5));
+        assert_eq!(entry.filled_quantity, dec!(1));
+    }
+
+    #[test]
+    fn test_order_index_rebuilt_on_deserialize() {
+        let mut outcome_book = OutcomeBook::default();
+        let bid = get_limit_order(OrderSide::BUY, dec!(0.4), dec!(5), TimeInForce::GTC);
+        let ask = get_limit_order(OrderSide::SELL, dec!(0.6), dec!(5), TimeInForce::GTC);
+        outcome_book.add_order(&bid);
+        outcome_book.add_order(&ask);
+        // duplicate order is not added again
+        outcome_book.add_order(&bid);
+
+        let serialized = serde_json::to_string(&outcome_book).unwrap();
+        let mut restored = serde_json::from_str::<OutcomeBook>(&serialized).unwrap();
+
+        assert_eq!(restored.bids.get(&dec!(0.4)).unwrap().orders.len(), 1);
+        let (price, entry) = restored.find_order(ask.id).unwrap();
+        assert_eq!(price, dec!(0.6));
+        assert_eq!(entry.total_quantity, dec!(5));
+
+        assert!(restored.remove_order(bid.id));
+        assert!(restored.bids.is_empty());
+    }
+
+    fn get_market_buy_order() -> Order {
+        let mut order = get_limit_order(
+            OrderSide::BUY,
+            Decimal::ZERO,
+            Decimal::ZERO,
+            TimeInForce::FOK,
+        );
+        order.order_type = OrderType::MARKET;
+        order
+    }
+
+    fn get_price_limit_book() -> OutcomeBook {
+        let mut outcome_book = OutcomeBook::default();
+        for price in [dec!(0.5), dec!(0.6), dec!(0.7)] {
+            outcome_book.add_order(&get_limit_order(
+                OrderSide::SELL,
+                price,
+                dec!(4),
+                TimeInForce::GTC,
+            ));
+        }
+        outcome_book
+    }
+
+    #[test]
+    fn test_market_order_within_price_limit() {
+        let mut outcome_book = get_price_limit_book();
+
+        // 4 * 0.5 + 2 * 0.6 = 3.2, within the 0.6 limit
+        let mut order = get_market_buy_order();
+        let matches =
+            outcome_book.create_market_order(&mut order, dec!(3.2), Some(dec!(0.6)), false, None);
+
+        assert_eq!(matches.len(), 2);
+        assert_eq!(order.status, OrderStatus::FILLED);
+        assert_eq!(order.filled_quantity, dec!(6));
+    }
+
+    #[test]
+    fn test_market_order_stopped_at_price_limit() {
+        let mut outcome_book = get_price_limit_book();
+
+        // budget reaches the 0.7 level, which is beyond the limit
+        let mut order = get_market_buy_order();
+        let matches =
+            outcome_book.create_market_order(&mut order, dec!(5), Some(dec!(0.6)), false, None);
+
+        assert!(matches.is_empty());
+        assert_eq!(order.status, OrderStatus::CANCELLED);
+        assert_eq!(order.status_reason.as_deref(), Some(PRICE_LIMIT_REASON));
+        assert_eq!(outcome_book.asks.len(), 3);
+
+        // without the limit, same budget is filled
+        let mut order = get_market_buy_order();
+        let matches = outcome_book.create_market_order(&mut order, dec!(5), None, false, None);
+        assert_eq!(matches.len(), 3);
+        assert_eq!(order.status, OrderStatus::FILLED);
+    }
+
+    #[test]
+    fn test_market_order_limits_worst_price() {
+        let limits = MarketOrderLimits::new(Some(dec!(0.6)), Some(dec!(0.1)));
+
+        // stricter of the price limit and the slippage from the midpoint
+        assert_eq!(
+            limits.worst_price(OrderSide::BUY, dec!(0.5)),
+            Some(dec!(0.55))
+        );
+        assert_eq!(
+            limits.worst_price(OrderSide::BUY, dec!(0.6)),
+            Some(dec!(0.6))
+        );
+        assert_eq!(
+            limits.worst_price(OrderSide::SELL, dec!(0.5)),
+            Some(dec!(0.6))
+        );
+        assert_eq!(
+            limits.worst_price(OrderSide::SELL, dec!(0.8)),
+            Some(dec!(0.72))
+        );
+
+        let limits = MarketOrderLimits::new(None, Some(dec!(0.1)));
+        assert_eq!(
+            limits.worst_price(OrderSide::SELL, dec!(0.5)),
+            Some(dec!(0.45))
+        );
+        assert_eq!(
+            MarketOrderLimits::default().worst_price(OrderSide::BUY, dec!(0.5)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_market_order_allow_partial() {
+        let mut outcome_book = get_price_limit_book();
+
+        // all or nothing order is cancelled in the thin book, time in force doesn't decide the fill rule of market orders
+        let mut order = get_market_buy_order();
+        order.time_in_force = TimeInForce::IOC;
+        let matches = outcome_book.create_market_order(&mut order, dec!(10), None, false, None);
+        assert!(matches.is_empty());
+        assert_eq!(order.status, OrderStatus::CANCELLED);
+
+        // partial order takes the whole book (4 * 0.5 + 4 * 0.6 + 4 * 0.7 = 7.2) and is filled for it
+        let mut order = get_market_buy_order();
+        let matches = outcome_book.create_market_order(&mut order, dec!(10), None, true, None);
+        assert_eq!(matches.len(), 3);
+        assert_eq!(order.status, OrderStatus::FILLED);
+        assert_eq!(order.quantity, dec!(12));
+        assert_eq!(order.filled_quantity, dec!(12));
+        assert!(outcome_book.asks.is_empty());
+
+        // nothing left to match
+        let mut order = get_market_buy_order();
+        let matches = outcome_book.create_market_order(&mut order, dec!(10), None, true, None);
+        assert!(matches.is_empty());
+        assert_eq!(order.status, OrderStatus::CANCELLED);
+    }
+
+    #[test]
+    fn test_market_order_allow_partial_within_price_limit() {
+        let mut outcome_book = get_price_limit_book();
+
+        let mut order = get_market_buy_order();
+        let matches =
+            outcome_book.create_market_order(&mut order, dec!(5), Some(dec!(0.6)), true, None);
+
+        assert_eq!(matches.len(), 2);
+        assert_eq!(order.status, OrderStatus::FILLED);
+        assert_eq!(order.filled_quantity, dec!(8));
+        assert_eq!(outcome_book.best_ask(), Some(dec!(0.7)));
+    }
 }
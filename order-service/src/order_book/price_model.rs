@@ -0,0 +1,412 @@
+/*
+ * Price models derive the displayed probability of the market's outcomes (`current_price` of every outcome),
+ * model of the market is selected by it's `price_model` column.
+ *
+ * ## Outcome prices
+ *
+ * 1. Book based models (midpoint, volume weighted midpoint and microprice) price the book of each outcome on it's own, one sided book is priced at it's only side.
+ * 2. Prices of all outcomes are normalized to sum up to 1, outcomes which are not priced share the remainder of the priced ones (complement of the other outcome in binary market).
+ * 3. Market which has nothing to be priced by is shown at uniform prices (0.5 / 0.5 for binary market).
+ */
+
+use db_service::schema::enums::{Outcome, PriceModelKind};
+use rust_decimal::Decimal;
+
+use super::{
+    lmsr::LmsrMarketMaker,
+    market_book::OutcomeMarket,
+    outcome_book::{OutcomeBook, PriceLevel},
+};
+
+// levels of each side used by the volume weighted midpoint
+const VWAP_DEPTH: usize = 5;
+
+/// State of the market the prices are derived from
+pub(crate) trait MarketView {
+    fn outcome_count(&self) -> usize;
+    fn get_outcome(&self, outcome_index: usize) -> Option<&OutcomeMarket>;
+    fn market_maker(&self) -> Option<&LmsrMarketMaker>;
+    fn liquidity_b(&self) -> Decimal;
+}
+
+pub(crate) trait PriceModel {
+    /// Prices of the outcomes by their index, `None` if the market has nothing to be priced by
+    fn prices(&self, market_book: &dyn MarketView) -> Option<Vec<Decimal>>;
+}
+
+pub(crate) fn get_price_model(kind: PriceModelKind) -> &'static dyn PriceModel {
+    match kind {
+        PriceModelKind::LmsrWeighted => &LmsrWeighted,
+        PriceModelKind::Midpoint => &Midpoint,
+        PriceModelKind::LastTrade => &LastTrade,
+        PriceModelKind::VwapMidpoint => &VwapMidpoint,
+        PriceModelKind::Microprice => &Microprice,
+    }
+}
+
+/// Price of the LMSR market maker if the market has one, otherwise funds of the buyers (resting bids and executed buys) weighted by the liquidity `b`.
+/// Markets without liquidity (`b = 0`) are priced by the book midpoint
+pub(crate) struct LmsrWeighted;
+
+impl PriceModel for LmsrWeighted {
+    fn prices(&self, market_book: &dyn MarketView) -> Option<Vec<Decimal>> {
+        if let Some(market_maker) = market_book.market_maker() {
+            return Some(vec![
+                market_maker.price(Outcome::YES),
+                market_maker.price(Outcome::NO),
+            ]);
+        }
+        if market_book.liquidity_b() <= Decimal::ZERO {
+            return Midpoint.prices(market_book);
+        }
+
+        // https://www.cultivatelabs.com/crowdsourced-forecasting-guide/how-does-logarithmic-market-scoring-rule-lmsr-work
+        // Refer above blogpost for better understanding on LMSR (Logarithmic Market Scoring Rule) price mechanism for prediction markets
+        let funds = (0..market_book.outcome_count())
+            .map(|index| get_total_funds(market_book, index))
+            .collect::<Vec<_>>();
+        let total_funds = funds.iter().sum::<Decimal>();
+        if total_funds <= Decimal::ZERO {
+            return None;
+        }
+
+        // b for every outcome
+        let total_liquidity = market_book.liquidity_b() * Decimal::from(funds.len());
+        let weights = funds
+            .into_iter()
+            .map(|outcome_funds| {
+                Some((market_book.liquidity_b() + outcome_funds) / (total_liquidity + total_funds))
+            })
+            .collect();
+
+        normalize(weights)
+    }
+}
+
+/// Midpoint of the best bid and ask of each outcome
+pub(crate) struct Midpoint;
+
+impl PriceModel for Midpoint {
+    fn prices(&self, market_book: &dyn MarketView) -> Option<Vec<Decimal>> {
+        get_book_prices(market_book, |book| {
+            match (book.best_bid(), book.best_ask()) {
+                (Some(bid), Some(ask)) => Some((bid + ask) / Decimal::TWO),
+                (bid, ask) => bid.or(ask),
+            }
+        })
+    }
+}
+
+/// Last traded price of each outcome
+pub(crate) struct LastTrade;
+
+impl PriceModel for LastTrade {
+    fn prices(&self, market_book: &dyn MarketView) -> Option<Vec<Decimal>> {
+        normalize(
+            (0..market_book.outcome_count())
+                .map(|index| {
+                    market_book
+                        .get_outcome(index)
+                        .and_then(|outcome| outcome.last_trade_price)
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Midpoint of the volume weighted bid and ask prices of the top `VWAP_DEPTH` levels of each outcome
+pub(crate) struct VwapMidpoint;
+
+impl PriceModel for VwapMidpoint {
+    fn prices(&self, market_book: &dyn MarketView) -> Option<Vec<Decimal>> {
+        get_book_prices(market_book, |book| {
+            let bid = get_vwap(book.bids.iter().rev().take(VWAP_DEPTH));
+            let ask = get_vwap(book.asks.iter().take(VWAP_DEPTH));
+            match (bid, ask) {
+                (Some(bid), Some(ask)) => Some((bid + ask) / Decimal::TWO),
+                (bid, ask) => bid.or(ask),
+            }
+        })
+    }
+}
+
+/// Best bid and ask of each outcome weighted by the visible quantity on the opposite side,
+/// price leans towards the side which is more likely to be consumed first
+pub(crate) struct Microprice;
+
+impl PriceModel for Microprice {
+    fn prices(&self, market_book: &dyn MarketView) -> Option<Vec<Decimal>> {
+        get_book_prices(market_book, |book| {
+            let bid = book.bids.iter().next_back();
+            let ask = book.asks.iter().next();
+            match (bid, ask) {
+                (Some((bid, bid_level)), Some((ask, ask_level))) => {
+                    let bid_quantity = bid_level.visible_quantity();
+                    let ask_quantity = ask_level.visible_quantity();
+                    let total_quantity = bid_quantity + ask_quantity;
+                    if total_quantity > Decimal::ZERO {
+                        Some((*bid * ask_quantity + *ask * bid_quantity) / total_quantity)
+                    } else {
+                        Some((*bid + *ask) / Decimal::TWO)
+                    }
+                }
+                (bid, ask) => bid.or(ask).map(|(price, _)| *price),
+            }
+        })
+    }
+}
+
+///// Helpers //////
+
+fn get_book_prices(
+    market_book: &dyn MarketView,
+    outcome_price: impl Fn(&OutcomeBook) -> Option<Decimal>,
+) -> Option<Vec<Decimal>> {
+    normalize(
+        (0..market_book.outcome_count())
+            .map(|index| {
+                market_book
+                    .get_outcome(index)
+                    .and_then(|outcome| outcome_price(&outcome.order_book))
+            })
+            .collect(),
+    )
+}
+
+// prices of all outcomes sum up to 1, outcomes without price share what's left by the priced ones
+fn normalize(prices: Vec<Option<Decimal>>) -> Option<Vec<Decimal>> {
+    let priced_total = prices.iter().flatten().sum::<Decimal>();
+    let unpriced_count = prices.iter().filter(|price| price.is_none()).count();
+    if unpriced_count == prices.len() {
+        return None;
+    }
+
+    if unpriced_count == 0 || priced_total > Decimal::ONE {
+        if priced_total <= Decimal::ZERO {
+            return None;
+        }
+        return Some(
+            prices
+                .into_iter()
+                .map(|price| price.map_or(Decimal::ZERO, |price| price / priced_total))
+                .collect(),
+        );
+    }
+
+    let remainder = (Decimal::ONE - priced_total) / Decimal::from(unpriced_count);
+    Some(
+        prices
+            .into_iter()
+            .map(|price| price.unwrap_or(remainder))
+            .collect(),
+    )
+}
+
+fn get_vwap<'a>(levels: impl Iterator<Item = (&'a Decimal, &'a PriceLevel)>) -> Option<Decimal> {
+    let (value, quantity) = levels.fold(
+        (Decimal::ZERO, Decimal::ZERO),
+        |(value, quantity), (price, level)| {
+            let level_quantity = level.visible_quantity();
+            (value + *price * level_quantity, quantity + level_quantity)
+        },
+    );
+
+    (quantity > Decimal::ZERO).then(|| value / quantity)
+}
+
+fn get_total_funds(market_book: &dyn MarketView, outcome_index: usize) -> Decimal {
+    let Some(outcome) = market_book.get_outcome(outcome_index) else {
+        return Decimal::ZERO;
+    };
+
+    // iterating over bids, because buyers have put their money. sellers are putting stocks (not money, so funds = bids for this part)
+    // hidden quantity of iceberg orders is not revealed through the market price
+    let book_funds: Decimal = outcome
+        .order_book
+        .bids
+        .iter()
+        .map(|(p, price_level)| *p * price_level.visible_quantity())
+        .sum();
+
+    book_funds + outcome.executed_buy_volume
+}
+
+#[cfg(test)]
+mod test {
+    use db_service::schema::{
+        enums::{OrderSide, OrderStatus},
+        orders::Order,
+    };
+    use rust_decimal_macros::dec;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::order_book::{
+        market_book::{MarketBook, NO_OUTCOME, YES_OUTCOME},
+        outcome_book::MarketRules,
+    };
+
+    fn get_market_book(liquidity_b: Decimal, price_model: PriceModelKind) -> MarketBook {
+        MarketBook::new(liquidity_b)
+            .with_rules(MarketRules::default().with_price_model(price_model))
+    }
+
+    fn get_order(outcome: Outcome, side: OrderSide, price: Decimal, quantity: Decimal) -> Order {
+        Order {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            outcome,
+            side,
+            price,
+            quantity,
+            status: OrderStatus::OPEN,
+            ..Default::default()
+        }
+    }
+
+    fn get_prices(market_book: &MarketBook) -> (Decimal, Decimal) {
+        (
+            market_book.current_price(YES_OUTCOME),
+            market_book.current_price(NO_OUTCOME),
+        )
+    }
+
+    #[test]
+    fn test_lmsr_weighted_price() {
+        let mut market_book = get_market_book(dec!(100), PriceModelKind::LmsrWeighted);
+        assert_eq!(get_prices(&market_book), (dec!(0.5), dec!(0.5)));
+
+        // yes buyers put 10 in, (b + 10) / (2b + 10)
+        market_book.add_order(&get_order(
+            Outcome::YES,
+            OrderSide::BUY,
+            dec!(0.5),
+            dec!(20),
+        ));
+        assert_eq!(
+            market_book.current_price(YES_OUTCOME),
+            dec!(110) / dec!(210)
+        );
+        assert_eq!(
+            market_book.current_prices().iter().sum::<Decimal>(),
+            Decimal::ONE
+        );
+    }
+
+    #[test]
+    fn test_midpoint_price() {
+        let mut market_book = get_market_book(dec!(0), PriceModelKind::Midpoint);
+
+        // one sided book is priced at it's only side, no cap on the price
+        market_book.add_order(&get_order(
+            Outcome::YES,
+            OrderSide::BUY,
+            dec!(0.97),
+            dec!(5),
+        ));
+        assert_eq!(get_prices(&market_book), (dec!(0.97), dec!(0.03)));
+
+        // yes midpoint 0.5, no is priced at it's bid 0.3, normalized by 0.8
+        let mut market_book = get_market_book(dec!(0), PriceModelKind::Midpoint);
+        market_book.add_order(&get_order(Outcome::YES, OrderSide::BUY, dec!(0.4), dec!(5)));
+        market_book.add_order(&get_order(
+            Outcome::YES,
+            OrderSide::SELL,
+            dec!(0.6),
+            dec!(5),
+        ));
+        market_book.add_order(&get_order(Outcome::NO, OrderSide::BUY, dec!(0.3), dec!(5)));
+        assert_eq!(get_prices(&market_book), (dec!(0.625), dec!(0.375)));
+    }
+
+    #[test]
+    fn test_last_trade_price() {
+        let mut market_book = get_market_book(dec!(100), PriceModelKind::LastTrade);
+
+        // resting orders don't move the price
+        market_book.add_order(&get_order(Outcome::YES, OrderSide::BUY, dec!(0.4), dec!(5)));
+        assert_eq!(get_prices(&market_book), (dec!(0.5), dec!(0.5)));
+
+        let mut sell_order = get_order(Outcome::YES, OrderSide::SELL, dec!(0.4), dec!(2));
+        market_book.process_order(&mut sell_order);
+        assert_eq!(get_prices(&market_book), (dec!(0.4), dec!(0.6)));
+    }
+
+    #[test]
+    fn test_vwap_midpoint_price() {
+        let mut market_book = get_market_book(dec!(0), PriceModelKind::VwapMidpoint);
+        market_book.add_order(&get_order(
+            Outcome::YES,
+            OrderSide::BUY,
+            dec!(0.4),
+            dec!(10),
+        ));
+        market_book.add_order(&get_order(
+            Outcome::YES,
+            OrderSide::BUY,
+            dec!(0.3),
+            dec!(30),
+        ));
+        market_book.add_order(&get_order(
+            Outcome::YES,
+            OrderSide::SELL,
+            dec!(0.6),
+            dec!(10),
+        ));
+
+        // bid vwap (4 + 9) / 40 = 0.325, ask vwap 0.6
+        assert_eq!(get_prices(&market_book), (dec!(0.4625), dec!(0.5375)));
+    }
+
+    #[test]
+    fn test_microprice() {
+        let mut market_book = get_market_book(dec!(0), PriceModelKind::Microprice);
+        market_book.add_order(&get_order(
+            Outcome::YES,
+            OrderSide::BUY,
+            dec!(0.4),
+            dec!(30),
+        ));
+        market_book.add_order(&get_order(
+            Outcome::YES,
+            OrderSide::SELL,
+            dec!(0.6),
+            dec!(10),
+        ));
+
+        // heavier bid side pulls the price towards the ask, (0.4 * 10 + 0.6 * 30) / 40
+        assert_eq!(get_prices(&market_book), (dec!(0.55), dec!(0.45)));
+    }
+
+    #[test]
+    fn test_categorical_prices() {
+        let mut market_book = MarketBook::new(dec!(0)).with_rules(
+            MarketRules::default()
+                .with_price_model(PriceModelKind::Midpoint)
+                .with_outcome_count(4),
+        );
+        assert_eq!(market_book.current_prices(), vec![dec!(0.25); 4]);
+
+        // outcomes 0 and 2 are priced, the others share the remaining 0.3
+        let mut order = get_order(Outcome::YES, OrderSide::BUY, dec!(0.5), dec!(5));
+        market_book.add_order(&order);
+        order = get_order(Outcome::YES, OrderSide::SELL, dec!(0.2), dec!(5));
+        order.outcome_index = 2;
+        market_book.add_order(&order);
+        assert_eq!(
+            market_book.current_prices(),
+            vec![dec!(0.5), dec!(0.15), dec!(0.2), dec!(0.15)]
+        );
+
+        // every outcome is priced, normalized by their total 1.25
+        for (outcome_index, price) in [(1, dec!(0.3)), (3, dec!(0.25))] {
+            order = get_order(Outcome::YES, OrderSide::BUY, price, dec!(5));
+            order.outcome_index = outcome_index;
+            market_book.add_order(&order);
+        }
+        assert_eq!(
+            market_book.current_prices(),
+            vec![dec!(0.4), dec!(0.24), dec!(0.16), dec!(0.2)]
+        );
+    }
+}
@@ -0,0 +1,469 @@
+/*
+ * Stop loss and take profit orders are not part of the order book until their trigger price is hit.
+ *
+ * ## Trigger rules
+ *
+ * 1. SELL stop loss is triggered when reference price falls to (or below) the trigger price.
+ * 2. SELL take profit is triggered when reference price rises to (or above) the trigger price.
+ * 3. BUY orders are triggered in the opposite direction (stop loss on rise, take profit on fall).
+ * 4. Triggered order with non zero price is converted into LIMIT order, otherwise into MARKET order.
+ *
+ * ## Trailing stops
+ *
+ * Stop loss order with trailing state, it's trigger price follows the best reference price seen since it's placement
+ * (highest for SELL, lowest for BUY) by a fixed offset or a percentage of the best price, rounded to the tick away from the best price.
+ * Trailing stops are executed as MARKET orders.
+ */
+
+use std::collections::HashMap;
+
+use db_service::schema::{
+    enums::{OrderSide, OrderStatus, OrderType},
+    orders::Order,
+    trailing_stops::TrailingStop,
+};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use utility_helpers::log_info;
+use uuid::Uuid;
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub(crate) struct TriggerBook {
+    pub(crate) orders: Vec<Order>,
+    #[serde(default)]
+    pub(crate) trailing_stops: HashMap<Uuid, TrailingStop>, // trailing state of the stop loss orders
+    #[serde(skip)]
+    moved_trailing_stops: HashMap<Uuid, (TrailingStop, Decimal)>, // moved since they were last persisted, along with their trigger price
+}
+
+impl TriggerBook {
+    pub(crate) fn add_order(&mut self, order: Order) -> bool {
+        if !is_trigger_order(&order) {
+            log_info!(
+                "Order with id {} is not a stop loss / take profit order, not adding to trigger book",
+                order.id
+            );
+            return false;
+        }
+
+        if order.trigger_price.is_none_or(|price| price > Decimal::ONE) {
+            log_info!(
+                "Trigger price should be between 0.0 and 1.0, but got: {:?}, not adding order",
+                order.trigger_price
+            );
+            return false;
+        }
+
+        self.orders.push(order);
+        true
+    }
+
+    /// Adds the trailing stop, it's trigger price is set by the best price seen so far (or the current `reference_price`)
+    pub(crate) fn add_trailing_stop(
+        &mut self,
+        mut order: Order,
+        mut trailing_stop: TrailingStop,
+        reference_price: Decimal,
+        tick_size: Decimal,
+    ) -> bool {
+        if order.order_type != OrderType::StopLoss || order.id != trailing_stop.order_id {
+            log_info!(
+                "Order with id {} is not a trailing stop loss order, not adding to trigger book",
+                order.id
+            );
+            return false;
+        }
+        let best_price = trailing_stop.best_price.unwrap_or(reference_price);
+        trailing_stop.best_price = Some(best_price);
+        let trigger_price = get_trailing_trigger_price(order.side, &trailing_stop, tick_size);
+        order.trigger_price = Some(trigger_price);
+        order.price = Decimal::ZERO; // executed as market order
+
+        let order_id = order.id;
+        if !self.add_order(order) {
+            return false;
+        }
+        self.trailing_stops.insert(order_id, trailing_stop);
+        self.moved_trailing_stops
+            .insert(order_id, (trailing_stop, trigger_price));
+        true
+    }
+
+    pub(crate) fn remove_order(&mut self, order_id: Uuid) -> Option<Order> {
+        let pos = self.orders.iter().position(|order| order.id == order_id)?;
+        self.trailing_stops.remove(&order_id);
+        self.moved_trailing_stops.remove(&order_id);
+        Some(self.orders.remove(pos))
+    }
+
+    /// Sets the quantity of the order waiting for it's trigger, returns false if the order is not in the book
+    pub(crate) fn resize_order(&mut self, order_id: Uuid, quantity: Decimal) -> bool {
+        let Some(order) = self.orders.iter_mut().find(|order| order.id == order_id) else {
+            return false;
+        };
+        order.quantity = quantity;
+        true
+    }
+
+    pub(crate) fn retain_orders(&mut self, keep: &impl Fn(&Uuid) -> bool) -> usize {
+        let orders_count = self.orders.len();
+        self.orders.retain(|order| keep(&order.id));
+        self.trailing_stops.retain(|order_id, _| keep(order_id));
+        self.moved_trailing_stops
+            .retain(|order_id, _| keep(order_id));
+        orders_count - self.orders.len()
+    }
+
+    /// Moves the trigger price of the trailing stops whose best price is improved by the `reference_price`
+    pub(crate) fn update_trailing_stops(
+        &mut self,
+        reference_price: impl Fn(&Order) -> Decimal,
+        tick_size: Decimal,
+    ) {
+        for order in self.orders.iter_mut() {
+            let Some(trailing_stop) = self.trailing_stops.get_mut(&order.id) else {
+                continue;
+            };
+            let price = reference_price(order);
+            let improved = match (trailing_stop.best_price, order.side) {
+                (None, _) => true,
+                (Some(best_price), OrderSide::SELL) => price > best_price,
+                (Some(best_price), OrderSide::BUY) => price < best_price,
+            };
+            if !improved {
+                continue;
+            }
+
+            trailing_stop.best_price = Some(price);
+            let trigger_price = get_trailing_trigger_price(order.side, trailing_stop, tick_size);
+            order.trigger_price = Some(trigger_price);
+            self.moved_trailing_stops
+                .insert(order.id, (*trailing_stop, trigger_price));
+        }
+    }
+
+    /// Trailing stops moved since the last call, along with their trigger price
+    pub(crate) fn take_moved_trailing_stops(&mut self) -> Vec<(TrailingStop, Decimal)> {
+        self.moved_trailing_stops
+            .drain()
+            .map(|(_, moved)| moved)
+            .collect()
+    }
+
+    /// Removes all triggered orders from the book (in arrival order) and converts them into executable orders
+    pub(crate) fn take_triggered_orders(
+        &mut self,
+        reference_price: impl Fn(&Order) -> Decimal,
+    ) -> Vec<Order> {
+        let mut triggered = Vec::new();
+        let mut idx = 0;
+
+        while idx < self.orders.len() {
+            let order = &self.orders[idx];
+            if is_triggered(order, reference_price(order)) {
+                let mut order = self.orders.remove(idx);
+                self.trailing_stops.remove(&order.id);
+                self.moved_trailing_stops.remove(&order.id);
+                convert_to_executable_order(&mut order);
+                triggered.push(order);
+            } else {
+                idx += 1;
+            }
+        }
+
+        triggered
+    }
+}
+
+pub(crate) fn is_trigger_order(order: &Order) -> bool {
+    matches!(
+        order.order_type,
+        OrderType::StopLoss | OrderType::TakeProfit
+    )
+}
+
+fn is_triggered(order: &Order, reference_price: Decimal) -> bool {
+    let Some(trigger_price) = order.trigger_price else {
+        return false;
+    };
+
+    match (order.order_type, order.side) {
+        (OrderType::StopLoss, OrderSide::SELL) | (OrderType::TakeProfit, OrderSide::BUY) => {
+            reference_price <= trigger_price
+        }
+        (OrderType::StopLoss, OrderSide::BUY) | (OrderType::TakeProfit, OrderSide::SELL) => {
+            reference_price >= trigger_price
+        }
+        _ => false,
+    }
+}
+
+/// Trigger price of the trailing stop at it's best price, SELL trigger is rounded down to the tick and BUY trigger is rounded up
+fn get_trailing_trigger_price(
+    side: OrderSide,
+    trailing_stop: &TrailingStop,
+    tick_size: Decimal,
+) -> Decimal {
+    let best_price = trailing_stop.best_price.unwrap_or_default();
+    let offset = match (trailing_stop.trail_amount, trailing_stop.trail_percent) {
+        (Some(trail_amount), _) => trail_amount,
+        (None, Some(trail_percent)) => best_price * trail_percent / dec!(100),
+        (None, None) => Decimal::ZERO,
+    };
+    let round_to_tick = |price: Decimal, round: fn(&Decimal) -> Decimal| {
+        if tick_size > Decimal::ZERO {
+            round(&(price / tick_size)) * tick_size
+        } else {
+            price
+        }
+    };
+
+    match side {
+        OrderSide::SELL => round_to_tick(best_price - offset, Decimal::floor).max(Decimal::ZERO),
+        OrderSide::BUY => round_to_tick(best_price + offset, Decimal::ceil).min(Decimal::ONE),
+    }
+}
+
+fn convert_to_executable_order(order: &mut Order) {
+    order.status = OrderStatus::OPEN;
+    order.order_type = if order.price > Decimal::ZERO {
+        OrderType::LIMIT
+    } else {
+        OrderType::MARKET
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::NaiveDateTime;
+    use db_service::schema::enums::{Outcome, SelfTradePrevention, TimeInForce};
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    fn get_created_at() -> NaiveDateTime {
+        chrono::Utc::now().naive_local()
+    }
+
+    fn get_trigger_order(
+        order_type: OrderType,
+        side: OrderSide,
+        price: Decimal,
+        trigger_price: Decimal,
+    ) -> Order {
+        Order {
+            created_at: get_created_at(),
+            filled_quantity: Decimal::ZERO,
+            id: Uuid::new_v4(),
+            market_id: Uuid::new_v4(),
+            outcome: Outcome::YES,
+            price,
+            quantity: dec!(10),
+            side,
+            status: OrderStatus::OPEN,
+            updated_at: get_created_at(),
+            user_id: Uuid::new_v4(),
+            order_type,
+            trigger_price: Some(trigger_price),
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: 0,
+            display_quantity: None,
+        }
+    }
+
+    #[test]
+    fn test_add_resize_and_remove_trigger_order() {
+        let mut trigger_book = TriggerBook::default();
+        let order = get_trigger_order(OrderType::StopLoss, OrderSide::SELL, dec!(0.3), dec!(0.4));
+        let order_id = order.id;
+
+        assert!(trigger_book.add_order(order));
+        assert_eq!(trigger_book.orders.len(), 1);
+
+        assert!(trigger_book.resize_order(order_id, dec!(6)));
+        assert_eq!(trigger_book.orders[0].quantity, dec!(6));
+        assert!(!trigger_book.resize_order(Uuid::new_v4(), dec!(6)));
+
+        let removed = trigger_book.remove_order(order_id);
+        assert!(removed.is_some());
+        assert_eq!(removed.unwrap().id, order_id);
+        assert!(trigger_book.orders.is_empty());
+        assert!(trigger_book.remove_order(order_id).is_none());
+    }
+
+    #[test]
+    fn test_add_invalid_trigger_order() {
+        let mut trigger_book = TriggerBook::default();
+
+        let limit_order =
+            get_trigger_order(OrderType::LIMIT, OrderSide::SELL, dec!(0.3), dec!(0.4));
+        assert!(!trigger_book.add_order(limit_order));
+
+        let mut missing_trigger =
+            get_trigger_order(OrderType::StopLoss, OrderSide::SELL, dec!(0.3), dec!(0.4));
+        missing_trigger.trigger_price = None;
+        assert!(!trigger_book.add_order(missing_trigger));
+
+        let invalid_trigger =
+            get_trigger_order(OrderType::TakeProfit, OrderSide::SELL, dec!(0.3), dec!(1.2));
+        assert!(!trigger_book.add_order(invalid_trigger));
+
+        assert!(trigger_book.orders.is_empty());
+    }
+
+    #[test]
+    fn test_trigger_directions() {
+        let sell_stop = get_trigger_order(OrderType::StopLoss, OrderSide::SELL, dec!(0), dec!(0.4));
+        assert!(is_triggered(&sell_stop, dec!(0.4)));
+        assert!(is_triggered(&sell_stop, dec!(0.35)));
+        assert!(!is_triggered(&sell_stop, dec!(0.45)));
+
+        let sell_take_profit =
+            get_trigger_order(OrderType::TakeProfit, OrderSide::SELL, dec!(0), dec!(0.7));
+        assert!(is_triggered(&sell_take_profit, dec!(0.7)));
+        assert!(!is_triggered(&sell_take_profit, dec!(0.65)));
+
+        let buy_stop = get_trigger_order(OrderType::StopLoss, OrderSide::BUY, dec!(0), dec!(0.6));
+        assert!(is_triggered(&buy_stop, dec!(0.61)));
+        assert!(!is_triggered(&buy_stop, dec!(0.59)));
+
+        let buy_take_profit =
+            get_trigger_order(OrderType::TakeProfit, OrderSide::BUY, dec!(0), dec!(0.2));
+        assert!(is_triggered(&buy_take_profit, dec!(0.2)));
+        assert!(!is_triggered(&buy_take_profit, dec!(0.25)));
+    }
+
+    #[test]
+    fn test_take_triggered_orders_conversion() {
+        let mut trigger_book = TriggerBook::default();
+
+        let stop_limit =
+            get_trigger_order(OrderType::StopLoss, OrderSide::SELL, dec!(0.3), dec!(0.4));
+        let stop_market =
+            get_trigger_order(OrderType::StopLoss, OrderSide::SELL, dec!(0), dec!(0.45));
+        let take_profit =
+            get_trigger_order(OrderType::TakeProfit, OrderSide::SELL, dec!(0), dec!(0.8));
+
+        let stop_limit_id = stop_limit.id;
+        let stop_market_id = stop_market.id;
+
+        trigger_book.add_order(stop_limit);
+        trigger_book.add_order(stop_market);
+        trigger_book.add_order(take_profit);
+
+        let triggered = trigger_book.take_triggered_orders(|_| dec!(0.4));
+
+        assert_eq!(triggered.len(), 2);
+        assert_eq!(triggered[0].id, stop_limit_id);
+        assert_eq!(triggered[0].order_type, OrderType::LIMIT);
+        assert_eq!(triggered[1].id, stop_market_id);
+        assert_eq!(triggered[1].order_type, OrderType::MARKET);
+
+        // take profit order is still waiting for trigger
+        assert_eq!(trigger_book.orders.len(), 1);
+        assert_eq!(trigger_book.orders[0].order_type, OrderType::TakeProfit);
+    }
+
+    #[test]
+    fn test_trailing_stop_follows_best_price() {
+        let mut trigger_book = TriggerBook::default();
+
+        let mut sell_stop =
+            get_trigger_order(OrderType::StopLoss, OrderSide::SELL, dec!(0), dec!(0));
+        sell_stop.trigger_price = None;
+        let sell_trailing = TrailingStop {
+            order_id: sell_stop.id,
+            trail_amount: Some(dec!(0.05)),
+            trail_percent: None,
+            best_price: None,
+        };
+        let mut buy_stop = get_trigger_order(OrderType::StopLoss, OrderSide::BUY, dec!(0), dec!(0));
+        buy_stop.trigger_price = None;
+        let buy_trailing = TrailingStop {
+            order_id: buy_stop.id,
+            trail_amount: None,
+            trail_percent: Some(dec!(10)),
+            best_price: Some(dec!(0.40)), // persisted best price is kept
+        };
+        let (sell_stop_id, buy_stop_id) = (sell_stop.id, buy_stop.id);
+
+        assert!(trigger_book.add_trailing_stop(sell_stop, sell_trailing, dec!(0.5), dec!(0.01)));
+        assert!(trigger_book.add_trailing_stop(buy_stop, buy_trailing, dec!(0.5), dec!(0.01)));
+        assert_eq!(trigger_book.orders[0].trigger_price, Some(dec!(0.45)));
+        assert_eq!(trigger_book.orders[1].trigger_price, Some(dec!(0.44)));
+        assert_eq!(trigger_book.take_moved_trailing_stops().len(), 2);
+
+        // sell trails the higher price and buy trails the lower price, BUY trigger is rounded up to the tick
+        trigger_book.update_trailing_stops(
+            |order| match order.side {
+                OrderSide::SELL => dec!(0.62),
+                OrderSide::BUY => dec!(0.35),
+            },
+            dec!(0.01),
+        );
+        assert_eq!(trigger_book.orders[0].trigger_price, Some(dec!(0.57)));
+        assert_eq!(trigger_book.orders[1].trigger_price, Some(dec!(0.39)));
+        let moved = trigger_book.take_moved_trailing_stops();
+        assert_eq!(moved.len(), 2);
+        assert!(moved.contains(&(
+            TrailingStop {
+                best_price: Some(dec!(0.62)),
+                ..sell_trailing
+            },
+            dec!(0.57)
+        )));
+
+        // worse prices don't move the trigger
+        trigger_book.update_trailing_stops(|_| dec!(0.5), dec!(0.01));
+        assert!(trigger_book.take_moved_trailing_stops().is_empty());
+        assert_eq!(trigger_book.orders[0].trigger_price, Some(dec!(0.57)));
+
+        let triggered = trigger_book.take_triggered_orders(|order| match order.side {
+            OrderSide::SELL => dec!(0.56),
+            OrderSide::BUY => dec!(0.38),
+        });
+        assert_eq!(triggered.len(), 1);
+        assert_eq!(triggered[0].id, sell_stop_id);
+        assert_eq!(triggered[0].order_type, OrderType::MARKET);
+        assert!(!trigger_book.trailing_stops.contains_key(&sell_stop_id));
+
+        assert!(trigger_book.remove_order(buy_stop_id).is_some());
+        assert!(trigger_book.trailing_stops.is_empty());
+    }
+
+    #[test]
+    fn test_removed_trailing_stops_are_not_persisted() {
+        let mut trigger_book = TriggerBook::default();
+        let mut stops = Vec::new();
+        for _ in 0..2 {
+            let mut stop =
+                get_trigger_order(OrderType::StopLoss, OrderSide::SELL, dec!(0), dec!(0));
+            stop.trigger_price = None;
+            let trailing = TrailingStop {
+                order_id: stop.id,
+                trail_amount: Some(dec!(0.05)),
+                trail_percent: None,
+                best_price: None,
+            };
+            stops.push(stop.id);
+            assert!(trigger_book.add_trailing_stop(stop, trailing, dec!(0.5), dec!(0.01)));
+        }
+        trigger_book.take_moved_trailing_stops();
+
+        // both stops are moved, then the first one is cancelled and the second one is pruned
+        trigger_book.update_trailing_stops(|_| dec!(0.6), dec!(0.01));
+        assert!(trigger_book.remove_order(stops[0]).is_some());
+        assert_eq!(
+            trigger_book.retain_orders(&|order_id| *order_id != stops[1]),
+            1
+        );
+
+        assert!(trigger_book.take_moved_trailing_stops().is_empty());
+        assert!(trigger_book.trailing_stops.is_empty());
+    }
+}
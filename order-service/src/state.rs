@@ -11,7 +11,13 @@ use proto_defs::proto_types::ws_common_types::{
     Channel, OperationType, Payload, WsData, WsMessage,
 };
 use rdkafka::{ClientConfig, producer::FutureProducer};
-use tokio::{net::TcpStream, sync::RwLock as AsyncRwLock};
+use tokio::{
+    net::TcpStream,
+    sync::{
+        Mutex as AsyncMutex, RwLock as AsyncRwLock,
+        mpsc::{self, UnboundedReceiver},
+    },
+};
 use tokio_tungstenite::{
     MaybeTlsStream, WebSocketStream, connect_async, tungstenite::Message as WsMessageSentType,
     tungstenite::client::IntoClientRequest,
@@ -19,20 +25,26 @@ use tokio_tungstenite::{
 use utility_helpers::{log_error, log_info, types::EnvVarConfig};
 use uuid::Uuid;
 
-use crate::order_book::global_book::GlobalMarketBook;
+use crate::order_book::{
+    book_feed::MarketBookUpdate, global_book::GlobalMarketBook, journal::MatchingJournal,
+    outcome_book::PostOnlyMode,
+};
 
 pub struct AppState {
     // async states
     pub db_pool: sqlx::PgPool,
     pub jetstream: async_nats::jetstream::Context, // it's already thread safe for async operations internally...
+    pub nats_client: async_nats::Client,           // core nats, for request-reply (quotes)
     pub producer: AsyncRwLock<FutureProducer>,
     pub ws_tx:
         AsyncRwLock<SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, WsMessageSentType>>,
     pub ws_rx: AsyncRwLock<SplitStream<WebSocketStream<MaybeTlsStream<TcpStream>>>>,
+    pub(crate) book_updates: AsyncMutex<UnboundedReceiver<MarketBookUpdate>>, // queued by the global book, published by the book update handler
 
     // sync states
-    // preferring RwLock rather than tokio's rwLock because the operations on orderbook are not async (to gain maximum performance)
-    pub order_book: Arc<RwLock<GlobalMarketBook>>,
+    // every market is guarded by it's own lock (parking lot, operations on orderbook are not async) inside the global book, so markets are matched in parallel
+    pub order_book: Arc<GlobalMarketBook>,
+    // preferring RwLock rather than tokio's rwLock because the operations are not async (to gain maximum performance)
     pub market_subs: Arc<RwLock<HashSet<Uuid>>>, // market id subscribers (for order book updates)
 }
 
@@ -47,7 +59,7 @@ impl AppState {
             .expect("Failed to connect to NATS server");
         log_info!("Connected to NATS");
 
-        let jetstream = async_nats::jetstream::new(nc);
+        let jetstream = async_nats::jetstream::new(nc.clone());
         let db_pool = sqlx::PgPool::connect(&env_var_config.database_url)
             .await
             .expect("Failed to connect to the database");
@@ -92,15 +104,38 @@ impl AppState {
         log_info!("Handshake complete with websocket service");
         let (tx, rx) = stream.split();
 
-        let order_book = Arc::new(RwLock::new(GlobalMarketBook::new()));
+        // post only orders crossing the book are rejected unless `POST_ONLY_MODE=reprice` is set
+        let post_only_mode: PostOnlyMode = std::env::var("POST_ONLY_MODE")
+            .ok()
+            .and_then(|mode| mode.parse().ok())
+            .unwrap_or_default();
+        log_info!("Post only mode - {:?}", post_only_mode);
+
+        // book updates are queued under the market lock and published one by one in their sequence
+        let (book_publisher, book_updates) = mpsc::unbounded_channel();
+        let mut global_book = GlobalMarketBook::new()
+            .with_post_only_mode(post_only_mode)
+            .with_book_publisher(book_publisher);
+
+        // every command applied on the book is journaled when `MATCHING_JOURNAL_PATH` is set
+        if let Ok(journal_path) = std::env::var("MATCHING_JOURNAL_PATH") {
+            let journal = MatchingJournal::open(&journal_path)
+                .map_err(|e| format!("Failed to open matching journal - {e}"))?;
+            global_book = global_book.with_journal(journal);
+            log_info!("Matching journal - {}", journal_path);
+        }
+
+        let order_book = Arc::new(global_book);
         let market_subs = Arc::new(RwLock::new(HashSet::new()));
 
         Ok(AppState {
             db_pool,
             jetstream,
+            nats_client: nc,
             producer: AsyncRwLock::new(producer),
             ws_rx: AsyncRwLock::new(rx),
             ws_tx: AsyncRwLock::new(tx),
+            book_updates: AsyncMutex::new(book_updates),
             order_book,
             market_subs,
         })
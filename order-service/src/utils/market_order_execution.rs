@@ -0,0 +1,136 @@
+use std::{collections::HashSet, sync::Arc};
+
+use db_service::schema::{market_order_executions::MarketOrderExecution, orders::Order};
+use rust_decimal::Decimal;
+use utility_helpers::nats_helper::types::MarketOrderCreateMessage;
+
+use crate::{
+    order_book::outcome_book::{MatchType, OrderBookMatchedOutput},
+    state::AppState,
+    utils::OrderServiceError,
+};
+
+/// Saves the average fill price, consumed price levels and unfilled budget of the market order
+pub async fn save_market_order_execution(
+    app_state: &Arc<AppState>,
+    order: &Order,
+    message: &MarketOrderCreateMessage,
+    matches: &[OrderBookMatchedOutput],
+) -> Result<(), OrderServiceError> {
+    get_market_order_execution(order, message, matches)
+        .create(&app_state.db_pool)
+        .await
+        .map_err(|e| format!("Failed to save market order execution {e:#?}"))?;
+
+    Ok(())
+}
+
+fn get_market_order_execution(
+    order: &Order,
+    message: &MarketOrderCreateMessage,
+    matches: &[OrderBookMatchedOutput],
+) -> MarketOrderExecution {
+    // self trade prevention doesn't execute anything
+    let executed_matches = matches
+        .iter()
+        .filter(|m| m.match_type != MatchType::SelfTradePrevention)
+        .collect::<Vec<_>>();
+
+    let filled_quantity = executed_matches
+        .iter()
+        .map(|m| m.matched_quantity)
+        .sum::<Decimal>();
+    let executed_value = executed_matches
+        .iter()
+        .map(|m| m.price * m.matched_quantity)
+        .sum::<Decimal>();
+    let levels_consumed = executed_matches
+        .iter()
+        .map(|m| m.price)
+        .collect::<HashSet<_>>()
+        .len();
+
+    MarketOrderExecution {
+        order_id: order.id,
+        budget: message.budget,
+        price_limit: message.price_limit,
+        max_slippage: message.max_slippage,
+        average_price: (filled_quantity > Decimal::ZERO)
+            .then(|| (executed_value / filled_quantity).normalize()),
+        filled_quantity,
+        levels_consumed: i32::try_from(levels_consumed).unwrap_or(i32::MAX),
+        unfilled_budget: (message.budget - executed_value).max(Decimal::ZERO),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rust_decimal_macros::dec;
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn get_match(
+        price: Decimal,
+        matched_quantity: Decimal,
+        match_type: MatchType,
+    ) -> OrderBookMatchedOutput {
+        OrderBookMatchedOutput {
+            order_id: Uuid::new_v4(),
+            opposite_order_id: Uuid::new_v4(),
+            matched_quantity,
+            price,
+            opposite_order_total_quantity: matched_quantity,
+            opposite_order_filled_quantity: matched_quantity,
+            match_type,
+        }
+    }
+
+    #[test]
+    fn test_market_order_execution() {
+        let order = Order::default();
+        let message = MarketOrderCreateMessage {
+            order_id: order.id,
+            market_id: order.market_id,
+            budget: dec!(5),
+            price_limit: Some(dec!(0.6)),
+            max_slippage: None,
+            allow_partial: false,
+        };
+        let matches = [
+            get_match(dec!(0.5), dec!(4), MatchType::Transfer),
+            get_match(dec!(0.5), dec!(2), MatchType::SelfTradePrevention),
+            get_match(dec!(0.6), dec!(1), MatchType::Transfer),
+            get_match(dec!(0.6), dec!(2), MatchType::Transfer),
+        ];
+
+        let execution = get_market_order_execution(&order, &message, &matches);
+
+        // 4 * 0.5 + 3 * 0.6 = 3.8
+        assert_eq!(execution.filled_quantity, dec!(7));
+        assert_eq!(execution.average_price, Some(dec!(3.8) / dec!(7)));
+        assert_eq!(execution.levels_consumed, 2);
+        assert_eq!(execution.unfilled_budget, dec!(1.2));
+        assert_eq!(execution.price_limit, Some(dec!(0.6)));
+    }
+
+    #[test]
+    fn test_unmatched_market_order_execution() {
+        let order = Order::default();
+        let message = MarketOrderCreateMessage {
+            order_id: order.id,
+            market_id: order.market_id,
+            budget: dec!(5),
+            price_limit: None,
+            max_slippage: Some(dec!(0.05)),
+            allow_partial: false,
+        };
+
+        let execution = get_market_order_execution(&order, &message, &[]);
+
+        assert_eq!(execution.average_price, None);
+        assert_eq!(execution.levels_consumed, 0);
+        assert_eq!(execution.unfilled_budget, dec!(5));
+    }
+}
@@ -0,0 +1,82 @@
+use db_service::schema::{enums::MarketStatus, market::Market, orders::Order};
+use utility_helpers::{
+    message_pack_helper::deserialize_from_message_pack,
+    nats_helper::{
+        NatsSubjects,
+        types::{
+            InitializeOrderBookMessage, MarketOrderCreateMessage, MarketStatusUpdateMessage,
+            OrderMessage, UpdateOrderMessage,
+        },
+    },
+};
+use uuid::Uuid;
+
+use crate::{order_book::outcome_book::MarketRules, state::AppState, utils::OrderServiceError};
+
+/// Returns the market the message belongs to, `None` if the message isn't of a market
+pub fn get_message_market_id(
+    subject: &NatsSubjects,
+    payload: &[u8],
+) -> Result<Option<Uuid>, OrderServiceError> {
+    let market_id = match subject {
+        NatsSubjects::OrderCreate | NatsSubjects::OrderCancel => {
+            deserialize_from_message_pack::<OrderMessage>(payload)?.market_id
+        }
+        NatsSubjects::OrderUpdate => {
+            deserialize_from_message_pack::<UpdateOrderMessage>(payload)?.market_id
+        }
+        NatsSubjects::MarketOrderCreate => {
+            deserialize_from_message_pack::<MarketOrderCreateMessage>(payload)?.market_id
+        }
+        NatsSubjects::InitializeOrderBook => {
+            // all orders of the message belong to the initialized market
+            let message =
+                deserialize_from_message_pack::<InitializeOrderBookMessage<Order>>(payload)?;
+            return Ok(message.orders.first().map(|order| order.market_id));
+        }
+        NatsSubjects::FinalizeMarket => parse_uuid(payload)?,
+        NatsSubjects::MarketStatusUpdate => {
+            deserialize_from_message_pack::<MarketStatusUpdateMessage<MarketStatus>>(payload)?
+                .market_id
+        }
+        _ => return Ok(None),
+    };
+
+    Ok(Some(market_id))
+}
+
+/// Market is settled or voided by the message, so no more messages of the market are expected
+pub fn is_final_market_message(subject: &NatsSubjects, payload: &[u8]) -> bool {
+    match subject {
+        NatsSubjects::FinalizeMarket => true,
+        NatsSubjects::MarketStatusUpdate => {
+            deserialize_from_message_pack::<MarketStatusUpdateMessage<MarketStatus>>(payload)
+                .is_ok_and(|message| message.to_status.is_final())
+        }
+        _ => false,
+    }
+}
+
+/// Returns the rules of the market all given orders belong to (initialized market), default rules if there are no orders
+pub async fn get_orders_market_rules(
+    app_state: &AppState,
+    orders: &[Order],
+) -> Result<MarketRules, OrderServiceError> {
+    let Some(market_id) = orders.first().map(|order| order.market_id) else {
+        return Ok(MarketRules::default());
+    };
+
+    let market = Market::get_market_by_id(&app_state.db_pool, &market_id)
+        .await
+        .map_err(|e| format!("Failed to get market {market_id} {e:#?}"))?
+        .ok_or_else(|| format!("Market {market_id} not found"))?;
+
+    Ok(MarketRules::from_market(&market))
+}
+
+pub fn parse_uuid(payload: &[u8]) -> Result<Uuid, OrderServiceError> {
+    let id = String::from_utf8(payload.to_vec())
+        .map_err(|_| "Failed to convert payload to string".to_string())?;
+    let id = Uuid::parse_str(&id).map_err(|_| "Failed to parse ID from string".to_string())?;
+    Ok(id)
+}
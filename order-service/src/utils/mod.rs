@@ -1,3 +1,9 @@
+pub mod market_order_execution;
+pub mod market_resolver;
+pub mod order_book_snapshot;
+pub mod order_groups;
+pub mod process_trigger_orders;
+pub mod trading_status;
 pub mod update_matched_orders;
 pub mod update_services;
 
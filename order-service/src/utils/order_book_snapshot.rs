@@ -0,0 +1,397 @@
+/*
+ * Order book snapshots are used to restore the global book on restart without re-matching every open order.
+ *
+ * ## Restore flow
+ *
+ * 1. Snapshot is tagged with the ack floor of `order_os` consumer, every message till it is already applied on the book.
+ * 2. Market workers process messages out of order across markets, so every market is snapshotted by it's worker along with the last stream sequence applied on it.
+ * 3. On boot the latest snapshot is loaded and the messages after the ack floor (till the last message of the stream) are replayed on the book only, messages already applied on their market are skipped. Their db / service side effects are already persisted.
+ * 4. Messages after the current ack floor may or may not be acked (acks are out of order across markets), so they are replayed only if their order is already processed as per the db, the rest are left for the nats handler.
+ * 5. Orders which are closed outside of jetstream (eg. expired orders) are removed from the restored book.
+ * 6. Unacked messages are delivered again to the nats handler and processed as usual.
+ */
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use async_nats::jetstream::consumer::{self, DeliverPolicy};
+use chrono::NaiveDateTime;
+use db_service::schema::{
+    enums::{MarketStatus, OrderStatus, OrderType, TimeInForce},
+    order_book_snapshots::OrderBookSnapshot,
+    orders::Order,
+    trailing_stops::TrailingStop,
+};
+use futures_util::StreamExt;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot;
+use utility_helpers::{
+    log_info,
+    message_pack_helper::{deserialize_from_message_pack, serialize_to_message_pack},
+    nats_helper::{
+        NatsSubjects,
+        types::{
+            InitializeOrderBookMessage, MarketOrderCreateMessage, MarketStatusUpdateMessage,
+            OrderMessage, UpdateOrderMessage,
+        },
+    },
+};
+use uuid::Uuid;
+
+use crate::{
+    order_book::{
+        market_book::MarketBook,
+        outcome_book::{MarketOrderLimits, MarketRules},
+        trigger_book::is_trigger_order,
+    },
+    state::AppState,
+    utils::{
+        OrderServiceError,
+        market_resolver::{get_message_market_id, get_orders_market_rules, parse_uuid},
+    },
+};
+
+const SNAPSHOTS_TO_KEEP: i64 = 5;
+
+/// Market book along with the last stream sequence applied on it
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MarketSnapshot {
+    pub(crate) market_book: MarketBook,
+    pub stream_sequence: u64,
+}
+
+/// Saves the snapshot of all markets, `market_snapshots` are requested from the market workers after reading the ack floor
+pub async fn save_order_book_snapshot(
+    app_state: &Arc<AppState>,
+    ack_floor: u64,
+    market_snapshots: Vec<(Uuid, oneshot::Receiver<Option<MarketSnapshot>>)>,
+) -> Result<(), OrderServiceError> {
+    let mut markets = HashMap::new();
+    for (market_id, market_snapshot) in market_snapshots {
+        let market_snapshot = market_snapshot
+            .await
+            .map_err(|_| format!("Worker of market {market_id} stopped before the snapshot"))?;
+
+        // market is finalized in the meantime
+        if let Some(market_snapshot) = market_snapshot {
+            markets.insert(market_id, market_snapshot);
+        }
+    }
+    let snapshot = serialize_to_message_pack(&markets)?;
+
+    OrderBookSnapshot::create_snapshot(&app_state.db_pool, i64::try_from(ack_floor)?, snapshot)
+        .await
+        .map_err(|e| format!("Failed to save order book snapshot {e:#?}"))?;
+
+    OrderBookSnapshot::delete_old_snapshots(&app_state.db_pool, SNAPSHOTS_TO_KEEP)
+        .await
+        .map_err(|e| format!("Failed to delete old order book snapshots {e:#?}"))?;
+
+    Ok(())
+}
+
+/// Restores the global book from the latest snapshot, returns `false` if there is no snapshot to restore from
+pub async fn restore_order_book(app_state: &Arc<AppState>) -> Result<bool, OrderServiceError> {
+    let snapshot = OrderBookSnapshot::get_latest_snapshot(&app_state.db_pool)
+        .await
+        .map_err(|e| format!("Failed to get latest order book snapshot {e:#?}"))?;
+
+    let Some(snapshot) = snapshot else {
+        return Ok(false);
+    };
+
+    let market_snapshots = deserialize_from_message_pack::<HashMap<Uuid, MarketSnapshot>>(
+        snapshot.snapshot.as_slice(),
+    )?;
+
+    let mut market_sequences = HashMap::new();
+    let mut markets = HashMap::new();
+    for (market_id, market_snapshot) in market_snapshots {
+        market_sequences.insert(market_id, market_snapshot.stream_sequence);
+        markets.insert(market_id, market_snapshot.market_book);
+    }
+    app_state.order_book.restore_markets(markets);
+
+    let replayed = replay_messages(
+        app_state,
+        u64::try_from(snapshot.stream_sequence)?,
+        &market_sequences,
+    )
+    .await?;
+
+    let live_order_ids = Order::get_live_order_ids(&app_state.db_pool)
+        .await
+        .map_err(|e| format!("Failed to get live orders {e:#?}"))?
+        .into_iter()
+        .collect::<HashSet<Uuid>>();
+    let removed = app_state.order_book.retain_orders(&live_order_ids);
+
+    log_info!(
+        "Order book restored from snapshot at sequence {}, replayed {} messages and removed {} closed orders",
+        snapshot.stream_sequence,
+        replayed,
+        removed
+    );
+    Ok(true)
+}
+
+/// Replays the messages which are processed after the snapshot, returns the number of replayed messages
+async fn replay_messages(
+    app_state: &Arc<AppState>,
+    snapshot_sequence: u64,
+    market_sequences: &HashMap<Uuid, u64>,
+) -> Result<u64, OrderServiceError> {
+    let mut stream = app_state.jetstream.get_stream("ORDER").await?;
+    let last_sequence = stream.info().await?.state.last_sequence;
+
+    let mut durable_consumer: consumer::PullConsumer = stream.get_consumer("order_os").await?;
+    let last_acked_sequence = durable_consumer.info().await?.ack_floor.stream_sequence;
+
+    if last_sequence <= snapshot_sequence {
+        return Ok(0);
+    }
+
+    let replay_consumer = stream
+        .create_consumer(consumer::pull::OrderedConfig {
+            deliver_policy: DeliverPolicy::ByStartSequence {
+                start_sequence: snapshot_sequence + 1,
+            },
+            ..Default::default()
+        })
+        .await?;
+    let mut messages = replay_consumer.messages().await?;
+
+    let mut replayed = 0;
+    while let Some(message) = messages.next().await {
+        let message = message?;
+        let info = message.info()?;
+        let stream_sequence = info.stream_sequence;
+
+        let published_at = chrono::DateTime::from_timestamp(
+            info.published.unix_timestamp(),
+            info.published.nanosecond(),
+        )
+        .map(|published_at| published_at.naive_utc())
+        .unwrap_or_default();
+
+        // message is already applied on it's market before the snapshot
+        let already_applied = match NatsSubjects::from_string(message.subject.as_str()) {
+            Some(subject) => get_message_market_id(&subject, &message.payload)?
+                .and_then(|market_id| market_sequences.get(&market_id))
+                .is_some_and(|market_sequence| stream_sequence <= *market_sequence),
+            None => false,
+        };
+
+        if !already_applied {
+            let replayed_message = replay_message(
+                app_state,
+                message.subject.as_str(),
+                &message.payload,
+                published_at,
+                stream_sequence > last_acked_sequence,
+            )
+            .await?;
+            if replayed_message {
+                replayed += 1;
+            }
+        }
+
+        if stream_sequence >= last_sequence {
+            break;
+        }
+    }
+
+    Ok(replayed)
+}
+
+/// Applies the message on the book, returns `false` if the message is left for the nats handler
+///
+/// Messages after the ack floor (`past_ack_floor`) are applied only if their order is already processed as per the db
+async fn replay_message(
+    app_state: &Arc<AppState>,
+    subject: &str,
+    payload: &[u8],
+    published_at: NaiveDateTime,
+    past_ack_floor: bool,
+) -> Result<bool, OrderServiceError> {
+    let Some(subject) = NatsSubjects::from_string(subject) else {
+        return Ok(false);
+    };
+
+    let market_id = match subject {
+        NatsSubjects::OrderCreate => {
+            let order_id = deserialize_from_message_pack::<OrderMessage>(payload)?.order_id;
+            replay_create_order(app_state, order_id, None, published_at).await?
+        }
+        NatsSubjects::MarketOrderCreate => {
+            let message = deserialize_from_message_pack::<MarketOrderCreateMessage>(payload)?;
+            replay_create_order(app_state, message.order_id, Some(message), published_at).await?
+        }
+        NatsSubjects::OrderCancel => {
+            let order_id = deserialize_from_message_pack::<OrderMessage>(payload)?.order_id;
+            let order = Order::find_order_by_id(order_id, &app_state.db_pool)
+                .await
+                .map_err(|e| format!("Failed to find order {e:#?}"))?;
+
+            // cancellation which is not processed yet is left for the nats handler
+            if past_ack_floor
+                && order
+                    .as_ref()
+                    .is_none_or(|order| order.status == OrderStatus::PendingCancel)
+            {
+                return Ok(false);
+            }
+
+            order.map(|order| {
+                let order_book = &app_state.order_book;
+                if is_trigger_order(&order) {
+                    order_book.remove_trigger_order(&order.market_id, order.id);
+                } else {
+                    order_book.remove_order(order.market_id, order.id);
+                }
+                order.market_id
+            })
+        }
+        NatsSubjects::OrderUpdate => {
+            let message = deserialize_from_message_pack::<UpdateOrderMessage>(payload)?;
+            let order = Order::find_order_by_id(message.order_id, &app_state.db_pool)
+                .await
+                .map_err(|e| format!("Failed to find order {e:#?}"))?;
+
+            // amendment which is not processed yet is left for the nats handler
+            if past_ack_floor
+                && order
+                    .as_ref()
+                    .is_none_or(|order| order.status == OrderStatus::PendingUpdate)
+            {
+                return Ok(false);
+            }
+
+            order.and_then(|mut order| {
+                let order_book = &app_state.order_book;
+                if is_trigger_order(&order)
+                    && order_book.resize_trigger_order(
+                        &order.market_id,
+                        order.id,
+                        message.new_quantity,
+                    )
+                {
+                    return Some(order.market_id);
+                }
+
+                // db order is already updated, so using the state of the order at the time of update from the book
+                let (price, entry) = order_book.find_order(&order.market_id, order.id)?;
+                order.price = price;
+                order.quantity = entry.total_quantity;
+                order.filled_quantity = entry.filled_quantity;
+                order.status = OrderStatus::PendingUpdate;
+
+                order_book.update_order(&mut order, message.new_price, message.new_quantity);
+                Some(order.market_id)
+            })
+        }
+        NatsSubjects::InitializeOrderBook => {
+            let message =
+                deserialize_from_message_pack::<InitializeOrderBookMessage<Order>>(payload)?;
+            let rules = get_orders_market_rules(app_state, &message.orders).await?;
+            // orders are added only once, initialization past the ack floor is delivered again to the nats handler
+            for order in message.orders.iter() {
+                let order_book = &app_state.order_book;
+                if order_book.find_order(&order.market_id, order.id).is_none() {
+                    order_book.add_order(order, message.liquidity_b, rules);
+                }
+            }
+            None
+        }
+        NatsSubjects::FinalizeMarket => {
+            let market_id = parse_uuid(payload)?;
+            app_state.order_book.remove_market(&market_id);
+            None
+        }
+        NatsSubjects::MarketStatusUpdate => {
+            let message =
+                deserialize_from_message_pack::<MarketStatusUpdateMessage<MarketStatus>>(payload)?;
+            app_state
+                .order_book
+                .apply_market_status(&message.market_id, message.to_status);
+            // trigger orders waiting during the freeze are executed once the market is reopened
+            message.to_status.is_tradable().then_some(message.market_id)
+        }
+        // book updates published by the order service are not applied on the book
+        _ => return Ok(false),
+    };
+
+    if let Some(market_id) = market_id {
+        app_state.order_book.execute_triggered_orders(&market_id);
+    }
+
+    Ok(true)
+}
+
+/// Processes the order from it's initial state, returns market id if the order is added to the book
+async fn replay_create_order(
+    app_state: &Arc<AppState>,
+    order_id: Uuid,
+    market_order_opt: Option<MarketOrderCreateMessage>,
+    published_at: NaiveDateTime,
+) -> Result<Option<Uuid>, OrderServiceError> {
+    let order = Order::find_order_by_id_with_market(order_id, &app_state.db_pool)
+        .await
+        .map_err(|e| format!("Failed to find order {e:#?}"))?;
+
+    // order which is never processed is left for the nats handler
+    if order.status == OrderStatus::UNSPECIFIED {
+        return Ok(None);
+    }
+
+    // good till date order which was expired before reaching the book
+    if order.time_in_force == TimeInForce::GTD
+        && order
+            .expires_at
+            .is_some_and(|expires_at| expires_at <= published_at)
+    {
+        return Ok(None);
+    }
+
+    let market_id = order.market_id;
+    let liquidity_b = order.liquidity_b;
+    let rules = MarketRules::from_order_with_market(&order);
+    let mut order: Order = order.into();
+    order.status = OrderStatus::OPEN;
+    order.filled_quantity = Decimal::ZERO;
+    order.status_reason = None;
+
+    // trailing stop trails again from it's placement, as the book is replayed from the snapshot
+    let trailing_stop = if order.order_type == OrderType::StopLoss {
+        TrailingStop::get_trailing_stop_by_order_id(&app_state.db_pool, &order_id)
+            .await
+            .map_err(|e| format!("Failed to find trailing stop {e:#?}"))?
+            .map(|trailing_stop| TrailingStop {
+                best_price: None,
+                ..trailing_stop
+            })
+    } else {
+        None
+    };
+
+    let order_book = &app_state.order_book;
+    if let Some(trailing_stop) = trailing_stop {
+        order_book.add_trailing_stop(order, trailing_stop, liquidity_b, rules);
+    } else if is_trigger_order(&order) {
+        order_book.add_trigger_order(order, liquidity_b, rules);
+    } else if let Some(market_order) = market_order_opt.as_ref() {
+        order_book.create_market_order(
+            &market_id,
+            &mut order,
+            market_order.budget,
+            MarketOrderLimits::from(market_order),
+        );
+    } else {
+        order_book.process_order(&mut order, liquidity_b, rules);
+    }
+
+    Ok(Some(market_id))
+}
@@ -0,0 +1,86 @@
+use std::sync::Arc;
+
+use db_service::schema::order_groups::OrderGroup;
+use utility_helpers::{
+    log_info,
+    message_pack_helper::serialize_to_message_pack,
+    nats_helper::{
+        NatsSubjects,
+        types::{OrderMessage, UpdateOrderMessage},
+    },
+};
+use uuid::Uuid;
+
+use crate::{state::AppState, utils::OrderServiceError};
+
+/// Resolves the OCO / bracket groups of the market after it's latest changes.
+///
+/// Linked orders are cancelled (or shrunk by the partial fill of their leg) and bracket exits are activated through the jetstream, so they are processed by the market's worker like the user's requests
+pub async fn process_order_groups(
+    app_state: Arc<AppState>,
+    market_id: Uuid,
+) -> Result<(), OrderServiceError> {
+    let cancelled_order_ids = OrderGroup::cancel_linked_orders(&app_state.db_pool, &market_id)
+        .await
+        .map_err(|e| format!("Failed to cancel linked orders: {e:#?}"))?;
+    for order_id in cancelled_order_ids {
+        log_info!("Cancelling order {order_id} linked to a done order of it's group");
+        publish_order_message(&app_state, NatsSubjects::OrderCancel, order_id, market_id).await?;
+    }
+
+    let shrunk_orders = OrderGroup::shrink_linked_orders(&app_state.db_pool, &market_id)
+        .await
+        .map_err(|e| format!("Failed to shrink linked orders: {e:#?}"))?;
+    for shrunk_order in shrunk_orders {
+        log_info!(
+            "Shrinking order {} to {} by the partial fill of it's group",
+            shrunk_order.order_id,
+            shrunk_order.new_quantity
+        );
+        let message = UpdateOrderMessage {
+            order_id: shrunk_order.order_id,
+            market_id,
+            new_quantity: shrunk_order.new_quantity,
+            new_price: shrunk_order.price,
+        };
+        let message_pack_encoded = serialize_to_message_pack(&message)?;
+        app_state
+            .jetstream
+            .publish(
+                NatsSubjects::OrderUpdate.to_string(),
+                message_pack_encoded.into(),
+            )
+            .await
+            .map_err(|e| format!("Failed to publish order update: {e:#?}"))?;
+    }
+
+    let activated_order_ids = OrderGroup::activate_bracket_exits(&app_state.db_pool, &market_id)
+        .await
+        .map_err(|e| format!("Failed to activate bracket exits: {e:#?}"))?;
+    for order_id in activated_order_ids {
+        log_info!("Activating bracket exit {order_id}");
+        publish_order_message(&app_state, NatsSubjects::OrderCreate, order_id, market_id).await?;
+    }
+
+    Ok(())
+}
+
+async fn publish_order_message(
+    app_state: &AppState,
+    subject: NatsSubjects,
+    order_id: Uuid,
+    market_id: Uuid,
+) -> Result<(), OrderServiceError> {
+    let subject = subject.to_string();
+    let message_pack_encoded = serialize_to_message_pack(&OrderMessage {
+        order_id,
+        market_id,
+    })?;
+    app_state
+        .jetstream
+        .publish(subject.clone(), message_pack_encoded.into())
+        .await
+        .map_err(|e| format!("Failed to publish {subject} of order {order_id}: {e:#?}"))?;
+
+    Ok(())
+}
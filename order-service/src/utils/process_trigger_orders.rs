@@ -0,0 +1,61 @@
+use std::sync::Arc;
+
+use db_service::schema::trailing_stops::TrailingStop;
+use utility_helpers::log_info;
+use uuid::Uuid;
+
+use crate::{
+    state::AppState,
+    utils::{
+        OrderServiceError, order_groups::process_order_groups, trading_status::record_trading_halt,
+        update_matched_orders::update_matched_orders, update_services::update_service_state,
+    },
+};
+
+/// Executes stop loss / take profit orders of the market whose trigger price is hit after the latest book changes
+pub async fn process_trigger_orders(
+    app_state: Arc<AppState>,
+    market_id: Uuid,
+) -> Result<(), OrderServiceError> {
+    let (triggered_orders, moved_trailing_stops) = {
+        // sync block
+        {
+            let order_book = &app_state.order_book;
+            let triggered_orders = order_book.execute_triggered_orders(&market_id);
+            (
+                triggered_orders,
+                order_book.take_moved_trailing_stops(&market_id),
+            )
+        }
+    };
+
+    // trailing state is persisted, so trailing stops keep their best price across restarts
+    TrailingStop::save_trailing_stops(&app_state.db_pool, &moved_trailing_stops)
+        .await
+        .map_err(|e| format!("Failed to save trailing stops: {e:#?}"))?;
+
+    for (order, matches) in triggered_orders {
+        log_info!(
+            "Order {} triggered at {:?}, executing as {:?} order",
+            order.id,
+            order.trigger_price,
+            order.order_type
+        );
+
+        order
+            .update(&app_state.db_pool)
+            .await
+            .map_err(|e| format!("Failed to update triggered order: {e:#?}"))?;
+
+        tokio::try_join!(
+            update_matched_orders(matches, app_state.clone(), &order),
+            update_service_state(app_state.clone(), &order)
+        )?;
+    }
+
+    // latest order (or the triggered ones) might have breached the price band
+    record_trading_halt(app_state.clone(), market_id).await?;
+
+    // filled or cancelled orders of the groups cancel / activate their linked orders
+    process_order_groups(app_state, market_id).await
+}
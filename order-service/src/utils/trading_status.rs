@@ -0,0 +1,130 @@
+/*
+ * Trading halts of the circuit breaker, halt is started by the matching engine and it's cool-down is tracked by the database
+ *
+ * - Pushes the halt / resume into clickhouse via kafka
+ * - Send the halt state to the websocket which serves the users
+ * - Publishes the halt / resume to NATS for other services to consume
+ */
+
+use std::{sync::Arc, time::Duration};
+
+use chrono::NaiveDateTime;
+use db_service::schema::market::Market;
+use futures_util::SinkExt;
+use rdkafka::producer::FutureRecord;
+use rust_decimal::Decimal;
+use tokio_tungstenite::tungstenite::Message as WsMessageType;
+use utility_helpers::{
+    kafka_topics::KafkaTopics,
+    log_error, log_info,
+    message_pack_helper::serialize_to_message_pack,
+    nats_helper::{NatsSubjects, types::MarketTradingStatusMessage},
+};
+use uuid::Uuid;
+
+use crate::{
+    order_book::circuit_breaker::PRICE_BAND_BREACH_REASON,
+    state::AppState,
+    utils::{OrderServiceError, update_services::get_price_poster_message},
+};
+
+/// Records the halt of the market once the price band is breached, halt is published only when it's recorded for the first time
+pub async fn record_trading_halt(
+    app_state: Arc<AppState>,
+    market_id: Uuid,
+) -> Result<(), OrderServiceError> {
+    if !app_state.order_book.is_trading_halted(&market_id) {
+        return Ok(());
+    }
+
+    let halted_at = chrono::Utc::now().naive_utc();
+    let resume_at = Market::halt_trading(&app_state.db_pool, &market_id, halted_at)
+        .await
+        .map_err(|e| format!("Failed to halt trading of market: {:#?}", e))?;
+    let Some(resume_at) = resume_at else {
+        return Ok(()); // halt is already recorded
+    };
+
+    log_info!("Trading of market {market_id} is halted till {resume_at}");
+    publish_trading_status(app_state, market_id, Some(resume_at)).await
+}
+
+/// Publishes the current trading status of the market, `resume_at` is the end of the halt's cool-down
+pub async fn publish_trading_status(
+    app_state: Arc<AppState>,
+    market_id: Uuid,
+    resume_at: Option<NaiveDateTime>,
+) -> Result<(), OrderServiceError> {
+    let current_time = chrono::Utc::now();
+
+    let (halted, outcome_prices, auction_indications) = {
+        // sync block
+        {
+            let order_book = &app_state.order_book;
+            (
+                order_book.is_trading_halted(&market_id),
+                order_book
+                    .get_market_prices(&market_id)
+                    .unwrap_or_else(|| vec![Decimal::new(5, 1); 2]),
+                order_book.get_auction_indications(&market_id),
+            )
+        }
+    };
+    let reason = halted.then(|| PRICE_BAND_BREACH_REASON.to_string());
+    let resume_at = resume_at.map(|resume_at| resume_at.and_utc().to_rfc3339());
+
+    // kafka (clickhouse)
+    let status_update = serde_json::json!({
+        "market_id": market_id.to_string(),
+        "status": if halted { "halted" } else { "resumed" },
+        "reason": reason,
+        "resume_at": resume_at,
+        "ts": current_time.to_rfc3339(),
+    })
+    .to_string();
+    let market_id_str = market_id.to_string();
+    let producer = app_state.producer.read().await;
+    let record = FutureRecord::to(KafkaTopics::MarketStatusUpdates.to_string())
+        .payload(&status_update)
+        .key(&market_id_str);
+    if let Err(e) = producer.send(record, Duration::from_secs(0)).await {
+        log_error!("Failed to send Kafka message: {:#?}", e);
+    }
+    drop(producer);
+
+    // nats
+    let status_message = MarketTradingStatusMessage {
+        market_id,
+        halted,
+        reason,
+        resume_at,
+        timestamp: current_time.to_rfc3339(),
+    };
+    let message_pack_encoded = serialize_to_message_pack(&status_message)?;
+    let subject = NatsSubjects::MarketTradingStatus(market_id).to_string();
+    if let Err(e) = app_state
+        .nats_client
+        .publish(subject, message_pack_encoded.into())
+        .await
+    {
+        log_error!("Failed to send NATS message: {:#?}", e);
+    }
+
+    // websocket
+    let bin_data = get_price_poster_message(
+        market_id,
+        &outcome_prices,
+        auction_indications.as_ref(),
+        halted,
+        current_time.timestamp_millis(),
+    )?;
+    let mut ws_publisher = app_state.ws_tx.write().await;
+    if let Err(e) = ws_publisher
+        .send(WsMessageType::Binary(bin_data.into()))
+        .await
+    {
+        log_error!("Failed to send WebSocket message: {:#?}", e);
+    }
+
+    Ok(())
+}
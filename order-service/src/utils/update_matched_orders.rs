@@ -2,15 +2,22 @@ use std::sync::Arc;
 
 use db_service::schema::{
     enums::{OrderSide, OrderStatus},
+    market_maker_accounts::MarketMakerAccount,
+    market_maker_trades::MarketMakerTrade,
     orders::Order,
     user_holdings::UserHoldings,
     user_trades::UserTrades,
     users::User,
 };
 use rust_decimal::Decimal;
+use sqlx::PgPool;
+
+use uuid::Uuid;
 
 use crate::{
-    order_book::outcome_book::OrderBookMatchedOutput, state::AppState, utils::OrderServiceError,
+    order_book::outcome_book::{MatchType, OrderBookMatchedOutput, SELF_TRADE_PREVENTION_REASON},
+    state::AppState,
+    utils::{OrderServiceError, update_services::update_service_state},
 };
 
 pub async fn update_matched_orders(
@@ -19,125 +26,544 @@ pub async fn update_matched_orders(
     order: &Order,
 ) -> Result<(), OrderServiceError> {
     for match_item in matched_order {
-        // update the opposite order's filled quantity
-        let current_order_id = match_item.order_id;
-        let opposite_order_id = match_item.opposite_order_id;
-        let quantity = match_item.matched_quantity;
-        let opposite_order_new_status = if match_item.opposite_order_filled_quantity
-            == match_item.opposite_order_total_quantity
-        {
+        // resting order of the same user is reduced (or cancelled) instead of being matched
+        if match_item.match_type == MatchType::SelfTradePrevention {
+            update_self_trade_prevented_order(&app_state, &match_item).await?;
+            continue;
+        }
+
+        save_matched_order(&app_state.db_pool, order, &match_item).await?;
+    }
+
+    Ok(())
+}
+
+/// Persists the trades, holdings and balances of the match of the `order`
+pub(crate) async fn save_matched_order(
+    db_pool: &PgPool,
+    order: &Order,
+    match_item: &OrderBookMatchedOutput,
+) -> Result<(), OrderServiceError> {
+    // market maker is the counterparty, there is no opposite order to update
+    if match_item.match_type == MatchType::MarketMaker {
+        return update_market_maker_match(db_pool, order, match_item).await;
+    }
+
+    // update the opposite order's filled quantity
+    let current_order_id = match_item.order_id;
+    let opposite_order_id = match_item.opposite_order_id;
+    let quantity = match_item.matched_quantity;
+    let opposite_order_new_status =
+        if match_item.opposite_order_filled_quantity == match_item.opposite_order_total_quantity {
             OrderStatus::FILLED
         } else {
             OrderStatus::OPEN
         };
 
-        // current order is updated previously, as it's get mutated from order matching engine
-        Order::update_order_status_and_filled_quantity(
-            &app_state.db_pool,
-            opposite_order_id,
-            opposite_order_new_status,
-            match_item.opposite_order_filled_quantity,
+    // current order is updated previously, as it's get mutated from order matching engine
+    Order::update_order_status_and_filled_quantity(
+        db_pool,
+        opposite_order_id,
+        opposite_order_new_status,
+        match_item.opposite_order_filled_quantity,
+    )
+    .await
+    .map_err(|e| format!("Failed to update opposite order: {:#?}", e))?;
+
+    // fetching user ids of current and opposite orders for updating user trades and holdings
+    let get_current_order_user_id_future = Order::get_order_user_id(db_pool, current_order_id);
+    let get_opposite_order_user_id_future = Order::get_order_user_id(db_pool, opposite_order_id);
+
+    // we want both id, so try_join instead of join
+    let (current_order_user_id, opposite_order_user_id) = tokio::try_join!(
+        get_current_order_user_id_future,
+        get_opposite_order_user_id_future
+    )
+    .map_err(|e| {
+        format!(
+            "Failed to get user ids for current order {:#?} and opposite order {:#?}: {:#?}",
+            current_order_id, opposite_order_id, e
         )
+    })?;
+
+    // complementary matches (YES against NO) mint or merge share pairs instead of transferring them
+    if match_item.match_type != MatchType::Transfer {
+        return update_complementary_match(db_pool, order, match_item, opposite_order_user_id)
+            .await;
+    }
+
+    /////// Database Transaction start ////////
+
+    // here we are preferring to use db transaction instead of rust's parallel (tokio::join) operation processing (it compromises performance and perform sequential processing), +we can't share `tx` across async tasks parallelly
+    let mut tx = db_pool.begin().await?;
+
+    let (current_order_type, opposite_order_type) = match order.side {
+        OrderSide::BUY => (OrderSide::SELL, OrderSide::BUY), // if my current order is buy, then trade type is sell for opposite order as I want to sell my shares
+        OrderSide::SELL => (OrderSide::BUY, OrderSide::SELL), // if my current order is sell, then trade type is buy for opposite order as I want to buy shares
+    };
+
+    // create current order's user trade
+    UserTrades::create_user_trade(
+        &mut *tx,
+        current_order_id,
+        opposite_order_id,
+        order.user_id,
+        order.market_id,
+        order.outcome,
+        order.outcome_index,
+        match_item.price,
+        quantity,
+        current_order_type,
+    )
+    .await
+    .map_err(|e| format!("Failed to create user trade: {:#?}", e))?;
+
+    // create opposite order's user trade
+    UserTrades::create_user_trade(
+        &mut *tx,
+        current_order_id,
+        opposite_order_id,
+        current_order_user_id,
+        order.market_id,
+        order.outcome,
+        order.outcome_index,
+        match_item.price,
+        quantity,
+        opposite_order_type,
+    )
+    .await?;
+
+    let (current_order_user_updated_holding, opposite_order_user_updated_holdings) =
+        match order.side {
+            OrderSide::BUY => (quantity, -quantity),
+            OrderSide::SELL => (-quantity, quantity),
+        };
+
+    UserHoldings::update_user_holdings(
+        &mut *tx,
+        current_order_user_id,
+        order.market_id,
+        current_order_user_updated_holding,
+        order.outcome,
+        order.outcome_index,
+    )
+    .await?;
+
+    UserHoldings::update_user_holdings(
+        &mut *tx,
+        opposite_order_user_id,
+        order.market_id,
+        opposite_order_user_updated_holdings,
+        order.outcome,
+        order.outcome_index,
+    )
+    .await?;
+
+    // updating user balances
+    User::update_two_users_balance(
+        &mut *tx,
+        current_order_user_id,
+        opposite_order_user_id,
+        (match_item.matched_quantity * match_item.price) * Decimal::from(100),
+        order.side,
+    )
+    .await?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit transaction: {:#?}", e))?;
+
+    /////// Database Transaction end ////////
+
+    Ok(())
+}
+
+async fn update_complementary_match(
+    db_pool: &PgPool,
+    order: &Order,
+    match_item: &OrderBookMatchedOutput,
+    opposite_order_user_id: Uuid,
+) -> Result<(), OrderServiceError> {
+    let quantity = match_item.matched_quantity;
+    let complementary_outcome = order.outcome.complement();
+    let complementary_price = Decimal::ONE - match_item.price;
+
+    let mut tx = db_pool.begin().await?;
+
+    // both users are on the same side of the trade, each one trades it's own outcome
+    UserTrades::create_user_trade(
+        &mut *tx,
+        match_item.order_id,
+        match_item.opposite_order_id,
+        order.user_id,
+        order.market_id,
+        order.outcome,
+        order.outcome_index,
+        match_item.price,
+        quantity,
+        order.side,
+    )
+    .await
+    .map_err(|e| format!("Failed to create user trade: {:#?}", e))?;
+
+    UserTrades::create_user_trade(
+        &mut *tx,
+        match_item.order_id,
+        match_item.opposite_order_id,
+        opposite_order_user_id,
+        order.market_id,
+        complementary_outcome,
+        order.outcome_index,
+        complementary_price,
+        quantity,
+        order.side,
+    )
+    .await
+    .map_err(|e| format!("Failed to create user trade: {:#?}", e))?;
+
+    // minted pair is credited to both users, merged pair is burned from both users
+    let holding_update = match match_item.match_type {
+        MatchType::Merge => -quantity,
+        _ => quantity,
+    };
+
+    UserHoldings::update_user_holdings(
+        &mut *tx,
+        order.user_id,
+        order.market_id,
+        holding_update,
+        order.outcome,
+        order.outcome_index,
+    )
+    .await?;
+
+    UserHoldings::update_user_holdings(
+        &mut *tx,
+        opposite_order_user_id,
+        order.market_id,
+        holding_update,
+        complementary_outcome,
+        order.outcome_index,
+    )
+    .await?;
+
+    // every pair is collateralized with 100 (payout of the winning share)
+    User::update_two_users_balance_same_side(
+        &mut *tx,
+        order.user_id,
+        (quantity * match_item.price) * Decimal::from(100),
+        opposite_order_user_id,
+        (quantity * complementary_price) * Decimal::from(100),
+        order.side,
+    )
+    .await?;
+
+    tx.commit()
         .await
-        .map_err(|e| format!("Failed to update opposite order: {:#?}", e))?;
-
-        // fetching user ids of current and opposite orders for updating user trades and holdings
-        let get_current_order_user_id_future =
-            Order::get_order_user_id(&app_state.db_pool, current_order_id);
-        let get_opposite_order_user_id_future =
-            Order::get_order_user_id(&app_state.db_pool, opposite_order_id);
-
-        // we want both id, so try_join instead of join
-        let (current_order_user_id, opposite_order_user_id) = tokio::try_join!(
-            get_current_order_user_id_future,
-            get_opposite_order_user_id_future
+        .map_err(|e| format!("Failed to commit transaction: {:#?}", e))?;
+
+    Ok(())
+}
+
+async fn update_market_maker_match(
+    db_pool: &PgPool,
+    order: &Order,
+    match_item: &OrderBookMatchedOutput,
+) -> Result<(), OrderServiceError> {
+    let quantity = match_item.matched_quantity;
+    let amount = quantity * match_item.price;
+
+    let mut tx = db_pool.begin().await?;
+
+    MarketMakerTrade {
+        market_id: order.market_id,
+        order_id: order.id,
+        user_id: order.user_id,
+        outcome: order.outcome,
+        side: order.side,
+        price: match_item.price,
+        quantity,
+        ..Default::default()
+    }
+    .create(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to create market maker trade: {:#?}", e))?;
+
+    // inventory and cash of the market maker's system account
+    MarketMakerAccount::apply_trade(
+        &mut *tx,
+        order.market_id,
+        order.outcome,
+        order.side,
+        quantity,
+        amount,
+    )
+    .await
+    .map_err(|e| format!("Failed to update market maker account: {:#?}", e))?;
+
+    let holding_update = match order.side {
+        OrderSide::BUY => quantity,
+        OrderSide::SELL => -quantity,
+    };
+    UserHoldings::update_user_holdings(
+        &mut *tx,
+        order.user_id,
+        order.market_id,
+        holding_update,
+        order.outcome,
+        order.outcome_index,
+    )
+    .await?;
+
+    User::update_user_balance(
+        &mut *tx,
+        order.user_id,
+        amount * Decimal::from(100),
+        order.side,
+    )
+    .await?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit transaction: {:#?}", e))?;
+
+    Ok(())
+}
+
+async fn update_self_trade_prevented_order(
+    app_state: &Arc<AppState>,
+    match_item: &OrderBookMatchedOutput,
+) -> Result<(), OrderServiceError> {
+    let resting_order = Order::find_order_by_id(match_item.opposite_order_id, &app_state.db_pool)
+        .await
+        .map_err(|e| format!("Failed to find resting order {:#?}", e))?;
+
+    let Some(mut resting_order) = resting_order else {
+        return Err(format!(
+            "Resting order {} of self trade prevention not found",
+            match_item.opposite_order_id
         )
-        .map_err(|e| {
-            format!(
-                "Failed to get user ids for current order {:#?} and opposite order {:#?}: {:#?}",
-                current_order_id, opposite_order_id, e
-            )
-        })?;
+        .into());
+    };
 
-        /////// Database Transaction start ////////
+    resting_order.quantity = match_item.opposite_order_total_quantity;
+    resting_order.filled_quantity = match_item.opposite_order_filled_quantity;
+    if resting_order.filled_quantity >= resting_order.quantity {
+        resting_order.status = OrderStatus::CANCELLED;
+        resting_order.status_reason = Some(SELF_TRADE_PREVENTION_REASON.to_string());
+    }
 
-        // here we are preferring to use db transaction instead of rust's parallel (tokio::join) operation processing (it compromises performance and perform sequential processing), +we can't share `tx` across async tasks parallelly
-        let mut tx = app_state.db_pool.begin().await?;
+    let resting_order = resting_order
+        .update(&app_state.db_pool)
+        .await
+        .map_err(|e| format!("Failed to update resting order {:#?}", e))?;
 
-        let (current_order_type, opposite_order_type) = match order.side {
-            OrderSide::BUY => (OrderSide::SELL, OrderSide::BUY), // if my current order is buy, then trade type is sell for opposite order as I want to sell my shares
-            OrderSide::SELL => (OrderSide::BUY, OrderSide::SELL), // if my current order is sell, then trade type is buy for opposite order as I want to buy shares
-        };
+    update_service_state(app_state.clone(), &resting_order).await
+}
 
-        // create current order's user trade
-        UserTrades::create_user_trade(
-            &mut *tx,
-            current_order_id,
-            opposite_order_id,
-            order.user_id,
-            order.market_id,
-            order.outcome,
-            match_item.price,
-            quantity,
-            current_order_type,
+#[cfg(test)]
+mod tests {
+    use chrono::DateTime;
+    use db_service::schema::{
+        enums::{
+            MarketStatus, OrderType, Outcome, PriceModelKind, SelfTradePrevention, TimeInForce,
+        },
+        market::{Market, NewMarket},
+        orders::NewOrder,
+    };
+    use rust_decimal_macros::dec;
+    use utility_helpers::types::GoogleClaims;
+
+    use super::*;
+    use crate::order_book::{global_book::GlobalMarketBook, outcome_book::MarketRules};
+
+    const BLUE_OUTCOME: i32 = 2;
+
+    async fn create_user(pool: &PgPool, name: &str) -> User {
+        User::create_new_user(
+            pool,
+            &GoogleClaims {
+                email: format!("{name}@gmail.com"),
+                exp: 0,
+                name: "temp".to_string(),
+                picture: "temp".to_string(),
+                sub: name.to_string(),
+            },
         )
         .await
-        .map_err(|e| format!("Failed to create user trade: {:#?}", e))?;
-
-        // create opposite order's user trade
-        UserTrades::create_user_trade(
-            &mut *tx,
-            current_order_id,
-            opposite_order_id,
-            current_order_user_id,
-            order.market_id,
-            order.outcome,
-            match_item.price,
-            quantity,
-            opposite_order_type,
+        .unwrap()
+    }
+
+    // open limit order of 10 yes shares of the blue outcome
+    async fn create_open_order(
+        pool: &PgPool,
+        user_id: Uuid,
+        market_id: Uuid,
+        side: OrderSide,
+    ) -> Order {
+        let mut order = Order::create_order(
+            pool,
+            &NewOrder {
+                user_id,
+                market_id,
+                price: dec!(0.40),
+                quantity: dec!(10),
+                side,
+                outcome: Outcome::YES,
+                order_type: OrderType::LIMIT,
+                trigger_price: None,
+                time_in_force: TimeInForce::GTC,
+                expires_at: None,
+                post_only: false,
+                self_trade_prevention: SelfTradePrevention::CancelNewest,
+                display_quantity: None,
+                outcome_index: BLUE_OUTCOME,
+            },
         )
-        .await?;
+        .await
+        .unwrap();
+        order.status = OrderStatus::OPEN;
+        order
+    }
 
-        let (current_order_user_updated_holding, opposite_order_user_updated_holdings) =
-            match order.side {
-                OrderSide::BUY => (quantity, -quantity),
-                OrderSide::SELL => (-quantity, quantity),
-            };
+    #[tokio::test]
+    async fn test_categorical_order_is_matched_and_settled() {
+        dotenv::dotenv().ok();
+        let pool = PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
 
-        UserHoldings::update_user_holdings(
-            &mut *tx,
-            current_order_user_id,
-            order.market_id,
-            current_order_user_updated_holding,
-            order.outcome,
+        let market_expiry = DateTime::parse_from_rfc3339("2030-01-01T00:00:00Z")
+            .unwrap()
+            .naive_utc();
+        let market = Market::create_new_market(
+            &pool,
+            &NewMarket {
+                name: "Test Categorical Market Settlement".to_string(),
+                description: "Test Description".to_string(),
+                logo: "Test Logo".to_string(),
+                liquidity_b: dec!(100),
+                tick_size: dec!(0.01),
+                min_quantity: dec!(0.01),
+                lot_size: dec!(0.01),
+                market_maker: false,
+                price_model: PriceModelKind::Midpoint,
+                outcomes: vec!["Red".to_string(), "Green".to_string(), "Blue".to_string()],
+                scalar_range: None,
+                market_expiry,
+            },
+        )
+        .await
+        .unwrap();
+        Market::transition_status(
+            &pool,
+            &market.id,
+            MarketStatus::DRAFT,
+            MarketStatus::OPEN,
+            None,
         )
-        .await?;
+        .await
+        .unwrap();
+        let rules = MarketRules::from_market(&market);
+        let order_book = GlobalMarketBook::new();
 
+        // seller rests the blue shares it's holding, as the bootstrap liquidity of the market initialization
+        let seller = create_user(&pool, "categorical_seller").await;
         UserHoldings::update_user_holdings(
-            &mut *tx,
-            opposite_order_user_id,
-            order.market_id,
-            opposite_order_user_updated_holdings,
-            order.outcome,
+            &pool,
+            seller.id,
+            market.id,
+            dec!(10),
+            Outcome::YES,
+            BLUE_OUTCOME,
         )
-        .await?;
-
-        // updating user balances
-        User::update_two_users_balance(
-            &mut *tx,
-            current_order_user_id,
-            opposite_order_user_id,
-            (match_item.matched_quantity * match_item.price) * Decimal::from(100),
-            order.side,
+        .await
+        .unwrap();
+        let sell_order = create_open_order(&pool, seller.id, market.id, OrderSide::SELL).await;
+        sell_order.update(&pool).await.unwrap();
+        order_book.add_order(&sell_order, market.liquidity_b, rules);
+
+        let buyer = create_user(&pool, "categorical_buyer").await;
+        User::update_user_balance(&pool, buyer.id, dec!(1000), OrderSide::SELL)
+            .await
+            .unwrap();
+        let mut buy_order = create_open_order(&pool, buyer.id, market.id, OrderSide::BUY).await;
+        let matches = order_book.process_order(&mut buy_order, market.liquidity_b, rules);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].match_type, MatchType::Transfer);
+        assert_eq!(buy_order.status, OrderStatus::FILLED);
+
+        let buy_order = buy_order.update(&pool).await.unwrap();
+        for match_item in &matches {
+            save_matched_order(&pool, &buy_order, match_item)
+                .await
+                .unwrap();
+        }
+
+        let holding = UserHoldings::get_user_holdings_by_outcome(
+            &pool,
+            buyer.id,
+            market.id,
+            Outcome::YES,
+            BLUE_OUTCOME,
         )
-        .await?;
+        .await
+        .unwrap();
+        assert_eq!(holding.shares, dec!(10));
+        // 10 shares at 0.40 (balance is in cents)
+        assert_eq!(
+            User::get_user_balance(&pool, buyer.id).await.unwrap(),
+            dec!(600)
+        );
+        assert_eq!(
+            User::get_user_balance(&pool, seller.id).await.unwrap(),
+            dec!(400)
+        );
+        let sell_order = Order::find_order_by_id(sell_order.id, &pool)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(sell_order.status, OrderStatus::FILLED);
 
-        tx.commit()
+        // market is settled on the blue outcome, each of it's yes shares pays 100
+        for (from, to) in [
+            (MarketStatus::OPEN, MarketStatus::CLOSED),
+            (MarketStatus::CLOSED, MarketStatus::RESOLVING),
+        ] {
+            Market::transition_status(&pool, &market.id, from, to, None)
+                .await
+                .unwrap();
+        }
+        Market::settle_market(&pool, &market.id, Outcome::YES, Some(BLUE_OUTCOME), None)
             .await
-            .map_err(|e| format!("Failed to commit transaction: {:#?}", e))?;
+            .unwrap();
+        assert_eq!(
+            User::get_user_balance(&pool, buyer.id).await.unwrap(),
+            dec!(1600)
+        );
+        assert_eq!(
+            User::get_user_balance(&pool, seller.id).await.unwrap(),
+            dec!(400)
+        );
 
-        /////// Database Transaction end ////////
+        // Clean up
+        for query in [
+            r#"DELETE FROM "polymarket"."user_trades" WHERE market_id = $1"#,
+            r#"DELETE FROM "polymarket"."user_holdings" WHERE market_id = $1"#,
+            r#"DELETE FROM "polymarket"."orders" WHERE market_id = $1"#,
+            r#"DELETE FROM "polymarket"."markets" WHERE id = $1"#,
+        ] {
+            sqlx::query(query)
+                .bind(market.id)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+        sqlx::query(r#"DELETE FROM "polymarket"."users" WHERE id = ANY($1)"#)
+            .bind(vec![buyer.id, seller.id])
+            .execute(&pool)
+            .await
+            .unwrap();
+        pool.close().await;
     }
-
-    Ok(())
 }
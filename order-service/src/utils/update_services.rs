@@ -4,14 +4,12 @@
  * - Pushes the price into clickhouse via kafka
  * - Send the data to the websocket which serves the users
  * - Publishes the data to NATS for other services to consume
+ * - Publishes the book updates (queued by the global book) to clickhouse and NATS in the order of their sequence
  */
 
 use std::{str::FromStr, sync::Arc, time::Duration};
 
-use db_service::schema::{
-    enums::{OrderStatus, Outcome},
-    orders::Order,
-};
+use db_service::schema::{enums::OrderStatus, orders::Order};
 use futures_util::SinkExt;
 use prost::Message;
 use proto_defs::proto_types::ws_common_types::{
@@ -24,11 +22,23 @@ use utility_helpers::{
     kafka_topics::KafkaTopics,
     log_error, log_info,
     message_pack_helper::serialize_to_message_pack,
-    nats_helper::{NatsSubjects, types::OrderBookUpdateData},
-    types::OrderBookDataStruct,
+    nats_helper::{
+        NatsSubjects,
+        types::{OrderBookUpdateData, OrderStatusUpdateMessage},
+    },
 };
 
-use crate::{state::AppState, utils::OrderServiceError};
+use uuid::Uuid;
+
+use crate::{
+    order_book::{
+        book_feed::{BookUpdate, MarketBookUpdate},
+        call_auction::AuctionIndication,
+        market_book::{NO_OUTCOME, YES_OUTCOME},
+    },
+    state::AppState,
+    utils::OrderServiceError,
+};
 
 pub async fn update_service_state(
     app_state: Arc<AppState>,
@@ -43,53 +53,36 @@ pub async fn update_service_state(
 
     let (producer, mut ws_publisher) = tokio::join!(producer_lock_future, ws_publisher_lock_future);
 
-    let js_guard = app_state.jetstream.clone();
-
     ////////////////////////////////////////////////////////////////////////////////////////////////////////
     ////////////////////////////////////// Sync code block star ///////////////////////////////////////
     ////////////////////////////////////////////////////////////////////////////////////////////////////////
 
     // market id validation and current market state
-    let (yes_price, no_price, yes_orders_data, no_orders_data, required_market_subs) = {
+    let (outcome_prices, auction_indications, halted) = {
         // sync block
         {
-            let order_book = app_state.order_book.read();
-
-            let yes_price = order_book
-                .get_market_price(&market_id, Outcome::YES)
-                .unwrap_or_else(|| Decimal::new(5, 1));
-            let no_price = order_book
-                .get_market_price(&market_id, Outcome::NO)
-                .unwrap_or_else(|| Decimal::new(5, 1));
-
-            let yes_orders = order_book.get_orders(&market_id, Outcome::YES);
-            let no_orders = order_book.get_orders(&market_id, Outcome::NO);
-
-            // processing yes orders
-            let yes_orders_data = if let Some(yes_orders) = yes_orders {
-                yes_orders.get_order_book()
-            } else {
-                OrderBookDataStruct::default()
-            };
-            // processing no orders
-            let no_orders_data = if let Some(no_orders) = no_orders {
-                no_orders.get_order_book()
-            } else {
-                OrderBookDataStruct::default()
-            };
-            let market_subs_guard = app_state.market_subs.read();
-            let required_market_subs = market_subs_guard.contains(&market_id);
+            let order_book = &app_state.order_book;
+
+            // prices of all outcomes, yes / no are the first two outcomes of binary market
+            let outcome_prices = order_book
+                .get_market_prices(&market_id)
+                .unwrap_or_else(|| vec![Decimal::new(5, 1); 2]);
+            // sequenced delta of the books is queued for the book update handler (kafka and nats)
+            order_book.publish_book_update(&market_id, Some(order.user_id));
+            // indicative uncross while the market is in the call auction
+            let auction_indications = order_book.get_auction_indications(&market_id);
+            let halted = order_book.is_trading_halted(&market_id);
 
             (
                 // passing states from sync codeblock to async code block....
-                yes_price,
-                no_price,
-                yes_orders_data,
-                no_orders_data,
-                required_market_subs,
+                outcome_prices,
+                auction_indications,
+                halted,
             )
         }
     };
+    let yes_price = outcome_prices.get(YES_OUTCOME).copied().unwrap_or_default();
+    let no_price = outcome_prices.get(NO_OUTCOME).copied().unwrap_or_default();
 
     log_info!(
         "Order processed.. YES Price: {}, NO Price: {}",
@@ -111,17 +104,7 @@ pub async fn update_service_state(
         "market_id": market_id.to_string(),
         "yes_price":yes_price.to_string(),
         "no_price": no_price.to_string(),
-        "ts": ts,
-    })
-    .to_string();
-
-    let data_to_publish_for_order_book_update = serde_json::json!({
-        "user_id": order.user_id.to_string(),
-        "market_id": market_id.to_string(),
-        "yes_asks": yes_orders_data.asks,
-        "yes_bids": yes_orders_data.bids,
-        "no_asks": no_orders_data.asks,
-        "no_bids": no_orders_data.bids,
+        "outcome_prices": outcome_prices.iter().map(|price| price.to_string()).collect::<Vec<_>>(),
         "ts": ts,
     })
     .to_string();
@@ -139,30 +122,22 @@ pub async fn update_service_state(
     .to_string();
 
     let price_update_topic = KafkaTopics::PriceUpdates.to_string();
-    let market_order_book_update_topic = KafkaTopics::MarketOrderBookUpdate.to_string();
     let volume_update_topic = KafkaTopics::VolumeUpdates.to_string();
 
     let market_id_str = market_id.to_string();
 
     let mut kafka_futures = vec![];
-    let mut nats_futures = vec![];
 
     let record_price_update = FutureRecord::to(price_update_topic)
         .payload(&data_to_publish_for_price_update)
         .key(&market_id_str);
-    let record_order_book_update = FutureRecord::to(market_order_book_update_topic)
-        .payload(&data_to_publish_for_order_book_update)
-        .key(&market_id_str);
     let record_volume_update = FutureRecord::to(volume_update_topic)
         .payload(&data_to_publish_for_volume_update)
         .key(&market_id_str);
 
     let send_producer_future_price = producer.send(record_price_update, Duration::from_secs(0));
-    let send_producer_future_order_book =
-        producer.send(record_order_book_update, Duration::from_secs(0));
 
     kafka_futures.push(send_producer_future_price);
-    kafka_futures.push(send_producer_future_order_book);
 
     if order.status == OrderStatus::FILLED {
         let send_producer_future_volume =
@@ -178,21 +153,24 @@ pub async fn update_service_state(
     ////////////////////////////////////////// NATS processing //////////////////////////////////////////
     /////////////////////////////////////////////////////////////////////////////////////////////////////////
 
-    let combined_data = OrderBookUpdateData {
-        yes_book: yes_orders_data.clone(),
-        no_book: no_orders_data.clone(),
-        market_id: market_id,
+    // order status (with rejection reason) for the order owner
+    let order_status_data = OrderStatusUpdateMessage {
+        order_id: order.id,
+        market_id,
+        status: order.status,
+        price: order.price,
+        filled_quantity: order.filled_quantity,
+        reason: order.status_reason.clone(),
         timestamp: current_time.to_rfc3339(),
     };
-
-    if required_market_subs {
-        let message_pack_encoded = serialize_to_message_pack(&combined_data)?;
-
-        // pushing message to queue
-        let subject = NatsSubjects::MarketBookUpdate(market_id).to_string();
-        let publish_msg_future = js_guard.publish(subject, message_pack_encoded.into());
-
-        nats_futures.push(publish_msg_future);
+    let order_status_encoded = serialize_to_message_pack(&order_status_data)?;
+    let order_status_subject = NatsSubjects::OrderStatusUpdate(order.user_id).to_string();
+    if let Err(e) = app_state
+        .nats_client
+        .publish(order_status_subject, order_status_encoded.into())
+        .await
+    {
+        log_error!("Failed to send NATS message: {:#?}", e);
     }
 
     ////////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -203,31 +181,13 @@ pub async fn update_service_state(
     ///////////////////////////// sending message to websocket ////////////////////////////////////////////
     /////////////////////////////////////////////////////////////////////////////////////////////////////
 
-    let yes_price = f64::from_str(&yes_price.to_string())
-        .map_err(|_| "Failed to parse yes price to f64".to_string())?;
-    let no_price = f64::from_str(&no_price.to_string())
-        .map_err(|_| "Failed to parse no price to f64".to_string())?;
-
-    let market_data = serde_json::json!({
-        "market_id": market_id,
-        "yes_price": yes_price,
-        "no_price": no_price,
-        "timestamp": current_time.timestamp_millis(),
-    })
-    .to_string();
-
-    let message = WsMessage {
-        id: None,
-        payload: Some(Payload {
-            ops: OperationType::Post as i32,
-            data: Some(WsData {
-                channel: Channel::Priceposter as i32,
-                params: market_data,
-            }),
-        }),
-    };
-
-    let bin_data = message.encode_to_vec();
+    let bin_data = get_price_poster_message(
+        market_id,
+        &outcome_prices,
+        auction_indications.as_ref(),
+        halted,
+        current_time.timestamp_millis(),
+    )?;
 
     let ws_broadcast_future = ws_publisher.send(WsMessageType::Binary(bin_data.into()));
 
@@ -237,7 +197,6 @@ pub async fn update_service_state(
 
     let result = tokio::join!(
         futures_util::future::join_all(kafka_futures),
-        futures_util::future::join_all(nats_futures),
         ws_broadcast_future
     );
     for res in result.0 {
@@ -246,17 +205,175 @@ pub async fn update_service_state(
             Err(e) => log_error!("Failed to send Kafka message: {:#?}", e),
         }
     }
-    for res in result.1 {
-        match res {
-            Ok(_) => log_info!("NATS message sent successfully"),
-            Err(e) => log_error!("Failed to send NATS message: {:#?}", e),
+    match result.1 {
+        Ok(_) => log_info!("WebSocket message sent successfully"),
+        Err(e) => log_error!("Failed to send WebSocket message: {:#?}", e),
+    }
+
+    Ok(())
+}
+
+/// Publishes the book update queued by the global book, kafka (clickhouse) gets every delta along with the periodic snapshots
+/// and nats gets them while the market has subscribers
+pub async fn publish_book_update(
+    app_state: &Arc<AppState>,
+    market_book_update: MarketBookUpdate,
+) -> Result<(), OrderServiceError> {
+    let MarketBookUpdate {
+        market_id,
+        user_id,
+        book_update,
+    } = market_book_update;
+    let ts = chrono::Utc::now().to_rfc3339();
+
+    // full books are only sent with the snapshots, consumers rebuild the books from the last snapshot and the deltas after it
+    let outcome_books = if book_update.is_snapshot {
+        book_update.books.as_slice()
+    } else {
+        &[]
+    };
+    let yes_orders_data = outcome_books.get(YES_OUTCOME).cloned().unwrap_or_default();
+    let no_orders_data = outcome_books.get(NO_OUTCOME).cloned().unwrap_or_default();
+    let data_to_publish_for_order_book_update = serde_json::json!({
+        "user_id": user_id.unwrap_or_default().to_string(),
+        "market_id": market_id.to_string(),
+        "yes_asks": yes_orders_data.asks,
+        "yes_bids": yes_orders_data.bids,
+        "no_asks": no_orders_data.asks,
+        "no_bids": no_orders_data.bids,
+        "outcome_bids": outcome_books.iter().map(|book| &book.bids).collect::<Vec<_>>(),
+        "outcome_asks": outcome_books.iter().map(|book| &book.asks).collect::<Vec<_>>(),
+        "epoch": book_update.epoch,
+        "sequence": book_update.sequence,
+        "is_snapshot": book_update.is_snapshot,
+        "outcome_bid_deltas": book_update.deltas.iter().map(|delta| &delta.bids).collect::<Vec<_>>(),
+        "outcome_ask_deltas": book_update.deltas.iter().map(|delta| &delta.asks).collect::<Vec<_>>(),
+        "checksum": book_update.checksum,
+        "ts": ts,
+    })
+    .to_string();
+
+    let market_id_str = market_id.to_string();
+    let record_order_book_update = FutureRecord::to(KafkaTopics::MarketOrderBookUpdate.to_string())
+        .payload(&data_to_publish_for_order_book_update)
+        .key(&market_id_str);
+    {
+        let producer = app_state.producer.read().await;
+        if let Err(e) = producer
+            .send(record_order_book_update, Duration::from_secs(0))
+            .await
+        {
+            log_error!("Failed to send Kafka message: {:#?}", e);
         }
     }
 
-    match result.2 {
-        Ok(_) => log_info!("WebSocket message sent successfully"),
-        Err(e) => log_error!("Failed to send WebSocket message: {:#?}", e),
+    let required_market_subs = app_state.market_subs.read().contains(&market_id);
+    if required_market_subs {
+        let book_update_data = get_book_update_message(market_id, book_update, ts);
+        let message_pack_encoded = serialize_to_message_pack(&book_update_data)?;
+
+        let subject = NatsSubjects::MarketBookUpdate(market_id).to_string();
+        app_state
+            .jetstream
+            .publish(subject, message_pack_encoded.into())
+            .await?;
     }
 
     Ok(())
 }
+
+/// Book update message of the market for the subscribers, deltas carry only the changed levels and books are sent with the snapshots
+pub(crate) fn get_book_update_message(
+    market_id: Uuid,
+    book_update: BookUpdate,
+    timestamp: String,
+) -> OrderBookUpdateData {
+    let (yes_book, no_book, outcome_books) = if book_update.is_snapshot {
+        let yes_book = book_update
+            .books
+            .get(YES_OUTCOME)
+            .cloned()
+            .unwrap_or_default();
+        let no_book = book_update
+            .books
+            .get(NO_OUTCOME)
+            .cloned()
+            .unwrap_or_default();
+        (yes_book, no_book, book_update.books)
+    } else {
+        Default::default()
+    };
+
+    OrderBookUpdateData {
+        yes_book,
+        no_book,
+        market_id,
+        timestamp,
+        outcome_books,
+        sequence: book_update.sequence,
+        is_snapshot: book_update.is_snapshot,
+        outcome_deltas: book_update.deltas,
+        checksum: book_update.checksum,
+        epoch: book_update.epoch,
+    }
+}
+
+/// Price poster message of the market for the websocket clients (prices, call auction and trading halt)
+pub fn get_price_poster_message(
+    market_id: Uuid,
+    outcome_prices: &[Decimal],
+    auction_indications: Option<&Vec<(usize, AuctionIndication)>>,
+    halted: bool,
+    timestamp: i64,
+) -> Result<Vec<u8>, OrderServiceError> {
+    let yes_price = outcome_prices.get(YES_OUTCOME).copied().unwrap_or_default();
+    let no_price = outcome_prices.get(NO_OUTCOME).copied().unwrap_or_default();
+
+    let yes_price = f64::from_str(&yes_price.to_string())
+        .map_err(|_| "Failed to parse yes price to f64".to_string())?;
+    let no_price = f64::from_str(&no_price.to_string())
+        .map_err(|_| "Failed to parse no price to f64".to_string())?;
+    let prices = outcome_prices
+        .iter()
+        .map(|price| f64::from_str(&price.to_string()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| "Failed to parse outcome prices to f64".to_string())?;
+    let auction = auction_indications
+        .into_iter()
+        .flatten()
+        .map(|(outcome_index, indication)| {
+            Ok::<_, std::num::ParseFloatError>(serde_json::json!({
+                "outcome_index": outcome_index,
+                "price": f64::from_str(&indication.price.to_string())?,
+                "volume": f64::from_str(&indication.volume.to_string())?,
+                "imbalance": f64::from_str(&indication.imbalance.to_string())?,
+            }))
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| "Failed to parse auction indications to f64".to_string())?;
+
+    let market_data = serde_json::json!({
+        "market_id": market_id,
+        "yes_price": yes_price,
+        "no_price": no_price,
+        "prices": prices,
+        "timestamp": timestamp,
+        "in_auction": auction_indications.is_some(),
+        "auction": auction,
+        "halted": halted,
+    })
+    .to_string();
+
+    let message = WsMessage {
+        id: None,
+        payload: Some(Payload {
+            ops: OperationType::Post as i32,
+            data: Some(WsData {
+                channel: Channel::Priceposter as i32,
+                params: market_data,
+            }),
+        }),
+    };
+
+    Ok(message.encode_to_vec())
+}
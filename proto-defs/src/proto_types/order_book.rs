@@ -18,6 +18,16 @@ pub struct OrderBook {
     #[prost(message, repeated, tag = "3")]
     pub asks: ::prost::alloc::vec::Vec<OrderLevel>,
 }
+/// order book of one outcome, outcome 0 / 1 are YES / NO of binary market
+#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct OutcomeBook {
+    #[prost(uint32, tag = "1")]
+    pub outcome_index: u32,
+    #[prost(message, optional, tag = "2")]
+    pub book: ::core::option::Option<OrderBook>,
+}
+/// full books are sent with the snapshots, deltas carry only the changed levels (level with 0 shares is removed)
 #[derive(serde::Serialize, serde::Deserialize)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct MarketBook {
@@ -27,4 +37,21 @@ pub struct MarketBook {
     pub yes_book: ::core::option::Option<OrderBook>,
     #[prost(message, optional, tag = "3")]
     pub no_book: ::core::option::Option<OrderBook>,
+    #[prost(message, repeated, tag = "4")]
+    pub outcome_books: ::prost::alloc::vec::Vec<OutcomeBook>,
+    /// per market, increased by one for every update, gap means an update is missed
+    #[prost(uint64, tag = "5")]
+    pub sequence: u64,
+    /// books are full, otherwise only outcome_deltas are set
+    #[prost(bool, tag = "6")]
+    pub is_snapshot: bool,
+    /// changed levels of the outcomes
+    #[prost(message, repeated, tag = "7")]
+    pub outcome_deltas: ::prost::alloc::vec::Vec<OutcomeBook>,
+    /// 32 bit FNV-1a of the books after the update, mismatch means the books have to be resynced from the next snapshot
+    #[prost(uint32, tag = "8")]
+    pub checksum: u32,
+    /// boot of the order service, sequence restarts from 1 when it changes (not a gap or a replay)
+    #[prost(uint64, tag = "9")]
+    pub epoch: u64,
 }
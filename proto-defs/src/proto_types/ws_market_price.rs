@@ -11,4 +11,31 @@ pub struct WsParamsPayload {
     pub no_price: f64,
     #[prost(uint64, tag = "4")]
     pub timestamp: u64,
+    /// prices of all outcomes by their index (yes / no for binary market)
+    #[prost(double, repeated, tag = "5")]
+    pub prices: ::prost::alloc::vec::Vec<f64>,
+    /// market is in the opening call auction, orders are not matched till it's uncrossed
+    #[prost(bool, tag = "6")]
+    pub in_auction: bool,
+    /// indicative uncross of the outcomes which cross, empty out of the auction
+    #[prost(message, repeated, tag = "7")]
+    pub auction: ::prost::alloc::vec::Vec<AuctionIndication>,
+    /// trading is halted by the circuit breaker, orders are rejected till it's resumed
+    #[prost(bool, tag = "8")]
+    pub halted: bool,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct AuctionIndication {
+    #[prost(uint32, tag = "1")]
+    pub outcome_index: u32,
+    /// indicative clearing price
+    #[prost(double, tag = "2")]
+    pub price: f64,
+    /// executable quantity at the price
+    #[prost(double, tag = "3")]
+    pub volume: f64,
+    /// demand - supply at the price
+    #[prost(double, tag = "4")]
+    pub imbalance: f64,
 }
@@ -12,6 +12,7 @@ CREATE TABLE market_price_data (
     market_id UUID,
     yes_price Float64,
     no_price Decimal(20, 8),
+    outcome_prices Array(Decimal(20, 8)), -- prices of all outcomes by their index (yes / no for binary market)
     ts DateTime('UTC'),
     created_at DateTime('UTC') DEFAULT now(),
 ) ENGINE = MergeTree
@@ -24,6 +25,7 @@ CREATE TABLE market_price_data_kafka (
     market_id UUID,
     yes_price Decimal(20, 8),
     no_price Decimal(20, 8),
+    outcome_prices Array(Decimal(20, 8)),
     ts String,
 ) ENGINE = Kafka(
     'redpanda:9092', -- broker (red panda)
@@ -41,6 +43,7 @@ SELECT
     market_id,
     yes_price,
     no_price,
+    outcome_prices,
     parseDateTimeBestEffort(ts) AS ts
 FROM market_price_data_kafka;
 
@@ -60,7 +63,20 @@ CREATE TABLE market_order_book (
     yes_asks Array(Tuple(price Float64, shares Float64, users UInt32)),
 
     no_bids Array(Tuple(price Float64, shares Float64, users UInt32)),
-    no_asks Array(Tuple(price Float64, shares Float64, users UInt32))
+    no_asks Array(Tuple(price Float64, shares Float64, users UInt32)),
+
+    -- books of all outcomes by their index (yes / no for binary market)
+    outcome_bids Array(Array(Tuple(price Float64, shares Float64, users UInt32))),
+    outcome_asks Array(Array(Tuple(price Float64, shares Float64, users UInt32))),
+
+    -- sequence of the book update (restarts when the epoch changes) and it's delta, level with 0 shares is removed
+    -- books above are only set on the snapshots, books in between are rebuilt from the last snapshot and it's following deltas
+    epoch UInt64,
+    sequence UInt64,
+    is_snapshot Bool,
+    outcome_bid_deltas Array(Array(Tuple(price Float64, shares Float64, users UInt32))),
+    outcome_ask_deltas Array(Array(Tuple(price Float64, shares Float64, users UInt32))),
+    checksum UInt32
 ) ENGINE = MergeTree    
 ORDER BY (market_id, ts);
 
@@ -75,7 +91,20 @@ CREATE TABLE market_order_book_analytical (
     yes_asks Array(Tuple(price Float64, shares Float64, users UInt32)),
 
     no_bids Array(Tuple(price Float64, shares Float64, users UInt32)),
-    no_asks Array(Tuple(price Float64, shares Float64, users UInt32))
+    no_asks Array(Tuple(price Float64, shares Float64, users UInt32)),
+
+    -- books of all outcomes by their index (yes / no for binary market)
+    outcome_bids Array(Array(Tuple(price Float64, shares Float64, users UInt32))),
+    outcome_asks Array(Array(Tuple(price Float64, shares Float64, users UInt32))),
+
+    -- sequence of the book update (restarts when the epoch changes) and it's delta, level with 0 shares is removed
+    -- books above are only set on the snapshots, books in between are rebuilt from the last snapshot and it's following deltas
+    epoch UInt64,
+    sequence UInt64,
+    is_snapshot Bool,
+    outcome_bid_deltas Array(Array(Tuple(price Float64, shares Float64, users UInt32))),
+    outcome_ask_deltas Array(Array(Tuple(price Float64, shares Float64, users UInt32))),
+    checksum UInt32
 ) ENGINE = MergeTree
 ORDER BY (market_id, ts);
 
@@ -89,6 +118,14 @@ CREATE TABLE market_order_book_kafka (
     yes_asks Array(Tuple(price Float64, shares Float64, users UInt32)),
     no_bids Array(Tuple(price Float64, shares Float64, users UInt32)),
     no_asks Array(Tuple(price Float64, shares Float64, users UInt32)),
+    outcome_bids Array(Array(Tuple(price Float64, shares Float64, users UInt32))),
+    outcome_asks Array(Array(Tuple(price Float64, shares Float64, users UInt32))),
+    epoch UInt64,
+    sequence UInt64,
+    is_snapshot Bool,
+    outcome_bid_deltas Array(Array(Tuple(price Float64, shares Float64, users UInt32))),
+    outcome_ask_deltas Array(Array(Tuple(price Float64, shares Float64, users UInt32))),
+    checksum UInt32,
 ) ENGINE = Kafka(
     'redpanda:9092', -- broker (red panda)
     'order-book-updates', -- topic
@@ -107,7 +144,15 @@ SELECT
     yes_bids,
     yes_asks,
     no_bids,
-    no_asks
+    no_asks,
+    outcome_bids,
+    outcome_asks,
+    epoch,
+    sequence,
+    is_snapshot,
+    outcome_bid_deltas,
+    outcome_ask_deltas,
+    checksum
 FROM market_order_book_kafka;
 
 -- materialize view to copy data from kafka to analytical table
@@ -121,7 +166,15 @@ SELECT
     yes_bids,
     yes_asks,
     no_bids,
-    no_asks
+    no_asks,
+    outcome_bids,
+    outcome_asks,
+    epoch,
+    sequence,
+    is_snapshot,
+    outcome_bid_deltas,
+    outcome_ask_deltas,
+    checksum
 FROM market_order_book_kafka;
 
 
@@ -179,3 +232,47 @@ SELECT
     price,
     quantity
 FROM market_volume_data_kafka;
+
+
+-- #############################################################################
+-- ----------- --------- ---for market status (circuit breaker halts) -----------------------
+
+-- CORE TABLE
+CREATE TABLE market_status_data (
+    market_id UUID,
+    status Enum8('halted' = 1, 'resumed' = 2),
+    reason Nullable(String), -- price band breach which halted the trading
+    resume_at Nullable(DateTime('UTC')), -- end of the halt's cool-down
+    ts DateTime('UTC'),
+
+    created_at DateTime('UTC') DEFAULT now(),
+) ENGINE = MergeTree
+PARTITION BY toYYYYMM(ts)
+ORDER BY (market_id, ts);
+
+-- KAFKA ENGINE TABLE
+CREATE TABLE market_status_data_kafka (
+    market_id UUID,
+    status Enum8('halted' = 1, 'resumed' = 2),
+    reason Nullable(String),
+    resume_at Nullable(String),
+    ts String
+) ENGINE = Kafka(
+    'redpanda:9092', -- broker (red panda)
+    'market-status-updates', -- topic
+    'consumer-group-market-status-updates', -- consumer group
+    'JSONEachRow' -- format
+);
+
+
+-- materialized view to copy data from kafka to core table
+DROP TABLE IF EXISTS market_status_data_mv;
+CREATE MATERIALIZED VIEW market_status_data_mv
+TO market_status_data AS
+SELECT
+    market_id,
+    status,
+    reason,
+    parseDateTimeBestEffortOrNull(resume_at) AS resume_at,
+    parseDateTimeBestEffort(ts) AS ts
+FROM market_status_data_kafka;
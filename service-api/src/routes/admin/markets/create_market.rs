@@ -1,10 +1,16 @@
+use std::collections::HashSet;
+
 use axum::{
     Json,
     extract::State,
     http::StatusCode,
     response::{IntoResponse, Response},
 };
-use db_service::schema::market::Market;
+use db_service::schema::{
+    enums::PriceModelKind,
+    market::{Market, NewMarket, PriceBand, ScalarRange},
+    market_outcomes::MarketOutcome,
+};
 use rust_decimal::{Decimal, prelude::FromPrimitive};
 use serde_json::json;
 use sqlx::types::chrono::{self, DateTime};
@@ -19,8 +25,19 @@ pub struct CreateMarketRequest {
     logo: Option<String>,
     liquidity_b: Option<f64>,
     market_expiry: Option<String>,
+    tick_size: Option<Decimal>,          // defaults to 0.01
+    min_quantity: Option<Decimal>,       // defaults to the lot size
+    lot_size: Option<Decimal>,           // defaults to 0.01
+    market_maker: Option<bool>, // LMSR market maker backed by `liquidity_b`, defaults to false
+    price_model: Option<PriceModelKind>, // displayed probability of the outcomes, defaults to lmsr_weighted
+    outcomes: Option<Vec<String>>, // names of the categorical market's outcomes, defaults to a binary Yes / No market
+    scalar: Option<ScalarRange>, // scalar market with Long / Short outcomes settled by a value between the bounds
+    price_band: Option<PriceBand>, // volatility circuit breaker, disabled by default
 }
 
+const DEFAULT_TICK_SIZE: Decimal = Decimal::from_parts(1, 0, 0, false, 2); // 0.01
+const DEFAULT_LOT_SIZE: Decimal = Decimal::from_parts(1, 0, 0, false, 2); // 0.01
+
 // Add market expiry in db
 pub async fn create_new_market(
     State(state): State<AppState>,
@@ -70,13 +87,120 @@ pub async fn create_new_market(
 
     let market_expiry = date_time.naive_utc();
 
+    let tick_size = payload.tick_size.unwrap_or(DEFAULT_TICK_SIZE);
+    let lot_size = payload.lot_size.unwrap_or(DEFAULT_LOT_SIZE);
+    let min_quantity = payload.min_quantity.unwrap_or(lot_size);
+
+    // prices are accepted in cents and complementary (YES / NO) prices must stay on the tick as well
+    if tick_size <= Decimal::ZERO
+        || tick_size > Decimal::ONE
+        || !(Decimal::ONE % tick_size).is_zero()
+        || !(tick_size % DEFAULT_TICK_SIZE).is_zero()
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "error": "Tick size must be a multiple of 0.01 which divides 1"
+            })),
+        ));
+    }
+    if lot_size <= Decimal::ZERO
+        || min_quantity <= Decimal::ZERO
+        || !(min_quantity % lot_size).is_zero()
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "error": "Lot size must be positive and min quantity must be a multiple of it"
+            })),
+        ));
+    }
+
+    if let Some(scalar) = &payload.scalar
+        && (scalar.lower_bound >= scalar.upper_bound || payload.outcomes.is_some())
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "error": "Scalar markets require a lower bound below the upper bound and no outcomes"
+            })),
+        ));
+    }
+
+    let outcomes = match payload.outcomes {
+        None if payload.scalar.is_some() => vec!["Long".to_string(), "Short".to_string()],
+        None => vec!["Yes".to_string(), "No".to_string()],
+        Some(outcomes) => {
+            let outcomes = outcomes
+                .into_iter()
+                .map(|name| name.trim().to_string())
+                .collect::<Vec<_>>();
+            let unique_names = outcomes.iter().collect::<HashSet<_>>();
+            if outcomes.len() < 3
+                || unique_names.len() != outcomes.len()
+                || outcomes.iter().any(|name| name.is_empty())
+            {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({
+                        "error": "Categorical markets require at least 3 unique non empty outcomes"
+                    })),
+                ));
+            }
+            outcomes
+        }
+    };
+
+    let market_maker = payload.market_maker.unwrap_or(false);
+    // LMSR market maker only quotes the binary outcomes
+    if market_maker && outcomes.len() > 2 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "error": "Market maker is only supported for binary markets"
+            })),
+        ));
+    }
+    // subsidy of the market maker is b * ln(2)
+    if market_maker && liquidity_b <= Decimal::ZERO {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "error": "Market maker requires a positive liquidity_b"
+            })),
+        ));
+    }
+
+    if let Some(price_band) = &payload.price_band
+        && (price_band.price_band <= Decimal::ZERO
+            || price_band.price_band >= Decimal::ONE
+            || price_band.window <= 0
+            || price_band.halt_cooldown < 0)
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "error": "Price band must be between 0 and 1 with a positive window and non negative cool-down"
+            })),
+        ));
+    }
+
     let market = Market::create_new_market(
-        name,
-        description,
-        logo,
-        liquidity_b,
-        market_expiry,
         &state.pg_pool,
+        &NewMarket {
+            name,
+            description,
+            logo,
+            liquidity_b,
+            tick_size,
+            min_quantity,
+            lot_size,
+            market_maker,
+            price_model: payload.price_model.unwrap_or_default(),
+            outcomes,
+            scalar_range: payload.scalar,
+            market_expiry,
+        },
     )
     .await
     .map_err(|e| {
@@ -89,6 +213,32 @@ pub async fn create_new_market(
         )
     })?;
 
+    if let Some(price_band) = &payload.price_band {
+        Market::update_price_band(&state.pg_pool, &market.id, price_band)
+            .await
+            .map_err(|e| {
+                log_error!("Error setting price band of market: {}", e);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({
+                        "error": "Failed to set price band of market"
+                    })),
+                )
+            })?;
+    }
+
+    let outcomes = MarketOutcome::get_outcomes_by_market_id(&state.pg_pool, market.id)
+        .await
+        .map_err(|e| {
+            log_error!("Error getting outcomes of market: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "error": "Failed to get outcomes of market"
+                })),
+            )
+        })?;
+
     let response = json!({
         "message": "Market created successfully",
         "market": {
@@ -97,6 +247,15 @@ pub async fn create_new_market(
             "description": market.description,
             "logo": market.logo,
             "liquidity_b": market.liquidity_b,
+            "tick_size": market.tick_size,
+            "min_quantity": market.min_quantity,
+            "lot_size": market.lot_size,
+            "market_maker": market.market_maker,
+            "price_model": market.price_model,
+            "market_type": market.market_type,
+            "outcomes": outcomes,
+            "scalar": market.scalar_range(),
+            "price_band": payload.price_band,
         }
     });
     Ok((StatusCode::CREATED, Json(response)).into_response())
@@ -1,98 +1,107 @@
-use axum::{
-    Json,
-    extract::State,
-    http::StatusCode,
-    response::{IntoResponse, Response},
-};
+use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
 use db_service::schema::{
-    enums::{MarketStatus, Outcome},
+    enums::{MarketStatus, MarketType, Outcome},
     market::Market,
 };
+use rust_decimal::Decimal;
 use serde::Deserialize;
 use serde_json::json;
-use utility_helpers::{log_error, nats_helper::NatsSubjects};
+use utility_helpers::log_error;
 use uuid::Uuid;
 
+use super::update_market_status::{get_market_for_transition, publish_market_status_update};
 use crate::state::AppState;
 
 #[derive(Deserialize)]
 pub struct FinalizeMarketRequest {
     pub market_id: Uuid,
+    #[serde(default)]
     pub final_outcome: Outcome,
+    #[serde(default)]
+    pub final_outcome_index: Option<i32>, // winning outcome of the categorical market (final_outcome must be yes)
+    #[serde(default)]
+    pub resolved_value: Option<Decimal>, // settles the scalar market
+    pub admin_id: Uuid, // admin who settles the market
 }
 
 pub async fn finalize_market(
     State(state): State<AppState>,
     Json(payload): Json<FinalizeMarketRequest>,
-) -> Result<impl IntoResponse, (StatusCode, Response)> {
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
     let market_id = payload.market_id;
     let final_outcome = payload.final_outcome;
 
-    let market = Market::get_market_by_id(&state.pg_pool, &market_id)
-        .await
-        .map_err(|e| {
-            log_error!("Failed to get market by ID: {:?}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({
-                    "error": "Failed to get market by ID"
-                }))
-                .into_response(),
-            )
-        })?;
-
-    if market.is_none() {
+    let market = get_market_for_transition(&state, &market_id, payload.admin_id).await?;
+    if market.status != MarketStatus::RESOLVING {
         return Err((
-            StatusCode::NOT_FOUND,
+            StatusCode::BAD_REQUEST,
             Json(json!({
-                "error": "Market not found"
-            }))
-            .into_response(),
+                "error": "Market is not being resolved"
+            })),
         ));
     }
 
-    let market = market.unwrap();
-    if market.status != MarketStatus::OPEN {
+    let valid_outcome = match (
+        market.market_type,
+        payload.final_outcome_index,
+        payload.resolved_value,
+    ) {
+        (MarketType::Binary, None, None) => final_outcome != Outcome::UNSPECIFIED,
+        (MarketType::Categorical, Some(index), None) => {
+            final_outcome == Outcome::YES && (0..market.outcome_count).contains(&index)
+        }
+        (MarketType::Scalar, None, Some(_)) => true,
+        _ => false,
+    };
+    if !valid_outcome {
         return Err((
             StatusCode::BAD_REQUEST,
             Json(json!({
-                "error": "Market is not open"
-            }))
-            .into_response(),
+                "error": "Binary markets are finalized with a final_outcome, categorical markets with a yes final_outcome and a valid final_outcome_index and scalar markets with a resolved_value"
+            })),
         ));
     }
 
-    Market::settle_market(&state.pg_pool, &market_id, final_outcome)
-        .await
-        .map_err(|e| {
-            log_error!("Failed to settle market: {:?}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({
-                    "error": "Failed to settle market"
-                }))
-                .into_response(),
+    let settlement = match payload.resolved_value {
+        Some(resolved_value) => {
+            Market::settle_scalar_market(
+                &state.pg_pool,
+                &market_id,
+                resolved_value,
+                Some(payload.admin_id),
             )
-        })?;
-
-    // push message to NATS
-    let nats_subject = NatsSubjects::FinalizeMarket.to_string();
-    let message = market_id.to_string().into_bytes();
-
-    state
-        .jetstream
-        .publish(nats_subject, message.into())
-        .await
-        .map_err(|e| {
-            log_error!("Failed to publish NATS message: {:?}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({
-                    "error": "Failed to publish NATS message"
-                }))
-                .into_response(),
+            .await
+        }
+        None => {
+            Market::settle_market(
+                &state.pg_pool,
+                &market_id,
+                final_outcome,
+                payload.final_outcome_index,
+                Some(payload.admin_id),
             )
-        })?;
+            .await
+        }
+    };
+    settlement.map_err(|e| {
+        log_error!("Failed to settle market: {:?}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "error": "Failed to settle market"
+            })),
+        )
+    })?;
+
+    // order book of the settled market is dropped by the order service
+    publish_market_status_update(
+        &state,
+        market_id,
+        MarketStatus::RESOLVING,
+        MarketStatus::SETTLED,
+        payload.admin_id,
+    )
+    .await?;
 
     Ok((
         StatusCode::OK,
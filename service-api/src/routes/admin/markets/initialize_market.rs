@@ -1,13 +1,22 @@
+use std::time::Duration;
+
 use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
 use db_service::schema::{
-    enums::{OrderSide, OrderStatus, OrderType, Outcome},
+    enums::{
+        MarketStatus, MarketType, OrderSide, OrderStatus, OrderType, Outcome, SelfTradePrevention,
+        TimeInForce,
+    },
     market::Market,
+    market_outcomes::MarketOutcome,
     orders::Order,
     user_holdings::UserHoldings,
     users::User,
 };
 use rand::Rng;
-use rust_decimal::{Decimal, prelude::FromPrimitive};
+use rust_decimal::{
+    Decimal,
+    prelude::{FromPrimitive, ToPrimitive},
+};
 use rust_decimal_macros::dec;
 use serde::Deserialize;
 use sqlx::types::chrono;
@@ -20,11 +29,14 @@ use uuid::Uuid;
 
 use crate::state::AppState;
 
+const MAX_AUCTION_DURATION: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
 #[derive(Deserialize)]
 pub struct InitializeMarketPayload {
     market_id: Uuid,
     depth: u32,
     quantity: u32,
+    auction_duration: Option<u64>, // seconds of the opening call auction, continuous matching starts right away if not set
 }
 
 pub async fn initialize_market(
@@ -34,6 +46,16 @@ pub async fn initialize_market(
     let market_id = payload.market_id;
     let depth = payload.depth;
     let quantity = payload.quantity;
+    let auction_duration = payload
+        .auction_duration
+        .filter(|duration| *duration > 0)
+        .map(Duration::from_secs);
+    if auction_duration.is_some_and(|duration| duration > MAX_AUCTION_DURATION) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({"error": "Auction duration can't be longer than 7 days"})),
+        ));
+    }
 
     let admin = User::get_or_create_admin(&state.pg_pool)
         .await
@@ -63,23 +85,41 @@ pub async fn initialize_market(
     }
     let market = market.unwrap();
 
-    // create user holdings
-    let holding_future_one = UserHoldings::create_user_holding_conflict_free(
+    // market is opened along with it's order book
+    let opened = Market::transition_status(
         &state.pg_pool,
-        admin.id,
-        market_id,
-        dec!(1000),
-        Outcome::YES,
-    );
-    let holding_future_two = UserHoldings::create_user_holding_conflict_free(
-        &state.pg_pool,
-        admin.id,
-        market_id,
-        dec!(1000),
-        Outcome::NO,
-    );
+        &market_id,
+        MarketStatus::DRAFT,
+        MarketStatus::OPEN,
+        Some(admin.id),
+    )
+    .await
+    .map_err(|e| {
+        log_error!("Failed to open market: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": "Failed to open market"})),
+        )
+    })?;
+    if !opened {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({"error": "Only draft markets can be initialized"})),
+        ));
+    }
 
-    let (yes_holdings, no_holdings) = tokio::try_join!(holding_future_one, holding_future_two)
+    // create user holdings of every bootstrapped outcome
+    let mut holdings = Vec::new();
+    for (outcome, outcome_index) in get_bootstrap_outcomes(&market) {
+        let holding = UserHoldings::update_user_holdings(
+            &state.pg_pool,
+            admin.id,
+            market_id,
+            dec!(1000),
+            outcome,
+            outcome_index,
+        )
+        .await
         .map_err(|e| {
             log_error!("Failed to create user holdings: {}", e);
             (
@@ -87,6 +127,8 @@ pub async fn initialize_market(
                 Json(serde_json::json!({"error": "Failed to create user holdings"})),
             )
         })?;
+        holdings.push(holding);
+    }
 
     /*
      * Each side of the market is initialized with `quantity` shares.
@@ -96,13 +138,12 @@ pub async fn initialize_market(
      */
 
     let random_orders = create_bootstrap_orders_with_stacked_price_levels(
-        market_id,
+        &market,
         admin.id,
         depth,
         quantity,
-        admin.balance,       // Admin balance
-        yes_holdings.shares, // Yes holdings
-        no_holdings.shares,  // No holdings
+        admin.balance, // Admin balance
+        &holdings,
     );
     // insert orders into database
     Order::insert_multiple_orders(&random_orders, &state.pg_pool)
@@ -117,6 +158,24 @@ pub async fn initialize_market(
 
     let random_orders_count = random_orders.len();
 
+    // auction must be scheduled before the order book is created, it's read from the market rules
+    let auction_opens_at = match auction_duration {
+        Some(duration) => {
+            let opens_at = chrono::Utc::now().naive_utc() + duration;
+            Market::schedule_auction(&state.pg_pool, &market_id, opens_at)
+                .await
+                .map_err(|e| {
+                    log_error!("Failed to schedule call auction: {}", e);
+                    (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(serde_json::json!({"error": "Failed to schedule call auction"})),
+                    )
+                })?;
+            Some(opens_at)
+        }
+        None => None,
+    };
+
     // preparing data for nats queue
     let order_book_initialize_data = InitializeOrderBookMessage {
         liquidity_b: market.liquidity_b,
@@ -144,24 +203,46 @@ pub async fn initialize_market(
             )
         })?;
 
+    let outcomes = MarketOutcome::get_outcomes_by_market_id(&state.pg_pool, market_id)
+        .await
+        .map_err(|e| {
+            log_error!("Failed to get outcomes of market: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"error": "Failed to get outcomes of market"})),
+            )
+        })?;
+
     Ok((
         StatusCode::OK,
         Json(serde_json::json!({
             "message": "Market initialized successfully",
             "market_id": market_id,
+            "outcomes": outcomes,
             "order_count": random_orders_count,
+            "auction_opens_at": auction_opens_at,
         })),
     ))
 }
 
+/// Outcomes quoted by the bootstrap liquidity, yes / no (long / short) books of the binary (scalar) market
+/// and the yes book of every outcome of the categorical market
+fn get_bootstrap_outcomes(market: &Market) -> Vec<(Outcome, i32)> {
+    match market.market_type {
+        MarketType::Binary | MarketType::Scalar => vec![(Outcome::YES, 0), (Outcome::NO, 0)],
+        MarketType::Categorical => (0..market.outcome_count)
+            .map(|outcome_index| (Outcome::YES, outcome_index))
+            .collect(),
+    }
+}
+
 pub fn create_bootstrap_orders_with_stacked_price_levels(
-    market_id: Uuid,
+    market: &Market,
     admin_id: Uuid,
     depth: u32,
     quantity: u32,
     admin_balance: Decimal,
-    yes_holdings: Decimal,
-    no_holdings: Decimal,
+    holdings: &[UserHoldings],
 ) -> Vec<Order> {
     use rand::{Rng, seq::SliceRandom};
     use rust_decimal::Decimal;
@@ -169,103 +250,83 @@ pub fn create_bootstrap_orders_with_stacked_price_levels(
     let mut rng = rand::rng();
     let now = chrono::Utc::now().naive_utc();
 
-    let price_pairs: Vec<(Decimal, Decimal)> = (1..99)
+    let market_id = market.id;
+
+    // bootstrap orders must be on the tick size and lot size of the market
+    let ticks = (Decimal::ONE / market.tick_size)
+        .to_i64()
+        .unwrap_or_default();
+    let price_pairs: Vec<(Decimal, Decimal)> = (1..ticks - 1)
         .step_by(2)
-        .map(|p| (Decimal::new(p, 2), Decimal::new(p + 1, 2)))
+        .map(|p| {
+            (
+                market.tick_size * Decimal::from(p),
+                market.tick_size * Decimal::from(p + 1),
+            )
+        })
         .collect();
+    if price_pairs.is_empty() {
+        return Vec::new(); // tick size is too large to stack price levels
+    }
 
     let mut shuffled = price_pairs.clone();
     shuffled.shuffle(&mut rng);
 
+    let bootstrap_order =
+        |side: OrderSide, holding: &UserHoldings, price: Decimal, qty: Decimal| Order {
+            id: Uuid::new_v4(),
+            user_id: admin_id,
+            market_id,
+            side,
+            outcome: holding.outcome,
+            price,
+            quantity: qty,
+            filled_quantity: Decimal::ZERO,
+            status: OrderStatus::OPEN,
+            order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            status_reason: None,
+            self_trade_prevention: SelfTradePrevention::CancelNewest,
+            outcome_index: holding.outcome_index,
+            display_quantity: None,
+            created_at: now,
+            updated_at: now,
+        };
+
     let mut orders = vec![];
     let mut remaining_balance = admin_balance;
-    let mut remaining_yes = yes_holdings;
-    let mut remaining_no = no_holdings;
+    let mut remaining_shares = holdings
+        .iter()
+        .map(|holding| holding.shares)
+        .collect::<Vec<_>>();
 
     for _ in 0..depth {
         let (buy_price, sell_price) = shuffled[rng.random_range(0..shuffled.len())];
 
         for _ in 0..quantity {
-            // BUY YES
-            let buy_yes_qty = random_qty();
-            let buy_yes_cost = buy_price * buy_yes_qty;
-            if remaining_balance >= buy_yes_cost {
-                orders.push(Order {
-                    id: Uuid::new_v4(),
-                    user_id: admin_id,
-                    market_id,
-                    side: OrderSide::BUY,
-                    outcome: Outcome::YES,
-                    price: buy_price,
-                    quantity: buy_yes_qty,
-                    filled_quantity: Decimal::ZERO,
-                    status: OrderStatus::OPEN,
-                    order_type: OrderType::LIMIT,
-                    created_at: now,
-                    updated_at: now,
-                });
-                remaining_balance -= buy_yes_cost;
-            }
-
-            // SELL YES
-            let sell_yes_qty = random_qty();
-            if remaining_yes >= sell_yes_qty {
-                orders.push(Order {
-                    id: Uuid::new_v4(),
-                    user_id: admin_id,
-                    market_id,
-                    side: OrderSide::SELL,
-                    outcome: Outcome::YES,
-                    price: sell_price,
-                    quantity: sell_yes_qty,
-                    filled_quantity: Decimal::ZERO,
-                    status: OrderStatus::OPEN,
-                    order_type: OrderType::LIMIT,
-                    created_at: now,
-                    updated_at: now,
-                });
-                remaining_yes -= sell_yes_qty;
-            }
-
-            // BUY NO
-            let buy_no_qty = random_qty();
-            let buy_no_cost = buy_price * buy_no_qty;
-            if remaining_balance >= buy_no_cost {
-                orders.push(Order {
-                    id: Uuid::new_v4(),
-                    user_id: admin_id,
-                    market_id,
-                    side: OrderSide::BUY,
-                    outcome: Outcome::NO,
-                    price: buy_price,
-                    quantity: buy_no_qty,
-                    filled_quantity: Decimal::ZERO,
-                    status: OrderStatus::OPEN,
-                    order_type: OrderType::LIMIT,
-                    created_at: now,
-                    updated_at: now,
-                });
-                remaining_balance -= buy_no_cost;
-            }
+            for (holding, remaining) in holdings.iter().zip(remaining_shares.iter_mut()) {
+                // BUY
+                let buy_qty = random_qty(market);
+                let buy_cost = buy_price * buy_qty;
+                if remaining_balance >= buy_cost {
+                    orders.push(bootstrap_order(OrderSide::BUY, holding, buy_price, buy_qty));
+                    remaining_balance -= buy_cost;
+                }
 
-            // SELL NO
-            let sell_no_qty = random_qty();
-            if remaining_no >= sell_no_qty {
-                orders.push(Order {
-                    id: Uuid::new_v4(),
-                    user_id: admin_id,
-                    market_id,
-                    side: OrderSide::SELL,
-                    outcome: Outcome::NO,
-                    price: sell_price,
-                    quantity: sell_no_qty,
-                    filled_quantity: Decimal::ZERO,
-                    status: OrderStatus::OPEN,
-                    order_type: OrderType::LIMIT,
-                    created_at: now,
-                    updated_at: now,
-                });
-                remaining_no -= sell_no_qty;
+                // SELL
+                let sell_qty = random_qty(market);
+                if *remaining >= sell_qty {
+                    orders.push(bootstrap_order(
+                        OrderSide::SELL,
+                        holding,
+                        sell_price,
+                        sell_qty,
+                    ));
+                    *remaining -= sell_qty;
+                }
             }
         }
     }
@@ -273,8 +334,9 @@ pub fn create_bootstrap_orders_with_stacked_price_levels(
     orders
 }
 
-fn random_qty() -> Decimal {
+fn random_qty(market: &Market) -> Decimal {
     let mut rng = rand::rng();
     let q = rng.random_range(5.0..60.0);
-    Decimal::from_f64(q).unwrap().round_dp(2)
+    let q = Decimal::from_f64(q).unwrap().round_dp(2);
+    ((q / market.lot_size).floor() * market.lot_size).max(market.min_quantity)
 }
@@ -0,0 +1,171 @@
+use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
+use db_service::schema::{enums::MarketStatus, market::Market, users::User};
+use serde::Deserialize;
+use serde_json::json;
+use sqlx::types::chrono;
+use utility_helpers::{
+    log_error,
+    message_pack_helper::serialize_to_message_pack,
+    nats_helper::{NatsSubjects, types::MarketStatusUpdateMessage},
+};
+use uuid::Uuid;
+
+use crate::state::AppState;
+
+#[derive(Deserialize)]
+pub struct UpdateMarketStatusRequest {
+    pub market_id: Uuid,
+    pub status: MarketStatus,
+    pub admin_id: Uuid, // admin who makes the transition
+}
+
+/// Moves the market along it's lifecycle, DRAFT -> OPEN <-> PAUSED -> CLOSED -> RESOLVING -> VOIDED.
+/// Resolving markets are settled by `/finalize`
+pub async fn update_market_status(
+    State(state): State<AppState>,
+    Json(payload): Json<UpdateMarketStatusRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let market_id = payload.market_id;
+    let status = payload.status;
+
+    if status == MarketStatus::SETTLED {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": "Markets are settled with their outcome by /finalize"})),
+        ));
+    }
+
+    let market = get_market_for_transition(&state, &market_id, payload.admin_id).await?;
+    if !market.status.can_transition_to(status) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "error": format!("Market can't be moved from {:?} to {:?}", market.status, status)
+            })),
+        ));
+    }
+
+    let transitioned = if status == MarketStatus::VOIDED {
+        // voided market refunds every share, so it's voided along with the payouts
+        Market::void_market(&state.pg_pool, &market_id, Some(payload.admin_id))
+            .await
+            .map(|_| true)
+    } else {
+        Market::transition_status(
+            &state.pg_pool,
+            &market_id,
+            market.status,
+            status,
+            Some(payload.admin_id),
+        )
+        .await
+    };
+    let transitioned = transitioned.map_err(|e| {
+        log_error!("Failed to update market status: {:?}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": "Failed to update market status"})),
+        )
+    })?;
+    if !transitioned {
+        // status is changed by someone else in between
+        return Err((
+            StatusCode::CONFLICT,
+            Json(json!({"error": "Market status has changed, try again"})),
+        ));
+    }
+
+    publish_market_status_update(&state, market_id, market.status, status, payload.admin_id)
+        .await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({
+            "message": "Market status updated successfully",
+            "market_id": market_id,
+            "from_status": market.status,
+            "status": status,
+        })),
+    ))
+}
+
+/// Market of the transition along with the check that the admin making it exists
+pub(super) async fn get_market_for_transition(
+    state: &AppState,
+    market_id: &Uuid,
+    admin_id: Uuid,
+) -> Result<Market, (StatusCode, Json<serde_json::Value>)> {
+    User::get_user_by_id(&state.pg_pool, admin_id)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => (
+                StatusCode::NOT_FOUND,
+                Json(json!({"error": "Admin not found"})),
+            ),
+            e => {
+                log_error!("Failed to get admin by ID: {:?}", e);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({"error": "Failed to get admin"})),
+                )
+            }
+        })?;
+
+    let market = Market::get_market_by_id(&state.pg_pool, market_id)
+        .await
+        .map_err(|e| {
+            log_error!("Failed to get market by ID: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": "Failed to get market by ID"})),
+            )
+        })?;
+
+    market.ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(json!({"error": "Market not found"})),
+        )
+    })
+}
+
+/// Announces the transition to the order service, which freezes, unfreezes or drops the market's book
+pub(super) async fn publish_market_status_update(
+    state: &AppState,
+    market_id: Uuid,
+    from_status: MarketStatus,
+    to_status: MarketStatus,
+    admin_id: Uuid,
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    let message = MarketStatusUpdateMessage {
+        market_id,
+        from_status,
+        to_status,
+        changed_by: Some(admin_id),
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    };
+    let binary_payload = serialize_to_message_pack(&message).map_err(|e| {
+        log_error!("Failed to serialize market status update: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": "Failed to serialize market status update"})),
+        )
+    })?;
+
+    state
+        .jetstream
+        .publish(
+            NatsSubjects::MarketStatusUpdate.to_string(),
+            binary_payload.into(),
+        )
+        .await
+        .map_err(|e| {
+            log_error!("Failed to publish NATS message: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": "Failed to publish NATS message"})),
+            )
+        })?;
+
+    Ok(())
+}
@@ -1,4 +1,7 @@
-use axum::{Router, routing::get};
+use axum::{
+    Router,
+    routing::{get, patch},
+};
 
 use crate::state::AppState;
 
@@ -6,6 +9,7 @@ pub mod holdings;
 pub mod metadata;
 pub mod orders;
 pub mod profile;
+pub mod self_trade_prevention;
 pub mod trades;
 
 pub fn router() -> Router<AppState> {
@@ -15,4 +19,8 @@ pub fn router() -> Router<AppState> {
         .route("/profile", get(profile::get_profile))
         .route("/metadata", get(metadata::get_metadata))
         .route("/holdings", get(holdings::get_user_holdings))
+        .route(
+            "/self-trade-prevention",
+            patch(self_trade_prevention::update_self_trade_prevention),
+        )
 }
@@ -6,7 +6,11 @@ use axum::{
 };
 use db_service::schema::{enums::OrderStatus, orders::Order};
 use serde_json::json;
-use utility_helpers::{log_error, nats_helper::NatsSubjects};
+use utility_helpers::{
+    log_error,
+    message_pack_helper::serialize_to_message_pack,
+    nats_helper::{NatsSubjects, types::OrderMessage},
+};
 use uuid::Uuid;
 
 use crate::state::AppState;
@@ -27,7 +31,7 @@ pub async fn cancel_order(
             )
         })?;
 
-    if order.is_none() {
+    let Some(order) = order else {
         return Err((
             StatusCode::NOT_FOUND,
             Json(json!({
@@ -35,7 +39,7 @@ pub async fn cancel_order(
             }))
             .into_response(),
         ));
-    }
+    };
 
     Order::update_order_status(id, OrderStatus::PendingCancel, &app_state.pg_pool)
         .await
@@ -52,12 +56,24 @@ pub async fn cancel_order(
     // assertion is not needed, as it's already checked while creating the order
 
     // publishing the order to the delete order queue
-    let order_id_str = id.to_string().into_bytes();
+    let message = OrderMessage {
+        order_id: id,
+        market_id: order.market_id,
+    };
+    let message_pack_encoded = serialize_to_message_pack(&message).map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "error": format!("Failed to serialize order message: {}", e)
+            }))
+            .into_response(),
+        )
+    })?;
     let subject = NatsSubjects::OrderCancel;
 
     app_state
         .jetstream
-        .publish(subject.to_string(), order_id_str.into())
+        .publish(subject.to_string(), message_pack_encoded.into())
         .await
         .map_err(|e| {
             log_error!("Failed to publish order to jetstream - {:?}", e);
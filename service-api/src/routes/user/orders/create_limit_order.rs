@@ -1,4 +1,3 @@
-use async_nats::jetstream;
 use auth_service::types::SessionTokenClaims;
 use axum::{
     Extension, Json,
@@ -7,21 +6,28 @@ use axum::{
     response::{IntoResponse, Response},
 };
 use db_service::schema::{
-    enums::{MarketStatus, OrderSide, OrderStatus, OrderType, Outcome},
+    enums::{MarketType, OrderSide, OrderType, Outcome, SelfTradePrevention, TimeInForce},
     market::Market,
-    orders::Order,
-    user_holdings::UserHoldings,
+    orders::{NewOrder, Order},
     users::User,
 };
 use rust_decimal::{Decimal, prelude::FromPrimitive};
 use rust_decimal_macros::dec;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use sqlx::types::Uuid;
-use utility_helpers::{log_error, log_info, nats_helper::NatsSubjects};
+use sqlx::types::{
+    Uuid,
+    chrono::{self, DateTime},
+};
+use utility_helpers::{log_error, log_info};
 
 use crate::{require_field, state::AppState};
 
+use super::order_checks::{
+    assert_order_stream, bad_request, get_tradable_market, publish_created_order, verify_balance,
+    verify_holdings,
+};
+
 #[derive(Deserialize, Serialize, Debug)]
 pub struct CreateOrderPayload {
     market_id: Option<Uuid>,
@@ -29,6 +35,12 @@ pub struct CreateOrderPayload {
     quantity: Option<f64>,
     side: Option<OrderSide>,
     outcome_side: Option<Outcome>,
+    time_in_force: Option<TimeInForce>, // defaults to gtc
+    expires_at: Option<String>,         // required for gtd orders (rfc3339)
+    post_only: Option<bool>, // maker only order, never matched immediately against the book
+    self_trade_prevention: Option<SelfTradePrevention>, // defaults to the account's mode
+    display_quantity: Option<f64>, // iceberg order, only this quantity is visible in the book
+    outcome_index: Option<u32>, // outcome of the categorical market (yes shares of it are traded)
 }
 
 pub async fn create_limit_order(
@@ -44,38 +56,7 @@ pub async fn create_limit_order(
 
     let market_id = payload.market_id.unwrap();
 
-    let market = Market::get_market_by_id(&app_state.pg_pool, &market_id)
-        .await
-        .map_err(|e| {
-            log_error!("Failed to get market - {:?}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({
-                    "error": "Failed to get market"
-                }))
-                .into_response(),
-            )
-        })?;
-
-    if market.is_none() {
-        return Err((
-            StatusCode::NOT_FOUND,
-            Json(json!({
-                "error": "Market not found"
-            }))
-            .into_response(),
-        ));
-    }
-
-    if market.unwrap().status == MarketStatus::SETTLED {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(json!({
-                "error": "Market is already settled, cannot create order"
-            }))
-            .into_response(),
-        ));
-    }
+    let market = get_tradable_market(&app_state, &market_id).await?;
 
     let side = payload.side.unwrap();
     let outcome_side = payload.outcome_side.unwrap();
@@ -84,146 +65,85 @@ pub async fn create_limit_order(
 
     let price = payload.price.unwrap();
     if price > 100 {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(json!({
-                "error": "Price must be between 0 and 100"
-            }))
-            .into_response(),
-        ));
+        return Err(bad_request("Price must be between 0 and 100"));
     }
-    // payload.quantity must be up to 2 decimal places
-    let quantity_valid_flg = has_max_two_decimal_places(payload.quantity.unwrap());
-    if !quantity_valid_flg {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(json!({
-                "error": "Quantity must be up to 2 decimal places"
-            }))
-            .into_response(),
-        ));
+    validate_market_increments(&market, from_u8(price) / dec!(100), from_f64(quantity))
+        .map_err(bad_request)?;
+    let outcome_index =
+        resolve_outcome_index(&market, outcome_side, payload.outcome_index).map_err(bad_request)?;
+
+    let time_in_force = payload.time_in_force.unwrap_or_default();
+    let expires_at = match (time_in_force, payload.expires_at) {
+        (TimeInForce::GTD, Some(expires_at)) => {
+            let date_time = DateTime::parse_from_rfc3339(&expires_at).map_err(|e| {
+                log_error!("Invalid expires_at format: {} due to {e}", expires_at);
+                (
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({
+                        "error": "Invalid expires_at format"
+                    }))
+                    .into_response(),
+                )
+            })?;
+
+            if date_time < chrono::Utc::now() {
+                return Err(bad_request("Order expiry date cannot be in the past"));
+            }
+            Some(date_time.naive_utc())
+        }
+        (TimeInForce::GTD, None) => {
+            return Err(bad_request("expires_at is required for gtd orders"));
+        }
+        (_, Some(_)) => {
+            return Err(bad_request("expires_at is only allowed for gtd orders"));
+        }
+        (_, None) => None,
+    };
+
+    let post_only = payload.post_only.unwrap_or(false);
+    if post_only && matches!(time_in_force, TimeInForce::IOC | TimeInForce::FOK) {
+        return Err(bad_request("Post only orders cannot be ioc or fok orders"));
     }
 
-    // asserting the channel exists (not publishing the message)
-    app_state
-        .jetstream
-        .get_or_create_stream(jetstream::stream::Config {
-            // these `ORDER` name does not indicate the operations on orders, instead it indicates that the streams is used by order-service microservice, so don't confuse it with the order name and same for it's topics, all topics are prefixed with `order.`
-            name: "ORDER".into(),
-            subjects: vec!["order.>".into()],
-            ..Default::default()
-        })
-        .await
-        .map_err(|e| {
-            log_error!("Failed to create jetstream stream - {:?}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({
-                    "error": "Failed to create jetstream stream"
-                }))
-                .into_response(),
-            )
-        })?;
+    let display_quantity = payload.display_quantity.map(from_f64);
+    if let Some(display_quantity) = display_quantity {
+        if display_quantity >= from_f64(quantity) {
+            return Err(bad_request(
+                "Display quantity must be less than the order quantity",
+            ));
+        }
+        if matches!(time_in_force, TimeInForce::IOC | TimeInForce::FOK) {
+            return Err(bad_request("Iceberg orders cannot be ioc or fok orders"));
+        }
+        // every refreshed slice must be a valid order of the market
+        validate_market_increments(&market, from_u8(price) / dec!(100), display_quantity)
+            .map_err(bad_request)?;
+    }
+
+    let self_trade_prevention =
+        resolve_self_trade_prevention(&app_state, user_id, payload.self_trade_prevention).await?;
+
+    assert_order_stream(&app_state).await?;
 
     ///////////////// Verifying user holdings ///////////////////////
     // if trade type is sell then check holdings, else check the user's balance
 
     if side == OrderSide::SELL {
-        let holdings = UserHoldings::get_user_holdings_by_outcome(
-            &app_state.pg_pool,
+        verify_holdings(
+            &app_state,
             user_id,
             market_id,
             outcome_side,
+            outcome_index,
+            from_f64(quantity),
         )
-        .await
-        .map_err(|e| {
-            log_error!("Failed to get user holdings - {:?}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({
-                    "error": "Failed to get user holdings"
-                }))
-                .into_response(),
-            )
-        })?;
-
-        if holdings.shares <= Decimal::ZERO || holdings.shares < from_f64(quantity) {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                Json(json!({
-                    "error": "You do not have enough shares to sell"
-                }))
-                .into_response(),
-            ));
-        }
+        .await?;
     } else {
-        let mut tx = app_state.pg_pool.begin().await.map_err(|e| {
-            log_error!("Failed to begin transaction - {:?}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({
-                    "error": "Failed to begin transaction"
-                }))
-                .into_response(),
-            )
-        })?;
-        let balance = User::get_user_balance(&mut *tx, user_id)
-            .await
-            .map_err(|e| {
-                log_error!("Failed to get user - {:?}", e);
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(json!({
-                        "error": "Failed to get user"
-                    }))
-                    .into_response(),
-                )
-            })?;
-
-        let total_user_locked_funds = Order::get_user_order_locked_funds(&mut *tx, claims.user_id)
-            .await
-            .map_err(|e| {
-                log_error!("Failed to get user orders total amount - {:?}", e);
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(json!({
-                        "error": "Failed to get user orders total amount"
-                    }))
-                    .into_response(),
-                )
-            })?;
-
-        tx.commit().await.map_err(|e| {
-            log_error!("Failed to commit transaction - {:?}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({
-                    "error": "Failed to commit transaction"
-                }))
-                .into_response(),
-            )
-        })?;
-
-        let balance = balance - total_user_locked_funds;
         let required_price = from_u8(price) * from_f64(quantity);
-        if balance < Decimal::ZERO || balance < required_price {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                Json(json!({
-                    "error": "You do not have enough balance to create order"
-                }))
-                .into_response(),
-            ));
-        }
+        let balance = verify_balance(&app_state, user_id, required_price).await?;
 
         if balance <= required_price {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                Json(json!({
-                    "error": "You do not have enough balance to buy"
-                }))
-                .into_response(),
-            ));
+            return Err(bad_request("You do not have enough balance to buy"));
         }
     }
 
@@ -231,14 +151,23 @@ pub async fn create_limit_order(
     let price = from_u8(price) / dec!(100); // scaling down the price to 2 decimal places (0-100 to 0.00-1.00)
 
     let order = Order::create_order(
-        user_id,
-        market_id,
-        price, // scaling down the price to 2 decimal places (0-100 to 0.00-1.00)
-        from_f64(quantity),
-        side,
-        outcome_side,
-        OrderType::LIMIT,
         &app_state.pg_pool,
+        &NewOrder {
+            user_id,
+            market_id,
+            price,
+            quantity: from_f64(quantity),
+            side,
+            outcome: outcome_side,
+            order_type: OrderType::LIMIT,
+            trigger_price: None,
+            time_in_force,
+            expires_at,
+            post_only,
+            self_trade_prevention,
+            display_quantity,
+            outcome_index,
+        },
     )
     .await
     .map_err(|e| {
@@ -252,45 +181,7 @@ pub async fn create_limit_order(
         )
     })?;
 
-    let mut is_failed = false;
-
-    // pushing the order to the jetstream
-    let order_id_str = order.id.to_string().into_bytes();
-
-    let subject = NatsSubjects::OrderCreate;
-
-    app_state
-        .jetstream
-        .publish(subject.to_string(), order_id_str.into())
-        .await
-        .map_err(|e| {
-            log_error!("Failed to publish order to jetstream - {:?}", e);
-            // delete the order from the database if publishing fails
-            is_failed = true;
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({
-                    "error": "Failed to publish order to jetstream, order will be deleted"
-                }))
-                .into_response(),
-            )
-        })?;
-
-    // if the order is failed, delete it from the database
-    if is_failed {
-        Order::update_order_status(order.id, OrderStatus::CANCELLED, &app_state.pg_pool)
-            .await
-            .map_err(|e| {
-                log_error!("Failed to delete order - {:?}", e);
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(json!({
-                        "error": "Failed to update order status to cancelled"
-                    }))
-                    .into_response(),
-                )
-            })?;
-    }
+    publish_created_order(&app_state, &order).await?;
 
     log_info!("Order published to jetstream - {:?}", order.id);
 
@@ -306,22 +197,98 @@ pub async fn create_limit_order(
             "quantity": order.quantity.to_string(),
             "filled_quantity": order.filled_quantity.to_string(),
             "status": order.status,
+            "time_in_force": order.time_in_force,
+            "expires_at": order.expires_at,
+            "post_only": order.post_only,
+            "self_trade_prevention": order.self_trade_prevention,
         }
     });
 
     Ok((StatusCode::CREATED, Json(response)))
 }
 
-fn from_f64(value: f64) -> Decimal {
+/// Self trade prevention mode requested with the order, falls back to the account default
+pub(super) async fn resolve_self_trade_prevention(
+    app_state: &AppState,
+    user_id: Uuid,
+    requested: Option<SelfTradePrevention>,
+) -> Result<SelfTradePrevention, (StatusCode, Response)> {
+    if let Some(self_trade_prevention) = requested {
+        return Ok(self_trade_prevention);
+    }
+
+    User::get_self_trade_prevention(&app_state.pg_pool, user_id)
+        .await
+        .map_err(|e| {
+            log_error!("Failed to get user self trade prevention - {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "error": "Failed to get user self trade prevention"
+                }))
+                .into_response(),
+            )
+        })
+}
+
+pub(super) fn from_f64(value: f64) -> Decimal {
     Decimal::from_f64(value)
         .unwrap_or_else(|| panic!("Failed to convert f64 to Decimal: {}", value))
 }
 
-fn from_u8(value: u8) -> Decimal {
+pub(super) fn from_u8(value: u8) -> Decimal {
     Decimal::from_u8(value).unwrap_or_else(|| panic!("Failed to convert u8 to Decimal: {}", value))
 }
 
-fn has_max_two_decimal_places(value: f64) -> bool {
-    let scaled = (value * 100.0).round();
-    (value * 100.0 - scaled).abs() < f64::EPSILON
+/// Checks the order price (0.00-1.00) and quantity against the tick size, min quantity and lot size of the market
+pub(super) fn validate_market_increments(
+    market: &Market,
+    price: Decimal,
+    quantity: Decimal,
+) -> Result<(), String> {
+    let error = if !(price % market.tick_size).is_zero() {
+        format!(
+            "Price must be a multiple of the market tick size {}",
+            market.tick_size
+        )
+    } else if quantity < market.min_quantity {
+        format!("Quantity must be at least {}", market.min_quantity)
+    } else if !(quantity % market.lot_size).is_zero() {
+        format!(
+            "Quantity must be a multiple of the market lot size {}",
+            market.lot_size
+        )
+    } else {
+        return Ok(());
+    };
+    Err(error)
+}
+
+/// Outcome index of the order, categorical markets only trade the yes shares of their outcomes (binary and scalar markets are always 0)
+pub(super) fn resolve_outcome_index(
+    market: &Market,
+    outcome: Outcome,
+    outcome_index: Option<u32>,
+) -> Result<i32, String> {
+    match (market.market_type, outcome_index) {
+        (MarketType::Binary | MarketType::Scalar, None | Some(0)) => Ok(0),
+        (MarketType::Binary | MarketType::Scalar, Some(_)) => {
+            Err("Outcome index is only allowed for categorical markets".to_string())
+        }
+        (MarketType::Categorical, None) => {
+            Err("Outcome index is required for categorical markets".to_string())
+        }
+        (MarketType::Categorical, Some(_)) if outcome != Outcome::YES => {
+            Err("Categorical market orders must be placed on the yes outcome side".to_string())
+        }
+        (MarketType::Categorical, Some(outcome_index)) => i32::try_from(outcome_index)
+            .ok()
+            .filter(|index| *index < market.outcome_count)
+            .ok_or_else(|| {
+                format!(
+                    "Outcome index must be less than the outcome count {}",
+                    market.outcome_count
+                )
+            }),
+    }
 }
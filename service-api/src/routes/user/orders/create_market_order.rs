@@ -1,4 +1,3 @@
-use async_nats::jetstream;
 use auth_service::types::SessionTokenClaims;
 use axum::{
     Extension, Json,
@@ -7,11 +6,8 @@ use axum::{
     response::{IntoResponse, Response},
 };
 use db_service::schema::{
-    enums::{MarketStatus, OrderSide, OrderStatus, OrderType, Outcome},
-    market::Market,
-    orders::Order,
-    user_holdings::UserHoldings,
-    users::User,
+    enums::{OrderSide, OrderType, Outcome, SelfTradePrevention, TimeInForce},
+    orders::{NewOrder, Order},
 };
 use rust_decimal::Decimal;
 use serde::Deserialize;
@@ -25,15 +21,29 @@ use uuid::Uuid;
 
 use crate::{require_field, state::AppState};
 
+use super::{
+    create_limit_order::{from_u8, resolve_outcome_index, resolve_self_trade_prevention},
+    order_checks::{
+        assert_order_stream, bad_request, get_tradable_market, internal_error,
+        publish_order_message, verify_balance, verify_holdings,
+    },
+};
+
 #[derive(Debug, Deserialize)]
 pub struct MarketOrderPayload {
     pub market_id: Option<Uuid>,
     pub price: Option<Decimal>,
     pub outcome: Option<Outcome>,
     pub side: Option<OrderSide>,
+    pub self_trade_prevention: Option<SelfTradePrevention>, // defaults to the account's mode
+    pub max_price: Option<u8>,                              // worst price (0-100) for buy orders
+    pub min_price: Option<u8>,                              // worst price (0-100) for sell orders
+    pub max_slippage: Option<Decimal>, // relative to the midpoint at the time of matching, 0.05 is 5%
+    pub allow_partial: Option<bool>, // fills what the liquidity allows instead of cancelling the order, defaults to false
+    pub outcome_index: Option<u32>, // outcome of the categorical market (yes shares of it are traded)
 }
 
-pub async fn create_limit_order(
+pub async fn create_market_order(
     State(app_state): State<AppState>,
     Extension(claims): Extension<SessionTokenClaims>,
     Json(payload): Json<MarketOrderPayload>,
@@ -49,207 +59,92 @@ pub async fn create_limit_order(
     require_field!(side);
 
     let market_id = market_id.unwrap();
-
-    let market = Market::get_market_by_id(&app_state.pg_pool, &market_id)
-        .await
-        .map_err(|e| {
-            log_error!("Failed to get market - {:?}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({
-                    "error": "Failed to get market"
-                }))
-                .into_response(),
-            )
-        })?;
-
-    if market.is_none() {
-        return Err((
-            StatusCode::NOT_FOUND,
-            Json(json!({
-                "error": "Market not found"
-            }))
-            .into_response(),
-        ));
-    }
-
-    if market.unwrap().status == MarketStatus::SETTLED {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(json!({
-                "error": "Market is already settled, cannot create order"
-            }))
-            .into_response(),
-        ));
-    }
+    let market = get_tradable_market(&app_state, &market_id).await?;
 
     let budget = budget.unwrap();
     let outcome = outcome.unwrap();
     let side = side.unwrap();
     let user_id = claims.user_id;
 
-    app_state
-        .jetstream
-        .get_or_create_stream(jetstream::stream::Config {
-            name: "ORDER".into(),
-            subjects: vec!["order.>".into()],
-            ..Default::default()
-        })
-        .await
-        .map_err(|e| {
-            log_error!("Failed to get or create stream: {}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({"error": "Failed to initialize message stream"})).into_response(),
-            )
-        })?;
+    let outcome_index =
+        resolve_outcome_index(&market, outcome, payload.outcome_index).map_err(bad_request)?;
+
+    let price_limit =
+        get_price_limit(side, payload.max_price, payload.min_price).map_err(bad_request)?;
+    if let Some(max_slippage) = payload.max_slippage
+        && (max_slippage <= Decimal::ZERO || max_slippage >= Decimal::ONE)
+    {
+        return Err(bad_request("max_slippage must be between 0 and 1"));
+    }
+
+    assert_order_stream(&app_state).await?;
 
     if side == OrderSide::SELL {
-        let holdings = UserHoldings::get_user_holdings_by_outcome(
-            &app_state.pg_pool,
+        verify_holdings(
+            &app_state,
             user_id,
             market_id,
             outcome,
+            outcome_index,
+            Decimal::ZERO,
         )
-        .await
-        .map_err(|e| {
-            log_error!("Failed to get user holdings: {}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({"error": "Failed to retrieve user holdings"})).into_response(),
-            )
-        })?;
-
-        if holdings.shares <= Decimal::ZERO {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                Json(json!({"error": "Insufficient shares to place a buy order"})).into_response(),
-            ));
-        }
+        .await?;
     } else {
-        let mut tx = app_state.pg_pool.begin().await.map_err(|e| {
-            log_error!("Failed to begin transaction - {:?}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({
-                    "error": "Failed to begin transaction"
-                }))
-                .into_response(),
-            )
-        })?;
-        let balance = User::get_user_balance(&mut *tx, user_id)
-            .await
-            .map_err(|e| {
-                log_error!("Failed to get user balance: {}", e);
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(json!({"error": "Failed to retrieve user balance"})).into_response(),
-                )
-            })?;
-
-        let total_user_locked_funds = Order::get_user_order_locked_funds(&mut *tx, claims.user_id)
-            .await
-            .map_err(|e| {
-                log_error!("Failed to get user orders total amount - {:?}", e);
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(json!({
-                        "error": "Failed to get user orders total amount"
-                    }))
-                    .into_response(),
-                )
-            })?;
+        verify_balance(&app_state, user_id, budget).await?;
+    }
 
-        tx.commit().await.map_err(|e| {
-            log_error!("Failed to commit transaction - {:?}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({
-                    "error": "Failed to commit transaction"
-                }))
-                .into_response(),
-            )
-        })?;
+    // market orders are either fully matched or cancelled, unless partial fills are allowed
+    let allow_partial = payload.allow_partial.unwrap_or(false);
 
-        if total_user_locked_funds + budget > balance {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                Json(json!({"error": "Insufficient balance to place a buy order"})).into_response(),
-            ));
-        }
-
-        if balance < budget {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                Json(json!({"error": "Insufficient balance to place a sell order"}))
-                    .into_response(),
-            ));
-        }
-    }
+    let self_trade_prevention =
+        resolve_self_trade_prevention(&app_state, user_id, payload.self_trade_prevention).await?;
 
     let order = Order::create_order(
-        user_id,
-        market_id,
-        Decimal::ZERO,
-        Decimal::ZERO,
-        side,
-        outcome,
-        OrderType::MARKET,
         &app_state.pg_pool,
+        &NewOrder {
+            user_id,
+            market_id,
+            price: Decimal::ZERO,
+            quantity: Decimal::ZERO,
+            side,
+            outcome,
+            order_type: OrderType::MARKET,
+            trigger_price: None,
+            time_in_force: TimeInForce::FOK, // market orders are never rested in the book
+            expires_at: None,
+            post_only: false,
+            self_trade_prevention,
+            display_quantity: None,
+            outcome_index,
+        },
     )
     .await
     .map_err(|e| {
         log_error!("Failed to create order: {}", e);
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({"error": "Failed to create order"})).into_response(),
-        )
+        internal_error("Failed to create order")
     })?;
 
     let market_order_create_message = MarketOrderCreateMessage {
         order_id: order.id,
+        market_id: order.market_id,
         budget: budget / Decimal::new(100, 0),
+        price_limit,
+        max_slippage: payload.max_slippage,
+        allow_partial,
     };
 
     let message_pack_encoded_message = serialize_to_message_pack(&market_order_create_message)
         .map_err(|e| {
             log_error!("Failed to serialize market order create message: {}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({"error": "Failed to serialize market order create message"}))
-                    .into_response(),
-            )
-        })?;
-
-    let subject = NatsSubjects::MarketOrderCreate;
-    let mut is_failed = false;
-
-    app_state
-        .jetstream
-        .publish(subject.to_string(), message_pack_encoded_message.into())
-        .await
-        .map_err(|e| {
-            log_error!("Failed to publish market order create message: {}", e);
-            is_failed = true;
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({"error": "Failed to publish market order create message"}))
-                    .into_response(),
-            )
+            internal_error("Failed to serialize market order create message")
         })?;
-
-    if is_failed {
-        Order::update_order_status(order.id, OrderStatus::CANCELLED, &app_state.pg_pool)
-            .await
-            .map_err(|e| {
-                log_error!("Failed to update order status to cancelled: {}", e);
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(json!({"error": "Failed to update order status to cancelled"}))
-                        .into_response(),
-                )
-            })?;
-    }
+    publish_order_message(
+        &app_state,
+        order.id,
+        NatsSubjects::MarketOrderCreate,
+        message_pack_encoded_message,
+    )
+    .await?;
 
     log_info!(
         "Market order created and published to jetstream - {:?}",
@@ -266,8 +161,37 @@ pub async fn create_limit_order(
             "outcome": outcome,
             "side": side,
             "status": order.status,
+            "price_limit": price_limit,
+            "max_slippage": payload.max_slippage,
+            "allow_partial": allow_partial,
         },
     });
 
     Ok((StatusCode::CREATED, Json(response)))
 }
+
+/// Worst price (0.00-1.00) the market order can be matched at, `max_price` is only allowed for buys and `min_price` for sells
+fn get_price_limit(
+    side: OrderSide,
+    max_price: Option<u8>,
+    min_price: Option<u8>,
+) -> Result<Option<Decimal>, String> {
+    let price_limit = match (side, max_price, min_price) {
+        (OrderSide::BUY, _, Some(_)) => {
+            return Err("min_price is only allowed for sell orders".into());
+        }
+        (OrderSide::SELL, Some(_), _) => {
+            return Err("max_price is only allowed for buy orders".into());
+        }
+        (OrderSide::BUY, price_limit, None) | (OrderSide::SELL, None, price_limit) => price_limit,
+    };
+
+    match price_limit {
+        Some(price_limit) if price_limit == 0 || price_limit > 100 => {
+            Err("Price limit must be between 1 and 100".into())
+        }
+        price_limit => {
+            Ok(price_limit.map(|price_limit| from_u8(price_limit) / Decimal::new(100, 0)))
+        }
+    }
+}
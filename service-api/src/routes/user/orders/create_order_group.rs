@@ -0,0 +1,364 @@
+use auth_service::types::SessionTokenClaims;
+use axum::{
+    Extension, Json,
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use db_service::schema::{
+    enums::{
+        OrderGroupLeg, OrderGroupType, OrderSide, OrderStatus, OrderType, Outcome,
+        SelfTradePrevention,
+    },
+    order_groups::{NewGroupLeg, NewOrderGroup, OrderGroup},
+    orders::Order,
+};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use utility_helpers::{
+    log_error, log_info,
+    message_pack_helper::serialize_to_message_pack,
+    nats_helper::{NatsSubjects, types::OrderMessage},
+};
+use uuid::Uuid;
+
+use crate::{require_field, state::AppState};
+
+use super::create_limit_order::{
+    from_f64, from_u8, resolve_outcome_index, resolve_self_trade_prevention,
+    validate_market_increments,
+};
+use super::order_checks::{
+    bad_request, get_tradable_market, internal_error, verify_balance, verify_holdings,
+};
+
+/// Take profit and stop loss of the same position, execution of one cancels the other.
+/// Legs are executed as market orders once triggered
+#[derive(Deserialize, Serialize, Debug)]
+pub struct CreateOcoOrderPayload {
+    market_id: Option<Uuid>,
+    side: Option<OrderSide>, // side of both legs, sell to exit a long position
+    outcome_side: Option<Outcome>,
+    quantity: Option<f64>,
+    take_profit_price: Option<u8>, // trigger prices (1-99)
+    stop_loss_price: Option<u8>,
+    self_trade_prevention: Option<SelfTradePrevention>,
+    outcome_index: Option<u32>,
+}
+
+/// Limit entry order with take profit and stop loss exits on the opposite side, exits are activated once the entry is filled
+#[derive(Deserialize, Serialize, Debug)]
+pub struct CreateBracketOrderPayload {
+    market_id: Option<Uuid>,
+    side: Option<OrderSide>, // side of the entry order
+    outcome_side: Option<Outcome>,
+    quantity: Option<f64>,
+    price: Option<u8>, // limit price of the entry (1-99)
+    take_profit_price: Option<u8>,
+    stop_loss_price: Option<u8>,
+    self_trade_prevention: Option<SelfTradePrevention>,
+    outcome_index: Option<u32>,
+}
+
+pub async fn create_oco_order(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<SessionTokenClaims>,
+    Json(payload): Json<CreateOcoOrderPayload>,
+) -> Result<impl IntoResponse, (StatusCode, Response)> {
+    require_field!(payload.market_id);
+    require_field!(payload.side);
+    require_field!(payload.outcome_side);
+    require_field!(payload.quantity);
+    require_field!(payload.take_profit_price);
+    require_field!(payload.stop_loss_price);
+
+    let side = payload.side.unwrap();
+    let quantity = from_f64(payload.quantity.unwrap());
+    let take_profit_price = payload.take_profit_price.unwrap();
+    let stop_loss_price = payload.stop_loss_price.unwrap();
+
+    validate_exit_prices(side, take_profit_price, stop_loss_price, None).map_err(bad_request)?;
+
+    let market = get_tradable_market(&app_state, &payload.market_id.unwrap()).await?;
+    let new_group = NewOrderGroup {
+        user_id: claims.user_id,
+        market_id: market.id,
+        group_type: OrderGroupType::Oco,
+        outcome: payload.outcome_side.unwrap(),
+        outcome_index: resolve_outcome_index(
+            &market,
+            payload.outcome_side.unwrap(),
+            payload.outcome_index,
+        )
+        .map_err(bad_request)?,
+        self_trade_prevention: resolve_self_trade_prevention(
+            &app_state,
+            claims.user_id,
+            payload.self_trade_prevention,
+        )
+        .await?,
+        legs: get_exit_legs(
+            side,
+            quantity,
+            take_profit_price,
+            stop_loss_price,
+            OrderStatus::UNSPECIFIED,
+        ),
+    };
+    for leg in new_group.legs.iter() {
+        validate_market_increments(&market, leg.trigger_price.unwrap_or_default(), quantity)
+            .map_err(bad_request)?;
+    }
+
+    // market execution is estimated with the higher trigger price, only one of the legs is executed
+    let execution_price = take_profit_price.max(stop_loss_price);
+    check_order_funds(&app_state, &new_group, side, quantity, execution_price).await?;
+
+    create_order_group(&app_state, new_group).await
+}
+
+pub async fn create_bracket_order(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<SessionTokenClaims>,
+    Json(payload): Json<CreateBracketOrderPayload>,
+) -> Result<impl IntoResponse, (StatusCode, Response)> {
+    require_field!(payload.market_id);
+    require_field!(payload.side);
+    require_field!(payload.outcome_side);
+    require_field!(payload.quantity);
+    require_field!(payload.price);
+    require_field!(payload.take_profit_price);
+    require_field!(payload.stop_loss_price);
+
+    let side = payload.side.unwrap();
+    let quantity = from_f64(payload.quantity.unwrap());
+    let price = payload.price.unwrap();
+    let take_profit_price = payload.take_profit_price.unwrap();
+    let stop_loss_price = payload.stop_loss_price.unwrap();
+
+    if price == 0 || price >= 100 {
+        return Err(bad_request("Price must be between 1 and 99"));
+    }
+    let exit_side = match side {
+        OrderSide::BUY => OrderSide::SELL,
+        OrderSide::SELL => OrderSide::BUY,
+    };
+    validate_exit_prices(exit_side, take_profit_price, stop_loss_price, Some(price))
+        .map_err(bad_request)?;
+
+    let market = get_tradable_market(&app_state, &payload.market_id.unwrap()).await?;
+    let mut legs = vec![NewGroupLeg {
+        leg: OrderGroupLeg::Entry,
+        side,
+        order_type: OrderType::LIMIT,
+        price: from_u8(price) / dec!(100),
+        trigger_price: None,
+        quantity,
+        status: OrderStatus::UNSPECIFIED,
+    }];
+    // exits are sent to the book once the entry is filled
+    legs.extend(get_exit_legs(
+        exit_side,
+        quantity,
+        take_profit_price,
+        stop_loss_price,
+        OrderStatus::PendingActivation,
+    ));
+    let new_group = NewOrderGroup {
+        user_id: claims.user_id,
+        market_id: market.id,
+        group_type: OrderGroupType::Bracket,
+        outcome: payload.outcome_side.unwrap(),
+        outcome_index: resolve_outcome_index(
+            &market,
+            payload.outcome_side.unwrap(),
+            payload.outcome_index,
+        )
+        .map_err(bad_request)?,
+        self_trade_prevention: resolve_self_trade_prevention(
+            &app_state,
+            claims.user_id,
+            payload.self_trade_prevention,
+        )
+        .await?,
+        legs,
+    };
+    for leg in new_group.legs.iter() {
+        let leg_price = leg.trigger_price.unwrap_or(leg.price);
+        validate_market_increments(&market, leg_price, quantity).map_err(bad_request)?;
+    }
+
+    // exits only trade the position opened by the entry
+    check_order_funds(&app_state, &new_group, side, quantity, price).await?;
+
+    create_order_group(&app_state, new_group).await
+}
+
+/// Take profit must be on the profitable side of the stop loss (and of the entry price of the bracket)
+fn validate_exit_prices(
+    exit_side: OrderSide,
+    take_profit_price: u8,
+    stop_loss_price: u8,
+    entry_price: Option<u8>,
+) -> Result<(), &'static str> {
+    if [take_profit_price, stop_loss_price]
+        .iter()
+        .any(|price| *price == 0 || *price >= 100)
+    {
+        return Err("Take profit and stop loss prices must be between 1 and 99");
+    }
+    // long position is closed by selling, so it's profit is above the stop loss
+    let (low, high) = match exit_side {
+        OrderSide::SELL => (stop_loss_price, take_profit_price),
+        OrderSide::BUY => (take_profit_price, stop_loss_price),
+    };
+    if low >= high || entry_price.is_some_and(|price| price <= low || price >= high) {
+        return Err(match exit_side {
+            OrderSide::SELL => "Stop loss must be below the take profit (and the entry price)",
+            OrderSide::BUY => "Take profit must be below the stop loss (and the entry price)",
+        });
+    }
+    Ok(())
+}
+
+fn get_exit_legs(
+    side: OrderSide,
+    quantity: Decimal,
+    take_profit_price: u8,
+    stop_loss_price: u8,
+    status: OrderStatus,
+) -> Vec<NewGroupLeg> {
+    [
+        (
+            OrderGroupLeg::TakeProfit,
+            OrderType::TakeProfit,
+            take_profit_price,
+        ),
+        (
+            OrderGroupLeg::StopLoss,
+            OrderType::StopLoss,
+            stop_loss_price,
+        ),
+    ]
+    .into_iter()
+    .map(|(leg, order_type, trigger_price)| NewGroupLeg {
+        leg,
+        side,
+        order_type,
+        price: Decimal::ZERO, // executed as market order once triggered
+        trigger_price: Some(from_u8(trigger_price) / dec!(100)),
+        quantity,
+        status,
+    })
+    .collect()
+}
+
+/// Checks the holdings of the sell orders and the balance (excluding the locked funds) of the buy orders, `price` is in 0-100
+async fn check_order_funds(
+    app_state: &AppState,
+    group: &NewOrderGroup,
+    side: OrderSide,
+    quantity: Decimal,
+    price: u8,
+) -> Result<(), (StatusCode, Response)> {
+    if side == OrderSide::SELL {
+        verify_holdings(
+            app_state,
+            group.user_id,
+            group.market_id,
+            group.outcome,
+            group.outcome_index,
+            quantity,
+        )
+        .await?;
+    } else {
+        verify_balance(app_state, group.user_id, from_u8(price) * quantity).await?;
+    }
+    Ok(())
+}
+
+/// Creates the group and sends it's active orders to the order service, group is cancelled if they can't be published
+async fn create_order_group(
+    app_state: &AppState,
+    new_group: NewOrderGroup,
+) -> Result<(StatusCode, Json<serde_json::Value>), (StatusCode, Response)> {
+    let (group, orders) = OrderGroup::create_order_group(&app_state.pg_pool, &new_group)
+        .await
+        .map_err(|e| {
+            log_error!("Failed to create order group - {:?}", e);
+            internal_error("Failed to create order group")
+        })?;
+
+    for order in orders.iter() {
+        if order.status != OrderStatus::UNSPECIFIED {
+            continue; // bracket exits are activated by the order service
+        }
+        let message = OrderMessage {
+            order_id: order.id,
+            market_id: order.market_id,
+        };
+        let message_pack_encoded = serialize_to_message_pack(&message).map_err(|e| {
+            log_error!("Failed to serialize order message - {:?}", e);
+            internal_error("Failed to serialize order message")
+        })?;
+        if let Err(e) = app_state
+            .jetstream
+            .publish(
+                NatsSubjects::OrderCreate.to_string(),
+                message_pack_encoded.into(),
+            )
+            .await
+        {
+            log_error!("Failed to publish order to jetstream - {:?}", e);
+            // already published legs are cancelled by the order service, as their siblings are cancelled
+            for order in orders.iter() {
+                Order::update_order_status(order.id, OrderStatus::CANCELLED, &app_state.pg_pool)
+                    .await
+                    .map_err(|e| {
+                        log_error!("Failed to cancel order - {:?}", e);
+                        internal_error("Failed to update order status to cancelled")
+                    })?;
+            }
+            return Err(internal_error(
+                "Failed to publish order to jetstream, order group is cancelled",
+            ));
+        }
+    }
+
+    log_info!("Order group published to jetstream - {:?}", group.id);
+
+    let legs = new_group
+        .legs
+        .iter()
+        .zip(orders.iter())
+        .map(|(leg, order)| {
+            json!({
+                "leg": leg.leg,
+                "id": order.id,
+                "side": order.side,
+                "order_type": order.order_type,
+                "price": order.price.to_string(),
+                "trigger_price": order.trigger_price.map(|p| p.to_string()),
+                "quantity": order.quantity.to_string(),
+                "status": order.status,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    Ok((
+        StatusCode::CREATED,
+        Json(json!({
+            "message": "Order group created successfully",
+            "group": {
+                "id": group.id,
+                "user_id": group.user_id,
+                "market_id": group.market_id,
+                "group_type": group.group_type,
+                "outcome": new_group.outcome,
+                "orders": legs,
+            }
+        })),
+    ))
+}
@@ -0,0 +1,168 @@
+use auth_service::types::SessionTokenClaims;
+use axum::{
+    Extension, Json,
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use db_service::schema::{
+    enums::{OrderSide, OrderType, Outcome, SelfTradePrevention, TimeInForce},
+    orders::{NewOrder, Order},
+};
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sqlx::types::Uuid;
+use utility_helpers::{log_error, log_info};
+
+use crate::{require_field, state::AppState};
+
+use super::create_limit_order::{
+    from_f64, from_u8, resolve_outcome_index, resolve_self_trade_prevention,
+    validate_market_increments,
+};
+use super::order_checks::{
+    assert_order_stream, bad_request, get_tradable_market, publish_created_order, verify_balance,
+    verify_holdings,
+};
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct CreateStopOrderPayload {
+    market_id: Option<Uuid>,
+    order_type: Option<OrderType>, // stop_loss or take_profit
+    trigger_price: Option<u8>,
+    price: Option<u8>, // limit price after trigger, if not provided then order is executed as market order
+    quantity: Option<f64>,
+    side: Option<OrderSide>,
+    outcome_side: Option<Outcome>,
+    self_trade_prevention: Option<SelfTradePrevention>, // defaults to the account's mode
+    outcome_index: Option<u32>, // outcome of the categorical market (yes shares of it are traded)
+}
+
+pub async fn create_stop_order(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<SessionTokenClaims>,
+    Json(payload): Json<CreateStopOrderPayload>,
+) -> Result<impl IntoResponse, (StatusCode, Response)> {
+    require_field!(payload.market_id);
+    require_field!(payload.order_type);
+    require_field!(payload.trigger_price);
+    require_field!(payload.quantity);
+    require_field!(payload.side);
+    require_field!(payload.outcome_side);
+
+    let order_type = payload.order_type.unwrap();
+    if order_type != OrderType::StopLoss && order_type != OrderType::TakeProfit {
+        return Err(bad_request("Order type must be stop_loss or take_profit"));
+    }
+
+    let market_id = payload.market_id.unwrap();
+
+    let market = get_tradable_market(&app_state, &market_id).await?;
+
+    let side = payload.side.unwrap();
+    let outcome_side = payload.outcome_side.unwrap();
+    let user_id = claims.user_id;
+    let quantity = payload.quantity.unwrap();
+    let trigger_price = payload.trigger_price.unwrap();
+    let price = payload.price.unwrap_or(0);
+
+    if trigger_price == 0 || trigger_price >= 100 {
+        return Err(bad_request("Trigger price must be between 1 and 99"));
+    }
+    if price > 100 {
+        return Err(bad_request("Price must be between 0 and 100"));
+    }
+    // price of market execution (0) is always on the tick
+    let outcome_index =
+        resolve_outcome_index(&market, outcome_side, payload.outcome_index).map_err(bad_request)?;
+    validate_market_increments(&market, from_u8(price) / dec!(100), from_f64(quantity))
+        .and_then(|_| {
+            validate_market_increments(
+                &market,
+                from_u8(trigger_price) / dec!(100),
+                from_f64(quantity),
+            )
+        })
+        .map_err(bad_request)?;
+
+    assert_order_stream(&app_state).await?;
+
+    ///////////////// Verifying user holdings ///////////////////////
+    // if trade type is sell then check holdings, else check the user's balance
+
+    if side == OrderSide::SELL {
+        verify_holdings(
+            &app_state,
+            user_id,
+            market_id,
+            outcome_side,
+            outcome_index,
+            from_f64(quantity),
+        )
+        .await?;
+    } else {
+        // market execution is estimated with trigger price
+        let execution_price = if price > 0 { price } else { trigger_price };
+        let required_price = from_u8(execution_price) * from_f64(quantity);
+        verify_balance(&app_state, user_id, required_price).await?;
+    }
+
+    ///////////////////////////////////////////////////////////////
+
+    let self_trade_prevention =
+        resolve_self_trade_prevention(&app_state, user_id, payload.self_trade_prevention).await?;
+
+    let order = Order::create_order(
+        &app_state.pg_pool,
+        &NewOrder {
+            user_id,
+            market_id,
+            price: from_u8(price) / dec!(100), // scaling down the price to 2 decimal places (0-100 to 0.00-1.00)
+            quantity: from_f64(quantity),
+            side,
+            outcome: outcome_side,
+            order_type,
+            trigger_price: Some(from_u8(trigger_price) / dec!(100)),
+            time_in_force: TimeInForce::GTC,
+            expires_at: None,
+            post_only: false,
+            self_trade_prevention,
+            display_quantity: None,
+            outcome_index,
+        },
+    )
+    .await
+    .map_err(|e| {
+        log_error!("Failed to create order - {:?}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "error": "Failed to create order"
+            }))
+            .into_response(),
+        )
+    })?;
+
+    publish_created_order(&app_state, &order).await?;
+
+    log_info!("Trigger order published to jetstream - {:?}", order.id);
+
+    let response = json!({
+        "message": "Order created successfully",
+        "order" : {
+            "id": order.id,
+            "user_id": order.user_id,
+            "market_id": order.market_id,
+            "side": order.side,
+            "outcome": order.outcome,
+            "order_type": order.order_type,
+            "price": order.price.to_string(),
+            "trigger_price": order.trigger_price.map(|p| p.to_string()),
+            "quantity": order.quantity.to_string(),
+            "status": order.status,
+        }
+    });
+
+    Ok((StatusCode::CREATED, Json(response)))
+}
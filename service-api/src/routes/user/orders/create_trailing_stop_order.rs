@@ -0,0 +1,157 @@
+use auth_service::types::SessionTokenClaims;
+use axum::{
+    Extension, Json,
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use db_service::schema::{
+    enums::{OrderSide, Outcome, SelfTradePrevention},
+    trailing_stops::{NewTrailingStopOrder, TrailingStop},
+};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sqlx::types::Uuid;
+use utility_helpers::{log_error, log_info};
+
+use crate::{require_field, state::AppState};
+
+use super::create_limit_order::{
+    from_f64, from_u8, resolve_outcome_index, resolve_self_trade_prevention,
+    validate_market_increments,
+};
+use super::order_checks::{
+    assert_order_stream, bad_request, get_tradable_market, publish_created_order, verify_balance,
+    verify_holdings,
+};
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct CreateTrailingStopPayload {
+    market_id: Option<Uuid>,
+    trail_amount: Option<u8>,   // offset from the best price in cents (1-99)
+    trail_percent: Option<f64>, // offset in percentage of the best price (0-100), either this or trail amount
+    quantity: Option<f64>,
+    side: Option<OrderSide>,
+    outcome_side: Option<Outcome>,
+    self_trade_prevention: Option<SelfTradePrevention>, // defaults to the account's mode
+    outcome_index: Option<u32>, // outcome of the categorical market (yes shares of it are traded)
+}
+
+/// Creates stop loss order whose trigger price trails the best market price seen since it's placement, it's executed as market order
+pub async fn create_trailing_stop_order(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<SessionTokenClaims>,
+    Json(payload): Json<CreateTrailingStopPayload>,
+) -> Result<impl IntoResponse, (StatusCode, Response)> {
+    require_field!(payload.market_id);
+    require_field!(payload.quantity);
+    require_field!(payload.side);
+    require_field!(payload.outcome_side);
+
+    let (trail_amount, trail_percent) = match (payload.trail_amount, payload.trail_percent) {
+        (Some(trail_amount), None) if trail_amount > 0 && trail_amount < 100 => {
+            (Some(from_u8(trail_amount) / dec!(100)), None)
+        }
+        (None, Some(trail_percent)) if trail_percent > 0.0 && trail_percent < 100.0 => {
+            (None, Some(from_f64(trail_percent)))
+        }
+        _ => {
+            return Err(bad_request(
+                "Either trail amount (1-99) or trail percent (0-100) is required",
+            ));
+        }
+    };
+
+    let market_id = payload.market_id.unwrap();
+
+    let market = get_tradable_market(&app_state, &market_id).await?;
+
+    let side = payload.side.unwrap();
+    let outcome_side = payload.outcome_side.unwrap();
+    let user_id = claims.user_id;
+    let quantity = payload.quantity.unwrap();
+
+    let outcome_index =
+        resolve_outcome_index(&market, outcome_side, payload.outcome_index).map_err(bad_request)?;
+    // trigger price is rounded to the tick by the order book, only the quantity is validated
+    validate_market_increments(&market, Decimal::ZERO, from_f64(quantity)).map_err(bad_request)?;
+
+    assert_order_stream(&app_state).await?;
+
+    ///////////////// Verifying user holdings ///////////////////////
+    // if trade type is sell then check holdings, else check the user's balance
+
+    if side == OrderSide::SELL {
+        verify_holdings(
+            &app_state,
+            user_id,
+            market_id,
+            outcome_side,
+            outcome_index,
+            from_f64(quantity),
+        )
+        .await?;
+    } else {
+        // trigger price moves with the market, so the execution is estimated with the worst price
+        let required_price = from_u8(100) * from_f64(quantity);
+        verify_balance(&app_state, user_id, required_price).await?;
+    }
+
+    ///////////////////////////////////////////////////////////////
+
+    let self_trade_prevention =
+        resolve_self_trade_prevention(&app_state, user_id, payload.self_trade_prevention).await?;
+
+    let (order, trailing_stop) = TrailingStop::create_trailing_stop_order(
+        &app_state.pg_pool,
+        &NewTrailingStopOrder {
+            user_id,
+            market_id,
+            side,
+            outcome: outcome_side,
+            outcome_index,
+            quantity: from_f64(quantity),
+            self_trade_prevention,
+            trail_amount,
+            trail_percent,
+        },
+    )
+    .await
+    .map_err(|e| {
+        log_error!("Failed to create order - {:?}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "error": "Failed to create order"
+            }))
+            .into_response(),
+        )
+    })?;
+
+    publish_created_order(&app_state, &order).await?;
+
+    log_info!(
+        "Trailing stop order published to jetstream - {:?}",
+        order.id
+    );
+
+    let response = json!({
+        "message": "Order created successfully",
+        "order" : {
+            "id": order.id,
+            "user_id": order.user_id,
+            "market_id": order.market_id,
+            "side": order.side,
+            "outcome": order.outcome,
+            "order_type": order.order_type,
+            "quantity": order.quantity.to_string(),
+            "trail_amount": trailing_stop.trail_amount.map(|a| a.to_string()),
+            "trail_percent": trailing_stop.trail_percent.map(|p| p.to_string()),
+            "status": order.status,
+        }
+    });
+
+    Ok((StatusCode::CREATED, Json(response)))
+}
@@ -39,6 +39,7 @@ pub async fn get_all_users_orders(
         "expired" => OrderStatus::EXPIRED,
         "pending_update" => OrderStatus::PendingUpdate,
         "pending_cancel" => OrderStatus::PendingCancel,
+        "pending_activation" => OrderStatus::PendingActivation,
         _ => {
             return Err((
                 StatusCode::BAD_REQUEST,
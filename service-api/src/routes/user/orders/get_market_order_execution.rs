@@ -0,0 +1,44 @@
+use auth_service::types::SessionTokenClaims;
+use axum::{
+    Extension, Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use db_service::schema::market_order_executions::MarketOrderExecution;
+use serde_json::json;
+use utility_helpers::log_error;
+use uuid::Uuid;
+
+use crate::state::AppState;
+
+/// Average fill price, consumed price levels and unfilled budget of the user's market order
+pub async fn get_market_order_execution(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<SessionTokenClaims>,
+    Path(order_id): Path<Uuid>,
+) -> Result<impl IntoResponse, (StatusCode, Response)> {
+    let execution = MarketOrderExecution::get_user_execution_by_order_id(
+        &app_state.pg_pool,
+        claims.user_id,
+        order_id,
+    )
+    .await
+    .map_err(|e| {
+        log_error!("Failed to fetch market order execution {e:?}");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"message": "Failed to fetch market order execution"})).into_response(),
+        )
+    })?;
+
+    let Some(execution) = execution else {
+        // order is not processed yet (or it's not a market order of the user)
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({"message": "Market order execution not found"})).into_response(),
+        ));
+    };
+
+    Ok(Json(json!({ "execution": execution })).into_response())
+}
@@ -0,0 +1,67 @@
+use auth_service::types::SessionTokenClaims;
+use axum::{
+    Extension, Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use db_service::schema::order_groups::OrderGroup;
+use serde_json::json;
+use utility_helpers::log_error;
+use uuid::Uuid;
+
+use crate::state::AppState;
+
+/// OCO / bracket group of the user along with the current state of it's orders
+pub async fn get_order_group(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<SessionTokenClaims>,
+    Path(group_id): Path<Uuid>,
+) -> Result<impl IntoResponse, (StatusCode, Response)> {
+    let group = OrderGroup::get_order_group_by_id(&app_state.pg_pool, &group_id)
+        .await
+        .map_err(|e| {
+            log_error!("Failed to fetch order group {e:?}");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"message": "Failed to fetch order group"})).into_response(),
+            )
+        })?;
+
+    let Some(group) = group.filter(|group| group.user_id == claims.user_id) else {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({"message": "Order group not found"})).into_response(),
+        ));
+    };
+
+    let orders = OrderGroup::get_group_orders(&app_state.pg_pool, &group_id)
+        .await
+        .map_err(|e| {
+            log_error!("Failed to fetch orders of the group {e:?}");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"message": "Failed to fetch orders of the group"})).into_response(),
+            )
+        })?;
+
+    Ok(Json(json!({ "group": group, "orders": orders })).into_response())
+}
+
+/// Groups of the user, latest first
+pub async fn get_user_order_groups(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<SessionTokenClaims>,
+) -> Result<impl IntoResponse, (StatusCode, Response)> {
+    let groups = OrderGroup::get_user_order_groups(&app_state.pg_pool, &claims.user_id)
+        .await
+        .map_err(|e| {
+            log_error!("Failed to fetch order groups {e:?}");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"message": "Failed to fetch order groups"})).into_response(),
+            )
+        })?;
+
+    Ok(Json(json!({ "groups": groups })).into_response())
+}
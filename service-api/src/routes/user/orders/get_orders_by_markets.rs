@@ -42,6 +42,7 @@ pub async fn get_user_orders_by_market(
         "expired" => Some(OrderStatus::EXPIRED),
         "pending_update" => Some(OrderStatus::PendingUpdate),
         "pending_cancel" => Some(OrderStatus::PendingCancel),
+        "pending_activation" => Some(OrderStatus::PendingActivation),
         "all" => None,
         _ => {
             return Err((
@@ -8,8 +8,15 @@ use crate::state::AppState;
 pub mod cancel_order;
 pub mod create_limit_order;
 pub mod create_market_order;
+pub mod create_order_group;
+pub mod create_stop_order;
+pub mod create_trailing_stop_order;
 pub mod get_all_users_orders;
+pub mod get_market_order_execution;
+pub mod get_order_groups;
 pub mod get_orders_by_markets;
+mod order_checks;
+pub mod quote_market_order;
 pub mod update_order;
 
 pub fn router() -> Router<AppState> {
@@ -21,12 +28,29 @@ pub fn router() -> Router<AppState> {
         )
         .route(
             "/create/market",
-            post(create_market_order::create_limit_order),
+            post(create_market_order::create_market_order),
         )
+        .route("/create/stop", post(create_stop_order::create_stop_order))
+        .route(
+            "/create/trailing_stop",
+            post(create_trailing_stop_order::create_trailing_stop_order),
+        )
+        .route("/create/oco", post(create_order_group::create_oco_order))
+        .route(
+            "/create/bracket",
+            post(create_order_group::create_bracket_order),
+        )
+        .route("/groups", get(get_order_groups::get_user_order_groups))
+        .route("/group/{id}", get(get_order_groups::get_order_group))
         .route(
             "/get/{id}",
             get(get_orders_by_markets::get_user_orders_by_market),
         )
+        .route(
+            "/execution/{id}",
+            get(get_market_order_execution::get_market_order_execution),
+        )
+        .route("/quote", post(quote_market_order::quote_market_order))
         .route("/cancel/{id}", delete(cancel_order::cancel_order))
         .route("/update", patch(update_order::update_order))
 }
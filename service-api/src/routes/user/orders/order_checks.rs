@@ -0,0 +1,210 @@
+/*
+ * Checks and publishing steps shared by the order creation routes
+ *
+ * Routes only add the checks of their order type (trigger price, trail, time in force...) on top of these.
+ */
+
+use async_nats::jetstream;
+use axum::{
+    Json,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use db_service::schema::{
+    enums::{OrderStatus, Outcome},
+    market::Market,
+    orders::Order,
+    user_holdings::UserHoldings,
+    users::User,
+};
+use rust_decimal::Decimal;
+use serde_json::json;
+use sqlx::types::Uuid;
+use utility_helpers::{
+    log_error,
+    message_pack_helper::serialize_to_message_pack,
+    nats_helper::{NatsSubjects, types::OrderMessage},
+};
+
+use crate::state::AppState;
+
+pub(super) fn bad_request(error: impl AsRef<str>) -> (StatusCode, Response) {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(json!({ "error": error.as_ref() })).into_response(),
+    )
+}
+
+pub(super) fn internal_error(error: &str) -> (StatusCode, Response) {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(json!({ "error": error })).into_response(),
+    )
+}
+
+/// Market of the order, it must be open for trading
+pub(super) async fn get_tradable_market(
+    app_state: &AppState,
+    market_id: &Uuid,
+) -> Result<Market, (StatusCode, Response)> {
+    let market = Market::get_market_by_id(&app_state.pg_pool, market_id)
+        .await
+        .map_err(|e| {
+            log_error!("Failed to get market - {:?}", e);
+            internal_error("Failed to get market")
+        })?;
+
+    let Some(market) = market else {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({
+                "error": "Market not found"
+            }))
+            .into_response(),
+        ));
+    };
+    if !market.status.is_tradable() {
+        return Err(bad_request(
+            "Market is not open for trading, cannot create order",
+        ));
+    }
+
+    Ok(market)
+}
+
+/// Asserts the `ORDER` stream exists (not publishing the message)
+pub(super) async fn assert_order_stream(
+    app_state: &AppState,
+) -> Result<(), (StatusCode, Response)> {
+    app_state
+        .jetstream
+        .get_or_create_stream(jetstream::stream::Config {
+            // these `ORDER` name does not indicate the operations on orders, instead it indicates that the streams is used by order-service microservice, so don't confuse it with the order name and same for it's topics, all topics are prefixed with `order.`
+            name: "ORDER".into(),
+            subjects: vec!["order.>".into()],
+            ..Default::default()
+        })
+        .await
+        .map_err(|e| {
+            log_error!("Failed to create jetstream stream - {:?}", e);
+            internal_error("Failed to create jetstream stream")
+        })?;
+
+    Ok(())
+}
+
+/// Sell order must be covered by the user's shares of the outcome
+pub(super) async fn verify_holdings(
+    app_state: &AppState,
+    user_id: Uuid,
+    market_id: Uuid,
+    outcome: Outcome,
+    outcome_index: i32,
+    quantity: Decimal,
+) -> Result<(), (StatusCode, Response)> {
+    let holdings = UserHoldings::get_user_holdings_by_outcome(
+        &app_state.pg_pool,
+        user_id,
+        market_id,
+        outcome,
+        outcome_index,
+    )
+    .await
+    .map_err(|e| {
+        log_error!("Failed to get user holdings - {:?}", e);
+        internal_error("Failed to get user holdings")
+    })?;
+
+    if holdings.shares <= Decimal::ZERO || holdings.shares < quantity {
+        return Err(bad_request("You do not have enough shares to sell"));
+    }
+    Ok(())
+}
+
+/// Buy order must be covered by the user's balance (minus the funds locked by open orders), returns the available balance
+pub(super) async fn verify_balance(
+    app_state: &AppState,
+    user_id: Uuid,
+    required_amount: Decimal,
+) -> Result<Decimal, (StatusCode, Response)> {
+    let mut tx = app_state.pg_pool.begin().await.map_err(|e| {
+        log_error!("Failed to begin transaction - {:?}", e);
+        internal_error("Failed to begin transaction")
+    })?;
+    let balance = User::get_user_balance(&mut *tx, user_id)
+        .await
+        .map_err(|e| {
+            log_error!("Failed to get user - {:?}", e);
+            internal_error("Failed to get user")
+        })?;
+
+    let total_user_locked_funds = Order::get_user_order_locked_funds(&mut *tx, user_id)
+        .await
+        .map_err(|e| {
+            log_error!("Failed to get user orders total amount - {:?}", e);
+            internal_error("Failed to get user orders total amount")
+        })?;
+
+    tx.commit().await.map_err(|e| {
+        log_error!("Failed to commit transaction - {:?}", e);
+        internal_error("Failed to commit transaction")
+    })?;
+
+    let balance = balance - total_user_locked_funds;
+    if balance < Decimal::ZERO || balance < required_amount {
+        return Err(bad_request(
+            "You do not have enough balance to create order",
+        ));
+    }
+    Ok(balance)
+}
+
+/// Pushes the created order to the jetstream, order is cancelled if it can't be published
+pub(super) async fn publish_created_order(
+    app_state: &AppState,
+    order: &Order,
+) -> Result<(), (StatusCode, Response)> {
+    let message = OrderMessage {
+        order_id: order.id,
+        market_id: order.market_id,
+    };
+    let message_pack_encoded = serialize_to_message_pack(&message).map_err(|e| {
+        log_error!("Failed to serialize order message - {:?}", e);
+        internal_error("Failed to serialize order message")
+    })?;
+    publish_order_message(
+        app_state,
+        order.id,
+        NatsSubjects::OrderCreate,
+        message_pack_encoded,
+    )
+    .await
+}
+
+/// Pushes the message of the new order to the jetstream, order is cancelled if it can't be published
+pub(super) async fn publish_order_message(
+    app_state: &AppState,
+    order_id: Uuid,
+    subject: NatsSubjects,
+    payload: Vec<u8>,
+) -> Result<(), (StatusCode, Response)> {
+    if let Err(e) = app_state
+        .jetstream
+        .publish(subject.to_string(), payload.into())
+        .await
+    {
+        log_error!("Failed to publish order to jetstream - {:?}", e);
+        Order::update_order_status(order_id, OrderStatus::CANCELLED, &app_state.pg_pool)
+            .await
+            .map_err(|e| {
+                log_error!("Failed to cancel order - {:?}", e);
+                internal_error("Failed to update order status to cancelled")
+            })?;
+
+        return Err(internal_error(
+            "Failed to publish order to jetstream, order is cancelled",
+        ));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,180 @@
+use std::time::Duration;
+
+use axum::{
+    Json,
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use db_service::schema::{
+    enums::{OrderSide, Outcome},
+    market::Market,
+};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use serde_json::json;
+use utility_helpers::{
+    log_error,
+    message_pack_helper::{deserialize_from_message_pack, serialize_to_message_pack},
+    nats_helper::{
+        NatsSubjects,
+        types::{QuoteRequestMessage, QuoteResponseMessage},
+    },
+};
+use uuid::Uuid;
+
+use crate::{require_field, state::AppState};
+
+use super::create_limit_order::resolve_outcome_index;
+
+const QUOTE_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Deserialize)]
+pub struct QuoteMarketOrderPayload {
+    pub market_id: Option<Uuid>,
+    pub outcome: Option<Outcome>,
+    pub side: Option<OrderSide>,
+    pub quantity: Option<Decimal>, // shares to buy or sell, either this or budget
+    pub budget: Option<Decimal>,   // amount to spend (or to receive for sells)
+    pub outcome_index: Option<u32>, // outcome of the categorical market (yes shares of it are traded)
+}
+
+/// Previews the execution of a market order ("you will pay ~X, price moves to Y"), nothing is placed or matched
+pub async fn quote_market_order(
+    State(app_state): State<AppState>,
+    Json(payload): Json<QuoteMarketOrderPayload>,
+) -> Result<impl IntoResponse, (StatusCode, Response)> {
+    require_field!(payload.market_id);
+    require_field!(payload.outcome);
+    require_field!(payload.side);
+
+    let (quantity, budget) = match (payload.quantity, payload.budget) {
+        (Some(quantity), None) if quantity > Decimal::ZERO => (Some(quantity), None),
+        (None, Some(budget)) if budget > Decimal::ZERO => (None, Some(budget)),
+        _ => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "error": "Either quantity or budget (greater than 0) is required"
+                }))
+                .into_response(),
+            ));
+        }
+    };
+
+    let market_id = payload.market_id.unwrap();
+    let market = Market::get_market_by_id(&app_state.pg_pool, &market_id)
+        .await
+        .map_err(|e| {
+            log_error!("Failed to get market - {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "error": "Failed to get market"
+                }))
+                .into_response(),
+            )
+        })?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(json!({
+                    "error": "Market not found"
+                }))
+                .into_response(),
+            )
+        })?;
+
+    let outcome = payload.outcome.unwrap();
+    let outcome_index =
+        resolve_outcome_index(&market, outcome, payload.outcome_index).map_err(|error| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "error": error })).into_response(),
+            )
+        })?;
+
+    let quote_request = QuoteRequestMessage {
+        market_id,
+        side: payload.side.unwrap(),
+        outcome,
+        outcome_index,
+        quantity,
+        budget,
+    };
+    let quote = request_quote(&app_state, &quote_request).await?;
+
+    let fills = quote
+        .fills
+        .iter()
+        .map(|fill| {
+            json!({
+                "price": fill.price.to_string(),
+                "quantity": fill.quantity.to_string(),
+            })
+        })
+        .collect::<Vec<_>>();
+    let outcome_prices = quote
+        .outcome_prices
+        .iter()
+        .map(|price| price.to_string())
+        .collect::<Vec<_>>();
+
+    let response = json!({
+        "market_id": quote.market_id,
+        "fills": fills,
+        "filled_quantity": quote.filled_quantity.to_string(),
+        "total_cost": quote.total_cost.to_string(),
+        "average_price": quote.average_price.map(|price| price.to_string()),
+        "midpoint": quote.midpoint.to_string(),
+        "current_yes_price": quote.current_yes_price.to_string(),
+        "current_no_price": quote.current_no_price.to_string(),
+        "outcome_prices": outcome_prices,
+        "reason": quote.reason,
+    });
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+// quote is simulated by the order service on it's in memory order book (nats request-reply)
+async fn request_quote(
+    app_state: &AppState,
+    quote_request: &QuoteRequestMessage<OrderSide, Outcome>,
+) -> Result<QuoteResponseMessage, (StatusCode, Response)> {
+    let internal_error = |error: &str| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": error })).into_response(),
+        )
+    };
+
+    let payload = serialize_to_message_pack(quote_request).map_err(|e| {
+        log_error!("Failed to serialize quote request - {:?}", e);
+        internal_error("Failed to serialize quote request")
+    })?;
+
+    let reply = tokio::time::timeout(
+        QUOTE_TIMEOUT,
+        app_state
+            .nats_client
+            .request(NatsSubjects::OrderBookQuote.to_string(), payload.into()),
+    )
+    .await
+    .map_err(|_| internal_error("Order service did not respond to the quote"))?
+    .map_err(|e| {
+        log_error!("Failed to request quote - {:?}", e);
+        internal_error("Failed to request quote")
+    })?;
+
+    deserialize_from_message_pack::<Result<QuoteResponseMessage, String>>(&reply.payload)
+        .map_err(|e| {
+            log_error!("Failed to deserialize quote - {:?}", e);
+            internal_error("Failed to deserialize quote")
+        })?
+        .map_err(|error| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "error": error })).into_response(),
+            )
+        })
+}
@@ -4,7 +4,11 @@ use axum::{
     http::StatusCode,
     response::{IntoResponse, Response},
 };
-use db_service::schema::{enums::OrderStatus, orders::Order};
+use db_service::schema::{
+    enums::{OrderStatus, OrderType},
+    market::Market,
+    orders::Order,
+};
 use rust_decimal::Decimal;
 use serde::Deserialize;
 use serde_json::json;
@@ -16,6 +20,8 @@ use uuid::Uuid;
 
 use crate::{require_field, state::AppState};
 
+use super::create_limit_order::validate_market_increments;
+
 #[derive(Deserialize)]
 pub struct UpdateOrderRequestData {
     pub order_id: Option<Uuid>,
@@ -57,6 +63,14 @@ pub async fn update_order(
 
     let order = order.unwrap();
 
+    if order.order_type == OrderType::StopLoss || order.order_type == OrderType::TakeProfit {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": "Stop loss and take profit orders can't be updated, cancel and create a new one"}))
+                .into_response(),
+        ));
+    }
+
     if order.quantity == order.filled_quantity {
         return Err((
             StatusCode::BAD_REQUEST,
@@ -72,8 +86,37 @@ pub async fn update_order(
         ));
     }
 
+    let market = Market::get_market_by_id(&app_state.pg_pool, &order.market_id)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": e.to_string()})).into_response(),
+            )
+        })?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(json!({"error": "Market not found"})).into_response(),
+            )
+        })?;
+    if !market.status.is_tradable() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": "Market is not open for trading, cannot update order"}))
+                .into_response(),
+        ));
+    }
+    validate_market_increments(&market, new_price, new_quantity).map_err(|error| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": error })).into_response(),
+        )
+    })?;
+
     let update_order_message = UpdateOrderMessage {
         order_id,
+        market_id: order.market_id,
         new_quantity,
         new_price,
     };
@@ -43,6 +43,7 @@ pub async fn get_profile(
         "avatar": user.avatar,
         "public_key": user.public_key,
         "balance": user.balance,
+        "self_trade_prevention": user.self_trade_prevention,
     });
 
     Ok((StatusCode::OK, Json(response)))
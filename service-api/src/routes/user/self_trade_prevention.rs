@@ -0,0 +1,59 @@
+use auth_service::types::SessionTokenClaims;
+use axum::{
+    Extension, Json,
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use db_service::schema::{enums::SelfTradePrevention, users::User};
+use serde::Deserialize;
+use serde_json::json;
+use utility_helpers::{log_error, redis::keys::RedisKey};
+
+use crate::{require_field, state::AppState};
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateSelfTradePreventionPayload {
+    self_trade_prevention: Option<SelfTradePrevention>,
+}
+
+/// Updates the account default self trade prevention mode (used by orders which don't specify it)
+pub async fn update_self_trade_prevention(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<SessionTokenClaims>,
+    Json(payload): Json<UpdateSelfTradePreventionPayload>,
+) -> Result<impl IntoResponse, (StatusCode, Response)> {
+    require_field!(payload.self_trade_prevention);
+
+    let user_id = claims.user_id;
+    let self_trade_prevention = payload.self_trade_prevention.unwrap();
+
+    User::update_self_trade_prevention(&app_state.pg_pool, user_id, self_trade_prevention)
+        .await
+        .map_err(|e| {
+            log_error!("Failed to update self trade prevention - {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "error": "Failed to update self trade prevention"
+                }))
+                .into_response(),
+            )
+        })?;
+
+    // cached profile holds the previous mode
+    if let Err(e) = app_state
+        .redis_helper
+        .clear_cache(&RedisKey::User(user_id).to_string())
+        .await
+    {
+        log_error!("Failed to clear user cache - {:?}", e);
+    }
+
+    let response = json!({
+        "message": "Self trade prevention updated successfully",
+        "self_trade_prevention": self_trade_prevention,
+    });
+
+    Ok((StatusCode::OK, Json(response)))
+}
@@ -15,6 +15,7 @@ pub struct AppState {
     pub pg_pool: sqlx::PgPool,
     pub auth_service: AuthService,
     pub jetstream: Context,
+    pub nats_client: async_nats::Client, // core nats, for request-reply with the order service
     pub bloom_filter: BloomFilterWrapper, // already thread safe
     pub redis_helper: RedisHelper,
 }
@@ -26,7 +27,7 @@ impl AppState {
         let env_var_config = EnvVarConfig::new()?;
 
         let ns = connect(&env_var_config.nc_url).await?;
-        let jetstream = jetstream::new(ns);
+        let jetstream = jetstream::new(ns.clone());
 
         let pg_pool = sqlx::PgPool::connect(&env_var_config.database_url).await?;
         let auth_service = AuthService::new(pg_pool.clone())?;
@@ -42,6 +43,7 @@ impl AppState {
             pg_pool,
             auth_service,
             jetstream,
+            nats_client: ns,
             bloom_filter,
             redis_helper,
         };
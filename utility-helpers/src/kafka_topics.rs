@@ -2,6 +2,7 @@ pub enum KafkaTopics {
     PriceUpdates,
     MarketOrderBookUpdate,
     VolumeUpdates,
+    MarketStatusUpdates,
 }
 
 impl KafkaTopics {
@@ -12,6 +13,8 @@ impl KafkaTopics {
             Some(KafkaTopics::PriceUpdates)
         } else if topic == "volume-updates" {
             Some(KafkaTopics::VolumeUpdates)
+        } else if topic == "market-status-updates" {
+            Some(KafkaTopics::MarketStatusUpdates)
         } else {
             None
         }
@@ -22,6 +25,7 @@ impl KafkaTopics {
             KafkaTopics::PriceUpdates => "price-updates",
             KafkaTopics::MarketOrderBookUpdate => "order-book-updates",
             KafkaTopics::VolumeUpdates => "volume-updates",
+            KafkaTopics::MarketStatusUpdates => "market-status-updates",
         }
     }
 }
@@ -1,4 +1,5 @@
 // these `ORDER` name does not indicate the operations on orders, instead it indicates that the streams is used by order-service microservice, so don't confuse it with the order name and same for it's topics, all topics are prefixed with `order.`
+// notifications which are not consumed from the stream (order status, trading status) are published over core nats outside of the `order.` prefix, so they are not stored in the stream
 
 pub mod types;
 
@@ -14,6 +15,10 @@ pub enum NatsSubjects {
     MarketOrderCreate,
     InitializeOrderBook,
     FinalizeMarket,
+    MarketStatusUpdate, // lifecycle transitions of the market, order books are frozen / unfrozen / dropped by them
+    OrderStatusUpdate(Uuid), // user id (core nats, kept out of the `ORDER` stream)
+    MarketTradingStatus(Uuid), // market id, trading halts and resumes of the circuit breaker (core nats, kept out of the `ORDER` stream)
+    OrderBookQuote, // request-reply of the read-only market order quote (core nats, kept out of the `ORDER` stream)
 }
 
 impl NatsSubjects {
@@ -28,6 +33,14 @@ impl NatsSubjects {
             NatsSubjects::MarketOrderCreate => "order.market_order_create".to_string(),
             NatsSubjects::InitializeOrderBook => "order.initialize_order_book".to_string(),
             NatsSubjects::FinalizeMarket => "order.finalize_market".to_string(),
+            NatsSubjects::MarketStatusUpdate => "order.market_status_update".to_string(),
+            NatsSubjects::OrderStatusUpdate(user_id) => {
+                format!("user.order.status.{}", user_id)
+            }
+            NatsSubjects::MarketTradingStatus(market_id) => {
+                format!("market.trading_status.{}", market_id)
+            }
+            NatsSubjects::OrderBookQuote => "quote.order_book".to_string(),
         }
     }
 
@@ -51,6 +64,20 @@ impl NatsSubjects {
             Some(NatsSubjects::InitializeOrderBook)
         } else if queue == "order.finalize_market" {
             Some(NatsSubjects::FinalizeMarket)
+        } else if queue == "order.market_status_update" {
+            Some(NatsSubjects::MarketStatusUpdate)
+        } else if queue.starts_with("user.order.status.") {
+            let user_id_str = queue.trim_start_matches("user.order.status.");
+            Uuid::parse_str(user_id_str)
+                .ok()
+                .map(NatsSubjects::OrderStatusUpdate)
+        } else if queue.starts_with("market.trading_status.") {
+            let market_id_str = queue.trim_start_matches("market.trading_status.");
+            Uuid::parse_str(market_id_str)
+                .ok()
+                .map(NatsSubjects::MarketTradingStatus)
+        } else if queue == "quote.order_book" {
+            Some(NatsSubjects::OrderBookQuote)
         } else {
             None
         }
@@ -2,7 +2,7 @@
  * This file contains types which are going to serialize using message pack pack and send to nats
  */
 
-use proto_defs::proto_types::order_book::{MarketBook, OrderBook, OrderLevel};
+use proto_defs::proto_types::order_book::{MarketBook, OrderBook, OrderLevel, OutcomeBook};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -12,17 +12,43 @@ use crate::{
     types::{OrderBookDataStruct, OrderLevel as OrderLevelStruct},
 };
 
+/// Update of the market's order books, full books are sent with the snapshots and only the changed levels with the deltas
 #[derive(Debug, Serialize, Deserialize)]
 pub struct OrderBookUpdateData {
     pub yes_book: OrderBookDataStruct,
     pub no_book: OrderBookDataStruct,
     pub market_id: Uuid,
     pub timestamp: String,
+    #[serde(default)]
+    pub outcome_books: Vec<OrderBookDataStruct>, // books of all outcomes by their index
+    #[serde(default)]
+    pub sequence: u64, // per market, increased by one for every update (client has missed an update if it's not contiguous)
+    #[serde(default = "default_snapshot")]
+    pub is_snapshot: bool, // books are full, otherwise only `outcome_deltas` are set
+    #[serde(default)]
+    pub outcome_deltas: Vec<OrderBookDataStruct>, // changed levels of the outcomes by their index, level with 0 shares is removed
+    #[serde(default)]
+    pub checksum: u32, // checksum of the books after the update (`get_books_checksum`)
+    #[serde(default)]
+    pub epoch: u64, // boot of the order service, sequence restarts when it changes
+}
+
+// updates published before the deltas carried the full books
+fn default_snapshot() -> bool {
+    true
+}
+
+/// Order created or cancelled in the market, `market_id` lets the order service dispatch the message without looking up the order
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OrderMessage {
+    pub order_id: Uuid,
+    pub market_id: Uuid,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UpdateOrderMessage {
     pub order_id: Uuid,
+    pub market_id: Uuid,
     pub new_quantity: Decimal,
     pub new_price: Decimal,
 }
@@ -30,7 +56,14 @@ pub struct UpdateOrderMessage {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MarketOrderCreateMessage {
     pub order_id: Uuid,
+    pub market_id: Uuid,
     pub budget: Decimal,
+    #[serde(default)]
+    pub price_limit: Option<Decimal>, // worst price (max for buys, min for sells)
+    #[serde(default)]
+    pub max_slippage: Option<Decimal>, // relative to the midpoint, 0.05 is 5%
+    #[serde(default)]
+    pub allow_partial: bool, // fills what the liquidity allows instead of cancelling the order
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -43,6 +76,82 @@ pub struct InitializeOrderBookMessage<T> {
     pub orders: Vec<T>,
 }
 
+/// Status of an order after it's processed by the matching engine (`reason` is set when the order is rejected)
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "T: Serialize",
+    deserialize = "T: serde::de::DeserializeOwned"
+))]
+pub struct OrderStatusUpdateMessage<T> {
+    pub order_id: Uuid,
+    pub market_id: Uuid,
+    pub status: T,
+    pub price: Decimal,
+    pub filled_quantity: Decimal,
+    pub reason: Option<String>,
+    pub timestamp: String,
+}
+
+/// Trading halt (or resume) of the market by the circuit breaker
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MarketTradingStatusMessage {
+    pub market_id: Uuid,
+    pub halted: bool,
+    pub reason: Option<String>,
+    pub resume_at: Option<String>, // end of the halt's cool-down
+    pub timestamp: String,
+}
+
+/// Lifecycle transition of the market, `changed_by` is the admin who made it (`None` for the expiry of the market)
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "T: Serialize",
+    deserialize = "T: serde::de::DeserializeOwned"
+))]
+pub struct MarketStatusUpdateMessage<T> {
+    pub market_id: Uuid,
+    pub from_status: T,
+    pub to_status: T,
+    pub changed_by: Option<Uuid>,
+    pub timestamp: String,
+}
+
+/// Read-only quote of a market order for `quantity` shares or `budget` (one of them is set), nothing is changed in the order book
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "S: Serialize, O: Serialize",
+    deserialize = "S: serde::de::DeserializeOwned, O: serde::de::DeserializeOwned"
+))]
+pub struct QuoteRequestMessage<S, O> {
+    pub market_id: Uuid,
+    pub side: S,
+    pub outcome: O,
+    pub outcome_index: i32,
+    pub quantity: Option<Decimal>,
+    pub budget: Option<Decimal>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QuoteFillLevel {
+    pub price: Decimal,
+    pub quantity: Decimal,
+}
+
+/// Reply of the quote request (`Result<QuoteResponseMessage, String>` is sent back), prices are the ones after the simulated execution
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QuoteResponseMessage {
+    pub market_id: Uuid,
+    pub fills: Vec<QuoteFillLevel>, // expected fills per price level, in the order of execution
+    pub filled_quantity: Decimal,
+    pub total_cost: Decimal,
+    pub average_price: Option<Decimal>, // none if nothing is filled
+    pub midpoint: Decimal,              // midpoint of the quoted outcome
+    pub current_yes_price: Decimal,
+    pub current_no_price: Decimal,
+    pub outcome_prices: Vec<Decimal>, // prices of all outcomes by their index
+    pub reason: Option<String>,       // why the order would not be (fully) filled
+}
+
 impl OrderBookUpdateData {
     pub fn get_prost_market_book(self, market_id: Uuid) -> MarketBook {
         let yes_book_bids = Self::get_order_level(&self.yes_book.bids);
@@ -60,10 +169,42 @@ impl OrderBookUpdateData {
             asks: no_book_asks,
         };
 
+        let outcome_books = self
+            .outcome_books
+            .iter()
+            .enumerate()
+            .map(|(outcome_index, book)| OutcomeBook {
+                outcome_index: outcome_index as u32,
+                book: Some(OrderBook {
+                    bids: Self::get_order_level(&book.bids),
+                    asks: Self::get_order_level(&book.asks),
+                }),
+            })
+            .collect();
+
+        let outcome_deltas = self
+            .outcome_deltas
+            .iter()
+            .enumerate()
+            .map(|(outcome_index, delta)| OutcomeBook {
+                outcome_index: outcome_index as u32,
+                book: Some(OrderBook {
+                    bids: Self::get_order_level(&delta.bids),
+                    asks: Self::get_order_level(&delta.asks),
+                }),
+            })
+            .collect();
+
         MarketBook {
             market_id: market_id.to_string(),
             yes_book: Some(yes_book),
             no_book: Some(no_book),
+            outcome_books,
+            sequence: self.sequence,
+            is_snapshot: self.is_snapshot,
+            outcome_deltas,
+            checksum: self.checksum,
+            epoch: self.epoch,
         }
     }
 
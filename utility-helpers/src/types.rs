@@ -90,15 +90,41 @@ impl EnvVarConfig {
  *
 */
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct OrderLevel {
     pub price: Decimal,
     pub shares: Decimal,
     pub users: usize,
 }
 
-#[derive(Serialize, Default, Deserialize, Clone, Debug)]
+#[derive(Serialize, Default, Deserialize, Clone, Debug, PartialEq)]
 pub struct OrderBookDataStruct {
     pub bids: Vec<OrderLevel>,
     pub asks: Vec<OrderLevel>,
 }
+
+/// Checksum (32 bit FNV-1a) of the books of all outcomes, clients compare it with their books to detect the missed updates.
+///
+/// Hashed text is `<outcome index>:<b|a>:<price>:<shares>;` of every level (bids then asks in ascending price), decimals without trailing zeros
+pub fn get_books_checksum(outcome_books: &[OrderBookDataStruct]) -> u32 {
+    let mut hash: u32 = 0x811c9dc5;
+    for (outcome_index, book) in outcome_books.iter().enumerate() {
+        let levels = book
+            .bids
+            .iter()
+            .map(|level| ('b', level))
+            .chain(book.asks.iter().map(|level| ('a', level)));
+        for (side, level) in levels {
+            let text = format!(
+                "{outcome_index}:{side}:{}:{};",
+                level.price.normalize(),
+                level.shares.normalize()
+            );
+            for byte in text.bytes() {
+                hash ^= byte as u32;
+                hash = hash.wrapping_mul(0x01000193);
+            }
+        }
+    }
+    hash
+}